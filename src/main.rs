@@ -29,23 +29,95 @@
 // - Fixes: Historie timing, server direct beschikbaar, scope fixes, borrow fixes, Send fixes.
 // ============================================================================
 
-use chrono::Utc;
+use chrono::{Offset, Utc};
 use dashmap::DashMap;
 use futures::{SinkExt, StreamExt};
 use lazy_static::lazy_static;
-use reqwest;
 use rss::Channel;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::Cursor;
 use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::time::{sleep, Duration};
 use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::Message;
 use warp::Filter;
 
+// ============================================================================
+// LOGGING (LOG LEVELS)
+// ============================================================================
+//
+// error < warn < info < debug. WHALE_RADAR_LOG (env) wint van AppConfig::log_level.
+
+const LOG_ERROR: u8 = 0;
+const LOG_WARN: u8 = 1;
+const LOG_INFO: u8 = 2;
+const LOG_DEBUG: u8 = 3;
+const LOG_ENV_VAR: &str = "WHALE_RADAR_LOG";
+// Token voor beschermde API endpoints (/api/test_signal); niet gezet = endpoint uit
+const API_TOKEN_ENV_VAR: &str = "WHALE_RADAR_API_TOKEN";
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LOG_INFO);
+
+fn parse_log_level(level: &str) -> Option<u8> {
+    match level.trim().to_lowercase().as_str() {
+        "error" => Some(LOG_ERROR),
+        "warn" | "warning" => Some(LOG_WARN),
+        "info" => Some(LOG_INFO),
+        "debug" => Some(LOG_DEBUG),
+        _ => None,
+    }
+}
+
+/// Zet het actieve log level: env var als die geldig is, anders het config level.
+fn init_log_level(config_level: &str) {
+    let level = std::env::var(LOG_ENV_VAR)
+        .ok()
+        .and_then(|v| parse_log_level(&v))
+        .or_else(|| parse_log_level(config_level))
+        .unwrap_or(LOG_INFO);
+    LOG_LEVEL.store(level, Ordering::Relaxed);
+}
+
+fn log_enabled(level: u8) -> bool {
+    level <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        if log_enabled(LOG_ERROR) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        if log_enabled(LOG_WARN) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        if log_enabled(LOG_INFO) {
+            println!($($arg)*);
+        }
+    };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if log_enabled(LOG_DEBUG) {
+            println!($($arg)*);
+        }
+    };
+}
+
 // ============================================================================
 // LAZY STATIC INITIALIZATION
 // ============================================================================
@@ -69,16 +141,6 @@ lazy_static! {
         map.insert("sol".to_string(), "SOL/EUR".to_string());
         map
     };
-    
-    // Pre-sorted keywords by length (descending) for efficient matching
-    static ref SORTED_KEYWORDS: Vec<(String, String)> = {
-        let mut keywords: Vec<(String, String)> = KEYWORD_MAP
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect();
-        keywords.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
-        keywords
-    };
 }
 
 lazy_static! {
@@ -122,588 +184,4191 @@ lazy_static! {
 // HOOFDSTUK 1 – CONFIGURATIE & CONSTANTES
 // ============================================================================
 
+/// Drempels voor whale_score. Een trade telt als tier wanneer hij boven de
+/// absolute notional OF (medium: EN) boven ewma_notional * multiplier ligt.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct AppConfig {
-    pump_conf_threshold: f64,
-    whale_pred_high_threshold: f64,
-    early_buy_threshold: f64,
-    alpha_buy_threshold: f64,
-    strong_buy_threshold: f64,
-    whale_min_notional: f64,
-    anomaly_strength_threshold: f64,
-    flow_weight: f64,
-    price_weight: f64,
-    whale_weight: f64,
-    volume_weight: f64,
-    anomaly_weight: f64,
-    trend_weight: f64,
-    initial_balance: f64,
-    base_notional: f64,
-    sl_pct: f64,
-    tp_pct: f64,
-    max_positions: usize,
-    enable_trading: bool,
-    ws_workers_per_chunk: usize,
-    rest_scan_interval_sec: u64,
-    cleanup_interval_sec: u64,
-    eval_horizon_sec: i64,
-    max_history: usize,
-    default_dir_filter: String,
-    include_stablecoins_default: bool,
-    heatmap_min_radius: f64,
-    heatmap_max_radius: f64,
-    chart_refresh_rate_sec: f64,
-    ai_success_threshold: f64,
-    ai_adjustment_step_up: f64,
-    ai_adjustment_step_down: f64,
-    ai_max_weight: f64,
+#[serde(default)]
+struct WhaleTiers {
+    // is_whale: notional > whale_min_notional && notional > ewma * detect_ewma_mult
+    detect_ewma_mult: f64,
+    large_notional: f64,
+    large_ewma_mult: f64,
+    medium_notional: f64,
+    medium_ewma_mult: f64,
 }
 
-impl Default for AppConfig {
+impl Default for WhaleTiers {
     fn default() -> Self {
         Self {
-            pump_conf_threshold: 0.7,
-            whale_pred_high_threshold: 8.0,
-            early_buy_threshold: 3.0,
-            alpha_buy_threshold: 7.5,
-            strong_buy_threshold: 5.0,
-            whale_min_notional: 5000.0,
-            anomaly_strength_threshold: 40.0,
-            flow_weight: 2.2,
-            price_weight: 0.7,
-            whale_weight: 1.4,
-            volume_weight: 1.3,
-            anomaly_weight: 1.5,
-            trend_weight: 1.1,
-            initial_balance: 10000.0,
-            base_notional: 100.0,
-            sl_pct: 0.02,
-            tp_pct: 0.05,
-            max_positions: 5,
-            enable_trading: true,
-            ws_workers_per_chunk: 20,
-            rest_scan_interval_sec: 20,
-            cleanup_interval_sec: 600,
-            eval_horizon_sec: 300,
-            max_history: 400,
-            default_dir_filter: "ALL".to_string(),
-            include_stablecoins_default: true,
-            heatmap_min_radius: 4.0,
-            heatmap_max_radius: 12.0,
-            chart_refresh_rate_sec: 1.0,
-            ai_success_threshold: 0.7,
-            ai_adjustment_step_up: 1.02,
-            ai_adjustment_step_down: 0.98,
-            ai_max_weight: 5.0,
+            detect_ewma_mult: 2.5,
+            large_notional: 50_000.0,
+            large_ewma_mult: 6.0,
+            medium_notional: 20_000.0,
+            medium_ewma_mult: 4.0,
         }
     }
 }
 
-const CONFIG_FILE: &str = "config.json";
+/// Opbouw van pump_score: elke term telt (waarde - drempel) * mult zodra de waarde
+/// boven de drempel komt; de som wordt op max_score afgekapt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct PumpSettings {
+    // Prijsstijging (%) over 5s / 30s / 120s
+    ret_5s_threshold: f64,
+    ret_5s_mult: f64,
+    ret_30s_threshold: f64,
+    ret_30s_mult: f64,
+    ret_120s_threshold: f64,
+    ret_120s_mult: f64,
+    // Buy-flow % van het korte venster (alleen bij Dir BUY)
+    flow_threshold: f64,
+    flow_mult: f64,
+    // Buy-flow % van het medium venster (alleen bij Dir 5m BUY)
+    flow_5m_threshold: f64,
+    flow_5m_mult: f64,
+    // Trade volume t.o.v. de EWMA
+    vol_ratio_threshold: f64,
+    vol_ratio_mult: f64,
+    // Deel van de whale score dat meetelt (geen drempel)
+    whale_mult: f64,
+    // Bovengrens; MEGA_PUMP/EARLY_PUMP labels gaan uit van 10
+    max_score: f64,
+}
 
-async fn load_config() -> AppConfig {
-    match tokio::fs::read_to_string(CONFIG_FILE).await {
-        Ok(content) => serde_json::from_str(content.as_str()).unwrap_or_default(),
-        Err(_) => {
-            let default = AppConfig::default();
-            if let Ok(json) = serde_json::to_string_pretty(&default) {
-                let _ = tokio::fs::write(CONFIG_FILE, json).await;
-            }
-            default
+impl Default for PumpSettings {
+    fn default() -> Self {
+        Self {
+            ret_5s_threshold: 0.3,
+            ret_5s_mult: 2.0,
+            ret_30s_threshold: 1.0,
+            ret_30s_mult: 1.0,
+            ret_120s_threshold: 2.0,
+            ret_120s_mult: 0.5,
+            flow_threshold: 65.0,
+            flow_mult: 0.08,
+            flow_5m_threshold: 60.0,
+            flow_5m_mult: 0.06,
+            vol_ratio_threshold: 1.5,
+            vol_ratio_mult: 1.0,
+            whale_mult: 0.7,
+            max_score: 10.0,
         }
     }
 }
 
-async fn save_config(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
-    let json = serde_json::to_string_pretty(config)?;
-    tokio::fs::write(CONFIG_FILE, json).await?;
-    Ok(())
+/// Flow-vensters (seconden) met BUY/SELL cutoffs als fractie kooporders.
+/// short = Markets "Flow"/"Dir", medium = bevestiging in scores, long = context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct FlowSettings {
+    short_window_sec: f64,
+    short_buy_cutoff: f64,
+    short_sell_cutoff: f64,
+    medium_window_sec: f64,
+    medium_buy_cutoff: f64,
+    medium_sell_cutoff: f64,
+    long_window_sec: f64,
+    long_buy_cutoff: f64,
+    long_sell_cutoff: f64,
+    // "volume" (som van volumes) of "count" (aantal trades; robuuster tegen één grote print)
+    basis: String,
 }
 
-// ============================================================================
-// HOOFDSTUK 2 – BAYESIAANS AI / ZELFLEREND SYSTEEM
-// ============================================================================
+impl Default for FlowSettings {
+    fn default() -> Self {
+        Self {
+            short_window_sec: 60.0,
+            short_buy_cutoff: 0.75,
+            short_sell_cutoff: 0.25,
+            medium_window_sec: 300.0,
+            medium_buy_cutoff: 0.70,
+            medium_sell_cutoff: 0.30,
+            long_window_sec: 900.0,
+            long_buy_cutoff: 0.65,
+            long_sell_cutoff: 0.35,
+            basis: "volume".to_string(),
+        }
+    }
+}
 
-const SIGNAL_FILE: &str = "signals.json";
-const MAX_HISTORY: usize = 20;
+/// Coëfficiënten voor de ticker-anomaly score in handle_ticker:
+/// score = jump * jump_weight + |day_ret| * day_ret_weight
+///       + (vol_ratio - 1) * vol_ratio_weight + ewma_abs_return * ewma_return_weight.
+/// Trigger: score > anomaly_strength_threshold EN (jump > min_jump_pct OF vol_ratio > min_vol_ratio).
+/// Daarna per pair een cooldown (tenzij de richting omdraait) en een optioneel richtingsfilter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct AnomalySettings {
+    jump_weight: f64,
+    day_ret_weight: f64,
+    vol_ratio_weight: f64,
+    ewma_return_weight: f64,
+    min_jump_pct: f64,
+    min_vol_ratio: f64,
+    // Geen nieuw ANOM signaal voor hetzelfde pair en dezelfde richting binnen dit venster (0 = uit)
+    cooldown_sec: i64,
+    // "BOTH", "BUY" of "SELL"
+    direction: String,
+}
 
-const VIRTUAL_INITIAL_BALANCE: f64 = 10_000.0;
-const VIRTUAL_BASE_NOTIONAL: f64 = 100.0;
-const VIRTUAL_MAX_POSITIONS: usize = 5;
-const VIRTUAL_SL_PCT: f64 = 0.02;
-const VIRTUAL_TP_PCT: f64 = 0.05;
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        Self {
+            jump_weight: 2.0,
+            day_ret_weight: 0.5,
+            vol_ratio_weight: 20.0,
+            ewma_return_weight: 1.0,
+            min_jump_pct: 0.3,
+            min_vol_ratio: 2.0,
+            cooldown_sec: 300,
+            direction: "BOTH".to_string(),
+        }
+    }
+}
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct SignalStats {
-    wins: u32,
-    losses: u32,
-    threshold: f64,
-    last_updated: Option<chrono::DateTime<chrono::Utc>>,
-    profit_history: std::vec::Vec<f64>,
+/// True als een nieuwe anomaly nog in de cooldown van de vorige valt. Een
+/// richtingswissel (BUY -> SELL of andersom) telt altijd als nieuw signaal.
+fn anomaly_in_cooldown(
+    last_ts: Option<i64>,
+    last_dir: Option<&str>,
+    direction: &str,
+    now: i64,
+    cooldown_sec: i64,
+) -> bool {
+    match (last_ts, last_dir) {
+        (Some(at), Some(dir)) => cooldown_sec > 0 && dir == direction && now - at < cooldown_sec,
+        _ => false,
+    }
 }
 
-impl SignalStats {
-    fn new(threshold: f64) -> Self {
+/// Wanneer een pair een "star" is: in de Stars tab (/api/stars) en in de stars historie.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct StarsSettings {
+    // Minimaal WH_PRED label: "LOW", "MEDIUM" of "HIGH"
+    min_whale_pred_label: String,
+    // Ook een ANOM vereisen, niet ouder dan anom_lookback_sec
+    require_anom: bool,
+    anom_lookback_sec: i64,
+    // Stars historie: per pair hooguit één entry per history_dedup_sec, en (met
+    // require_anom) nooit twee entries op basis van dezelfde ANOM
+    history_dedup_sec: i64,
+}
+
+impl Default for StarsSettings {
+    fn default() -> Self {
         Self {
-            wins: 0,
-            losses: 0,
-            threshold,
-            last_updated: None,
-            profit_history: std::vec::Vec::new(),
+            min_whale_pred_label: "HIGH".to_string(),
+            require_anom: true,
+            anom_lookback_sec: 5 * 3600,
+            history_dedup_sec: 3600,
         }
     }
+}
 
-    fn update(&mut self, profit: f64) {
-        if profit > 0.0 { self.wins += 1; } else { self.losses += 1; }
-        self.profit_history.push(profit);
-        if self.profit_history.len() > MAX_HISTORY {
-            self.profit_history.remove(0);
+/// Wash-trading heuristiek: gelijke (binnen volume_tolerance) buy/sell prints die
+/// elkaar direct opvolgen binnen window_sec. Vanaf min_round_trips zo'n paren is
+/// het pair "suspicious"; reliability gaat dan met reliability_penalty omlaag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct WashSettings {
+    enabled: bool,
+    window_sec: f64,
+    // Relatief verschil in volume dat nog als "gelijk" telt (0.001 = 0.1%)
+    volume_tolerance: f64,
+    min_round_trips: usize,
+    // Fractie van de reliability score die eraf gaat (0 = alleen de badge)
+    reliability_penalty: f64,
+}
+
+impl Default for WashSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_sec: 120.0,
+            volume_tolerance: 0.001,
+            min_round_trips: 3,
+            reliability_penalty: 0.5,
         }
+    }
+}
 
-        let total = (self.wins + self.losses) as f64;
-        let p_success = (self.wins as f64 + 1.0) / (total + 2.0);
-        let recent_avg: f64 = if !self.profit_history.is_empty() {
-            self.profit_history.iter().sum::<f64>() / self.profit_history.len() as f64
-        } else { 0.0 };
+/// Prijs/flow divergentie over window_sec: stijgende buy-flow bij stilstaande prijs
+/// (BULLISH, accumulatie) of stijgende prijs bij dalende buy-flow (BEARISH,
+/// distributie). Flow = buy-aandeel (%) in het medium flow venster.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct DivergenceSettings {
+    enabled: bool,
+    window_sec: f64,
+    // Minimale verandering van het buy-aandeel in procentpunten
+    min_flow_delta: f64,
+    // BULLISH: prijs beweegt niet meer dan dit (%)
+    max_stall_pct: f64,
+    // BEARISH: prijs stijgt minstens dit (%)
+    min_price_rise_pct: f64,
+    // Zelfde label pas opnieuw signaleren na zoveel sec; voorkomt een signal bij elke
+    // flip rond de drempel ("" -> BULLISH -> "" -> BULLISH)
+    cooldown_sec: i64,
+}
 
-        if p_success > 0.7 && recent_avg > 0.0 && self.threshold > 0.1 {
-            self.threshold -= 0.015;
-        } else if p_success < 0.5 && recent_avg < 0.0 && self.threshold < 0.99 {
-            self.threshold += 0.015;
+impl Default for DivergenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_sec: 300.0,
+            min_flow_delta: 15.0,
+            max_stall_pct: 0.3,
+            min_price_rise_pct: 0.5,
+            cooldown_sec: 900,
         }
-
-        self.threshold = self.threshold.clamp(0.1, 0.99);
-        self.last_updated = Some(Utc::now());
-        println!("[AI] Threshold {:.3} | success={:.2} | trend={:.4}", self.threshold, p_success, recent_avg);
     }
 }
 
-async fn load_signal_stats() -> HashMap<String, SignalStats> {
-    match tokio::fs::read_to_string(SIGNAL_FILE).await {
-        Ok(content) => serde_json::from_str(content.as_str()).unwrap_or_default(),
-        Err(_) => HashMap::new(),
+/// Vergelijkt de oudste en nieuwste sample (ts, buy_share, price) in het venster;
+/// pas als het venster minstens half gevuld is. Geeft (label, flow delta) terug.
+fn detect_divergence(
+    samples: &VecDeque<(f64, f64, f64)>,
+    now: f64,
+    settings: &DivergenceSettings,
+) -> Option<(&'static str, f64)> {
+    let &(t0, share0, price0) = samples.front()?;
+    let &(_, share1, price1) = samples.back()?;
+    if now - t0 < settings.window_sec * 0.5 || price0 <= 0.0 {
+        return None;
+    }
+    let flow_delta = share1 - share0;
+    let price_delta = (price1 - price0) / price0 * 100.0;
+    if flow_delta >= settings.min_flow_delta && price_delta.abs() <= settings.max_stall_pct {
+        Some(("BULLISH", flow_delta))
+    } else if flow_delta <= -settings.min_flow_delta && price_delta >= settings.min_price_rise_pct {
+        Some(("BEARISH", flow_delta))
+    } else {
+        None
     }
 }
 
-async fn save_signal_stats(map: &HashMap<String, SignalStats>) {
-    if let Ok(json) = serde_json::to_string_pretty(map) {
-        if let Err(e) = tokio::fs::write(SIGNAL_FILE, json).await {
-            eprintln!("[ERR] Kon signals.json niet opslaan: {}", e);
-        }
-    }
+/// Nieuw DIVERGENCE signal bij het ingaan van een label, behalve als hetzelfde label
+/// binnen cooldown_sec al gesignaleerd is. `last_signal` = (label, ts) van het vorige signal.
+fn divergence_should_signal(label: &str, prev: &str, last_signal: (&str, i64), now: i64, cooldown_sec: i64) -> bool {
+    label != prev && (label != last_signal.0 || now.saturating_sub(last_signal.1) >= cooldown_sec)
 }
 
-// ============================================================================
-// HOOFDSTUK 3 – CORE DATA STRUCTUREN
-// ============================================================================
+/// Aantal opeenvolgende prints (ts, volume, is_buy) met tegengestelde kant en
+/// (vrijwel) hetzelfde volume.
+fn wash_round_trips(prints: &[(f64, f64, bool)], tolerance: f64) -> usize {
+    prints
+        .windows(2)
+        .filter(|w| {
+            let (a, b) = (w[0], w[1]);
+            let max = a.1.max(b.1);
+            a.2 != b.2 && max > 0.0 && (a.1 - b.1).abs() / max <= tolerance
+        })
+        .count()
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct TradeState {
-    buy_volume: f64,
-    sell_volume: f64,
-    trade_count: u64,
-    ewma_trade_size: Option<f64>,
-    ewma_notional: Option<f64>,
-    ewma_volume: Option<f64>,
-    last_whale: bool,
-    last_whale_side: Option<String>,
-    last_whale_volume: Option<f64>,
-    last_whale_notional: Option<f64>,
-    last_early: Option<String>,
-    last_alpha: Option<String>,
-    last_score: f64,
-    last_rating: Option<String>,
-    last_flow_pct: f64,
-    last_dir: String,
-    recent_buys: std::vec::Vec<(f64, f64)>,
-    recent_sells: std::vec::Vec<(f64, f64)>,
-    recent_buys_5m: std::vec::Vec<(f64, f64)>,
-    recent_sells_5m: std::vec::Vec<(f64, f64)>,
-    last_flow_pct_5m: f64,
-    last_dir_5m: String,
-    recent_prices: std::vec::Vec<(f64, f64)>,
-    last_pump_score: f64,
-    last_pump_signal: Option<String>,
-    whale_pred_score: f64,
-    whale_pred_label: Option<String>,
-    last_update_ts: i64,
-    news_sentiment: f64,
-    recent_anom: bool,
-    last_whale_pred_high: bool,
+/// Trade Advice tab (/api/trade_advice): limit entry onder de huidige prijs en
+/// een exit-ladder van vier niveaus boven de entry, allemaal in %.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct TradeAdviceSettings {
+    entry_pct: f64,
+    exit_pct_1: f64,
+    exit_pct_2: f64,
+    exit_pct_3: f64,
+    exit_pct_4: f64,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct CandleState {
-    open: Option<f64>,
-    high: Option<f64>,
-    low: Option<f64>,
-    close: Option<f64>,
-    pct_change: Option<f64>,
-    first_ts: Option<i64>,
-    last_ts: Option<i64>,
-    last_update_ts: i64,
+impl Default for TradeAdviceSettings {
+    fn default() -> Self {
+        Self {
+            entry_pct: 0.5,
+            exit_pct_1: 5.0,
+            exit_pct_2: 10.0,
+            exit_pct_3: 15.0,
+            exit_pct_4: 20.0,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct TickerState {
-    last_price: Option<f64>,
-    last_vol24h: Option<f64>,
-    ewma_vol24h: Option<f64>,
-    ewma_abs_return: Option<f64>,
-    last_anom_ts: Option<i64>,
-    last_anom_dir: Option<String>,
-    last_anom_strength: Option<f64>,
+impl TradeAdviceSettings {
+    fn exit_pcts(&self) -> [f64; 4] {
+        [self.exit_pct_1, self.exit_pct_2, self.exit_pct_3, self.exit_pct_4]
+    }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct OrderbookState {
-    bids: std::vec::Vec<(f64, f64)>,
-    asks: std::vec::Vec<(f64, f64)>,
-    timestamp: i64,
+/// Stille uren voor alerts (HH:MM in `timezone`; start > end loopt over middernacht).
+/// Signals worden gewoon vastgelegd, alleen de kanalen uit quiet_hours_channels
+/// zwijgen; met summary komt er na afloop één samenvatting per kanaal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct QuietHoursSettings {
+    enabled: bool,
+    start: String,
+    end: String,
+    summary: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct Row {
-    pair: String,
-    price: f64,
-    pct: f64,
-    whale: bool,
-    whale_side: String,
-    whale_volume: f64,
-    whale_notional: f64,
-    flow_pct: f64,
-    dir: String,
-    early: String,
-    alpha: String,
-    pump_score: f64,
-    pump_label: String,
-    trades: u64,
-    buys: f64,
-    sells: f64,
-    o: f64,
-    h: f64,
-    l: f64,
-    c: f64,
-    score: f64,
-    rating: String,
-    whale_pred_score: f64,
-    whale_pred_label: String,
-    reliability_score: f64,
-    reliability_label: String,
-    news_sentiment: f64,
-}
-
-#[derive(Debug, Clone)]
-struct ScoreWeights {
-    flow_w: f64,
-    price_w: f64,
-    whale_w: f64,
-    volume_w: f64,
-    anomaly_w: f64,
-    trend_w: f64,
-}
-impl Default for ScoreWeights {
+impl Default for QuietHoursSettings {
     fn default() -> Self {
         Self {
-            flow_w: 2.2,
-            price_w: 0.7,
-            whale_w: 1.4,
-            volume_w: 1.3,
-            anomaly_w: 1.5,
-            trend_w: 1.1,
+            enabled: false,
+            start: "23:00".to_string(),
+            end: "07:00".to_string(),
+            summary: true,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SignalEvent {
-    ts: i64,
-    pair: String,
-    signal_type: String,
-    direction: String,
-    strength: f64,
-    flow_pct: f64,
-    pct: f64,
-    whale: bool,
-    whale_side: String,
-    volume: f64,
-    notional: f64,
-    price: f64,
-    rating: String,
-    total_score: f64,
-    flow_score: f64,
-    price_score: f64,
-    whale_score: f64,
-    volume_score: f64,
-    anomaly_score: f64,
-    trend_score: f64,
-    evaluated: bool,
-    ret_5m: Option<f64>,
-    eval_horizon_sec: Option<i64>,
+impl QuietHoursSettings {
+    fn active(&self, timezone: &str, now: i64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (Some(start), Some(end)) = (parse_hhmm(&self.start), parse_hhmm(&self.end)) else {
+            return false;
+        };
+        let local = now + tz_offset_sec(timezone, now) as i64;
+        let minute = (local.rem_euclid(86_400) / 60) as u32;
+        in_quiet_window(minute, start, end)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct TopRow {
-    ts: i64,
-    pair: String,
-    price: f64,
-    pct: f64,
-    flow_pct: f64,
-    dir: String,
-    early: String,
-    alpha: String,
-    pump_score: f64,
-    pump_label: String,
-    whale: bool,
-    whale_side: String,
-    whale_volume: f64,
-    whale_notional: f64,
-    total_score: f64,
-    analysis: String,
-    whale_pred_score: f64,
-    whale_pred_label: String,
-    reliability_score: f64,
-    reliability_label: String,
-    signal_type: String,
+/// "HH:MM" -> minuut van de dag.
+fn parse_hhmm(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct Top10Response {
-    best3: std::vec::Vec<TopRow>,
-    risers: std::vec::Vec<TopRow>,
-    fallers: std::vec::Vec<TopRow>,
+/// start == end betekent geen stille uren.
+fn in_quiet_window(minute: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        (start..end).contains(&minute)
+    } else {
+        minute >= start || minute < end
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct HeatmapPoint {
-    pair: String,
-    flow_pct: f64,
-    pump_score: f64,
-    ts: i64,
-    reliability_score: f64,
-}
+// Kanalen waar dispatch_alerts naar aflevert
+const ALERT_CHANNELS: &[&str] = &["dashboard"];
 
-#[derive(Debug, Clone, Serialize)]
-struct BacktestResult {
-    signal_type: String,
-    direction: String,
-    total_trades: usize,
-    winrate: f64,
-    avg_win: f64,
-    avg_loss: f64,
-    expectancy: f64,
-    pnl_sum: f64,
-    max_drawdown: f64,
-    best_trade: f64,
-    worst_trade: f64,
-    max_losing_streak: usize,
-    equity_curve: std::vec::Vec<f64>,
+/// Offset van de geconfigureerde timezone; leeg = lokale servertijd, onbekend = UTC.
+fn tz_offset_sec(timezone: &str, ts: i64) -> i32 {
+    if timezone.is_empty() {
+        return chrono::DateTime::from_timestamp(ts, 0)
+            .map(|d| d.with_timezone(&chrono::Local).offset().local_minus_utc())
+            .unwrap_or(0);
+    }
+    let Ok(tz) = timezone.parse::<chrono_tz::Tz>() else {
+        return 0;
+    };
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|d| d.with_timezone(&tz).offset().fix().local_minus_utc())
+        .unwrap_or(0)
 }
 
-const STARS_HISTORY_FILE: &str = "stars_history.json";
-
+/// Drempels en taal van de analysetekst (Row.analysis). language "auto" volgt
+/// `locale`: nl-* geeft Nederlands, elke andere locale Engels.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct StarsHistory {
-    history: std::vec::Vec<TopRow>,
-    dirty: bool,
+#[serde(default)]
+struct AnalysisSettings {
+    language: String,
+    max_chars: usize,
+    volume_decimals: usize,
+    strong_pct: f64,
+    mild_pct: f64,
+    strong_flow_pct: f64,
+    moderate_flow_pct: f64,
+    strong_pump_score: f64,
+    moderate_pump_score: f64,
+    positive_news: f64,
+    negative_news: f64,
 }
 
-// ============================================================================
-// HOOFDSTUK 5 – MANUAL TRADING MODULE (AANGEPAST)
-// ============================================================================
-
-const MANUAL_TRADES_FILE: &str = "manual_trades.json";
-const MANUAL_EQUITY_FILE: &str = "manual_trades_equity.json";
-const MANUAL_BASE_NOTIONAL: f64 = 100.0;
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        Self {
+            language: "auto".to_string(),
+            max_chars: 200,
+            volume_decimals: 2,
+            strong_pct: 5.0,
+            mild_pct: 1.0,
+            strong_flow_pct: 70.0,
+            moderate_flow_pct: 60.0,
+            strong_pump_score: 5.0,
+            moderate_pump_score: 2.0,
+            positive_news: 0.7,
+            negative_news: 0.3,
+        }
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ManualTrade {
-    pair: String,
-    entry_price: f64,
-    size: f64,
-    open_ts: i64,
-    stop_loss: f64,
-    take_profit: f64,
-    fee_pct: f64,
-    manual_amount: f64,
+impl AnalysisSettings {
+    fn language_for(&self, locale: &str) -> &'static str {
+        match self.language.as_str() {
+            "nl" => "nl",
+            "en" => "en",
+            _ if locale.is_empty() || locale.to_ascii_lowercase().starts_with("nl") => "nl",
+            _ => "en",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct TradeRecord {
-    pair: String,
-    entry_price: f64,
-    exit_price: f64,
-    size: f64,
-    pnl: f64,
-    open_ts: i64,
-    close_ts: i64,
-    reason: String,
+/// Voegt zinnen samen tot max_chars tekens; zinnen die niet meer passen vallen
+/// weg i.p.v. halverwege afgekapt te worden (alleen een te lange eerste zin wordt gekapt).
+fn join_within(parts: &[String], max_chars: usize) -> String {
+    let mut out = String::new();
+    let mut len = 0;
+    for part in parts {
+        let part_len = part.chars().count();
+        let sep = usize::from(!out.is_empty());
+        if len + sep + part_len > max_chars {
+            if out.is_empty() {
+                out = part.chars().take(max_chars).collect();
+            }
+            break;
+        }
+        if sep == 1 {
+            out.push(' ');
+        }
+        out.push_str(part);
+        len += sep + part_len;
+    }
+    out
 }
 
+/// Valutaconversie voor ranking en whale drempels over verschillende quotes heen
+/// (/api/fx). Koersen zijn "1 quote = rate base_currency".
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct ManualTraderState {
-    initial_balance: f64,
-    balance: f64,
-    trades: HashMap<String, ManualTrade>,
-    equity_curve: std::vec::Vec<(i64, f64)>,
+#[serde(default)]
+struct FxSettings {
+    base_currency: String,
+    // "kraken" = Kraken Ticker (quote/base of base/quote pair), "static" = alleen static_rates
+    source: String,
+    refresh_sec: u64,
+    // Handmatige koersen; ook fallback voor quotes waar Kraken geen pair voor heeft
+    static_rates: HashMap<String, f64>,
 }
 
-impl ManualTraderState {
-    fn new() -> Self {
+impl Default for FxSettings {
+    fn default() -> Self {
         Self {
-            initial_balance: VIRTUAL_INITIAL_BALANCE,
-            balance: VIRTUAL_INITIAL_BALANCE,
-            trades: HashMap::new(),
-            equity_curve: std::vec::Vec::new(),
+            base_currency: "EUR".to_string(),
+            source: "kraken".to_string(),
+            refresh_sec: 600,
+            static_rates: HashMap::new(),
         }
     }
+}
 
-    async fn load() -> Self {
-        match tokio::fs::read_to_string(MANUAL_TRADES_FILE).await {
-            Ok(content) => {
-                match serde_json::from_str(content.as_str()) {
-                    Ok(state) => state,
-                    Err(e) => {
-                        eprintln!("[WARN] Failed to parse {}: {}. Starting fresh.", MANUAL_TRADES_FILE, e);
-                        Self::new()
-                    }
-                }
-            }
-            Err(_) => Self::new(),
-        }
+fn whale_pred_rank(label: &str) -> u8 {
+    match label {
+        "HIGH" => 3,
+        "MEDIUM" => 2,
+        "LOW" => 1,
+        _ => 0,
     }
+}
 
-    async fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(self)?;
-        tokio::fs::write(MANUAL_TRADES_FILE, json).await?;
-        Ok(())
+impl StarsSettings {
+    fn qualifies(&self, whale_pred_label: &str, last_anom_ts: Option<i64>, now: i64) -> bool {
+        if whale_pred_rank(whale_pred_label) < whale_pred_rank(&self.min_whale_pred_label).max(1) {
+            return false;
+        }
+        !self.require_anom
+            || last_anom_ts.is_some_and(|at| (0..=self.anom_lookback_sec).contains(&(now - at)))
     }
+}
 
-    async fn save_equity(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(&self.equity_curve)?;
-        tokio::fs::write(MANUAL_EQUITY_FILE, json).await?;
-        Ok(())
-    }
+// Harde grens per sample buffer (recent_prices) en voor buys + sells samen per flow
+// venster: vangnet voor pairs met extreem veel trades binnen het tijdvenster
+const SAMPLE_BUFFER_CAP: usize = 20_000;
 
-    fn add_trade(&mut self, pair: &str, price: f64, sl_pct: f64, tp_pct: f64, fee_pct: f64, manual_amount: f64) -> bool {
-        if self.trades.contains_key(pair) {
-            return false;
-        }
-        let size = manual_amount / price;
-        let sl = price * (1.0 - sl_pct / 100.0);
-        let tp = price * (1.0 + tp_pct / 100.0);
-        let trade = ManualTrade {
-            pair: pair.to_string(),
-            entry_price: price,
-            size,
-            open_ts: chrono::Utc::now().timestamp(),
-            stop_loss: sl,
-            take_profit: tp,
-            fee_pct,
-            manual_amount,
-        };
-        self.trades.insert(pair.to_string(), trade);
-        println!(
-            "[MANUAL TRADE] OPEN {} at {:.5} size {:.5} amount {:.2} SL={:.5} TP={:.5} fee={:.2}%",
-            pair, price, size, manual_amount, sl, tp, fee_pct
-        );
-        true
+/// Evict vooraan alles ouder dan `cutoff` en alles boven SAMPLE_BUFFER_CAP. Gaat
+/// uit van (vrijwel) oplopende ts, zoals de trade stream die levert; een enkele
+/// oudere sample achter een nieuwere verdwijnt een trade later.
+fn evict_before(buf: &mut VecDeque<(f64, f64)>, cutoff: f64) {
+    while buf.front().is_some_and(|(x, _)| *x < cutoff) || buf.len() > SAMPLE_BUFFER_CAP {
+        buf.pop_front();
     }
+}
 
-    fn close_trade(&mut self, pair: &str, exit_price: f64) -> bool {
-        if let Some(trade) = self.trades.remove(pair) {
-            let pnl = (exit_price - trade.entry_price) * trade.size;
-            let fee_amount = pnl.abs() * (trade.fee_pct / 100.0);
-            let net_pnl = pnl - fee_amount;
-            self.balance += net_pnl;
-            let now = chrono::Utc::now().timestamp();
-            self.equity_curve.push((now, self.balance));
-            if self.equity_curve.len() > 365 {
-                self.equity_curve.remove(0);
-            }
-            println!(
-                "[MANUAL TRADE] CLOSED {} at {:.5} Gross PnL={:.2} Fee={:.2} Net PnL={:.2}",
-                pair, exit_price, pnl, fee_amount, net_pnl
-            );
-            true
+/// Zoals evict_before, maar SAMPLE_BUFFER_CAP geldt voor buys en sells samen. Bij een
+/// volle buffer valt de oudste sample van beide kanten weg, zodat het venster aan
+/// beide kanten even ver terugkijkt en de buy/sell verhouding niet scheef trekt.
+fn evict_flow_before(buys: &mut VecDeque<(f64, f64)>, sells: &mut VecDeque<(f64, f64)>, cutoff: f64) {
+    evict_before(buys, cutoff);
+    evict_before(sells, cutoff);
+    while buys.len() + sells.len() > SAMPLE_BUFFER_CAP {
+        let oldest_is_buy = match (buys.front(), sells.front()) {
+            (Some(b), Some(s)) => b.0 <= s.0,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        if oldest_is_buy {
+            buys.pop_front();
         } else {
-            false
+            sells.pop_front();
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct ManualTradeView {
-    pair: String,
-    entry_price: f64,
-    size: f64,
-    open_ts: i64,
-    stop_loss: f64,
-    take_profit: f64,
-    current_price: f64,
-    pnl_abs: f64,
-    pnl_pct: f64,
-    fee_pct: f64,
-    manual_amount: f64,
+fn push_bounded(buf: &mut VecDeque<(f64, f64)>, sample: (f64, f64), cutoff: f64) {
+    buf.push_back(sample);
+    evict_before(buf, cutoff);
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct ManualTradesResponse {
-    balance: f64,
-    initial_balance: f64,
-    trades: std::vec::Vec<ManualTradeView>,
+/// Voegt een trade toe aan het venster, gooit oude trades weg en geeft
+/// (flow_pct, richting) terug. `window` = (seconden, buy_cutoff, sell_cutoff).
+/// Fractie kooporders (0-1) over de samples vanaf `since`: op volume of op aantal
+/// trades; None zonder samples.
+fn buy_share<'a>(
+    buys: impl Iterator<Item = &'a (f64, f64)>,
+    sells: impl Iterator<Item = &'a (f64, f64)>,
+    since: f64,
+    by_count: bool,
+) -> Option<f64> {
+    let weight = |(ts, v): &&(f64, f64)| if *ts < since { 0.0 } else if by_count { 1.0 } else { *v };
+    let b: f64 = buys.map(|x| weight(&x)).sum();
+    let s: f64 = sells.map(|x| weight(&x)).sum();
+    (b + s > 0.0).then(|| b / (b + s))
 }
 
+fn update_flow_window(
+    buys: &mut VecDeque<(f64, f64)>,
+    sells: &mut VecDeque<(f64, f64)>,
+    ts: f64,
+    volume: f64,
+    is_buy: bool,
+    window: (f64, f64, f64),
+    by_count: bool,
+) -> (f64, String) {
+    let (window_sec, buy_cutoff, sell_cutoff) = window;
+    let cutoff = ts - window_sec;
+    if is_buy {
+        buys.push_back((ts, volume));
+    } else {
+        sells.push_back((ts, volume));
+    }
+    evict_flow_before(buys, sells, cutoff);
+
+    if let Some(f) = buy_share(buys.iter(), sells.iter(), cutoff, by_count) {
+        if f > buy_cutoff {
+            (f * 100.0, "BUY".to_string())
+        } else if f < sell_cutoff {
+            ((1.0 - f) * 100.0, "SELL".to_string())
+        } else {
+            (50.0, "NEUTR".to_string())
+        }
+    } else {
+        (50.0, "NEUTR".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct AppConfig {
+    pump_conf_threshold: f64,
+    // whale_pred score cutoffs: >= high -> HIGH, >= medium -> MEDIUM, >= low -> LOW
+    whale_pred_high_threshold: f64,
+    whale_pred_medium_threshold: f64,
+    whale_pred_low_threshold: f64,
+    // Early/Alpha flags: puur op total_score (bij BUY flow), los van het rating-label.
+    // Early = vroege opbouw, Alpha = sterke setup; Alpha impliceert Early (alpha >= early).
+    early_buy_threshold: f64,
+    alpha_buy_threshold: f64,
+    strong_buy_threshold: f64,
+    whale_min_notional: f64,
+    // Rolling window (sec) voor cumulatieve whale net flow
+    whale_flow_window_sec: f64,
+    // Trades met notional hieronder (dust) worden genegeerd in handle_trade
+    min_trade_notional: f64,
+    whale_tiers: WhaleTiers,
+    flow: FlowSettings,
+    pump: PumpSettings,
+    anomaly_strength_threshold: f64,
+    anomaly: AnomalySettings,
+    stars: StarsSettings,
+    wash: WashSettings,
+    analysis: AnalysisSettings,
+    divergence: DivergenceSettings,
+    trade_advice: TradeAdviceSettings,
+    // Quotes waarvan pairs gevolgd worden (alleen bij opstart gelezen)
+    quote_currencies: std::vec::Vec<String>,
+    // Weergavenaam per pair ("BTC/EUR" -> "Bitcoin"); de pair key zelf blijft ongewijzigd
+    pair_aliases: HashMap<String, String>,
+    fx: FxSettings,
+    flow_weight: f64,
+    price_weight: f64,
+    whale_weight: f64,
+    volume_weight: f64,
+    anomaly_weight: f64,
+    trend_weight: f64,
+    initial_balance: f64,
+    // Backtest: deel van de equity per trade bij de samengestelde curve (1.0 = alles)
+    backtest_position_fraction: f64,
+    base_notional: f64,
+    sl_pct: f64,
+    tp_pct: f64,
+    max_positions: usize,
+    // Minuten dat een pair na een verliesgevende close niet opnieuw geopend mag
+    // worden (manual en auto trader); 0 = uit
+    loss_cooldown_min: u64,
+    // Max grootte van één positie als % van de balance (100 = geen extra limiet)
+    max_position_pct: f64,
+    // Max notional per pair in account currency (0 = uit)
+    max_pair_exposure: f64,
+    // Paper fills (manual + auto): slippage in bps bovenop de fill prijs, en bij een
+    // vers boek (orderbook_fresh_sec) buys tegen de ask en sells tegen de bid
+    slippage_bps: f64,
+    fill_at_book: bool,
+    enable_trading: bool,
+    // Auto paper trader (ALPHA BUY) expliciet aanzetten; enable_trading alleen is niet genoeg
+    auto_trading_enabled: bool,
+    // Fee per kant (% van de notional) die de auto trader bij entry en exit rekent
+    auto_fee_pct: f64,
+    ws_workers_per_chunk: usize,
+    rest_scan_interval_sec: u64,
+    cleanup_interval_sec: u64,
+    // Retentie in run_cleanup: pairs zonder update, candles zonder trade, orderbooks
+    trade_retention_sec: i64,
+    candle_retention_sec: i64,
+    orderbook_retention_sec: i64,
+    // Boek telt mee in whale_score / whale_pred (en Row.orderbook_available) zolang het niet ouder is dan dit
+    orderbook_fresh_sec: i64,
+    // Aantal ruwe trades per pair in de tape (/api/tape)
+    tape_size: usize,
+    eval_horizon_sec: i64,
+    // Cap van de in-memory signals buffer; overflow gaat naar het archief
+    max_history: usize,
+    signal_archive_enabled: bool,
+    signal_archive_max_bytes: u64,
+    // EWMA smoothing-factor: nieuw = (1 - alpha) * oud + alpha * waarde.
+    // Hoger = reageert sneller op nieuwe trades, lager = gladder/trager.
+    ewma_alpha: f64,
+    // volume_score op notional (prijs*volume) t.o.v. ewma_notional i.p.v. base volume,
+    // zodat spikes vergelijkbaar zijn tussen dure en goedkope coins
+    volume_score_notional: bool,
+    default_dir_filter: String,
+    include_stablecoins_default: bool,
+    heatmap_min_radius: f64,
+    heatmap_max_radius: f64,
+    // Standaard auto-refresh interval van het dashboard (header selector kan dit overrulen)
+    chart_refresh_rate_sec: f64,
+    derivatives_enabled: bool,
+    derivatives_scan_interval_sec: u64,
+    // Pair is STALE als er langer dan dit geen update was
+    stale_threshold_sec: i64,
+    // Early/Alpha/whale en de signalled-markering tellen niet meer mee in snapshot/Top 10
+    // als er zo lang geen versterkende activiteit was (0 = nooit verlopen)
+    signal_expiry_sec: i64,
+    // Zonder update langer dan dit is reliability hard 0 ("STALE"), ongeacht de sub-scores
+    reliability_max_age_sec: i64,
+    // /api/stats?active_only=true: alleen pairs met rating of whale/pump/ANOM binnen dit venster
+    active_window_sec: i64,
+    // WS worker reconnect zodra deze fractie van zijn actieve pairs stale is
+    stale_reconnect_fraction: f64,
+    // Kraken WebSocket v2 (wss://ws.kraken.com/v2); v1 blijft fallback
+    kraken_ws_v2: bool,
+    // Ticker via WebSocket naar handle_ticker; de REST scanner pollt dan alleen pairs zonder verse WS ticker
+    ticker_ws: bool,
+    // Min. tijd tussen twee handle_ticker calls per pair vanuit de WS (jump/vol_ratio zijn per tick)
+    ticker_ws_min_interval_sec: i64,
+    // Trade, book en ticker over één WS verbinding per chunk (na herstart); uit = aparte verbindingen
+    ws_multiplex: bool,
+    event_log_enabled: bool,
+    event_log_max_bytes: u64,
+    // Alerts inbox ook naar alerts.jsonl schrijven (rotatie op event_log_max_bytes)
+    alerts_persist: bool,
+    // News sentiment per pair naar news_sentiment.json schrijven en bij start inladen
+    news_persist: bool,
+    news_persist_interval_sec: u64,
+    // Pair dat nieuws zonder herkenbare coin krijgt; leeg = MARKET (marktbrede gauge)
+    news_catch_all_pair: String,
+    quiet_hours: QuietHoursSettings,
+    // Per alert kanaal: zwijgen tijdens quiet hours? Ontbrekende kanalen niet
+    quiet_hours_channels: HashMap<String, bool>,
+    // Periodieke dump van alle markets rows naar snapshots/market_YYYY-MM-DD.jsonl
+    // voor analyse op lange termijn; retention = aantal dagbestanden dat bewaard blijft
+    market_snapshot_enabled: bool,
+    market_snapshot_interval_min: u64,
+    market_snapshot_retention: usize,
+    // Valuta voor account-bedragen (manual trader); pair-bedragen gebruiken de eigen quote
+    display_currency: String,
+    // Dashboard formattering: BCP 47 locale (bv. "nl-NL") en IANA tijdzone
+    // (bv. "Europe/Amsterdam"); leeg = instelling van de browser
+    locale: String,
+    timezone: String,
+    // "error" | "warn" | "info" | "debug"; WHALE_RADAR_LOG env var heeft voorrang
+    log_level: String,
+    // TTL (ms) van de snapshot/top10 cache; 0 = altijd herberekenen
+    snapshot_cache_ttl_ms: u64,
+    // Aantal rijen in Best N (conviction) en Top N stijgers/dalers
+    best_n: usize,
+    top_n: usize,
+    // "multiply" | "gate" | "off"; bepaalt Best 3 via de conviction score
+    conviction_mode: String,
+    conviction_min_reliability: f64,
+    ai_success_threshold: f64,
+    ai_adjustment_step_up: f64,
+    ai_adjustment_step_down: f64,
+    ai_max_weight: f64,
+    // Per signaaltype aan/uit; ontbrekende types staan aan
+    enabled_signal_types: HashMap<String, bool>,
+    // Signalen onder deze reliability (0-100) worden niet uitgezonden; 0 = uit
+    min_reliability_for_signal: f64,
+    // Per signaaltype: valt het onder de reliability gate? Ontbrekende types wel
+    reliability_gate_types: HashMap<String, bool>,
+    // Per signaaltype de self-eval horizon (sec) voor ret_5m en weight learning;
+    // ontbrekende types erven eval_horizon_sec (standaard leeg: alles erft)
+    eval_horizon_by_type: HashMap<String, i64>,
+    // EARLY/ALPHA alleen evalueren bij de close van een bar van signal_candle_sec
+    // i.p.v. op elke trade; WHALE/ANOM e.d. blijven event-driven
+    signal_on_candle_close: bool,
+    signal_candle_sec: i64,
+}
+
+// MEGA_PUMP vraagt pump_conf >= pump_conf_threshold + deze marge (default 0.7 + 0.2)
+const PUMP_CONF_MEGA_MARGIN: f64 = 0.2;
+
+const SIGNAL_TYPES: &[&str] = &["WHALE", "WH_PRED", "EARLY", "ALPHA", "EARLY_PUMP", "MEGA_PUMP", "ANOM", "DIVERGENCE"];
+
+/// True als de laatste versterkende activiteit ouder is dan ttl_sec (ttl 0 = nooit).
+fn signal_expired(last_active_ts: i64, now: i64, ttl_sec: i64) -> bool {
+    ttl_sec > 0 && now.saturating_sub(last_active_ts) > ttl_sec
+}
+
+fn pair_display_name(aliases: &HashMap<String, String>, pair: &str) -> String {
+    aliases.get(pair).map(|a| a.trim().to_string()).unwrap_or_else(|| pair.to_string())
+}
+
+impl AppConfig {
+    fn signal_type_enabled(&self, signal_type: &str) -> bool {
+        self.enabled_signal_types.get(signal_type).copied().unwrap_or(true)
+    }
+
+    fn eval_horizon_for(&self, signal_type: &str) -> i64 {
+        self.eval_horizon_by_type.get(signal_type).copied().unwrap_or(self.eval_horizon_sec)
+    }
+
+    fn passes_reliability_gate(&self, signal_type: &str, reliability_score: f64) -> bool {
+        let gated = self.reliability_gate_types.get(signal_type).copied().unwrap_or(true);
+        !gated || reliability_score >= self.min_reliability_for_signal
+    }
+
+    fn loss_cooldown_sec(&self) -> i64 {
+        self.loss_cooldown_min as i64 * 60
+    }
+
+    /// Grootste toegestane notional voor een nieuwe positie bij deze balance:
+    /// max_position_pct van de balance, en niet boven max_pair_exposure (als > 0).
+    fn position_cap(&self, balance: f64) -> f64 {
+        let cap = balance * self.max_position_pct / 100.0;
+        if self.max_pair_exposure > 0.0 {
+            cap.min(self.max_pair_exposure)
+        } else {
+            cap
+        }
+    }
+
+    /// "multiply": score * reliability/100, "gate": 0 onder conviction_min_reliability,
+    /// anders ("off") de ongewijzigde score.
+    fn conviction(&self, score: f64, reliability_score: f64) -> f64 {
+        let rel = (reliability_score / 100.0).clamp(0.0, 1.0);
+        match self.conviction_mode.as_str() {
+            "multiply" => score * rel,
+            "gate" => {
+                if reliability_score >= self.conviction_min_reliability {
+                    score
+                } else {
+                    0.0
+                }
+            }
+            _ => score,
+        }
+    }
+
+    /// Basis sanity-checks voordat een config wordt toegepast (form of import).
+    fn validate(&self) -> Result<(), std::vec::Vec<String>> {
+        let mut errors = std::vec::Vec::new();
+        let mut check = |ok: bool, msg: &str| {
+            if !ok {
+                errors.push(msg.to_string());
+            }
+        };
+
+        for (name, v) in [
+            ("flow_weight", self.flow_weight),
+            ("price_weight", self.price_weight),
+            ("whale_weight", self.whale_weight),
+            ("volume_weight", self.volume_weight),
+            ("anomaly_weight", self.anomaly_weight),
+            ("trend_weight", self.trend_weight),
+            ("whale_min_notional", self.whale_min_notional),
+            ("min_trade_notional", self.min_trade_notional),
+            ("anomaly_strength_threshold", self.anomaly_strength_threshold),
+            ("anomaly.jump_weight", self.anomaly.jump_weight),
+            ("anomaly.day_ret_weight", self.anomaly.day_ret_weight),
+            ("anomaly.vol_ratio_weight", self.anomaly.vol_ratio_weight),
+            ("anomaly.ewma_return_weight", self.anomaly.ewma_return_weight),
+            ("anomaly.min_jump_pct", self.anomaly.min_jump_pct),
+            ("anomaly.min_vol_ratio", self.anomaly.min_vol_ratio),
+        ] {
+            check(v.is_finite() && v >= 0.0, &format!("{} moet >= 0 zijn", name));
+        }
+        check(self.anomaly.cooldown_sec >= 0, "anomaly.cooldown_sec moet >= 0 zijn");
+        check(
+            whale_pred_rank(&self.stars.min_whale_pred_label) > 0,
+            "stars.min_whale_pred_label moet LOW, MEDIUM of HIGH zijn",
+        );
+        check(self.stars.anom_lookback_sec > 0, "stars.anom_lookback_sec moet > 0 zijn");
+        check(self.stars.history_dedup_sec >= 0, "stars.history_dedup_sec moet >= 0 zijn");
+        check(self.wash.window_sec > 0.0, "wash.window_sec moet > 0 zijn");
+        check(
+            (0.5..=60.0).contains(&self.chart_refresh_rate_sec),
+            "chart_refresh_rate_sec moet tussen 0.5 en 60 liggen",
+        );
+        check(self.divergence.window_sec >= 30.0, "divergence.window_sec moet >= 30 zijn");
+        check(
+            matches!(self.analysis.language.as_str(), "auto" | "nl" | "en"),
+            "analysis.language moet auto, nl of en zijn",
+        );
+        check(self.analysis.max_chars >= 20, "analysis.max_chars moet >= 20 zijn");
+        check(self.analysis.volume_decimals <= 10, "analysis.volume_decimals moet <= 10 zijn");
+        check(
+            self.analysis.mild_pct <= self.analysis.strong_pct
+                && self.analysis.moderate_flow_pct <= self.analysis.strong_flow_pct
+                && self.analysis.moderate_pump_score <= self.analysis.strong_pump_score
+                && self.analysis.negative_news <= self.analysis.positive_news,
+            "analysis: matige drempels moeten <= sterke drempels zijn",
+        );
+        check(
+            self.divergence.min_flow_delta > 0.0 && self.divergence.min_flow_delta <= 100.0,
+            "divergence.min_flow_delta moet in (0, 100] liggen",
+        );
+        check(
+            self.divergence.max_stall_pct >= 0.0 && self.divergence.min_price_rise_pct > 0.0,
+            "divergence.max_stall_pct moet >= 0 en min_price_rise_pct > 0 zijn",
+        );
+        check(self.divergence.cooldown_sec >= 0, "divergence.cooldown_sec moet >= 0 zijn");
+        check(
+            (0.0..1.0).contains(&self.wash.volume_tolerance),
+            "wash.volume_tolerance moet in [0, 1) liggen",
+        );
+        check(self.wash.min_round_trips >= 1, "wash.min_round_trips moet >= 1 zijn");
+        check(
+            (0.0..100.0).contains(&self.trade_advice.entry_pct),
+            "trade_advice.entry_pct moet in [0, 100) liggen",
+        );
+        let exits = self.trade_advice.exit_pcts();
+        check(
+            exits[0] > 0.0 && exits.windows(2).all(|w| w[0] < w[1]),
+            "trade_advice.exit_pct_1..4 moeten > 0 en oplopend zijn",
+        );
+        check(
+            (0.0..=1.0).contains(&self.wash.reliability_penalty),
+            "wash.reliability_penalty moet tussen 0 en 1 liggen",
+        );
+        check(
+            matches!(self.anomaly.direction.as_str(), "BOTH" | "BUY" | "SELL"),
+            "anomaly.direction moet BOTH, BUY of SELL zijn",
+        );
+        check((0.0..=1.0).contains(&self.pump_conf_threshold), "pump_conf_threshold moet tussen 0 en 1 liggen");
+        check(
+            0.0 <= self.whale_pred_low_threshold
+                && self.whale_pred_low_threshold <= self.whale_pred_medium_threshold
+                && self.whale_pred_medium_threshold <= self.whale_pred_high_threshold
+                && self.whale_pred_high_threshold <= 10.0,
+            "whale_pred drempels moeten 0 <= low <= medium <= high <= 10 zijn",
+        );
+        check(self.ewma_alpha > 0.0 && self.ewma_alpha <= 1.0, "ewma_alpha moet in (0, 1] liggen");
+        check(self.initial_balance > 0.0, "initial_balance moet > 0 zijn");
+        check(
+            self.backtest_position_fraction > 0.0 && self.backtest_position_fraction <= 1.0,
+            "backtest_position_fraction moet in (0, 1] liggen",
+        );
+        check(self.base_notional > 0.0, "base_notional moet > 0 zijn");
+        check(self.sl_pct > 0.0 && self.sl_pct < 1.0, "sl_pct moet in (0, 1) liggen");
+        check(self.tp_pct > 0.0, "tp_pct moet > 0 zijn");
+        check(self.max_positions >= 1, "max_positions moet >= 1 zijn");
+        check(
+            self.max_position_pct > 0.0 && self.max_position_pct <= 100.0,
+            "max_position_pct moet in (0, 100] liggen",
+        );
+        check(
+            self.max_pair_exposure.is_finite() && self.max_pair_exposure >= 0.0,
+            "max_pair_exposure moet >= 0 zijn",
+        );
+        check(self.ws_workers_per_chunk >= 1, "ws_workers_per_chunk moet >= 1 zijn");
+        check(self.eval_horizon_sec > 0, "eval_horizon_sec moet > 0 zijn");
+        check(
+            self.alpha_buy_threshold >= self.early_buy_threshold,
+            "alpha_buy_threshold moet >= early_buy_threshold zijn",
+        );
+        check(self.whale_flow_window_sec > 0.0, "whale_flow_window_sec moet > 0 zijn");
+        check(self.cleanup_interval_sec > 0, "cleanup_interval_sec moet > 0 zijn");
+        check(self.trade_retention_sec > 0, "trade_retention_sec moet > 0 zijn");
+        check(self.candle_retention_sec > 0, "candle_retention_sec moet > 0 zijn");
+        check(self.orderbook_retention_sec > 0, "orderbook_retention_sec moet > 0 zijn");
+        check(self.orderbook_fresh_sec > 0, "orderbook_fresh_sec moet > 0 zijn");
+        check((1..=1000).contains(&self.tape_size), "tape_size moet tussen 1 en 1000 liggen");
+        check((0.0..=1000.0).contains(&self.slippage_bps), "slippage_bps moet tussen 0 en 1000 liggen");
+        check((0.0..=5.0).contains(&self.auto_fee_pct), "auto_fee_pct moet tussen 0 en 5 liggen");
+        check(self.max_history > 0, "max_history moet > 0 zijn");
+        check((1..=TOP_N_MAX).contains(&self.best_n), "best_n moet tussen 1 en 100 liggen");
+        check((1..=TOP_N_MAX).contains(&self.top_n), "top_n moet tussen 1 en 100 liggen");
+        check(
+            self.min_trade_notional < self.whale_min_notional,
+            "min_trade_notional moet kleiner zijn dan whale_min_notional",
+        );
+        check(
+            parse_log_level(&self.log_level).is_some(),
+            "log_level moet error, warn, info of debug zijn",
+        );
+        check(self.stale_threshold_sec > 0, "stale_threshold_sec moet > 0 zijn");
+        check(self.signal_expiry_sec >= 0, "signal_expiry_sec moet >= 0 zijn");
+        check(self.reliability_max_age_sec > 0, "reliability_max_age_sec moet > 0 zijn");
+        check(self.ticker_ws_min_interval_sec >= 0, "ticker_ws_min_interval_sec moet >= 0 zijn");
+        check(self.market_snapshot_interval_min >= 1, "market_snapshot_interval_min moet >= 1 zijn");
+        check(
+            (5..=3600).contains(&self.news_persist_interval_sec),
+            "news_persist_interval_sec moet tussen 5 en 3600 liggen",
+        );
+        check(self.market_snapshot_retention >= 1, "market_snapshot_retention moet >= 1 zijn");
+        check(
+            (0.0..=100.0).contains(&self.min_reliability_for_signal),
+            "min_reliability_for_signal moet tussen 0 en 100 liggen",
+        );
+        check(self.signal_candle_sec >= 10, "signal_candle_sec moet >= 10 zijn");
+        check(
+            self.eval_horizon_by_type.values().all(|h| *h >= 60),
+            "eval_horizon_by_type moet per type >= 60 zijn",
+        );
+        check(!self.quote_currencies.is_empty(), "quote_currencies mag niet leeg zijn");
+        check(
+            self.news_catch_all_pair.is_empty()
+                || self.news_catch_all_pair.split_once('/').is_some_and(|(b, q)| !b.is_empty() && !q.is_empty()),
+            "news_catch_all_pair moet leeg of een BASE/QUOTE pair zijn",
+        );
+        for (pair, alias) in &self.pair_aliases {
+            check(
+                pair.split_once('/').is_some_and(|(b, q)| !b.is_empty() && !q.is_empty()),
+                &format!("pair_aliases: '{}' is geen BASE/QUOTE pair", pair),
+            );
+            check(
+                !alias.trim().is_empty() && alias.chars().count() <= 40,
+                &format!("pair_aliases.{}: alias moet 1-40 tekens zijn", pair),
+            );
+        }
+        check(
+            parse_hhmm(&self.quiet_hours.start).is_some() && parse_hhmm(&self.quiet_hours.end).is_some(),
+            "quiet_hours.start/end moeten HH:MM zijn",
+        );
+        check(!self.fx.base_currency.trim().is_empty(), "fx.base_currency mag niet leeg zijn");
+        check(
+            matches!(self.fx.source.as_str(), "kraken" | "static"),
+            "fx.source moet kraken of static zijn",
+        );
+        check(self.fx.refresh_sec >= 60, "fx.refresh_sec moet >= 60 zijn");
+        check(
+            self.fx.static_rates.values().all(|r| *r > 0.0),
+            "fx.static_rates moeten > 0 zijn",
+        );
+        check(
+            self.stale_reconnect_fraction > 0.0 && self.stale_reconnect_fraction <= 1.0,
+            "stale_reconnect_fraction moet in (0, 1] liggen",
+        );
+        check(self.ai_adjustment_step_up >= 1.0, "ai_adjustment_step_up moet >= 1 zijn");
+        check(
+            self.ai_adjustment_step_down > 0.0 && self.ai_adjustment_step_down <= 1.0,
+            "ai_adjustment_step_down moet in (0, 1] liggen",
+        );
+        check(!self.display_currency.trim().is_empty(), "display_currency mag niet leeg zijn");
+        check(
+            self.locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+            "locale moet een BCP 47 tag zijn (bv. nl-NL)",
+        );
+        check(
+            self.timezone.is_empty() || self.timezone.parse::<chrono_tz::Tz>().is_ok(),
+            "timezone moet een IANA naam zijn (bv. Europe/Amsterdam)",
+        );
+
+        let fl = &self.flow;
+        for (name, window, buy, sell) in [
+            ("short", fl.short_window_sec, fl.short_buy_cutoff, fl.short_sell_cutoff),
+            ("medium", fl.medium_window_sec, fl.medium_buy_cutoff, fl.medium_sell_cutoff),
+            ("long", fl.long_window_sec, fl.long_buy_cutoff, fl.long_sell_cutoff),
+        ] {
+            check(window > 0.0, &format!("flow.{}_window_sec moet > 0 zijn", name));
+            check(
+                (0.0..=1.0).contains(&sell) && (0.0..=1.0).contains(&buy) && sell < buy,
+                &format!("flow.{} cutoffs: 0 <= sell < buy <= 1", name),
+            );
+        }
+        check(matches!(fl.basis.as_str(), "volume" | "count"), "flow.basis moet volume of count zijn");
+        let p = &self.pump;
+        check(
+            [
+                p.ret_5s_threshold, p.ret_5s_mult, p.ret_30s_threshold, p.ret_30s_mult,
+                p.ret_120s_threshold, p.ret_120s_mult, p.flow_threshold, p.flow_mult,
+                p.flow_5m_threshold, p.flow_5m_mult, p.vol_ratio_threshold, p.vol_ratio_mult, p.whale_mult,
+            ]
+            .iter()
+            .all(|v| v.is_finite() && *v >= 0.0),
+            "pump: drempels en multipliers moeten >= 0 zijn",
+        );
+        check(p.max_score > 0.0 && p.max_score <= 100.0, "pump.max_score moet in (0, 100] liggen");
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            pump_conf_threshold: 0.7,
+            whale_pred_high_threshold: 7.0,
+            whale_pred_medium_threshold: 4.0,
+            whale_pred_low_threshold: 2.0,
+            early_buy_threshold: 2.2,
+            alpha_buy_threshold: 5.0,
+            strong_buy_threshold: 5.0,
+            whale_min_notional: 5000.0,
+            whale_flow_window_sec: 60.0,
+            min_trade_notional: 1.0,
+            whale_tiers: WhaleTiers::default(),
+            flow: FlowSettings::default(),
+            pump: PumpSettings::default(),
+            anomaly_strength_threshold: 40.0,
+            anomaly: AnomalySettings::default(),
+            stars: StarsSettings::default(),
+            wash: WashSettings::default(),
+            analysis: AnalysisSettings::default(),
+            divergence: DivergenceSettings::default(),
+            trade_advice: TradeAdviceSettings::default(),
+            quote_currencies: vec!["EUR".to_string()],
+            pair_aliases: HashMap::new(),
+            fx: FxSettings::default(),
+            flow_weight: 2.2,
+            price_weight: 0.7,
+            whale_weight: 1.4,
+            volume_weight: 1.3,
+            anomaly_weight: 1.5,
+            trend_weight: 1.1,
+            initial_balance: VIRTUAL_INITIAL_BALANCE,
+            backtest_position_fraction: 1.0,
+            base_notional: VIRTUAL_BASE_NOTIONAL,
+            sl_pct: VIRTUAL_SL_PCT,
+            tp_pct: VIRTUAL_TP_PCT,
+            max_positions: VIRTUAL_MAX_POSITIONS,
+            loss_cooldown_min: 0,
+            max_position_pct: 100.0,
+            max_pair_exposure: 0.0,
+            slippage_bps: 5.0,
+            fill_at_book: true,
+            enable_trading: true,
+            auto_trading_enabled: false,
+            auto_fee_pct: 0.26,
+            ws_workers_per_chunk: 20,
+            rest_scan_interval_sec: 20,
+            cleanup_interval_sec: 600,
+            trade_retention_sec: 12 * 3600,
+            candle_retention_sec: 24 * 3600,
+            orderbook_retention_sec: 60,
+            orderbook_fresh_sec: 10,
+            tape_size: 50,
+            eval_horizon_sec: 300,
+            max_history: 2000,
+            signal_archive_enabled: true,
+            signal_archive_max_bytes: 50_000_000,
+            ewma_alpha: 0.1,
+            volume_score_notional: false,
+            default_dir_filter: "ALL".to_string(),
+            include_stablecoins_default: true,
+            heatmap_min_radius: 4.0,
+            heatmap_max_radius: 12.0,
+            chart_refresh_rate_sec: 1.0,
+            derivatives_enabled: false,
+            derivatives_scan_interval_sec: 60,
+            stale_threshold_sec: 300,
+            signal_expiry_sec: 900,
+            reliability_max_age_sec: 300,
+            active_window_sec: 300,
+            stale_reconnect_fraction: 0.8,
+            kraken_ws_v2: false,
+            ticker_ws: true,
+            ticker_ws_min_interval_sec: 5,
+            ws_multiplex: false,
+            event_log_enabled: false,
+            event_log_max_bytes: 10_000_000,
+            alerts_persist: false,
+            news_persist: true,
+            news_persist_interval_sec: 60,
+            news_catch_all_pair: String::new(),
+            quiet_hours: QuietHoursSettings::default(),
+            quiet_hours_channels: ALERT_CHANNELS.iter().map(|c| (c.to_string(), true)).collect(),
+            market_snapshot_enabled: false,
+            market_snapshot_interval_min: 15,
+            market_snapshot_retention: 14,
+            display_currency: "EUR".to_string(),
+            locale: String::new(),
+            timezone: String::new(),
+            log_level: "info".to_string(),
+            snapshot_cache_ttl_ms: 500,
+            best_n: 3,
+            top_n: 10,
+            conviction_mode: "multiply".to_string(),
+            conviction_min_reliability: 50.0,
+            ai_success_threshold: 0.7,
+            ai_adjustment_step_up: 1.02,
+            ai_adjustment_step_down: 0.98,
+            ai_max_weight: 5.0,
+            enabled_signal_types: SIGNAL_TYPES.iter().map(|t| (t.to_string(), true)).collect(),
+            min_reliability_for_signal: 30.0,
+            // WHALE prints zijn op zich al informatief; ANOM komt van REST zonder trade-historie
+            reliability_gate_types: SIGNAL_TYPES
+                .iter()
+                .map(|t| (t.to_string(), !matches!(*t, "WHALE" | "ANOM")))
+                .collect(),
+            eval_horizon_by_type: HashMap::new(),
+            signal_on_candle_close: false,
+            signal_candle_sec: 60,
+        }
+    }
+}
+
+const CONFIG_FILE: &str = "config.json";
+
+fn ewma(prev: f64, value: f64, alpha: f64) -> f64 {
+    let a = alpha.clamp(0.0, 1.0);
+    (1.0 - a) * prev + a * value
+}
+
+/// Oude defaults die nog in bestaande config.json staan. Een groep wordt alleen
+/// gemigreerd als alle velden nog de oude waarde hebben, zodat een bewust gekozen
+/// combinatie (en de onderlinge validatie) intact blijft. (veld, oud, nieuw)
+const CONFIG_DEFAULT_MIGRATIONS: &[&[(&str, f64, f64)]] = &[
+    // Early/Alpha los van het rating-label: oude defaults lieten bijna niets door
+    &[("early_buy_threshold", 3.0, 2.2), ("alpha_buy_threshold", 7.5, 5.0)],
+    // HIGH cutoff was hardcoded 7.0; de ongebruikte config default stond op 8.0
+    &[("whale_pred_high_threshold", 8.0, 7.0)],
+];
+
+/// Zet oude defaults in een ingelezen config om naar de huidige; geeft de
+/// gewijzigde velden terug als "veld: oud -> nieuw".
+fn migrate_config_defaults(raw: &mut Value) -> std::vec::Vec<String> {
+    let mut migrated = std::vec::Vec::new();
+    let Some(obj) = raw.as_object_mut() else {
+        return migrated;
+    };
+    for group in CONFIG_DEFAULT_MIGRATIONS {
+        if !group.iter().all(|(k, old, _)| obj.get(*k).and_then(|v| v.as_f64()) == Some(*old)) {
+            continue;
+        }
+        for (k, old, new) in group.iter() {
+            obj.insert(k.to_string(), serde_json::json!(new));
+            migrated.push(format!("{}: {} -> {}", k, old, new));
+        }
+    }
+    // eval_horizon_by_type stond vooraf ingevuld met 300 voor elk type; dan erft
+    // niets eval_horizon_sec. Precies die map betekent "niet ingesteld".
+    let prefilled = obj.get("eval_horizon_by_type").and_then(|v| v.as_object()).is_some_and(|m| {
+        m.len() == SIGNAL_TYPES.len()
+            && SIGNAL_TYPES
+                .iter()
+                .all(|t| m.get(*t).and_then(|v| v.as_i64()) == Some(SELF_EVAL_HORIZON_SEC))
+    });
+    if prefilled {
+        obj.insert("eval_horizon_by_type".to_string(), serde_json::json!({}));
+        migrated.push("eval_horizon_by_type: vooraf ingevuld -> leeg (erft eval_horizon_sec)".to_string());
+    }
+    migrated
+}
+
+async fn load_config() -> AppConfig {
+    match tokio::fs::read_to_string(CONFIG_FILE).await {
+        Ok(content) => {
+            let Ok(mut raw) = serde_json::from_str::<Value>(&content) else {
+                return AppConfig::default();
+            };
+            let migrated = migrate_config_defaults(&mut raw);
+            let cfg: AppConfig = serde_json::from_value(raw).unwrap_or_default();
+            if !migrated.is_empty() {
+                log_info!("[CONFIG] oude defaults gemigreerd: {}", migrated.join(", "));
+                if let Err(e) = save_config(&cfg).await {
+                    log_warn!("[WARN] Kon gemigreerde config niet opslaan: {}", e);
+                }
+            }
+            cfg
+        }
+        Err(_) => {
+            let default = AppConfig::default();
+            if let Ok(json) = serde_json::to_string_pretty(&default) {
+                let _ = tokio::fs::write(CONFIG_FILE, json).await;
+            }
+            default
+        }
+    }
+}
+
+/// Eén gewijzigd config veld; geneste secties als dotted pad (bv. "flow.short_window_sec").
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ConfigChange {
+    field: String,
+    old: serde_json::Value,
+    new: serde_json::Value,
+}
+
+fn diff_json(prefix: &str, old: &serde_json::Value, new: &serde_json::Value, out: &mut std::vec::Vec<ConfigChange>) {
+    match (old, new) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            let keys: std::collections::BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let field = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                let null = serde_json::Value::Null;
+                diff_json(&field, a.get(key).unwrap_or(&null), b.get(key).unwrap_or(&null), out);
+            }
+        }
+        _ if old != new => out.push(ConfigChange {
+            field: prefix.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Veld-voor-veld verschil tussen de actieve en de nieuwe config (arrays als geheel).
+fn config_diff(old: &AppConfig, new: &AppConfig) -> std::vec::Vec<ConfigChange> {
+    let mut out = std::vec::Vec::new();
+    diff_json(
+        "",
+        &serde_json::to_value(old).unwrap_or_default(),
+        &serde_json::to_value(new).unwrap_or_default(),
+        &mut out,
+    );
+    out
+}
+
+async fn save_config(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(config)?;
+    tokio::fs::write(CONFIG_FILE, json).await?;
+    Ok(())
+}
+
+// ============================================================================
+// HOOFDSTUK 2 – BAYESIAANS AI / ZELFLEREND SYSTEEM
 // ============================================================================
-// HOOFDSTUK 6 – ENGINE (HART VAN HET SYSTEEM)
-// ============================================================================
 
-#[derive(Clone)]
-struct Engine {
-    trades: Arc<DashMap<String, TradeState>>,
-    candles: Arc<DashMap<String, CandleState>>,
-    tickers: Arc<DashMap<String, TickerState>>,
-    orderbooks: Arc<DashMap<String, OrderbookState>>,
-    signals: Arc<Mutex<std::vec::Vec<SignalEvent>>>,
-    signalled_pairs: Arc<DashMap<String, bool>>,
-    weights: Arc<Mutex<ScoreWeights>>,
-    manual_trader: Arc<Mutex<ManualTraderState>>,
-    news_sentiment: Arc<DashMap<String, (f64, i64, String)>>,
-    stars_history: Arc<Mutex<StarsHistory>>,
-}
+const SIGNAL_FILE: &str = "signals.json";
+const MAX_HISTORY: usize = 20;
+// Aantal (ts, threshold, winrate) punten per signaaltype voor /api/signal_stats/history
+const SIGNAL_STATS_HISTORY_MAX: usize = 500;
+
+const VIRTUAL_INITIAL_BALANCE: f64 = 10_000.0;
+const VIRTUAL_BASE_NOTIONAL: f64 = 100.0;
+const VIRTUAL_MAX_POSITIONS: usize = 5;
+const VIRTUAL_SL_PCT: f64 = 0.02;
+const VIRTUAL_TP_PCT: f64 = 0.05;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SignalStatsPoint {
+    ts: i64,
+    threshold: f64,
+    winrate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SignalStats {
+    wins: u32,
+    losses: u32,
+    threshold: f64,
+    last_updated: Option<chrono::DateTime<chrono::Utc>>,
+    profit_history: std::vec::Vec<f64>,
+    // Verloop van threshold en winrate na elke update (begrensd)
+    #[serde(default)]
+    history: std::vec::Vec<SignalStatsPoint>,
+}
+
+impl SignalStats {
+    fn new(threshold: f64) -> Self {
+        Self {
+            wins: 0,
+            losses: 0,
+            threshold,
+            last_updated: None,
+            profit_history: std::vec::Vec::new(),
+            history: std::vec::Vec::new(),
+        }
+    }
+
+    fn winrate(&self) -> f64 {
+        let total = self.wins + self.losses;
+        if total > 0 { self.wins as f64 / total as f64 * 100.0 } else { 0.0 }
+    }
+
+    fn update(&mut self, profit: f64) {
+        if profit > 0.0 { self.wins += 1; } else { self.losses += 1; }
+        self.profit_history.push(profit);
+        if self.profit_history.len() > MAX_HISTORY {
+            self.profit_history.remove(0);
+        }
+
+        let total = (self.wins + self.losses) as f64;
+        let p_success = (self.wins as f64 + 1.0) / (total + 2.0);
+        let recent_avg: f64 = if !self.profit_history.is_empty() {
+            self.profit_history.iter().sum::<f64>() / self.profit_history.len() as f64
+        } else { 0.0 };
+
+        if p_success > 0.7 && recent_avg > 0.0 && self.threshold > 0.1 {
+            self.threshold -= 0.015;
+        } else if p_success < 0.5 && recent_avg < 0.0 && self.threshold < 0.99 {
+            self.threshold += 0.015;
+        }
+
+        self.threshold = self.threshold.clamp(0.1, 0.99);
+        self.last_updated = Some(Utc::now());
+        self.history.push(SignalStatsPoint {
+            ts: Utc::now().timestamp(),
+            threshold: self.threshold,
+            winrate: self.winrate(),
+        });
+        if self.history.len() > SIGNAL_STATS_HISTORY_MAX {
+            let n = self.history.len() - SIGNAL_STATS_HISTORY_MAX;
+            self.history.drain(0..n);
+        }
+        log_debug!("[AI] Threshold {:.3} | success={:.2} | trend={:.4}", self.threshold, p_success, recent_avg);
+    }
+}
+
+async fn load_signal_stats() -> HashMap<String, SignalStats> {
+    match tokio::fs::read_to_string(SIGNAL_FILE).await {
+        Ok(content) => serde_json::from_str(content.as_str()).unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+async fn save_signal_stats(map: &HashMap<String, SignalStats>) {
+    if let Ok(json) = serde_json::to_string_pretty(map) {
+        if let Err(e) = tokio::fs::write(SIGNAL_FILE, json).await {
+            log_error!("[ERR] Kon signals.json niet opslaan: {}", e);
+        }
+    }
+}
+
+// ============================================================================
+// HOOFDSTUK 3 – CORE DATA STRUCTUREN
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct TradeState {
+    buy_volume: f64,
+    sell_volume: f64,
+    trade_count: u64,
+    ewma_trade_size: Option<f64>,
+    ewma_notional: Option<f64>,
+    ewma_volume: Option<f64>,
+    last_whale: bool,
+    // Laatste whale print en laatste trade met een pump label (active_window_sec)
+    #[serde(default)]
+    last_whale_ts: i64,
+    #[serde(default)]
+    last_pump_ts: i64,
+    // Laatste trade met een whale of Early/Alpha BUY (basis voor signal_expiry_sec)
+    #[serde(default)]
+    signal_active_ts: i64,
+    last_whale_side: Option<String>,
+    last_whale_volume: Option<f64>,
+    last_whale_notional: Option<f64>,
+    // Whale prints binnen whale_flow_window_sec: (ts, notional), buy positief / sell negatief
+    #[serde(default)]
+    recent_whales: std::vec::Vec<(f64, f64)>,
+    last_early: Option<String>,
+    last_alpha: Option<String>,
+    last_score: f64,
+    last_rating: Option<String>,
+    last_flow_pct: f64,
+    last_dir: String,
+    recent_buys: VecDeque<(f64, f64)>,
+    recent_sells: VecDeque<(f64, f64)>,
+    recent_buys_5m: VecDeque<(f64, f64)>,
+    recent_sells_5m: VecDeque<(f64, f64)>,
+    last_flow_pct_5m: f64,
+    last_dir_5m: String,
+    recent_buys_long: VecDeque<(f64, f64)>,
+    recent_sells_long: VecDeque<(f64, f64)>,
+    last_flow_pct_long: f64,
+    last_dir_long: String,
+    recent_prices: VecDeque<(f64, f64)>,
+    // Laatste prijs per minuut (ts, prijs), ~1u historie voor 5m/1h changes
+    #[serde(default)]
+    minute_closes: std::vec::Vec<(f64, f64)>,
+    last_pump_score: f64,
+    #[serde(default)]
+    last_pump_conf: f64,
+    last_pump_signal: Option<String>,
+    whale_pred_score: f64,
+    whale_pred_label: Option<String>,
+    last_update_ts: i64,
+    // Alleen WS trades (last_update_ts wordt ook door de REST ticker gezet)
+    last_trade_ts: i64,
+    // Grootste trade sinds de laatste candle-reset (blijft staan, anders dan last_whale_*)
+    max_notional_seen: f64,
+    max_notional_ts: i64,
+    max_notional_side: Option<String>,
+    news_sentiment: f64,
+    recent_anom: bool,
+    // Prints (ts, volume, is_buy) binnen wash.window_sec voor de wash-trading heuristiek
+    #[serde(default)]
+    recent_prints: std::vec::Vec<(f64, f64, bool)>,
+    #[serde(default)]
+    suspicious: bool,
+    // Bar-close evaluatie (signal_on_candle_close): index van de lopende bar,
+    // de laatste evaluatie daarin en de EARLY/ALPHA stand bij de vorige close
+    #[serde(default)]
+    signal_bar: i64,
+    #[serde(skip)]
+    bar_eval: Option<BarCloseEval>,
+    #[serde(default)]
+    bar_early: String,
+    #[serde(default)]
+    bar_alpha: String,
+    // Laatste tape_size ruwe trades, oudste vooraan
+    #[serde(default)]
+    tape: VecDeque<TapeTrade>,
+    // (ts, buy-aandeel %, prijs) binnen divergence.window_sec
+    #[serde(default)]
+    divergence_samples: VecDeque<(f64, f64, f64)>,
+    // "BULLISH", "BEARISH" of leeg
+    #[serde(default)]
+    divergence: String,
+    // Label en ts van het laatste DIVERGENCE signal (cooldown)
+    #[serde(default)]
+    divergence_signal: String,
+    #[serde(default)]
+    divergence_signal_ts: i64,
+}
+
+/// Eén ruwe trade in de tape; side is "b" of "s" zoals in de trade stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TapeTrade {
+    ts: f64,
+    price: f64,
+    volume: f64,
+    side: String,
+}
+
+// Stand van EARLY/ALPHA na de laatste trade in een bar, met het event zoals het
+// bij de close wordt uitgezonden (prijs/scores van die laatste trade)
+#[derive(Debug, Clone)]
+struct BarCloseEval {
+    early: String,
+    alpha: String,
+    event: SignalEvent,
+}
+
+/// Sluit de lopende bar van `t`: EARLY/ALPHA na de laatste trade in de bar, vergeleken
+/// met de vorige close. De events krijgen het sluitmoment van de bar als ts, zodat
+/// een backtest instapt op een verhandelbare close; received_at vervalt omdat de
+/// wachttijd tot de close geen verwerkingslatency is.
+fn close_signal_bar(t: &mut TradeState, candle_sec: i64) -> std::vec::Vec<SignalEvent> {
+    let Some(c) = t.bar_eval.take() else {
+        return std::vec::Vec::new();
+    };
+    let close_ts = (t.signal_bar + 1) * candle_sec;
+    let prev_early = std::mem::replace(&mut t.bar_early, c.early.clone());
+    let prev_alpha = std::mem::replace(&mut t.bar_alpha, c.alpha.clone());
+    let mut out = std::vec::Vec::new();
+    if c.early != "NONE" && c.early != prev_early {
+        out.push(SignalEvent {
+            ts: close_ts,
+            signal_type: "EARLY".to_string(),
+            direction: c.early.clone(),
+            received_at: None,
+            ..c.event.clone()
+        });
+    }
+    if c.alpha != "NONE" && c.alpha != prev_alpha {
+        out.push(SignalEvent {
+            ts: close_ts,
+            signal_type: "ALPHA".to_string(),
+            direction: c.alpha.clone(),
+            received_at: None,
+            ..c.event
+        });
+    }
+    out
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CandleState {
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    close: Option<f64>,
+    pct_change: Option<f64>,
+    first_ts: Option<i64>,
+    last_ts: Option<i64>,
+    last_update_ts: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TickerState {
+    last_price: Option<f64>,
+    last_vol24h: Option<f64>,
+    ewma_vol24h: Option<f64>,
+    ewma_abs_return: Option<f64>,
+    last_anom_ts: Option<i64>,
+    last_anom_dir: Option<String>,
+    last_anom_strength: Option<f64>,
+    // 24h verandering t.o.v. Kraken's dag-open (ticker "o")
+    day_change_pct: Option<f64>,
+    last_update_ts: i64,
+    // Laatste ticker bericht via de WebSocket (0 = nooit); REST slaat verse pairs over
+    ws_update_ts: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct OrderbookState {
+    bids: std::vec::Vec<(f64, f64)>,
+    asks: std::vec::Vec<(f64, f64)>,
+    timestamp: i64,
+}
+
+// /api/orderbook geeft 404 voor oudere boeken
+const ORDERBOOK_API_MAX_AGE_SEC: i64 = 60;
+
+#[derive(Debug, Clone, Serialize)]
+struct OrderbookView {
+    pair: String,
+    bids: std::vec::Vec<(f64, f64)>,
+    asks: std::vec::Vec<(f64, f64)>,
+    timestamp: i64,
+    age_sec: i64,
+    best_bid: f64,
+    best_ask: f64,
+    spread: f64,
+    spread_pct: f64,
+    bid_volume: f64,
+    ask_volume: f64,
+    // bid_volume / (bid + ask) over top 10; > 0.65 geeft een whale boost
+    imbalance: f64,
+    // Boek is vers genoeg om scores te beïnvloeden
+    score_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Row {
+    pair: String,
+    // AppConfig::pair_aliases of gewoon de pair; alleen voor weergave
+    display_name: String,
+    price: f64,
+    // Sinds first-seen (CandleState), dus geen 24h; zie pct_24h voor Kraken's dag-open
+    pct: f64,
+    whale: bool,
+    whale_side: String,
+    whale_volume: f64,
+    whale_notional: f64,
+    // Leesbare notional ("950", "12.3k", "4.56M"); whale_notional blijft de ruwe waarde
+    whale_notional_fmt: String,
+    flow_pct: f64,
+    dir: String,
+    early: String,
+    alpha: String,
+    pump_score: f64,
+    pump_conf: f64,
+    pump_label: String,
+    trades: u64,
+    buys: f64,
+    sells: f64,
+    o: f64,
+    h: f64,
+    l: f64,
+    c: f64,
+    score: f64,
+    rating: String,
+    whale_pred_score: f64,
+    whale_pred_label: String,
+    reliability_score: f64,
+    reliability_label: String,
+    news_sentiment: f64,
+    funding_rate: Option<f64>,
+    open_interest: Option<f64>,
+    flow_pct_long: f64,
+    dir_long: String,
+    stale: bool,
+    max_notional: f64,
+    max_notional_side: String,
+    max_notional_fmt: String,
+    // Aantal decimalen voor prijsweergave, afhankelijk van de prijsgrootte
+    precision: u32,
+    // None zolang er nog onvoldoende historie is
+    pct_5m: Option<f64>,
+    pct_1h: Option<f64>,
+    // t.o.v. Kraken's dag-open (ticker)
+    pct_24h: Option<f64>,
+    // Cumulatieve whale notional binnen whale_flow_window_sec; net = buys - sells
+    whale_buy_flow: f64,
+    whale_sell_flow: f64,
+    whale_net_flow: f64,
+    // False zolang er geen vers boek is (orderbook_fresh_sec): whale boosts tellen dan niet mee
+    orderbook_available: bool,
+    // Wash-trading patroon gezien (WashSettings); reliability is dan gedempt
+    suspicious: bool,
+    // Prijs/flow divergentie (DivergenceSettings): "BULLISH", "BEARISH" of leeg
+    divergence: String,
+}
+
+/// Funding/open interest van de perpetual met dezelfde base asset (Kraken Futures).
+#[derive(Debug, Clone, Serialize)]
+struct DerivativesState {
+    symbol: String,
+    funding_rate: f64,
+    open_interest: f64,
+    mark_price: f64,
+    ts: i64,
+}
+
+/// Losse componenten van de reliability score (max 40 + 20 + 20 + 15 + 15).
+#[derive(Debug, Clone, Serialize)]
+struct ReliabilityBreakdown {
+    trade_density: f64,
+    volume_stability: f64,
+    flow_consistency: f64,
+    recency: f64,
+    time_density: f64,
+    trades_60s: usize,
+    vol_ratio: f64,
+    flow_pct_60s: f64,
+    secs_since_update: i64,
+    suspicious: bool,
+    score: f64,
+    label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BiggestPrint {
+    notional: f64,
+    side: String,
+    ts: i64,
+}
+
+/// Buy-aandeel van één flow-venster op beide grondslagen (pair detail vergelijking).
+#[derive(Debug, Clone, Serialize)]
+struct FlowBasisComparison {
+    window: &'static str,
+    window_sec: f64,
+    volume_buy_pct: Option<f64>,
+    count_buy_pct: Option<f64>,
+    buy_trades: usize,
+    sell_trades: usize,
+}
+
+/// /api/pairs: elk bekend pair (trades of ticker), ook zonder signal.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct PairInfo {
+    pair: String,
+    last_price: Option<f64>,
+    last_update_ts: i64,
+    trade_count: u64,
+}
+
+/// Instellingen voor compute_reliability: STALE-grens, wash-penalty en de
+/// flow-vensters waarop recent_buys/recent_buys_5m zijn afgekapt.
+#[derive(Debug, Clone)]
+struct ReliabilityParams {
+    max_age_sec: i64,
+    wash_penalty: f64,
+    short_window_sec: f64,
+    medium_window_sec: f64,
+}
+
+impl From<&AppConfig> for ReliabilityParams {
+    fn from(cfg: &AppConfig) -> Self {
+        Self {
+            max_age_sec: cfg.reliability_max_age_sec,
+            wash_penalty: cfg.wash.reliability_penalty,
+            short_window_sec: cfg.flow.short_window_sec,
+            medium_window_sec: cfg.flow.medium_window_sec,
+        }
+    }
+}
+
+/// (meetperiode, schaalfactor naar `target` seconden) voor een buffer die
+/// `window_sec` aan data bevat.
+fn reliability_span(window_sec: f64, target: f64) -> (f64, f64) {
+    let span = window_sec.min(target);
+    if span > 0.0 {
+        (span, target / span)
+    } else {
+        (target, 1.0)
+    }
+}
+
+/// Config-velden voor het opbouwen van een Row (snapshot en pair detail).
+struct RowConfig {
+    stale_threshold: i64,
+    whale_window: f64,
+    book_fresh_sec: i64,
+    reliability: ReliabilityParams,
+    aliases: HashMap<String, String>,
+    signal_expiry: i64,
+}
+
+impl From<&AppConfig> for RowConfig {
+    fn from(cfg: &AppConfig) -> Self {
+        Self {
+            stale_threshold: cfg.stale_threshold_sec,
+            whale_window: cfg.whale_flow_window_sec,
+            book_fresh_sec: cfg.orderbook_fresh_sec,
+            reliability: ReliabilityParams::from(cfg),
+            aliases: cfg.pair_aliases.clone(),
+            signal_expiry: cfg.signal_expiry_sec,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PairDetail {
+    row: Row,
+    reliability: ReliabilityBreakdown,
+    derivatives: Option<DerivativesState>,
+    biggest_print: Option<BiggestPrint>,
+    // Actieve grondslag (flow.basis) en beide varianten per venster
+    flow_basis: String,
+    flow_comparison: std::vec::Vec<FlowBasisComparison>,
+}
+
+#[derive(Debug, Clone)]
+struct ScoreWeights {
+    flow_w: f64,
+    price_w: f64,
+    whale_w: f64,
+    volume_w: f64,
+    anomaly_w: f64,
+    trend_w: f64,
+}
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            flow_w: 2.2,
+            price_w: 0.7,
+            whale_w: 1.4,
+            volume_w: 1.3,
+            anomaly_w: 1.5,
+            trend_w: 1.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignalEvent {
+    ts: i64,
+    pair: String,
+    signal_type: String,
+    direction: String,
+    strength: f64,
+    flow_pct: f64,
+    pct: f64,
+    whale: bool,
+    whale_side: String,
+    volume: f64,
+    notional: f64,
+    #[serde(default)]
+    notional_fmt: String,
+    price: f64,
+    rating: String,
+    total_score: f64,
+    flow_score: f64,
+    price_score: f64,
+    whale_score: f64,
+    volume_score: f64,
+    anomaly_score: f64,
+    trend_score: f64,
+    evaluated: bool,
+    ret_5m: Option<f64>,
+    // Werkelijke leeftijd (sec) van het signal toen de evaluator ret_5m vastlegde;
+    // door het evaluator-interval iets boven horizon_sec. None = nog niet geëvalueerd
+    eval_horizon_sec: Option<i64>,
+    // Oplopend volgnummer, toegekend in push_signal (0 = nog niet gepusht)
+    #[serde(default)]
+    seq: u64,
+    // Reliability (0-100) van het pair op het moment van het signaal
+    #[serde(default)]
+    reliability_score: f64,
+    // Percentiel (0-100) van strength binnen hetzelfde signaaltype in de buffer;
+    // strength zelf blijft de ruwe waarde (notional, score, pump_score, ...)
+    #[serde(default)]
+    strength_norm: f64,
+    // Handmatig geïnjecteerd via /api/test_signal; telt niet mee in evaluator/backtest
+    #[serde(default)]
+    test: bool,
+    // Ontvangstmoment van de veroorzakende WS trade, voor de signal latency in /metrics
+    #[serde(skip)]
+    received_at: Option<std::time::Instant>,
+    // Beoogde self-eval horizon (sec) die bij de push voor dit type gold; bepaalt
+    // wanneer het signal geëvalueerd wordt (zie eval_horizon_sec). 0 = oud event
+    #[serde(default)]
+    horizon_sec: i64,
+}
+
+impl SignalEvent {
+    fn horizon(&self) -> i64 {
+        if self.horizon_sec > 0 {
+            self.horizon_sec
+        } else {
+            SELF_EVAL_HORIZON_SEC
+        }
+    }
+}
+
+/// Bouwt een test signal uit een (gedeeltelijk) SignalEvent JSON object; ontbrekende
+/// velden krijgen neutrale defaults. Het event is meteen "geëvalueerd" zonder ret_5m,
+/// zodat de self-evaluator en backtest het overslaan.
+fn test_signal_from_json(body: &Value, now: i64) -> Result<SignalEvent, String> {
+    let mut base = serde_json::json!({
+        "ts": now,
+        "pair": "TEST/EUR",
+        "signal_type": "ALPHA",
+        "direction": "BUY",
+        "strength": 0.0,
+        "flow_pct": 0.0,
+        "pct": 0.0,
+        "whale": false,
+        "whale_side": "",
+        "volume": 0.0,
+        "notional": 0.0,
+        "price": 0.0,
+        "rating": "TEST",
+        "total_score": 0.0,
+        "flow_score": 0.0,
+        "price_score": 0.0,
+        "whale_score": 0.0,
+        "volume_score": 0.0,
+        "anomaly_score": 0.0,
+        "trend_score": 0.0,
+        "evaluated": true,
+        "ret_5m": null,
+        "eval_horizon_sec": null,
+    });
+    match body {
+        Value::Object(fields) => {
+            for (k, v) in fields {
+                base[k.as_str()] = v.clone();
+            }
+        }
+        Value::Null => {}
+        _ => return Err("body moet een JSON object zijn".to_string()),
+    }
+    let mut ev: SignalEvent = serde_json::from_value(base).map_err(|e| format!("ongeldig signal: {}", e))?;
+    if !SIGNAL_TYPES.contains(&ev.signal_type.as_str()) {
+        return Err(format!("onbekend signal_type: {}", ev.signal_type));
+    }
+    ev.notional_fmt = format_notional(ev.notional);
+    ev.evaluated = true;
+    ev.ret_5m = None;
+    ev.eval_horizon_sec = None;
+    ev.seq = 0;
+    ev.strength_norm = 0.0;
+    ev.test = true;
+    Ok(ev)
+}
+
+/// /api/pending_signals: signal dat nog op zijn 5m-uitkomst wacht.
+/// 0 seconden = horizon bereikt, wordt bij de volgende evaluator-run afgerond.
+#[derive(Debug, Clone, Serialize)]
+struct PendingSignal {
+    #[serde(flatten)]
+    signal: SignalEvent,
+    seconds_remaining: i64,
+}
+
+/// Eén verstuurd alert (inbox); delivered=false met error als het niet aankwam.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AlertRecord {
+    ts: i64,
+    pair: String,
+    #[serde(rename = "type")]
+    alert_type: String,
+    channel: String,
+    delivered: bool,
+    error: Option<String>,
+    // Vrije tekst, bv. de quiet hours samenvatting
+    #[serde(default)]
+    message: Option<String>,
+}
+
+const ALERTS_MAX: usize = 500;
+
+/// Percentielrang (0-100) van `value` binnen `others`; 50 zonder vergelijkingsmateriaal.
+fn percentile_rank(others: &[f64], value: f64) -> f64 {
+    if others.is_empty() {
+        return 50.0;
+    }
+    let below = others.iter().filter(|v| **v < value).count() as f64;
+    let equal = others.iter().filter(|v| **v == value).count() as f64;
+    (below + 0.5 * equal) / others.len() as f64 * 100.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TopRow {
+    ts: i64,
+    pair: String,
+    // Alleen in responses: stars_history.json slaat hem niet op, /api/stars_history
+    // vult hem met de alias die op dat moment geldt
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    display_name: String,
+    price: f64,
+    pct: f64,
+    // Zelfde basis als Row; ontbreken in oudere stars_history.json
+    #[serde(default)]
+    pct_1h: Option<f64>,
+    #[serde(default)]
+    pct_24h: Option<f64>,
+    flow_pct: f64,
+    dir: String,
+    early: String,
+    alpha: String,
+    pump_score: f64,
+    pump_label: String,
+    whale: bool,
+    whale_side: String,
+    whale_volume: f64,
+    whale_notional: f64,
+    // Ontbreekt in oudere stars_history.json; wordt bij het laden aangevuld
+    #[serde(default)]
+    whale_notional_fmt: String,
+    total_score: f64,
+    analysis: String,
+    whale_pred_score: f64,
+    whale_pred_label: String,
+    reliability_score: f64,
+    reliability_label: String,
+    signal_type: String,
+    // Ranking gecorrigeerd voor reliability (zie AppConfig::conviction)
+    #[serde(default)]
+    conviction: f64,
+    // Stars historie: ts van de ANOM waarmee de entry kwalificeerde (bij stars.require_anom)
+    #[serde(default)]
+    anom_ts: Option<i64>,
+}
+
+impl TopRow {
+    /// Ruwe Top 10 ranking: total + pump * 1.5 + whale_pred.
+    fn rank_score(&self) -> f64 {
+        self.total_score + self.pump_score * 1.5 + self.whale_pred_score * 1.0
+    }
+}
+
+const TOP_N_MAX: usize = 100;
+
+/// Sorteerkolom voor ?sort= op /api/stats en /api/top10 (zonder sort blijft de
+/// standaard ranking staan).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SortField {
+    Score,
+    Flow,
+    Pump,
+    Reliability,
+    Pct,
+    #[serde(rename = "pct_1h")]
+    Pct1h,
+    #[serde(rename = "pct_24h")]
+    Pct24h,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+trait SortableRow {
+    /// None (bv. nog geen 1h historie) komt altijd onderaan, ongeacht order.
+    fn sort_value(&self, field: SortField) -> Option<f64>;
+}
+
+impl SortableRow for Row {
+    fn sort_value(&self, field: SortField) -> Option<f64> {
+        match field {
+            SortField::Score => Some(self.score),
+            SortField::Flow => Some(self.flow_pct),
+            SortField::Pump => Some(self.pump_score),
+            SortField::Reliability => Some(self.reliability_score),
+            SortField::Pct => Some(self.pct),
+            SortField::Pct1h => self.pct_1h,
+            SortField::Pct24h => self.pct_24h,
+        }
+    }
+}
+
+impl SortableRow for TopRow {
+    fn sort_value(&self, field: SortField) -> Option<f64> {
+        match field {
+            SortField::Score => Some(self.total_score),
+            SortField::Flow => Some(self.flow_pct),
+            SortField::Pump => Some(self.pump_score),
+            SortField::Reliability => Some(self.reliability_score),
+            SortField::Pct => Some(self.pct),
+            SortField::Pct1h => self.pct_1h,
+            SortField::Pct24h => self.pct_24h,
+        }
+    }
+}
+
+fn sort_rows<T: SortableRow>(rows: &mut [T], field: SortField, order: SortOrder) {
+    rows.sort_by(|a, b| match (a.sort_value(field), b.sort_value(field)) {
+        (Some(x), Some(y)) => match order {
+            SortOrder::Asc => score_cmp(x, y),
+            SortOrder::Desc => score_cmp(y, x),
+        },
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+/// /api/stats?active_only=true&sort=flow|pump|reliability|pct|pct_1h|pct_24h|score&order=asc|desc
+#[derive(Debug, Clone, Default, Deserialize)]
+struct StatsQuery {
+    active_only: Option<bool>,
+    sort: Option<SortField>,
+    #[serde(default)]
+    order: SortOrder,
+}
+
+/// /api/top10?limit=<n> overschrijft AppConfig::top_n voor risers/fallers;
+/// sort/order herordent risers en fallers (best3 blijft op conviction).
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Top10Query {
+    limit: Option<usize>,
+    sort: Option<SortField>,
+    #[serde(default)]
+    order: SortOrder,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Top10Response {
+    best3: std::vec::Vec<TopRow>,
+    risers: std::vec::Vec<TopRow>,
+    fallers: std::vec::Vec<TopRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct HeatmapPoint {
+    pair: String,
+    flow_pct: f64,
+    pump_score: f64,
+    ts: i64,
+    reliability_score: f64,
+    // Extra assen voor de heatmap dropdowns
+    score: f64,
+    whale_pred_score: f64,
+    pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MoverRow {
+    pair: String,
+    price: f64,
+    precision: u32,
+    pct: f64,
+    vol24h: f64,
+    quote_volume: f64,
+    signalled: bool,
+    ts: i64,
+}
+
+/// Telling van flow-richtingen (BUY/SELL/NEUTR) over een groep pairs.
+#[derive(Debug, Clone, Default, Serialize)]
+struct FlowCounts {
+    pairs: usize,
+    buy: usize,
+    sell: usize,
+    neutral: usize,
+    buy_pct: f64,
+    sell_pct: f64,
+}
+
+impl FlowCounts {
+    fn add(&mut self, dir: &str) {
+        self.pairs += 1;
+        match dir {
+            "BUY" => self.buy += 1,
+            "SELL" => self.sell += 1,
+            _ => self.neutral += 1,
+        }
+        self.buy_pct = self.buy as f64 / self.pairs as f64 * 100.0;
+        self.sell_pct = self.sell as f64 / self.pairs as f64 * 100.0;
+    }
+}
+
+/// Per quote valuta ook het buy/sell volume (notional in die quote, sinds de candle-reset).
+#[derive(Debug, Clone, Default, Serialize)]
+struct QuoteBreadth {
+    #[serde(flatten)]
+    counts: FlowCounts,
+    buy_notional: f64,
+    sell_notional: f64,
+}
+
+/// /api/market_breadth: totaal over alle niet-stale pairs + per quote valuta.
+/// Notional is alleen optelbaar binnen één quote, dus `total` telt enkel richtingen.
+#[derive(Debug, Clone, Default, Serialize)]
+struct MarketBreadth {
+    total: FlowCounts,
+    by_quote: std::collections::BTreeMap<String, QuoteBreadth>,
+    stale_pairs: usize,
+}
+
+/// Eén advies per BUY pair. exit_5..exit_20 zijn de vier ladder-niveaus
+/// (trade_advice.exit_pct_1..4, standaard 5/10/15/20%).
+#[derive(Debug, Clone, Serialize)]
+struct TradeAdviceRow {
+    pair: String,
+    price: f64,
+    precision: u32,
+    rating: String,
+    score: f64,
+    entry_price: f64,
+    exit_5: f64,
+    exit_10: f64,
+    exit_15: f64,
+    exit_20: f64,
+}
+
+/// Manual balance als elk advies met base_notional wordt gekocht en op het
+/// betreffende exit-niveau wordt verkocht (zonder fees).
+#[derive(Debug, Clone, Serialize)]
+struct TradeAdviceEquity {
+    balance: f64,
+    notional_per_trade: f64,
+    equity_5: f64,
+    equity_10: f64,
+    equity_15: f64,
+    equity_20: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TradeAdvice {
+    entry_pct: f64,
+    exit_pcts: [f64; 4],
+    rows: std::vec::Vec<TradeAdviceRow>,
+    equity: TradeAdviceEquity,
+}
+
+/// /api/movers?dir=up|down&limit=20
+#[derive(Debug, Clone, Default, Deserialize)]
+struct MoversQuery {
+    dir: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BacktestResult {
+    signal_type: String,
+    direction: String,
+    total_trades: usize,
+    winrate: f64,
+    avg_win: f64,
+    avg_loss: f64,
+    expectancy: f64,
+    pnl_sum: f64,
+    max_drawdown: f64,
+    best_trade: f64,
+    worst_trade: f64,
+    max_losing_streak: usize,
+    // Standaardafwijking van de returns per trade (%)
+    return_stddev: f64,
+    // expectancy / return_stddev (per trade, niet geannualiseerd); 0 bij nul variantie
+    sharpe: f64,
+    // Cumulatieve SOM van de % returns (niet samengesteld)
+    equity_curve: std::vec::Vec<f64>,
+    // Accountwaarde (account valuta) na elke trade: start_balance, elke trade
+    // position_fraction van de equity ingezet, returns samengesteld
+    equity_curve_compounded: std::vec::Vec<f64>,
+    start_balance: f64,
+    position_fraction: f64,
+    compounded_return_pct: f64,
+    compounded_max_drawdown_pct: f64,
+    // Minder dan min_trades trades: statistisch onbetrouwbaar
+    low_sample: bool,
+}
+
+/// /api/signals?pair=&limit=&before=<seq>&min_score=&min_strength=&types=EARLY,ALPHA&dir=BUY;
+/// zonder parameters de volledige buffer.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SignalsQuery {
+    pair: Option<String>,
+    limit: Option<usize>,
+    // Cursor op seq: alleen signals met een lager volgnummer. ts is niet uniek,
+    // dus pagineren op ts slaat signals met dezelfde seconde over.
+    before: Option<u64>,
+    min_score: Option<f64>,
+    min_strength: Option<f64>,
+    // Komma-gescheiden signaaltypes, hoofdletterongevoelig
+    types: Option<String>,
+    dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct BacktestQuery {
+    since: Option<i64>,
+    until: Option<i64>,
+    min_trades: Option<usize>,
+    // Ook signals uit signals_archive.jsonl meenemen
+    archive: Option<bool>,
+    // Overschrijft backtest_position_fraction voor de samengestelde curve
+    fraction: Option<f64>,
+}
+
+/// Accountwaarde na elke trade bij fixed fractional sizing: equity *= 1 + fraction * r/100.
+/// Geeft (curve, max drawdown % vanaf de piek).
+fn compound_equity(returns_pct: &[f64], start_balance: f64, fraction: f64) -> (std::vec::Vec<f64>, f64) {
+    let mut equity = start_balance;
+    let mut peak = start_balance;
+    let mut max_dd_pct = 0.0_f64;
+    let curve = returns_pct
+        .iter()
+        .map(|r| {
+            // Verlies kan nooit meer zijn dan de ingezette equity
+            equity = (equity * (1.0 + fraction * r / 100.0)).max(0.0);
+            peak = peak.max(equity);
+            if peak > 0.0 {
+                max_dd_pct = max_dd_pct.max((peak - equity) / peak * 100.0);
+            }
+            equity
+        })
+        .collect();
+    (curve, max_dd_pct)
+}
+
+const STARS_HISTORY_FILE: &str = "stars_history.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StarsHistory {
+    history: std::vec::Vec<TopRow>,
+    dirty: bool,
+}
+
+impl StarsHistory {
+    /// Nieuwe entry voor `pair` alleen als alle eerdere minstens dedup_sec ouder zijn
+    /// en geen ervan door dezelfde ANOM gekwalificeerd werd.
+    fn accepts(&self, pair: &str, ts: i64, anom_ts: Option<i64>, dedup_sec: i64) -> bool {
+        self.history.iter().filter(|r| r.pair == pair).all(|r| {
+            r.ts != ts && ts.saturating_sub(r.ts) >= dedup_sec && (anom_ts.is_none() || r.anom_ts != anom_ts)
+        })
+    }
+}
+
+// ============================================================================
+// HOOFDSTUK 5 – MANUAL TRADING MODULE (AANGEPAST)
+// ============================================================================
+
+const MANUAL_TRADES_FILE: &str = "manual_trades.json";
+const MANUAL_EQUITY_FILE: &str = "manual_trades_equity.json";
+const MANUAL_BASE_NOTIONAL: f64 = 100.0;
+
+/// Notitie + tags bij een manual trade: waarom is hij geopend.
+#[derive(Debug, Clone, Default)]
+struct TradeAnnotation {
+    note: Option<String>,
+    tags: std::vec::Vec<String>,
+}
+
+impl TradeAnnotation {
+    /// Lege notitie wordt None; tags getrimd, lowercase, zonder lege of dubbele.
+    fn new(note: Option<&str>, tags: &[String]) -> Self {
+        let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        let mut clean: std::vec::Vec<String> = std::vec::Vec::new();
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if !tag.is_empty() && !clean.contains(&tag) {
+                clean.push(tag);
+            }
+        }
+        Self { note, tags: clean }
+    }
+}
+
+/// Gesimuleerde paper fill: prijs na spread en slippage, plus de kosten daarvan
+/// t.o.v. de referentieprijs (candle close) in bps; positief = ongunstig.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fill {
+    price: f64,
+    slippage_bps: f64,
+}
+
+impl Fill {
+    fn exact(price: f64) -> Self {
+        Self { price, slippage_bps: 0.0 }
+    }
+}
+
+/// Buys tegen de beste ask, sells tegen de beste bid (als `book` gegeven is),
+/// daarna `slippage_bps` in de ongunstige richting.
+fn simulate_fill(reference: f64, is_buy: bool, book: Option<(f64, f64)>, slippage_bps: f64) -> Fill {
+    let base = match book {
+        Some((_, ask)) if is_buy && ask > 0.0 => ask,
+        Some((bid, _)) if !is_buy && bid > 0.0 => bid,
+        _ => reference,
+    };
+    let slip = slippage_bps / 10_000.0;
+    let price = if is_buy { base * (1.0 + slip) } else { base * (1.0 - slip) };
+    let cost = if reference > 0.0 {
+        let rel = (price - reference) / reference * 10_000.0;
+        if is_buy { rel } else { -rel }
+    } else {
+        0.0
+    };
+    Fill { price, slippage_bps: cost }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManualTrade {
+    pair: String,
+    entry_price: f64,
+    size: f64,
+    open_ts: i64,
+    stop_loss: f64,
+    take_profit: f64,
+    fee_pct: f64,
+    manual_amount: f64,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    tags: std::vec::Vec<String>,
+    // Openstaande limit close: sluit zodra een trade-prijs >= deze limit is
+    #[serde(default)]
+    limit_close: Option<f64>,
+    // Spread + slippage kosten van de entry fill (bps)
+    #[serde(default)]
+    entry_slippage_bps: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TradeRecord {
+    pair: String,
+    entry_price: f64,
+    exit_price: f64,
+    size: f64,
+    pnl: f64,
+    open_ts: i64,
+    close_ts: i64,
+    reason: String,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    tags: std::vec::Vec<String>,
+    // Gedeeltelijke close: fractie van de positie die hier gesloten is
+    #[serde(default)]
+    partial_fraction: Option<f64>,
+    // Limit close: de ingestelde limit (None = market close tegen de actuele prijs)
+    #[serde(default)]
+    limit_price: Option<f64>,
+    // Aangenomen spread + slippage van entry en exit samen (bps t.o.v. de candle close)
+    #[serde(default)]
+    slippage_bps: f64,
+}
+
+impl TradeRecord {
+    fn has_tag(&self, tag: Option<&str>) -> bool {
+        match tag.map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()) {
+            Some(tag) => self.tags.contains(&tag),
+            None => true,
+        }
+    }
+}
+
+/// /api/manual_trades?tag= en /api/manual_stats?tag= (filter op gesloten trades)
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ManualTradesQuery {
+    tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManualTraderState {
+    initial_balance: f64,
+    balance: f64,
+    trades: HashMap<String, ManualTrade>,
+    equity_curve: std::vec::Vec<(i64, f64)>,
+    #[serde(default)]
+    closed_trades: std::vec::Vec<TradeRecord>,
+    // pair -> close ts van de laatste verliesgevende close (loss cooldown)
+    #[serde(default)]
+    loss_closes: HashMap<String, i64>,
+    // Limit fills vanuit handle_trade; run_manual_trader_saver schrijft weg
+    #[serde(skip)]
+    dirty: bool,
+}
+
+const MANUAL_MAX_CLOSED_TRADES: usize = 1000;
+
+/// Resterende cooldown (sec) na een verliesgevende close; None als er geen (meer) is.
+fn loss_cooldown_remaining(loss_closes: &HashMap<String, i64>, pair: &str, now: i64, cooldown_sec: i64) -> Option<i64> {
+    if cooldown_sec <= 0 {
+        return None;
+    }
+    loss_closes
+        .get(pair)
+        .map(|closed_at| closed_at + cooldown_sec - now)
+        .filter(|remaining| *remaining > 0)
+}
+
+impl ManualTraderState {
+    fn new() -> Self {
+        Self {
+            initial_balance: VIRTUAL_INITIAL_BALANCE,
+            balance: VIRTUAL_INITIAL_BALANCE,
+            trades: HashMap::new(),
+            equity_curve: std::vec::Vec::new(),
+            closed_trades: std::vec::Vec::new(),
+            loss_closes: HashMap::new(),
+            dirty: false,
+        }
+    }
+
+    async fn load() -> Self {
+        match tokio::fs::read_to_string(MANUAL_TRADES_FILE).await {
+            Ok(content) => {
+                match serde_json::from_str(content.as_str()) {
+                    Ok(state) => state,
+                    Err(e) => {
+                        log_warn!("[WARN] Failed to parse {}: {}. Starting fresh.", MANUAL_TRADES_FILE, e);
+                        Self::new()
+                    }
+                }
+            }
+            Err(_) => Self::new(),
+        }
+    }
+
+    async fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(MANUAL_TRADES_FILE, json).await?;
+        Ok(())
+    }
+
+    async fn save_equity(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(&self.equity_curve)?;
+        tokio::fs::write(MANUAL_EQUITY_FILE, json).await?;
+        Ok(())
+    }
+
+    // Sizing modes: "fixed" (value = bedrag), "pct_balance" (value = % van balance),
+    // "fixed_risk" (value = % van balance dat verloren gaat als de SL geraakt wordt).
+    fn compute_amount(&self, sizing_mode: &str, sizing_value: f64, sl_pct: f64) -> Option<f64> {
+        let amount = match sizing_mode {
+            "pct_balance" => self.balance * sizing_value / 100.0,
+            "fixed_risk" => {
+                if sl_pct <= 0.0 {
+                    return None;
+                }
+                let risk_amount = self.balance * sizing_value / 100.0;
+                risk_amount / (sl_pct / 100.0)
+            }
+            _ => sizing_value,
+        };
+        if amount <= 0.0 || amount > self.balance {
+            return None;
+        }
+        Some(amount)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_trade(&mut self, pair: &str, fill: Fill, sl_pct: f64, tp_pct: f64, fee_pct: f64, manual_amount: f64, annotation: TradeAnnotation, loss_cooldown_sec: i64, max_amount: f64) -> Result<(), String> {
+        if self.trades.contains_key(pair) {
+            return Err(format!("Er staat al een positie open voor {}", pair));
+        }
+        let now = chrono::Utc::now().timestamp();
+        if let Some(remaining) = loss_cooldown_remaining(&self.loss_closes, pair, now, loss_cooldown_sec) {
+            log_info!("[MANUAL TRADE] REJECT {}: loss cooldown nog {}s", pair, remaining);
+            return Err(format!(
+                "Cooldown na verlies op {}: nog {}m{:02}s voordat je opnieuw kunt openen",
+                pair, remaining / 60, remaining % 60
+            ));
+        }
+        if manual_amount > max_amount {
+            log_info!(
+                "[MANUAL TRADE] REJECT {}: amount {:.2} boven positie-limiet {:.2}",
+                pair, manual_amount, max_amount
+            );
+            return Err(format!(
+                "Bedrag {:.2} voor {} is groter dan de positie-limiet {:.2} (max_position_pct / max_pair_exposure)",
+                manual_amount, pair, max_amount
+            ));
+        }
+        let price = fill.price;
+        let size = manual_amount / price;
+        let sl = price * (1.0 - sl_pct / 100.0);
+        let tp = price * (1.0 + tp_pct / 100.0);
+        let trade = ManualTrade {
+            pair: pair.to_string(),
+            entry_price: price,
+            size,
+            open_ts: chrono::Utc::now().timestamp(),
+            stop_loss: sl,
+            take_profit: tp,
+            fee_pct,
+            manual_amount,
+            note: annotation.note,
+            tags: annotation.tags,
+            limit_close: None,
+            entry_slippage_bps: fill.slippage_bps,
+        };
+        log_info!(
+            "[MANUAL TRADE] OPEN {} at {:.5} size {:.5} amount {:.2} SL={:.5} TP={:.5} fee={:.2}% slippage={:.1}bps tags=[{}]",
+            pair, price, size, manual_amount, sl, tp, fee_pct, fill.slippage_bps, trade.tags.join(",")
+        );
+        self.trades.insert(pair.to_string(), trade);
+        Ok(())
+    }
+
+    fn close_trade(&mut self, pair: &str, fill: Fill, reason: &str) -> bool {
+        let exit_price = fill.price;
+        if let Some(trade) = self.trades.remove(pair) {
+            let (pnl, fee_amount, net_pnl) = self.realize(&trade, trade.size, fill, reason, None);
+            if net_pnl < 0.0 {
+                self.loss_closes.insert(pair.to_string(), chrono::Utc::now().timestamp());
+            }
+            log_info!(
+                "[MANUAL TRADE] CLOSED {} at {:.5} Gross PnL={:.2} Fee={:.2} Net PnL={:.2} ({})",
+                pair, exit_price, pnl, fee_amount, net_pnl, reason
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sluit `fraction` (0..1] van de positie; de rest blijft open met dezelfde SL/TP.
+    /// Een fractie van 1 is gewoon een volledige close.
+    fn partial_close(&mut self, pair: &str, fill: Fill, fraction: f64, reason: &str) -> Result<(), String> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(format!("Fractie {} moet tussen 0 en 1 liggen", fraction));
+        }
+        let trade = self
+            .trades
+            .get(pair)
+            .cloned()
+            .ok_or_else(|| format!("Geen open positie voor {}", pair))?;
+        if fraction >= 1.0 {
+            self.close_trade(pair, fill, reason);
+            return Ok(());
+        }
+        let exit_price = fill.price;
+        let closed_size = trade.size * fraction;
+        let (pnl, fee_amount, net_pnl) = self.realize(&trade, closed_size, fill, reason, Some(fraction));
+        if let Some(open) = self.trades.get_mut(pair) {
+            open.size -= closed_size;
+            open.manual_amount *= 1.0 - fraction;
+        }
+        log_info!(
+            "[MANUAL TRADE] PARTIAL CLOSE {} {:.0}% at {:.5} size {:.5} Gross PnL={:.2} Fee={:.2} Net PnL={:.2} ({})",
+            pair, fraction * 100.0, exit_price, closed_size, pnl, fee_amount, net_pnl, reason
+        );
+        Ok(())
+    }
+
+    /// Zet een limit close (None wist hem). Staat de prijs al op of boven de
+    /// limit, dan vult hij direct als market sell (`market`, zoals een marketable limit).
+    fn set_limit_close(&mut self, pair: &str, limit: Option<f64>, market: Fill) -> Result<bool, String> {
+        let current_price = market.price;
+        if limit.is_some_and(|l| !(l.is_finite() && l > 0.0)) {
+            return Err("Limit prijs moet > 0 zijn".to_string());
+        }
+        let trade = self
+            .trades
+            .get_mut(pair)
+            .ok_or_else(|| format!("Geen open positie voor {}", pair))?;
+        trade.limit_close = limit;
+        match limit {
+            Some(l) if current_price >= l => Ok(self.fill_limit_close(pair, market).is_some()),
+            Some(l) => {
+                log_info!("[MANUAL TRADE] LIMIT CLOSE {} gezet op {:.5} (prijs {:.5})", pair, l, current_price);
+                Ok(false)
+            }
+            None => {
+                log_info!("[MANUAL TRADE] LIMIT CLOSE {} geannuleerd", pair);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Vult een openstaande limit close als de fill prijs de limit bereikt; vanuit
+    /// de trade stream is dat de limit zelf zonder slippage (zie manual_check_limit).
+    fn fill_limit_close(&mut self, pair: &str, fill: Fill) -> Option<TradeRecord> {
+        let limit = self.trades.get(pair)?.limit_close.filter(|l| fill.price >= *l)?;
+        self.close_trade(pair, fill, "LIMIT");
+        let record = self.closed_trades.last_mut()?;
+        record.limit_price = Some(limit);
+        self.dirty = true;
+        Some(record.clone())
+    }
+
+    // Realiseert PnL over `size` van de trade: balance, equity curve en closed history.
+    fn realize(&mut self, trade: &ManualTrade, size: f64, fill: Fill, reason: &str, partial_fraction: Option<f64>) -> (f64, f64, f64) {
+        let exit_price = fill.price;
+        let pnl = (exit_price - trade.entry_price) * size;
+        let fee_amount = pnl.abs() * (trade.fee_pct / 100.0);
+        let net_pnl = pnl - fee_amount;
+        self.balance += net_pnl;
+        let now = chrono::Utc::now().timestamp();
+        self.equity_curve.push((now, self.balance));
+        if self.equity_curve.len() > 365 {
+            self.equity_curve.remove(0);
+        }
+        self.closed_trades.push(TradeRecord {
+            pair: trade.pair.clone(),
+            entry_price: trade.entry_price,
+            exit_price,
+            size,
+            pnl: net_pnl,
+            open_ts: trade.open_ts,
+            close_ts: now,
+            reason: reason.to_string(),
+            note: trade.note.clone(),
+            tags: trade.tags.clone(),
+            partial_fraction,
+            limit_price: None,
+            slippage_bps: trade.entry_slippage_bps + fill.slippage_bps,
+        });
+        if self.closed_trades.len() > MANUAL_MAX_CLOSED_TRADES {
+            self.closed_trades.remove(0);
+        }
+        (pnl, fee_amount, net_pnl)
+    }
+
+    // Gerealiseerde statistieken over de gesloten trades (bedragen in account-valuta).
+    fn stats(&self, tag: Option<&str>) -> ManualStats {
+        let closed: std::vec::Vec<&TradeRecord> = self.closed_trades.iter().filter(|t| t.has_tag(tag)).collect();
+        let n = closed.len();
+        let mut wins = 0usize;
+        let mut losses = 0usize;
+        let mut win_sum = 0.0_f64;
+        let mut loss_sum = 0.0_f64;
+        let mut cum = 0.0_f64;
+        let mut peak = 0.0_f64;
+        let mut max_dd = 0.0_f64;
+        let mut best_trade = 0.0_f64;
+        let mut worst_trade = 0.0_f64;
+        let mut losing_streak = 0usize;
+        let mut max_losing_streak = 0usize;
+
+        for (i, t) in closed.iter().enumerate() {
+            let r = t.pnl;
+            cum += r;
+            peak = peak.max(cum);
+            max_dd = max_dd.max(peak - cum);
+            if i == 0 || r > best_trade {
+                best_trade = r;
+            }
+            if i == 0 || r < worst_trade {
+                worst_trade = r;
+            }
+            if r > 0.0 {
+                wins += 1;
+                win_sum += r;
+                losing_streak = 0;
+            } else {
+                losses += 1;
+                loss_sum += r;
+                losing_streak += 1;
+                max_losing_streak = max_losing_streak.max(losing_streak);
+            }
+        }
+
+        ManualStats {
+            total_trades: n,
+            winrate: if n > 0 { wins as f64 / n as f64 * 100.0 } else { 0.0 },
+            avg_win: if wins > 0 { win_sum / wins as f64 } else { 0.0 },
+            avg_loss: if losses > 0 { loss_sum / losses as f64 } else { 0.0 },
+            expectancy: if n > 0 { cum / n as f64 } else { 0.0 },
+            profit_factor: if loss_sum < 0.0 { win_sum / loss_sum.abs() } else { 0.0 },
+            pnl_sum: cum,
+            max_drawdown: max_dd,
+            best_trade,
+            worst_trade,
+            max_losing_streak,
+        }
+    }
+
+    // Sluit alle posities (op de meegegeven prijs, anders entry) en zet de balance terug.
+    fn reset(&mut self, prices: &HashMap<String, f64>) {
+        let pairs: std::vec::Vec<String> = self.trades.keys().cloned().collect();
+        for pair in pairs {
+            let entry = self.trades.get(&pair).map(|t| t.entry_price).unwrap_or(0.0);
+            let exit_price = prices.get(&pair).copied().filter(|p| *p > 0.0).unwrap_or(entry);
+            self.close_trade(&pair, Fill::exact(exit_price), "RESET");
+        }
+        self.balance = self.initial_balance;
+        self.loss_closes.clear();
+        self.equity_curve.clear();
+        self.equity_curve.push((chrono::Utc::now().timestamp(), self.balance));
+        log_info!("[MANUAL TRADE] RESET account to {:.2}", self.balance);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ManualTradeView {
+    pair: String,
+    entry_price: f64,
+    size: f64,
+    open_ts: i64,
+    stop_loss: f64,
+    take_profit: f64,
+    current_price: f64,
+    pnl_abs: f64,
+    pnl_pct: f64,
+    fee_pct: f64,
+    manual_amount: f64,
+    note: Option<String>,
+    tags: std::vec::Vec<String>,
+    limit_close: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PortfolioPosition {
+    pair: String,
+    // Marktwaarde (size * actuele prijs) in account currency
+    exposure: f64,
+    // Aandeel in de totale exposure (0-1)
+    weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PortfolioCorrelation {
+    a: String,
+    b: String,
+    corr: f64,
+    // Aantal gedeelde minuut-returns
+    samples: usize,
+}
+
+/// /api/portfolio: exposure van de open manual posities (allemaal long) en
+/// hoeveel onafhankelijke posities dat ongeveer zijn gezien de 1m-correlaties.
+#[derive(Debug, Clone, Serialize)]
+struct PortfolioSnapshot {
+    total_exposure: f64,
+    // Exposure als % van balance + open PnL
+    exposure_pct: f64,
+    positions: std::vec::Vec<PortfolioPosition>,
+    correlations: std::vec::Vec<PortfolioCorrelation>,
+    effective_positions: f64,
+    // total / sqrt(effective): exposure in één pair met (bij gelijke vol) hetzelfde risico
+    correlation_adjusted_exposure: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ManualStats {
+    total_trades: usize,
+    winrate: f64,
+    avg_win: f64,
+    avg_loss: f64,
+    expectancy: f64,
+    profit_factor: f64,
+    pnl_sum: f64,
+    max_drawdown: f64,
+    best_trade: f64,
+    worst_trade: f64,
+    max_losing_streak: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ManualTradesResponse {
+    balance: f64,
+    initial_balance: f64,
+    trades: std::vec::Vec<ManualTradeView>,
+    closed_trades: std::vec::Vec<TradeRecord>,
+    // Pairs met een actieve loss cooldown: (pair, resterende seconden)
+    cooldowns: std::vec::Vec<(String, i64)>,
+}
+
+// ============================================================================
+// HOOFDSTUK 5b – AUTO PAPER TRADER (ALPHA BUY FORWARD TEST)
+// ============================================================================
+//
+// Volledig los van de manual trader: eigen balance, posities en bestand.
+// Opent een base_notional positie bij een ALPHA BUY signaal (alleen als zowel
+// enable_trading als auto_trading_enabled aan staan) en sluit op SL/TP (sl_pct/tp_pct
+// uit AppConfig, als fractie). PnL is netto na auto_fee_pct op entry en exit.
+
+const AUTO_TRADES_FILE: &str = "auto_trades.json";
+// Kill-switch: overleeft een herstart zodat een pauze niet stilletjes vervalt
+const TRADING_PAUSE_FILE: &str = "trading_paused.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AutoPosition {
+    pair: String,
+    entry_price: f64,
+    size: f64,
+    notional: f64,
+    open_ts: i64,
+    stop_loss: f64,
+    take_profit: f64,
+    signal_score: f64,
+    #[serde(default)]
+    entry_slippage_bps: f64,
+    // Fee % per kant bij openen; een latere config-wijziging raakt open posities niet
+    #[serde(default)]
+    fee_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AutoTraderState {
+    initial_balance: f64,
+    balance: f64,
+    positions: HashMap<String, AutoPosition>,
+    closed_trades: std::vec::Vec<TradeRecord>,
+    equity_curve: std::vec::Vec<(i64, f64)>,
+    #[serde(default)]
+    loss_closes: HashMap<String, i64>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl AutoTraderState {
+    fn new(initial_balance: f64) -> Self {
+        Self {
+            initial_balance,
+            balance: initial_balance,
+            positions: HashMap::new(),
+            closed_trades: std::vec::Vec::new(),
+            equity_curve: std::vec::Vec::new(),
+            loss_closes: HashMap::new(),
+            dirty: false,
+        }
+    }
+
+    async fn load(initial_balance: f64) -> Self {
+        match tokio::fs::read_to_string(AUTO_TRADES_FILE).await {
+            Ok(content) => match serde_json::from_str(content.as_str()) {
+                Ok(state) => state,
+                Err(e) => {
+                    log_warn!("[WARN] Failed to parse {}: {}. Starting fresh.", AUTO_TRADES_FILE, e);
+                    Self::new(initial_balance)
+                }
+            },
+            Err(_) => Self::new(initial_balance),
+        }
+    }
+
+    async fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(AUTO_TRADES_FILE, json).await?;
+        Ok(())
+    }
+
+    fn open_position(&mut self, pair: &str, fill: Fill, ts: i64, score: f64, cfg: &AppConfig) -> bool {
+        let price = fill.price;
+        if price <= 0.0 || self.positions.contains_key(pair) || self.positions.len() >= cfg.max_positions {
+            return false;
+        }
+        // Te grote base_notional wordt afgekapt op de positie-limiet
+        let notional = cfg.base_notional.min(cfg.position_cap(self.balance));
+        if notional <= 0.0 {
+            return false;
+        }
+        let exposure: f64 = self.positions.values().map(|p| p.notional).sum();
+        if exposure + notional > self.balance {
+            return false;
+        }
+        if loss_cooldown_remaining(&self.loss_closes, pair, ts, cfg.loss_cooldown_sec()).is_some() {
+            log_debug!("[AUTO TRADE] SKIP {}: loss cooldown actief", pair);
+            return false;
+        }
+        let position = AutoPosition {
+            pair: pair.to_string(),
+            entry_price: price,
+            size: notional / price,
+            notional,
+            open_ts: ts,
+            stop_loss: price * (1.0 - cfg.sl_pct),
+            take_profit: price * (1.0 + cfg.tp_pct),
+            signal_score: score,
+            entry_slippage_bps: fill.slippage_bps,
+            fee_pct: cfg.auto_fee_pct,
+        };
+        log_info!(
+            "[AUTO TRADE] OPEN {} at {:.5} size {:.5} SL={:.5} TP={:.5}",
+            pair, price, position.size, position.stop_loss, position.take_profit
+        );
+        self.positions.insert(pair.to_string(), position);
+        self.dirty = true;
+        true
+    }
+
+    fn close_position(&mut self, pair: &str, fill: Fill, ts: i64, reason: &str) -> Option<TradeRecord> {
+        let pos = self.positions.remove(pair)?;
+        let exit_price = fill.price;
+        let fees = (pos.notional + exit_price * pos.size) * pos.fee_pct / 100.0;
+        let pnl = (exit_price - pos.entry_price) * pos.size - fees;
+        self.balance += pnl;
+        if pnl < 0.0 {
+            self.loss_closes.insert(pair.to_string(), ts);
+        }
+        self.equity_curve.push((ts, self.balance));
+        if self.equity_curve.len() > 1000 {
+            self.equity_curve.remove(0);
+        }
+        let record = TradeRecord {
+            pair: pair.to_string(),
+            entry_price: pos.entry_price,
+            exit_price,
+            size: pos.size,
+            pnl,
+            open_ts: pos.open_ts,
+            close_ts: ts,
+            reason: reason.to_string(),
+            note: None,
+            tags: std::vec::Vec::new(),
+            partial_fraction: None,
+            limit_price: None,
+            slippage_bps: pos.entry_slippage_bps + fill.slippage_bps,
+        };
+        self.closed_trades.push(record.clone());
+        if self.closed_trades.len() > MANUAL_MAX_CLOSED_TRADES {
+            self.closed_trades.remove(0);
+        }
+        self.dirty = true;
+        log_info!("[AUTO TRADE] CLOSED {} at {:.5} PnL={:.2} na fees {:.2} ({})", pair, exit_price, pnl, fees, reason);
+        Some(record)
+    }
+
+    /// SL/TP triggeren op de trade-prijs; de close vult tegen `fill` (bid + slippage).
+    fn check_exit(&mut self, pair: &str, price: f64, fill: Fill, ts: i64) -> Option<TradeRecord> {
+        let reason = {
+            let pos = self.positions.get(pair)?;
+            if price <= pos.stop_loss {
+                "SL"
+            } else if price >= pos.take_profit {
+                "TP"
+            } else {
+                return None;
+            }
+        };
+        self.close_position(pair, fill, ts, reason)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AutoPositionView {
+    pair: String,
+    entry_price: f64,
+    size: f64,
+    notional: f64,
+    open_ts: i64,
+    stop_loss: f64,
+    take_profit: f64,
+    current_price: f64,
+    pnl_abs: f64,
+    pnl_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AutoTradesResponse {
+    enabled: bool,
+    balance: f64,
+    initial_balance: f64,
+    positions: std::vec::Vec<AutoPositionView>,
+    closed_trades: std::vec::Vec<TradeRecord>,
+    equity_curve: std::vec::Vec<(i64, f64)>,
+}
+
+// ============================================================================
+// HOOFDSTUK 6 – ENGINE (HART VAN HET SYSTEEM)
+// ============================================================================
+
+#[derive(Clone)]
+struct Engine {
+    trades: Arc<DashMap<String, TradeState>>,
+    candles: Arc<DashMap<String, CandleState>>,
+    tickers: Arc<DashMap<String, TickerState>>,
+    orderbooks: Arc<DashMap<String, OrderbookState>>,
+    signals: Arc<Mutex<std::vec::Vec<SignalEvent>>>,
+    // pair -> ts van het laatste signal
+    signalled_pairs: Arc<DashMap<String, i64>>,
+    weights: Arc<Mutex<ScoreWeights>>,
+    manual_trader: Arc<Mutex<ManualTraderState>>,
+    auto_trader: Arc<Mutex<AutoTraderState>>,
+    news_sentiment: Arc<DashMap<String, (f64, i64, String)>>,
+    // Gezet bij elke sentiment update; run_news_sentiment_saver schrijft weg
+    news_dirty: Arc<AtomicBool>,
+    // key = genormaliseerde base asset (bv. "BTC")
+    derivatives: Arc<DashMap<String, DerivativesState>>,
+    stars_history: Arc<Mutex<StarsHistory>>,
+    config: Arc<Mutex<AppConfig>>,
+    // events/alerts/signal-archief regels naar de jsonl-writer thread
+    jsonl_tx: std::sync::mpsc::Sender<JsonlLine>,
+    started_at: i64,
+    signal_seq: Arc<AtomicU64>,
+    // Zelflerende threshold/winrate per signaaltype, bijgewerkt door run_self_evaluator
+    signal_stats: Arc<Mutex<HashMap<String, SignalStats>>>,
+    trading_paused: Arc<AtomicBool>,
+    alerts: Arc<Mutex<std::vec::Vec<AlertRecord>>>,
+    // Tijdens quiet hours onderdrukte alerts: kanaal -> signal type -> aantal
+    quiet_suppressed: Arc<Mutex<HashMap<String, HashMap<String, usize>>>>,
+    fx: Arc<Mutex<FxRates>>,
+    snapshot_cache: Arc<TtlCache<(), std::vec::Vec<Row>>>,
+    top10_cache: Arc<TtlCache<usize, Top10Response>>,
+    http_stats: Arc<HttpStats>,
+    signal_latency: Arc<SignalLatency>,
+    // Geparst signal-archief voor de Backtest tab, geldig zolang de bestanden niet wijzigen
+    signal_archive: Arc<Mutex<Option<SignalArchiveCache>>>,
+}
+
+/// Korte TTL cache voor snapshots die de UI elke seconde (per tab) opvraagt.
+/// Gelijktijdige requests wachten op dezelfde berekening i.p.v. elk opnieuw te rekenen.
+struct TtlCache<K, V> {
+    entry: Mutex<Option<(std::time::Instant, K, V)>>,
+}
+
+impl<K: PartialEq, V: Clone> TtlCache<K, V> {
+    fn new() -> Self {
+        Self { entry: Mutex::new(None) }
+    }
+
+    fn get_or_compute(&self, ttl_ms: u64, key: K, compute: impl FnOnce() -> V) -> V {
+        if ttl_ms == 0 {
+            return compute();
+        }
+        let mut entry = self.entry.lock().unwrap();
+        if let Some((at, k, v)) = entry.as_ref() {
+            if *k == key && at.elapsed() < Duration::from_millis(ttl_ms) {
+                return v.clone();
+            }
+        }
+        let v = compute();
+        *entry = Some((std::time::Instant::now(), key, v.clone()));
+        v
+    }
+}
+
+/// Routes zonder pad-parameters, voor HttpStats::route_key.
+const HTTP_ROUTE_KEYS: &[&str] = &[
+    "/api/alerts",
+    "/api/auto_trades",
+    "/api/backtest",
+    "/api/config",
+    "/api/config/export",
+    "/api/config/import",
+    "/api/config/reset",
+    "/api/fx",
+    "/api/health",
+    "/api/heatmap",
+    "/api/http_stats",
+    "/api/manual_equity",
+    "/api/manual_stats",
+    "/api/manual_trade",
+    "/api/manual_trade/limit",
+    "/api/manual_trade/partial",
+    "/api/manual_trades",
+    "/api/manual_trades/reset",
+    "/api/market_breadth",
+    "/api/movers",
+    "/api/news",
+    "/api/pairs",
+    "/api/pause",
+    "/api/pending_signals",
+    "/api/portfolio",
+    "/api/signal_stats/history",
+    "/api/signals",
+    "/api/signals/unread",
+    "/api/stars",
+    "/api/stars_history",
+    "/api/stats",
+    "/api/test_signal",
+    "/api/top10",
+    "/api/trade_advice",
+    "/api/version",
+];
+
+/// Routes met pad-parameters (/api/pair/<base>/<quote>): alles eronder telt als de route zelf.
+const HTTP_PARAM_ROUTE_KEYS: &[&str] = &["/api/orderbook", "/api/pair", "/api/tape"];
+
+/// Bovengrenzen (ms) van de latency-buckets; de laatste bucket is alles daarboven.
+const HTTP_LATENCY_BUCKETS_MS: [u64; 9] = [1, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// Tellers per endpoint. Alleen atomics, zodat de logging-wrapper nooit op een lock wacht.
+#[derive(Default)]
+struct EndpointStats {
+    count: AtomicU64,
+    errors: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    buckets: [AtomicU64; HTTP_LATENCY_BUCKETS_MS.len() + 1],
+}
+
+#[derive(Serialize, Clone)]
+struct EndpointStatsSnapshot {
+    path: String,
+    count: u64,
+    errors: u64,
+    avg_ms: f64,
+    max_ms: f64,
+    // Percentielen zijn de bovengrens van de bucket waarin ze vallen (None = boven 1000ms)
+    p50_ms: Option<u64>,
+    p95_ms: Option<u64>,
+    p99_ms: Option<u64>,
+    buckets: std::vec::Vec<u64>,
+}
+
+#[derive(Default)]
+struct HttpStats {
+    endpoints: DashMap<String, EndpointStats>,
+}
+
+impl HttpStats {
+    /// Houdt de kardinaliteit laag: alleen bekende routes krijgen een eigen key
+    /// (`/api/pair/BTC/EUR` telt als `/api/pair`, `/api/v1/x` als `/api/x`); al het
+    /// andere, ook onbekende /api/ paden, valt onder "other", zodat gescande URLs de
+    /// map niet laten groeien.
+    fn route_key(path: &str) -> String {
+        if path == "/" {
+            return "/".to_string();
+        }
+        let path = match path.strip_prefix(API_VERSION_PREFIX) {
+            Some(rest) => format!("/api/{}", rest),
+            None => path.to_string(),
+        };
+        let path = path.trim_end_matches('/');
+        if let Some(key) = HTTP_ROUTE_KEYS.iter().find(|k| **k == path) {
+            return key.to_string();
+        }
+        HTTP_PARAM_ROUTE_KEYS
+            .iter()
+            .find(|k| path.strip_prefix(**k).is_some_and(|rest| rest.starts_with('/')))
+            .map_or_else(|| "other".to_string(), |k| k.to_string())
+    }
+
+    fn record(&self, path: &str, status: u16, elapsed: Duration) {
+        let us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let ms = us / 1000;
+        let bucket = HTTP_LATENCY_BUCKETS_MS
+            .iter()
+            .position(|b| ms < *b)
+            .unwrap_or(HTTP_LATENCY_BUCKETS_MS.len());
+        let key = Self::route_key(path);
+        let stats = self.endpoints.entry(key).or_default();
+        stats.count.fetch_add(1, Ordering::Relaxed);
+        if status >= 400 {
+            stats.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        stats.total_us.fetch_add(us, Ordering::Relaxed);
+        stats.max_us.fetch_max(us, Ordering::Relaxed);
+        stats.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> std::vec::Vec<EndpointStatsSnapshot> {
+        let mut out: std::vec::Vec<EndpointStatsSnapshot> = self
+            .endpoints
+            .iter()
+            .map(|e| {
+                let s = e.value();
+                let count = s.count.load(Ordering::Relaxed);
+                let buckets: std::vec::Vec<u64> = s.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect();
+                let percentile = |p: f64| -> Option<u64> {
+                    let target = ((count as f64) * p).ceil().max(1.0) as u64;
+                    let mut seen = 0;
+                    for (i, n) in buckets.iter().enumerate() {
+                        seen += n;
+                        if seen >= target {
+                            return HTTP_LATENCY_BUCKETS_MS.get(i).copied();
+                        }
+                    }
+                    None
+                };
+                EndpointStatsSnapshot {
+                    path: e.key().clone(),
+                    count,
+                    errors: s.errors.load(Ordering::Relaxed),
+                    avg_ms: if count > 0 { s.total_us.load(Ordering::Relaxed) as f64 / count as f64 / 1000.0 } else { 0.0 },
+                    max_ms: s.max_us.load(Ordering::Relaxed) as f64 / 1000.0,
+                    p50_ms: percentile(0.50),
+                    p95_ms: percentile(0.95),
+                    p99_ms: percentile(0.99),
+                    buckets,
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+        out
+    }
+}
+
+// Aantal recente signals waarover p50/p99 van de signal latency lopen
+const SIGNAL_LATENCY_WINDOW: usize = 1024;
+
+/// Tijd van WS trade-ontvangst tot het signal in de buffer staat (en dus via de
+/// API zichtbaar is). Percentielen over de laatste SIGNAL_LATENCY_WINDOW signals.
+#[derive(Default)]
+struct SignalLatency {
+    recent_us: Mutex<VecDeque<u64>>,
+    count: AtomicU64,
+    total_us: AtomicU64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+struct SignalLatencySnapshot {
+    count: u64,
+    sum_sec: f64,
+    window: usize,
+    p50_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+impl SignalLatency {
+    fn record(&self, elapsed: Duration) {
+        let us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(us, Ordering::Relaxed);
+        let mut recent = self.recent_us.lock().unwrap();
+        recent.push_back(us);
+        if recent.len() > SIGNAL_LATENCY_WINDOW {
+            recent.pop_front();
+        }
+    }
+
+    fn snapshot(&self) -> SignalLatencySnapshot {
+        let mut sorted: std::vec::Vec<u64> = self.recent_us.lock().unwrap().iter().copied().collect();
+        sorted.sort_unstable();
+        // Nearest-rank percentiel
+        let pct = |p: f64| -> f64 {
+            if sorted.is_empty() {
+                return 0.0;
+            }
+            let rank = ((sorted.len() as f64) * p).ceil().max(1.0) as usize;
+            sorted[rank.min(sorted.len()) - 1] as f64 / 1000.0
+        };
+        SignalLatencySnapshot {
+            count: self.count.load(Ordering::Relaxed),
+            sum_sec: self.total_us.load(Ordering::Relaxed) as f64 / 1e6,
+            window: sorted.len(),
+            p50_ms: pct(0.50),
+            p99_ms: pct(0.99),
+            max_ms: sorted.last().map(|us| *us as f64 / 1000.0).unwrap_or(0.0),
+        }
+    }
+}
+
+/// /metrics in Prometheus text formaat.
+fn render_metrics(latency: &SignalLatencySnapshot) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP whale_radar_signal_latency_seconds Tijd van trade-ontvangst tot signal push (laatste signals)\n");
+    out.push_str("# TYPE whale_radar_signal_latency_seconds summary\n");
+    for (q, ms) in [("0.5", latency.p50_ms), ("0.99", latency.p99_ms)] {
+        out.push_str(&format!("whale_radar_signal_latency_seconds{{quantile=\"{}\"}} {}\n", q, ms / 1000.0));
+    }
+    out.push_str(&format!("whale_radar_signal_latency_seconds_sum {}\n", latency.sum_sec));
+    out.push_str(&format!("whale_radar_signal_latency_seconds_count {}\n", latency.count));
+    out
+}
+
+const MINUTE_CLOSES_MAX_AGE_SEC: f64 = 3600.0 + 300.0;
+
+/// Houdt per minuut alleen de laatste prijs bij en gooit samples ouder dan ~1u weg.
+fn push_minute_close(samples: &mut std::vec::Vec<(f64, f64)>, ts: f64, price: f64) {
+    if price <= 0.0 {
+        return;
+    }
+    let minute = (ts / 60.0).floor();
+    match samples.last_mut() {
+        Some(last) if (last.0 / 60.0).floor() == minute => *last = (ts, price),
+        _ => samples.push((ts, price)),
+    }
+    let cutoff = ts - MINUTE_CLOSES_MAX_AGE_SEC;
+    samples.retain(|(x, _)| *x >= cutoff);
+}
+
+// Min. aantal gedeelde minuten voor een correlatie in /api/portfolio
+const PORTFOLIO_MIN_OVERLAP: usize = 10;
+
+/// Minuut-returns uit minute_closes, op minuut-index; alleen aansluitende minuten.
+fn minute_returns(closes: &[(f64, f64)]) -> std::collections::BTreeMap<i64, f64> {
+    closes
+        .windows(2)
+        .filter_map(|w| {
+            let (m0, m1) = ((w[0].0 / 60.0).floor() as i64, (w[1].0 / 60.0).floor() as i64);
+            (m1 == m0 + 1 && w[0].1 > 0.0).then(|| (m1, w[1].1 / w[0].1 - 1.0))
+        })
+        .collect()
+}
+
+/// Pearson correlatie van de minuut-returns over de gedeelde minuten:
+/// (correlatie, aantal minuten); None onder PORTFOLIO_MIN_OVERLAP of zonder variantie.
+fn return_correlation(
+    a: &std::collections::BTreeMap<i64, f64>,
+    b: &std::collections::BTreeMap<i64, f64>,
+) -> Option<(f64, usize)> {
+    let pairs: std::vec::Vec<(f64, f64)> = a.iter().filter_map(|(m, x)| b.get(m).map(|y| (*x, *y))).collect();
+    let n = pairs.len();
+    if n < PORTFOLIO_MIN_OVERLAP {
+        return None;
+    }
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n as f64;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n as f64;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x <= 0.0 || var_y <= 0.0 {
+        return None;
+    }
+    Some(((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0), n))
+}
+
+/// "Effective number of bets": (Σw)² / ΣΣ w_i·w_j·ρ_ij. Gelijke gewichten geven N bij
+/// ongecorreleerde en 1 bij perfect gecorreleerde posities; onbekende ρ telt als 0.
+fn effective_positions(weights: &[f64], corr: impl Fn(usize, usize) -> Option<f64>) -> f64 {
+    let total: f64 = weights.iter().sum();
+    let mut denom = 0.0;
+    for (i, wi) in weights.iter().enumerate() {
+        for (j, wj) in weights.iter().enumerate() {
+            let rho = if i == j { 1.0 } else { corr(i, j).unwrap_or(0.0) };
+            denom += wi * wj * rho;
+        }
+    }
+    if denom > 0.0 { total * total / denom } else { weights.len() as f64 }
+}
+
+/// Som van whale buy en sell notional (beide positief) binnen `window` seconden vóór `now`.
+fn whale_flow(samples: &[(f64, f64)], now: f64, window: f64) -> (f64, f64) {
+    samples
+        .iter()
+        .filter(|(ts, _)| *ts >= now - window)
+        .fold((0.0, 0.0), |(buys, sells), (_, n)| {
+            if *n >= 0.0 {
+                (buys + n, sells)
+            } else {
+                (buys, sells - n)
+            }
+        })
+}
+
+/// Return (%) over `window` seconden: t.o.v. de laatste sample van vóór now - window.
+/// None als de historie nog niet zo ver teruggaat.
+fn timeframe_return(samples: &[(f64, f64)], now: f64, price: f64, window: f64) -> Option<f64> {
+    if price <= 0.0 {
+        return None;
+    }
+    samples
+        .iter()
+        .rev()
+        .find(|(pt, p_old)| *pt <= now - window && *p_old > 0.0)
+        .map(|(_, p_old)| (price - *p_old) / *p_old * 100.0)
+}
+
+/// Notional voor weergave zonder valuta: "950", "12.3k", "4.56M", "1.20B".
+/// De grenzen liggen net onder 1000 zodat afronding nooit "1000.0k" geeft.
+fn format_notional(v: f64) -> String {
+    let a = v.abs();
+    if a >= 999_995_000.0 {
+        format!("{:.2}B", v / 1e9)
+    } else if a >= 999_950.0 {
+        format!("{:.2}M", v / 1e6)
+    } else if a >= 999.5 {
+        format!("{:.1}k", v / 1e3)
+    } else {
+        format!("{:.0}", v)
+    }
+}
+
+// Toegestane afwijking van de doel-leeftijd als fractie daarvan. Bij 0.5 overlappen
+// de 5s/30s/120s vensters (2.5-7.5, 15-45, 60-180) niet, zodat één sample niet
+// drie keer in pump_score meetelt.
+const RETURN_AGE_TOLERANCE: f64 = 0.5;
+
+/// Return (%) t.o.v. de sample waarvan de leeftijd het dichtst bij `target_age`
+/// ligt, mits binnen RETURN_AGE_TOLERANCE daarvan. Ruimer dan vaste smalle
+/// vensters, zodat dun verhandelde pairs toch een return krijgen; zonder passende
+/// sample is de return 0.
+fn return_near_age(samples: &VecDeque<(f64, f64)>, now: f64, price: f64, target_age: f64) -> f64 {
+    if price <= 0.0 {
+        return 0.0;
+    }
+    let tolerance = target_age * RETURN_AGE_TOLERANCE;
+    samples
+        .iter()
+        .filter(|(pt, p_old)| now - *pt > 0.0 && *p_old > 0.0)
+        .filter(|(pt, _)| ((now - *pt) - target_age).abs() <= tolerance)
+        .min_by(|a, b| {
+            let da = ((now - a.0) - target_age).abs();
+            let db = ((now - b.0) - target_age).abs();
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(_, p_old)| (price - *p_old) / *p_old * 100.0)
+        .unwrap_or(0.0)
+}
+
+/// Alles wat de scoring van één trade nodig heeft; handle_trade vult dit uit
+/// TradeState, ticker (ANOM) en orderbook.
+#[derive(Debug, Clone)]
+struct TradeScoreInput<'a> {
+    is_buy: bool,
+    is_whale: bool,
+    volume: f64,
+    notional: f64,
+    // EWMA's inclusief deze trade
+    ewma_trade_size: f64,
+    ewma_notional: f64,
+    ewma_volume: f64,
+    pct: f64,
+    flow_pct: f64,
+    dir: &'a str,
+    flow_pct_5m: f64,
+    dir_5m: &'a str,
+    // Returns (%) over ~5s / 30s / 120s; negatieve returns tellen als 0
+    ret_5s: f64,
+    ret_30s: f64,
+    ret_120s: f64,
+    // Bid-aandeel van een vers orderboek (None = geen vers boek, geen boost)
+    book_ratio: Option<f64>,
+    // Strength van een ANOM van max. 10 min oud
+    anom_strength: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TradeScores {
+    flow_score: f64,
+    price_score: f64,
+    whale_score: f64,
+    volume_score: f64,
+    anomaly_score: f64,
+    trend_score: f64,
+    total_score: f64,
+    rating: String,
+    pump_score: f64,
+    pump_conf: f64,
+    pump_label: String,
+    whale_pred_score: f64,
+    whale_pred_label: String,
+    early: String,
+    alpha: String,
+}
+
+/// NaN/Inf (bv. door een prijs van 0 of een lege window) telt als 0, zodat één
+/// kapotte trade geen scores of sorteringen vergiftigt.
+fn finite_or_zero(v: f64) -> f64 {
+    if v.is_finite() {
+        v
+    } else {
+        0.0
+    }
+}
+
+/// Totale ordening voor score-sorts; niet-eindige waarden sorteren als 0.
+fn score_cmp(a: f64, b: f64) -> std::cmp::Ordering {
+    finite_or_zero(a).total_cmp(&finite_or_zero(b))
+}
+
+fn rating_for_score(total_score: f64) -> &'static str {
+    if total_score >= 7.5 {
+        "ALPHA BUY"
+    } else if total_score >= 5.0 {
+        "STRONG BUY"
+    } else if total_score >= 3.5 {
+        "BUY"
+    } else if total_score >= 2.2 {
+        "EARLY BUY"
+    } else {
+        "NONE"
+    }
+}
+
+/// EARLY_PUMP vanaf pump_conf_threshold, MEGA_PUMP vraagt een marge extra zekerheid
+fn pump_label_for(pump_score: f64, pump_conf: f64, dir: &str, conf_threshold: f64) -> &'static str {
+    if pump_score >= 7.0 && pump_conf >= conf_threshold + PUMP_CONF_MEGA_MARGIN && dir == "BUY" {
+        "MEGA_PUMP"
+    } else if pump_score >= 4.0 && pump_conf >= conf_threshold && dir == "BUY" {
+        "EARLY_PUMP"
+    } else {
+        "NONE"
+    }
+}
+
+/// De config-velden die score_trade leest; per trade gekopieerd i.p.v. de hele AppConfig.
+#[derive(Debug, Clone)]
+struct ScoreConfig {
+    whale_tiers: WhaleTiers,
+    volume_score_notional: bool,
+    pump: PumpSettings,
+    pump_conf_threshold: f64,
+    early_buy_threshold: f64,
+    alpha_buy_threshold: f64,
+    whale_pred_high_threshold: f64,
+    whale_pred_medium_threshold: f64,
+    whale_pred_low_threshold: f64,
+}
+
+impl From<&AppConfig> for ScoreConfig {
+    fn from(cfg: &AppConfig) -> Self {
+        Self {
+            whale_tiers: cfg.whale_tiers.clone(),
+            volume_score_notional: cfg.volume_score_notional,
+            pump: cfg.pump.clone(),
+            pump_conf_threshold: cfg.pump_conf_threshold,
+            early_buy_threshold: cfg.early_buy_threshold,
+            alpha_buy_threshold: cfg.alpha_buy_threshold,
+            whale_pred_high_threshold: cfg.whale_pred_high_threshold,
+            whale_pred_medium_threshold: cfg.whale_pred_medium_threshold,
+            whale_pred_low_threshold: cfg.whale_pred_low_threshold,
+        }
+    }
+}
+
+/// Wat handle_trade per trade uit de config nodig heeft. AppConfig bevat HashMaps
+/// (pair_aliases, enabled_signal_types, reliability_gate_types, eval_horizon_by_type,
+/// quiet_hours_channels) en strings (quote_currencies, locale, timezone, log_level); die per trade
+/// clonen kost meer dan de scoring zelf.
+#[derive(Debug, Clone)]
+struct TradeConfig {
+    min_trade_notional: f64,
+    tape_size: usize,
+    ewma_alpha: f64,
+    whale_min_notional: f64,
+    whale_flow_window_sec: f64,
+    orderbook_fresh_sec: i64,
+    reliability: ReliabilityParams,
+    signal_on_candle_close: bool,
+    signal_candle_sec: i64,
+    wash: WashSettings,
+    flow: FlowSettings,
+    divergence: DivergenceSettings,
+    stars: StarsSettings,
+    score: ScoreConfig,
+}
+
+impl From<&AppConfig> for TradeConfig {
+    fn from(cfg: &AppConfig) -> Self {
+        Self {
+            min_trade_notional: cfg.min_trade_notional,
+            tape_size: cfg.tape_size,
+            ewma_alpha: cfg.ewma_alpha,
+            whale_min_notional: cfg.whale_min_notional,
+            whale_flow_window_sec: cfg.whale_flow_window_sec,
+            orderbook_fresh_sec: cfg.orderbook_fresh_sec,
+            reliability: ReliabilityParams::from(cfg),
+            signal_on_candle_close: cfg.signal_on_candle_close,
+            signal_candle_sec: cfg.signal_candle_sec,
+            wash: cfg.wash.clone(),
+            flow: cfg.flow.clone(),
+            divergence: cfg.divergence.clone(),
+            stars: cfg.stars.clone(),
+            score: ScoreConfig::from(cfg),
+        }
+    }
+}
+
+fn whale_pred_label_for(whale_pred_score: f64, cfg: &ScoreConfig) -> &'static str {
+    if whale_pred_score >= cfg.whale_pred_high_threshold {
+        "HIGH"
+    } else if whale_pred_score >= cfg.whale_pred_medium_threshold {
+        "MEDIUM"
+    } else if whale_pred_score >= cfg.whale_pred_low_threshold {
+        "LOW"
+    } else {
+        "NONE"
+    }
+}
+
+/// Pure scoring van één trade: sub-scores, total/rating, pump en whale_pred, en
+/// de EARLY/ALPHA flags. Geen state; handle_trade schrijft het resultaat weg.
+fn score_trade(input: &TradeScoreInput, weights: &ScoreWeights, cfg: &ScoreConfig) -> TradeScores {
+    let tiers = &cfg.whale_tiers;
+    let flow_pct = input.flow_pct;
+    let flow_pct_5m = input.flow_pct_5m;
+    let n1 = input.ewma_notional;
+
+    let mut flow_score_short = 0.0_f64;
+    if flow_pct > 75.0 {
+        flow_score_short = 3.0;
+    } else if flow_pct > 65.0 {
+        flow_score_short = 2.0;
+    } else if flow_pct > 55.0 {
+        flow_score_short = 1.0;
+    }
+
+    let mut flow_score_long = 0.0;
+    if input.dir_5m == "BUY" && flow_pct_5m > 75.0 {
+        flow_score_long = 2.0;
+    } else if input.dir_5m == "BUY" && flow_pct_5m > 65.0 {
+        flow_score_long = 1.0;
+    }
+
+    let flow_score = (flow_score_short + 0.5 * flow_score_long).min(3.0);
+
+    let mut price_score = 0.0;
+    if input.pct > 2.0 {
+        price_score = 3.0;
+    } else if input.pct > 1.0 {
+        price_score = 2.0;
+    } else if input.pct > 0.3 {
+        price_score = 1.0;
+    }
+
+    let mut whale_score = 0.0;
+    if input.is_whale {
+        if input.notional > tiers.large_notional || input.notional > n1 * tiers.large_ewma_mult {
+            whale_score = 3.0;
+        } else if input.notional > tiers.medium_notional && input.notional > n1 * tiers.medium_ewma_mult {
+            whale_score = 2.0;
+        } else {
+            whale_score = 1.0;
+        }
+    }
+
+    if let Some(bid_ratio) = input.book_ratio {
+        if (input.is_buy && bid_ratio > 0.65) || (!input.is_buy && bid_ratio < 0.35) {
+            whale_score += 0.5;
+        }
+
+        if (bid_ratio > 0.75 && input.is_buy) || (bid_ratio < 0.25 && !input.is_buy) {
+            whale_score += 0.3;
+        }
+    }
+
+    if whale_score > 4.0 {
+        whale_score = 4.0;
+    }
+
+    let mut volume_score = 0.0;
+    let vol_ratio = if cfg.volume_score_notional {
+        if n1 > 0.0 { input.notional / n1 } else { 1.0 }
+    } else if input.ewma_volume > 0.0 {
+        input.volume / input.ewma_volume
+    } else {
+        1.0
+    };
+    if vol_ratio > 2.5 {
+        volume_score = 3.0;
+    } else if vol_ratio > 1.5 {
+        volume_score = 2.0;
+    } else if vol_ratio > 1.2 {
+        volume_score = 1.0;
+    }
+
+    let mut anomaly_score = 0.0;
+    if let Some(anom_strength) = input.anom_strength {
+        if anom_strength > 80.0 {
+            anomaly_score = 3.0;
+        } else if anom_strength > 40.0 {
+            anomaly_score = 2.0;
+        } else if anom_strength > 0.0 {
+            anomaly_score = 1.0;
+        }
+    }
+
+    let mut trend_score = 0.0;
+    if input.is_whale && input.is_buy && input.pct > 0.0 && flow_pct > 60.0 {
+        trend_score += 1.0;
+    }
+
+    let ret_5s = input.ret_5s.max(0.0);
+    let ret_30s = input.ret_30s.max(0.0);
+    let ret_120s = input.ret_120s.max(0.0);
+
+    let pc = &cfg.pump;
+    let mut pump_score = 0.0_f64;
+
+    if ret_5s > pc.ret_5s_threshold {
+        pump_score += (ret_5s - pc.ret_5s_threshold) * pc.ret_5s_mult;
+    }
+    if ret_30s > pc.ret_30s_threshold {
+        pump_score += (ret_30s - pc.ret_30s_threshold) * pc.ret_30s_mult;
+    }
+    if ret_120s > pc.ret_120s_threshold {
+        pump_score += (ret_120s - pc.ret_120s_threshold) * pc.ret_120s_mult;
+    }
+    if input.dir == "BUY" && flow_pct > pc.flow_threshold {
+        pump_score += (flow_pct - pc.flow_threshold) * pc.flow_mult;
+    }
+    if input.dir_5m == "BUY" && flow_pct_5m > pc.flow_5m_threshold {
+        pump_score += (flow_pct_5m - pc.flow_5m_threshold) * pc.flow_5m_mult;
+    }
+    if vol_ratio > pc.vol_ratio_threshold {
+        pump_score += (vol_ratio - pc.vol_ratio_threshold) * pc.vol_ratio_mult;
+    }
+    if whale_score > 0.0 {
+        pump_score += whale_score * pc.whale_mult;
+    }
+
+    pump_score = pump_score.clamp(0.0, pc.max_score);
+
+    let mut pump_conf = 0.0_f64;
+    if ret_5s > 0.5 {
+        pump_conf += 0.4;
+    }
+    if ret_30s > 1.5 {
+        pump_conf += 0.3;
+    }
+    if ret_120s > 3.0 {
+        pump_conf += 0.2;
+    }
+    if input.dir == "BUY" && flow_pct > 70.0 {
+        pump_conf += 0.3;
+    }
+    if input.dir_5m == "BUY" && flow_pct_5m > 65.0 {
+        pump_conf += 0.2;
+    }
+    if vol_ratio > 2.0 {
+        pump_conf += 0.2;
+    }
+    if whale_score >= 2.0 {
+        pump_conf += 0.2;
+    }
+
+    let (flow_score, price_score, whale_score) =
+        (finite_or_zero(flow_score), finite_or_zero(price_score), finite_or_zero(whale_score));
+    let (volume_score, anomaly_score, trend_score) =
+        (finite_or_zero(volume_score), finite_or_zero(anomaly_score), finite_or_zero(trend_score));
+    let (pump_score, pump_conf) = (finite_or_zero(pump_score), finite_or_zero(pump_conf));
+    let pump_label = pump_label_for(pump_score, pump_conf, input.dir, cfg.pump_conf_threshold);
+
+    let total_score = finite_or_zero(
+        weights.flow_w * flow_score
+            + weights.price_w * price_score
+            + weights.whale_w * whale_score
+            + weights.volume_w * volume_score
+            + weights.anomaly_w * anomaly_score
+            + weights.trend_w * trend_score,
+    );
+
+    let mut whale_pred_score = 0.0;
+
+    if !input.is_whale && input.dir == "BUY" && flow_pct > 60.0 {
+        whale_pred_score += (flow_pct - 60.0) * 0.08;
+    }
+
+    if !input.is_whale && input.dir_5m == "BUY" && flow_pct_5m > 55.0 {
+        whale_pred_score += (flow_pct_5m - 55.0) * 0.06;
+    }
+
+    if !input.is_whale && input.volume < input.ewma_trade_size * 0.8 {
+        whale_pred_score += 1.0;
+    }
+
+    if ret_5s < 0.5 && ret_30s < 1.0 && input.pct >= -0.5 {
+        whale_pred_score += 1.0;
+    }
+
+    if vol_ratio < 1.3 {
+        whale_pred_score += 0.5;
+    }
+
+    if let Some(bid_ratio) = input.book_ratio {
+        if bid_ratio > 0.65 {
+            whale_pred_score += (bid_ratio - 0.65) * 2.0;
+        }
+    }
+
+    whale_pred_score = finite_or_zero(whale_pred_score).clamp(0.0, 10.0);
+
+    // Elke flag heeft een eigen drempel; het rating-label is alleen weergave
+    let is_buy_dir = input.dir == "BUY";
+    let early = if is_buy_dir && total_score >= cfg.early_buy_threshold { "BUY" } else { "NONE" };
+    let alpha = if is_buy_dir && total_score >= cfg.alpha_buy_threshold { "BUY" } else { "NONE" };
+
+    TradeScores {
+        flow_score,
+        price_score,
+        whale_score,
+        volume_score,
+        anomaly_score,
+        trend_score,
+        total_score,
+        rating: rating_for_score(total_score).to_string(),
+        pump_score,
+        pump_conf,
+        pump_label: pump_label.to_string(),
+        whale_pred_score,
+        whale_pred_label: whale_pred_label_for(whale_pred_score, cfg).to_string(),
+        early: early.to_string(),
+        alpha: alpha.to_string(),
+    }
+}
+
+impl Engine {
+    fn new(config: Arc<Mutex<AppConfig>>) -> Self {
+        Self {
+            trades: Arc::new(DashMap::new()),
+            candles: Arc::new(DashMap::new()),
+            tickers: Arc::new(DashMap::new()),
+            orderbooks: Arc::new(DashMap::new()),
+            signals: Arc::new(Mutex::new(std::vec::Vec::new())),
+            signalled_pairs: Arc::new(DashMap::new()),
+            weights: Arc::new(Mutex::new(ScoreWeights::default())),
+            manual_trader: Arc::new(Mutex::new(ManualTraderState::new())),
+            auto_trader: Arc::new(Mutex::new(AutoTraderState::new(VIRTUAL_INITIAL_BALANCE))),
+            news_sentiment: Arc::new(DashMap::new()),
+            news_dirty: Arc::new(AtomicBool::new(false)),
+            derivatives: Arc::new(DashMap::new()),
+            stars_history: Arc::new(Mutex::new(StarsHistory { history: std::vec::Vec::new(), dirty: false })),
+            config,
+            jsonl_tx: spawn_jsonl_writer(),
+            started_at: Utc::now().timestamp(),
+            signal_seq: Arc::new(AtomicU64::new(0)),
+            signal_stats: Arc::new(Mutex::new(HashMap::new())),
+            trading_paused: Arc::new(AtomicBool::new(false)),
+            alerts: Arc::new(Mutex::new(std::vec::Vec::new())),
+            quiet_suppressed: Arc::new(Mutex::new(HashMap::new())),
+            fx: Arc::new(Mutex::new(FxRates::default())),
+            snapshot_cache: Arc::new(TtlCache::new()),
+            top10_cache: Arc::new(TtlCache::new()),
+            http_stats: Arc::new(HttpStats::default()),
+            signal_latency: Arc::new(SignalLatency::default()),
+            signal_archive: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn stale_pair_count(&self, now_ts: i64) -> usize {
+        let threshold = self.config.lock().unwrap().stale_threshold_sec;
+        self.trades
+            .iter()
+            .filter(|t| now_ts.saturating_sub(t.last_trade_ts) > threshold)
+            .count()
+    }
+
+    /// Fractie van de pairs die sinds `since` via WS getraded hebben maar nu stil
+    /// liggen. None als er te weinig actieve pairs zijn om iets te zeggen.
+    fn stale_trade_fraction(&self, pairs: &[String], since: i64, now_ts: i64, threshold: i64) -> Option<f64> {
+        let mut active = 0usize;
+        let mut stale = 0usize;
+        for pair in pairs {
+            if let Some(t) = self.trades.get(pair) {
+                if t.last_trade_ts >= since {
+                    active += 1;
+                    if now_ts.saturating_sub(t.last_trade_ts) > threshold {
+                        stale += 1;
+                    }
+                }
+            }
+        }
+        if active < 4 {
+            return None;
+        }
+        Some(stale as f64 / active as f64)
+    }
+
+    fn health_snapshot(&self) -> Value {
+        let now_ts = Utc::now().timestamp();
+        let pairs_tracked = self.trades.len();
+        let stale_pairs = self.stale_pair_count(now_ts);
+        let (stale_threshold_sec, stale_reconnect_fraction) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.stale_threshold_sec, cfg.stale_reconnect_fraction)
+        };
+        let degraded = pairs_tracked == 0
+            || stale_pairs as f64 >= pairs_tracked as f64 * stale_reconnect_fraction;
+        serde_json::json!({
+            "status": if degraded { "degraded" } else { "ok" },
+            "uptime_sec": now_ts - self.started_at,
+            "pairs_tracked": pairs_tracked,
+            "stale_pairs": stale_pairs,
+            "stale_threshold_sec": stale_threshold_sec,
+            "trading_paused": self.is_trading_paused(),
+        })
+    }
+
+    fn log_event(&self, kind: &str, data: Value) {
+        let (enabled, max_bytes) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.event_log_enabled, cfg.event_log_max_bytes)
+        };
+        if !enabled {
+            return;
+        }
+        let line = serde_json::json!({
+            "ts": Utc::now().timestamp(),
+            "kind": kind,
+            "data": data,
+        });
+        self.write_jsonl(EVENT_LOG_FILE, EVENT_LOG_ROTATED_FILE, line.to_string(), max_bytes);
+    }
+
+    /// Zet een regel in de wachtrij van de jsonl-writer; blokkeert nooit op disk I/O.
+    fn write_jsonl(&self, path: &'static str, rotated_path: &'static str, line: String, max_bytes: u64) {
+        if self.jsonl_tx.send(JsonlLine { path, rotated_path, line, max_bytes }).is_err() {
+            log_error!("[ERR] jsonl-writer gestopt; regel voor {} verloren", path);
+        }
+    }
+
+    fn mark_signalled(&self, pair: &str) {
+        self.signalled_pairs.insert(pair.to_string(), Utc::now().timestamp());
+    }
+
+    fn push_signal(&self, mut ev: SignalEvent) {
+        // Test signals slaan de gates over: ze moeten de alert-keten altijd raken
+        if !ev.test {
+            let cfg = self.config.lock().unwrap();
+            if !cfg.signal_type_enabled(&ev.signal_type) {
+                return;
+            }
+            if !cfg.passes_reliability_gate(&ev.signal_type, ev.reliability_score) {
+                return;
+            }
+        }
+        ev.horizon_sec = self.config.lock().unwrap().eval_horizon_for(&ev.signal_type);
+        ev.seq = self.signal_seq.fetch_add(1, Ordering::SeqCst) + 1;
+        self.log_event("signal", serde_json::to_value(&ev).unwrap_or_default());
+        if ev.test {
+            log_info!("[TEST SIGNAL] {} {} {} geïnjecteerd", ev.pair, ev.signal_type, ev.direction);
+        } else {
+            if ev.signal_type == "ALPHA" && ev.direction == "BUY" {
+                self.auto_trade_on_signal(&ev);
+            }
+            self.mark_signalled(&ev.pair);
+        }
+        self.dispatch_alerts(&ev);
+        let (max_history, archive_enabled, archive_max_bytes) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.max_history.max(1), cfg.signal_archive_enabled, cfg.signal_archive_max_bytes)
+        };
+        let overflow: std::vec::Vec<SignalEvent> = {
+            let mut buf = self.signals.lock().unwrap();
+            let same_type: std::vec::Vec<f64> = buf
+                .iter()
+                .filter(|e| e.signal_type == ev.signal_type)
+                .map(|e| e.strength)
+                .collect();
+            ev.strength_norm = percentile_rank(&same_type, ev.strength);
+            if let Some(at) = ev.received_at {
+                self.signal_latency.record(at.elapsed());
+            }
+            buf.push(ev);
+            if buf.len() > max_history {
+                let n = buf.len() - max_history;
+                buf.drain(0..n).collect()
+            } else {
+                std::vec::Vec::new()
+            }
+        };
+        // Alleen geëvalueerde signals hebben een uitkomst waar een backtest iets aan heeft;
+        // test signals horen er nooit in.
+        if archive_enabled && !overflow.is_empty() {
+            for old in overflow.iter().filter(|e| e.evaluated && !e.test) {
+                let line = serde_json::to_string(old).unwrap_or_default();
+                self.write_jsonl(SIGNAL_ARCHIVE_FILE, SIGNAL_ARCHIVE_ROTATED_FILE, line, archive_max_bytes);
+            }
+        }
+    }
+
+    // Enige kanaal is voorlopig het dashboard (signals badge); tijdens een pauze of
+    // quiet hours wordt het alert onderdrukt en als niet afgeleverd vastgelegd.
+    fn dispatch_alerts(&self, ev: &SignalEvent) {
+        let (quiet, channels) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.quiet_hours.active(&cfg.timezone, Utc::now().timestamp()), cfg.quiet_hours_channels.clone())
+        };
+        for channel in ALERT_CHANNELS {
+            let error = if self.is_trading_paused() {
+                Some("onderdrukt: trading gepauzeerd".to_string())
+            } else if quiet && channels.get(*channel).copied().unwrap_or(false) {
+                *self
+                    .quiet_suppressed
+                    .lock()
+                    .unwrap()
+                    .entry(channel.to_string())
+                    .or_default()
+                    .entry(ev.signal_type.clone())
+                    .or_default() += 1;
+                Some("onderdrukt: quiet hours".to_string())
+            } else {
+                None
+            };
+            self.record_alert(AlertRecord {
+                ts: ev.ts,
+                pair: ev.pair.clone(),
+                alert_type: ev.signal_type.clone(),
+                channel: channel.to_string(),
+                delivered: error.is_none(),
+                error,
+                message: None,
+            });
+        }
+    }
+
+    /// Na afloop van de quiet hours: per kanaal één SUMMARY alert met de aantallen
+    /// onderdrukte alerts per type (of alleen wissen als summary uit staat).
+    fn flush_quiet_summary(&self, now: i64) {
+        let (quiet, summary) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.quiet_hours.active(&cfg.timezone, now), cfg.quiet_hours.summary)
+        };
+        if quiet {
+            return;
+        }
+        let suppressed = std::mem::take(&mut *self.quiet_suppressed.lock().unwrap());
+        if !summary {
+            return;
+        }
+        for (channel, counts) in suppressed {
+            let total: usize = counts.values().sum();
+            if total == 0 {
+                continue;
+            }
+            let mut parts: std::vec::Vec<(String, usize)> = counts.into_iter().collect();
+            parts.sort();
+            let detail: std::vec::Vec<String> = parts.iter().map(|(t, n)| format!("{} {}", n, t)).collect();
+            self.record_alert(AlertRecord {
+                ts: now,
+                pair: String::new(),
+                alert_type: "SUMMARY".to_string(),
+                channel,
+                delivered: true,
+                error: None,
+                message: Some(format!("{} alerts onderdrukt tijdens quiet hours: {}", total, detail.join(", "))),
+            });
+        }
+    }
+
+    fn record_alert(&self, alert: AlertRecord) {
+        let (persist, max_bytes) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.alerts_persist, cfg.event_log_max_bytes)
+        };
+        if persist {
+            let line = serde_json::to_string(&alert).unwrap_or_default();
+            self.write_jsonl(ALERTS_FILE, ALERTS_ROTATED_FILE, line, max_bytes);
+        }
+        let mut buf = self.alerts.lock().unwrap();
+        buf.push(alert);
+        if buf.len() > ALERTS_MAX {
+            let n = buf.len() - ALERTS_MAX;
+            buf.drain(0..n);
+        }
+    }
+
+    fn record_http(&self, path: &str, status: u16, elapsed: Duration) {
+        self.http_stats.record(path, status, elapsed);
+    }
+
+    fn alerts_snapshot(&self) -> std::vec::Vec<AlertRecord> {
+        self.alerts.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    async fn load_alerts(&self) {
+        if !self.config.lock().unwrap().alerts_persist {
+            return;
+        }
+        let mut loaded = std::vec::Vec::new();
+        for path in [ALERTS_ROTATED_FILE, ALERTS_FILE] {
+            if let Ok(content) = tokio::fs::read_to_string(path).await {
+                loaded.extend(content.lines().filter_map(|l| serde_json::from_str::<AlertRecord>(l).ok()));
+            }
+        }
+        if loaded.len() > ALERTS_MAX {
+            loaded.drain(0..loaded.len() - ALERTS_MAX);
+        }
+        *self.alerts.lock().unwrap() = loaded;
+    }
+
+    /// Paper fill voor `pair` rond `reference` volgens slippage_bps / fill_at_book.
+    fn paper_fill(&self, pair: &str, reference: f64, is_buy: bool, cfg: &AppConfig, now: i64) -> Fill {
+        let book = if cfg.fill_at_book {
+            self.orderbooks.get(pair).and_then(|ob| {
+                let age = now.saturating_sub(ob.timestamp);
+                if !(0..=cfg.orderbook_fresh_sec).contains(&age) {
+                    return None;
+                }
+                Some((ob.bids.first()?.0, ob.asks.first()?.0))
+            })
+        } else {
+            None
+        };
+        simulate_fill(reference, is_buy, book, cfg.slippage_bps)
+    }
 
-impl Engine {
-    fn new() -> Self {
-        Self {
-            trades: Arc::new(DashMap::new()),
-            candles: Arc::new(DashMap::new()),
-            tickers: Arc::new(DashMap::new()),
-            orderbooks: Arc::new(DashMap::new()),
-            signals: Arc::new(Mutex::new(std::vec::Vec::new())),
-            signalled_pairs: Arc::new(DashMap::new()),
-            weights: Arc::new(Mutex::new(ScoreWeights::default())),
-            manual_trader: Arc::new(Mutex::new(ManualTraderState::new())),
-            news_sentiment: Arc::new(DashMap::new()),
-            stars_history: Arc::new(Mutex::new(StarsHistory { history: std::vec::Vec::new(), dirty: false })),
+    fn auto_trade_on_signal(&self, ev: &SignalEvent) {
+        let cfg = self.config.lock().unwrap().clone();
+        if !cfg.enable_trading || !cfg.auto_trading_enabled || self.is_trading_paused() {
+            return;
+        }
+        let fill = self.paper_fill(&ev.pair, ev.price, true, &cfg, ev.ts);
+        let opened = self
+            .auto_trader
+            .lock()
+            .unwrap()
+            .open_position(&ev.pair, fill, ev.ts, ev.total_score, &cfg);
+        if opened {
+            self.log_event("auto_open", serde_json::json!({
+                "pair": ev.pair,
+                "price": ev.price,
+                "score": ev.total_score,
+            }));
         }
     }
 
-    fn mark_signalled(&self, pair: &str) {
-        self.signalled_pairs.insert(pair.to_string(), true);
+    fn auto_trade_check_exit(&self, pair: &str, price: f64, ts: i64) {
+        let record = {
+            let mut trader = self.auto_trader.lock().unwrap();
+            if !trader.positions.contains_key(pair) {
+                return;
+            }
+            let cfg = self.config.lock().unwrap().clone();
+            let fill = self.paper_fill(pair, price, false, &cfg, ts);
+            trader.check_exit(pair, price, fill, ts)
+        };
+        if let Some(record) = record {
+            self.log_event("auto_close", serde_json::to_value(&record).unwrap_or_default());
+        }
     }
 
-    fn push_signal(&self, ev: SignalEvent) {
-        self.mark_signalled(&ev.pair);
-        let mut buf = self.signals.lock().unwrap();
-        buf.push(ev);
-        if buf.len() > 400 {
-            let overflow = buf.len() - 400;
-            buf.drain(0..overflow);
+    fn auto_trades_snapshot(&self) -> AutoTradesResponse {
+        let enabled = {
+            let cfg = self.config.lock().unwrap();
+            cfg.enable_trading && cfg.auto_trading_enabled
+        };
+        let trader = self.auto_trader.lock().unwrap();
+        let positions = trader
+            .positions
+            .values()
+            .map(|p| {
+                let current_price = self.candles.get(&p.pair).and_then(|c| c.close).unwrap_or(p.entry_price);
+                AutoPositionView {
+                    pair: p.pair.clone(),
+                    entry_price: p.entry_price,
+                    size: p.size,
+                    notional: p.notional,
+                    open_ts: p.open_ts,
+                    stop_loss: p.stop_loss,
+                    take_profit: p.take_profit,
+                    current_price,
+                    pnl_abs: (current_price - p.entry_price) * p.size,
+                    pnl_pct: if p.entry_price > 0.0 {
+                        (current_price - p.entry_price) / p.entry_price * 100.0
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+        let mut closed_trades = trader.closed_trades.clone();
+        closed_trades.reverse();
+        AutoTradesResponse {
+            enabled,
+            balance: trader.balance,
+            initial_balance: trader.initial_balance,
+            positions,
+            closed_trades,
+            equity_curve: trader.equity_curve.clone(),
         }
     }
 
     fn update_sentiment(&self, pair: &str, sentiment: f64, title: &str) {
         self.news_sentiment.insert(pair.to_string(), (sentiment, Utc::now().timestamp(), title.to_string()));
+        self.news_dirty.store(true, Ordering::SeqCst);
         if let Some(mut ts) = self.trades.get_mut(pair) {
             ts.news_sentiment = sentiment;
             ts.last_update_ts = Utc::now().timestamp();
@@ -715,8 +4380,9 @@ impl Engine {
         }
     }
 
-    fn add_to_stars_history(&self, row: TopRow) {
-        println!("[STAR] Adding to history: {} at ts {}", row.pair, row.ts);
+    fn add_to_stars_history(&self, mut row: TopRow) {
+        row.conviction = self.config.lock().unwrap().conviction(row.rank_score(), row.reliability_score);
+        log_debug!("[STAR] Adding to history: {} at ts {}", row.pair, row.ts);
         let mut history = self.stars_history.lock().unwrap();
         history.history.push(row);
         history.dirty = true;
@@ -725,32 +4391,77 @@ impl Engine {
         }
     }
 
-    async fn save_stars_history(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let history = self.stars_history.lock().unwrap();
-        let json = serde_json::to_string_pretty(&*history)?;
-        tokio::fs::write(STARS_HISTORY_FILE, json).await?;
-        println!("[STARS SAVER] Saved history with {} entries", history.history.len());
-        Ok(())
+    fn news_sentiment_snapshot(&self) -> std::vec::Vec<NewsSentimentEntry> {
+        let mut entries: std::vec::Vec<NewsSentimentEntry> = self
+            .news_sentiment
+            .iter()
+            .map(|e| NewsSentimentEntry {
+                pair: e.key().clone(),
+                sentiment: e.value().0,
+                last_update: e.value().1,
+                title: e.value().2.clone(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.pair.cmp(&b.pair));
+        entries
+    }
+
+    async fn load_news_sentiment(&self) {
+        if !self.config.lock().unwrap().news_persist {
+            return;
+        }
+        let Ok(content) = tokio::fs::read_to_string(NEWS_SENTIMENT_FILE).await else {
+            return;
+        };
+        match serde_json::from_str::<std::vec::Vec<NewsSentimentEntry>>(&content) {
+            Ok(entries) => {
+                for e in entries.iter().filter(|e| e.sentiment.is_finite()) {
+                    self.news_sentiment.insert(e.pair.clone(), (e.sentiment.clamp(0.0, 1.0), e.last_update, e.title.clone()));
+                }
+                log_info!("[NEWS] Loaded sentiment for {} pairs from {}", self.news_sentiment.len(), NEWS_SENTIMENT_FILE);
+            }
+            Err(e) => log_warn!("[WARN] Failed to parse {}: {}", NEWS_SENTIMENT_FILE, e),
+        }
     }
 
     async fn load_stars_history(&self) -> Result<(), Box<dyn std::error::Error>> {
-        match tokio::fs::read_to_string(STARS_HISTORY_FILE).await {
-            Ok(content) => {
-                match serde_json::from_str(content.as_str()) {
-                    Ok(h) => {
-                        let mut history = self.stars_history.lock().unwrap();
-                        *history = h;
-                        println!("[STARS] Loaded history with {} entries", history.history.len());
-                    }
-                    Err(_) => {}
+        if let Ok(content) = tokio::fs::read_to_string(STARS_HISTORY_FILE).await {
+            if let Ok(mut h) = serde_json::from_str::<StarsHistory>(content.as_str()) {
+                for r in h.history.iter_mut().filter(|r| r.whale_notional_fmt.is_empty()) {
+                    r.whale_notional_fmt = format_notional(r.whale_notional);
                 }
+                // Oudere bestanden bevatten nog de alias van toen
+                for r in h.history.iter_mut() {
+                    r.display_name.clear();
+                }
+                let mut history = self.stars_history.lock().unwrap();
+                *history = h;
+                log_info!("[STARS] Loaded history with {} entries", history.history.len());
             }
-            Err(_) => {}
         }
         Ok(())
     }
 
-    fn handle_trade(&self, pair: &str, price: f64, volume: f64, side: &str, ts: f64) {
+    /// Koers van de quote van `pair` naar fx.base_currency; 1.0 als die (nog) onbekend is.
+    fn fx_rate(&self, pair: &str) -> f64 {
+        let Some(quote) = quote_currency(pair) else {
+            return 1.0;
+        };
+        let fx = self.fx.lock().unwrap();
+        if quote == fx.base_currency {
+            return 1.0;
+        }
+        fx.rates.get(quote).copied().unwrap_or(1.0)
+    }
+
+    fn handle_trade(&self, pair: &str, price: f64, volume: f64, side: &str, ts: f64, received_at: std::time::Instant) {
+        let cfg = TradeConfig::from(&*self.config.lock().unwrap());
+        // Drempels en whale scoring in fx.base_currency; getoonde notionals blijven in de quote
+        let fx = self.fx_rate(pair);
+        // Dust telt niet mee in trade_count/flow/EWMA; whales liggen altijd ruim boven de grens
+        if price * volume * fx < cfg.min_trade_notional {
+            return;
+        }
         let ts_int = ts.floor() as i64;
         let mut t = self.trades.entry(pair.to_string()).or_default();
 
@@ -761,6 +4472,12 @@ impl Engine {
         let prev_pred_label = t.whale_pred_label.clone().unwrap_or_else(|| "NONE".to_string());
 
         t.last_update_ts = ts_int;
+        t.last_trade_ts = ts_int;
+
+        t.tape.push_back(TapeTrade { ts, price, volume, side: side.to_string() });
+        while t.tape.len() > cfg.tape_size {
+            t.tape.pop_front();
+        }
 
         if side == "b" {
             t.buy_volume += volume;
@@ -772,24 +4489,32 @@ impl Engine {
         let notional = price * volume;
 
         let s0 = t.ewma_trade_size.unwrap_or(volume);
-        let s1 = 0.9 * s0 + 0.1 * volume;
+        let s1 = ewma(s0, volume, cfg.ewma_alpha);
         t.ewma_trade_size = Some(s1);
 
         let n0 = t.ewma_notional.unwrap_or(notional);
-        let n1 = 0.9 * n0 + 0.1 * notional;
+        let n1 = ewma(n0, notional, cfg.ewma_alpha);
         t.ewma_notional = Some(n1);
 
         let v0 = t.ewma_volume.unwrap_or(volume);
-        let v1 = 0.9 * v0 + 0.1 * volume;
+        let v1 = ewma(v0, volume, cfg.ewma_alpha);
         t.ewma_volume = Some(v1);
 
-        let min_notional = 5_000.0_f64;
-        let is_whale = notional > min_notional && notional > n1 * 2.5;
+        if notional > t.max_notional_seen {
+            t.max_notional_seen = notional;
+            t.max_notional_ts = ts_int;
+            t.max_notional_side = Some(side.to_string());
+        }
+
+        let tiers = &cfg.score.whale_tiers;
+        let is_whale = notional * fx > cfg.whale_min_notional && notional > n1 * tiers.detect_ewma_mult;
         if is_whale {
             t.last_whale = true;
+            t.last_whale_ts = ts_int;
             t.last_whale_side = Some(side.to_string());
             t.last_whale_volume = Some(volume);
             t.last_whale_notional = Some(notional);
+            t.recent_whales.push((ts, if side == "b" { notional } else { -notional }));
         } else {
             t.last_whale = false;
             t.last_whale_side = None;
@@ -797,6 +4522,25 @@ impl Engine {
             t.last_whale_notional = None;
         }
 
+        let whale_cutoff = ts - cfg.whale_flow_window_sec;
+        t.recent_whales.retain(|(x, _)| *x >= whale_cutoff);
+
+        if cfg.wash.enabled {
+            t.recent_prints.push((ts, volume, side == "b"));
+            let wash_cutoff = ts - cfg.wash.window_sec;
+            t.recent_prints.retain(|(x, _, _)| *x >= wash_cutoff);
+            let round_trips = wash_round_trips(&t.recent_prints, cfg.wash.volume_tolerance);
+            let suspicious = round_trips >= cfg.wash.min_round_trips;
+            if suspicious && !t.suspicious {
+                log_warn!("[WASH] {} verdacht: {} gelijke buy/sell round trips binnen {}s", pair, round_trips, cfg.wash.window_sec);
+            }
+            t.suspicious = suspicious;
+        } else {
+            t.recent_prints.clear();
+            t.suspicious = false;
+        }
+        let (whale_buy_flow, whale_sell_flow) = whale_flow(&t.recent_whales, ts, cfg.whale_flow_window_sec);
+
         let mut c = self.candles.entry(pair.to_string()).or_default();
         c.last_update_ts = ts_int;
 
@@ -819,74 +4563,64 @@ impl Engine {
 
         let pct = c.pct_change.unwrap_or(0.0);
 
-        t.recent_prices.push((ts, price));
-        let cutoff_price = ts - 300.0;
-        t.recent_prices.retain(|(x, _)| *x >= cutoff_price);
-
-        let cutoff = ts - 60.0;
-        if side == "b" {
-            t.recent_buys.push((ts, volume));
-        } else {
-            t.recent_sells.push((ts, volume));
-        }
-        t.recent_buys.retain(|(x, _)| *x >= cutoff);
-        t.recent_sells.retain(|(x, _)| *x >= cutoff);
-
-        let b: f64 = t.recent_buys.iter().map(|(_, v)| *v).sum();
-        let s: f64 = t.recent_sells.iter().map(|(_, v)| *v).sum();
-        let tot = b + s;
-
-        let (flow_pct, dir) = if tot > 0.0 {
-            let f = b / tot;
-            if f > 0.75 {
-                (f * 100.0, "BUY".to_string())
-            } else if f < 0.25 {
-                ((1.0 - f) * 100.0, "SELL".to_string())
-            } else {
-                (50.0, "NEUTR".to_string())
-            }
-        } else {
-            (50.0, "NEUTR".to_string())
-        };
+        push_bounded(&mut t.recent_prices, (ts, price), ts - 300.0);
+        push_minute_close(&mut t.minute_closes, ts, price);
 
-        t.last_flow_pct = flow_pct;
-        t.last_dir = dir.clone();
+        let fl = &cfg.flow;
+        let by_count = fl.basis == "count";
+        let is_buy = side == "b";
+        // Herborrow via de guard zodat buys/sells tegelijk mutable geleend kunnen worden
+        let st = &mut *t;
 
-        let cutoff5 = ts - 300.0;
-        if side == "b" {
-            t.recent_buys_5m.push((ts, volume));
-        } else {
-            t.recent_sells_5m.push((ts, volume));
-        }
-        t.recent_buys_5m.retain(|(x, _)| *x >= cutoff5);
-        t.recent_sells_5m.retain(|(x, _)| *x >= cutoff5);
+        let (flow_pct, dir) = update_flow_window(
+            &mut st.recent_buys, &mut st.recent_sells, ts, volume, is_buy,
+            (fl.short_window_sec, fl.short_buy_cutoff, fl.short_sell_cutoff),
+            by_count,
+        );
+        st.last_flow_pct = flow_pct;
+        st.last_dir = dir.clone();
 
-        let b5: f64 = t.recent_buys_5m.iter().map(|(_, v)| *v).sum();
-        let s5: f64 = t.recent_sells_5m.iter().map(|(_, v)| *v).sum();
-        let tot5 = b5 + s5;
+        let (flow_pct_5m, dir_5m) = update_flow_window(
+            &mut st.recent_buys_5m, &mut st.recent_sells_5m, ts, volume, is_buy,
+            (fl.medium_window_sec, fl.medium_buy_cutoff, fl.medium_sell_cutoff),
+            by_count,
+        );
+        st.last_flow_pct_5m = flow_pct_5m;
+        st.last_dir_5m = dir_5m.clone();
 
-        let (flow_pct_5m, dir_5m) = if tot5 > 0.0 {
-            let f = b5 / tot5;
-            if f > 0.70 {
-                (f * 100.0, "BUY".to_string())
-            } else if f < 0.30 {
-                ((1.0 - f) * 100.0, "SELL".to_string())
-            } else {
-                (50.0, "NEUTR".to_string())
+        let (flow_pct_long, dir_long) = update_flow_window(
+            &mut st.recent_buys_long, &mut st.recent_sells_long, ts, volume, is_buy,
+            (fl.long_window_sec, fl.long_buy_cutoff, fl.long_sell_cutoff),
+            by_count,
+        );
+        st.last_flow_pct_long = flow_pct_long;
+        st.last_dir_long = dir_long;
+
+        let dv = &cfg.divergence;
+        let divergence = if dv.enabled {
+            let share = buy_share(st.recent_buys_5m.iter(), st.recent_sells_5m.iter(), f64::MIN, by_count)
+                .map_or(50.0, |f| f * 100.0);
+            let samples = &mut st.divergence_samples;
+            samples.push_back((ts, share, price));
+            while samples.front().is_some_and(|(x, _, _)| *x < ts - dv.window_sec) || samples.len() > SAMPLE_BUFFER_CAP {
+                samples.pop_front();
             }
+            detect_divergence(samples, ts, dv)
         } else {
-            (50.0, "NEUTR".to_string())
+            st.divergence_samples.clear();
+            None
         };
-
-        t.last_flow_pct_5m = flow_pct_5m;
-        t.last_dir_5m = dir_5m.clone();
+        let prev_divergence = std::mem::replace(
+            &mut st.divergence,
+            divergence.map(|(label, _)| label.to_string()).unwrap_or_default(),
+        );
 
         let (anom_strength, has_recent_anom) = {
             if let Some(tk) = self.tickers.get(pair) {
                 let strength = tk.last_anom_strength.unwrap_or(0.0);
                 if let Some(at) = tk.last_anom_ts {
                     let age = ts_int.saturating_sub(at);
-                    if age >= 0 && age <= 600 {
+                    if (0..=600).contains(&age) {
                         (strength, true)
                     } else {
                         (0.0, false)
@@ -899,311 +4633,92 @@ impl Engine {
             }
         };
 
-        let mut flow_score_short = 0.0;
-        if flow_pct > 75.0 {
-            flow_score_short = 3.0;
-        } else if flow_pct > 65.0 {
-            flow_score_short = 2.0;
-        } else if flow_pct > 55.0 {
-            flow_score_short = 1.0;
-        }
-
-        let mut flow_score_long = 0.0;
-        if dir_5m == "BUY" && flow_pct_5m > 75.0 {
-            flow_score_long = 2.0;
-        } else if dir_5m == "BUY" && flow_pct_5m > 65.0 {
-            flow_score_long = 1.0;
-        }
-
-        let mut flow_score = flow_score_short + 0.5 * flow_score_long;
-        if flow_score > 3.0 {
-            flow_score = 3.0;
-        }
-
-        let mut price_score = 0.0;
-        if pct > 2.0 {
-            price_score = 3.0;
-        } else if pct > 1.0 {
-            price_score = 2.0;
-        } else if pct > 0.3 {
-            price_score = 1.0;
-        }
-
-        let mut whale_score = 0.0;
-        if is_whale {
-            if notional > 50_000.0 || notional > n1 * 6.0 {
-                whale_score = 3.0;
-            } else if notional > 20_000.0 && notional > n1 * 4.0 {
-                whale_score = 2.0;
-            } else {
-                whale_score = 1.0;
-            }
-        }
-
-        if let Some(ob) = self.orderbooks.get(pair) {
-            let age = ts_int.saturating_sub(ob.timestamp);
-            if age >= 0 && age <= 10 {
-                let bid_volume: f64 = ob.bids.iter().take(10).map(|(_, v)| v).sum();
-                let ask_volume: f64 = ob.asks.iter().take(10).map(|(_, v)| v).sum();
-                let total_volume = bid_volume + ask_volume;
-
-                if total_volume > 0.0 {
-                    let bid_ratio = bid_volume / total_volume;
-                    
-                    if side == "b" && bid_ratio > 0.65 {
-                        whale_score += 0.5;
-                    } else if side == "s" && bid_ratio < 0.35 {
-                        whale_score += 0.5;
-                    }
-
-                    if bid_ratio > 0.75 && side == "b" {
-                        whale_score += 0.3;
-                    } else if bid_ratio < 0.25 && side == "s" {
-                        whale_score += 0.3;
-                    }
-                }
-            }
-        }
-
-        if whale_score > 4.0 {
-            whale_score = 4.0;
-        }
-
-        let mut volume_score = 0.0;
-        let vol_ratio = if v1 > 0.0 { volume / v1 } else { 1.0 };
-        if vol_ratio > 2.5 {
-            volume_score = 3.0;
-        } else if vol_ratio > 1.5 {
-            volume_score = 2.0;
-        } else if vol_ratio > 1.2 {
-            volume_score = 1.0;
-        }
-
-        let mut anomaly_score = 0.0;
-        if has_recent_anom {
-            if anom_strength > 80.0 {
-                anomaly_score = 3.0;
-            } else if anom_strength > 40.0 {
-                anomaly_score = 2.0;
-            } else if anom_strength > 0.0 {
-                anomaly_score = 1.0;
-            }
-        }
-
-        let mut trend_score = 0.0;
-        if is_whale && side == "b" && pct > 0.0 && flow_pct > 60.0 {
-            trend_score += 1.0;
-        }
-
-        let mut ret_5s = 0.0_f64;
-        let mut ret_30s = 0.0_f64;
-        let mut ret_120s = 0.0_f64;
-
-        for (pt, p_old) in t.recent_prices.iter() {
-            let age = ts - *pt;
-            if *p_old > 0.0 && price > 0.0 {
-                if age >= 5.0 && age <= 7.0 {
-                    ret_5s = (price - *p_old) / *p_old * 100.0;
-                }
-                if age >= 30.0 && age <= 40.0 {
-                    ret_30s = (price - *p_old) / *p_old * 100.0;
-                }
-                if age >= 110.0 && age <= 130.0 {
-                    ret_120s = (price - *p_old) / *p_old * 100.0;
-                }
-            }
-        }
-
-        if ret_5s < 0.0 {
-            ret_5s = 0.0;
-        }
-        if ret_30s < 0.0 {
-            ret_30s = 0.0;
-        }
-        if ret_120s < 0.0 {
-            ret_120s = 0.0;
-        }
-
-        let mut pump_score = 0.0_f64;
+        let ret_5s = return_near_age(&t.recent_prices, ts, price, 5.0);
+        let ret_30s = return_near_age(&t.recent_prices, ts, price, 30.0);
+        let ret_120s = return_near_age(&t.recent_prices, ts, price, 120.0);
 
-        if ret_5s > 0.3 {
-            pump_score += (ret_5s - 0.3) * 2.0;
-        }
-        if ret_30s > 1.0 {
-            pump_score += (ret_30s - 1.0) * 1.0;
-        }
-        if ret_120s > 2.0 {
-            pump_score += (ret_120s - 2.0) * 0.5;
-        }
-        if dir == "BUY" && flow_pct > 65.0 {
-            pump_score += (flow_pct - 65.0) * 0.08;
-        }
-        if dir_5m == "BUY" && flow_pct_5m > 60.0 {
-            pump_score += (flow_pct_5m - 60.0) * 0.06;
-        }
-        if vol_ratio > 1.5 {
-            pump_score += (vol_ratio - 1.5) * 1.0;
-        }
-        if whale_score > 0.0 {
-            pump_score += whale_score * 0.7;
-        }
+        // Zonder vers boek (OB workers nog niet warm) geen boost
+        let book_ratio = self.fresh_book_ratio(pair, ts_int, cfg.orderbook_fresh_sec);
 
-        if pump_score < 0.0 {
-            pump_score = 0.0;
-        }
-        if pump_score > 10.0 {
-            pump_score = 10.0;
-        }
+        let weights = self.weights.lock().unwrap().clone();
+        let TradeScores {
+            flow_score,
+            price_score,
+            whale_score,
+            volume_score,
+            anomaly_score,
+            trend_score,
+            total_score,
+            rating,
+            pump_score,
+            pump_conf,
+            pump_label,
+            whale_pred_score,
+            whale_pred_label,
+            early: new_early,
+            alpha: new_alpha,
+        } = score_trade(
+            &TradeScoreInput {
+                is_buy,
+                is_whale,
+                volume,
+                notional: notional * fx,
+                ewma_trade_size: s1,
+                ewma_notional: n1 * fx,
+                ewma_volume: v1,
+                pct,
+                flow_pct,
+                dir: &dir,
+                flow_pct_5m,
+                dir_5m: &dir_5m,
+                ret_5s,
+                ret_30s,
+                ret_120s,
+                book_ratio,
+                anom_strength: has_recent_anom.then_some(anom_strength),
+            },
+            &weights,
+            &cfg.score,
+        );
 
         t.last_pump_score = pump_score;
-
-        let mut pump_conf = 0.0_f64;
-        if ret_5s > 0.5 {
-            pump_conf += 0.4;
-        }
-        if ret_30s > 1.5 {
-            pump_conf += 0.3;
-        }
-        if ret_120s > 3.0 {
-            pump_conf += 0.2;
-        }
-        if dir == "BUY" && flow_pct > 70.0 {
-            pump_conf += 0.3;
-        }
-        if dir_5m == "BUY" && flow_pct_5m > 65.0 {
-            pump_conf += 0.2;
-        }
-        if vol_ratio > 2.0 {
-            pump_conf += 0.2;
-        }
-        if whale_score >= 2.0 {
-            pump_conf += 0.2;
-        }
-
-        let mut pump_label = "NONE".to_string();
-        if pump_score >= 7.0 && pump_conf >= 0.9 && dir == "BUY" {
-            pump_label = "MEGA_PUMP".to_string();
-        } else if pump_score >= 4.0 && pump_conf >= 0.5 && dir == "BUY" {
-            pump_label = "EARLY_PUMP".to_string();
+        t.last_pump_conf = pump_conf;
+        if pump_label != "NONE" {
+            t.last_pump_ts = ts_int;
         }
         t.last_pump_signal = Some(pump_label.clone());
-
-        let weights = self.weights.lock().unwrap().clone();
-        let total_score = weights.flow_w * flow_score
-            + weights.price_w * price_score
-            + weights.whale_w * whale_score
-            + weights.volume_w * volume_score
-            + weights.anomaly_w * anomaly_score
-            + weights.trend_w * trend_score;
-
-        let rating = if total_score >= 7.5 {
-            "ALPHA BUY".to_string()
-        } else if total_score >= 5.0 {
-            "STRONG BUY".to_string()
-        } else if total_score >= 3.5 {
-            "BUY".to_string()
-        } else if total_score >= 2.2 {
-            "EARLY BUY".to_string()
-        } else {
-            "NONE".to_string()
-        };
-
         t.last_score = total_score;
         t.last_rating = Some(rating.clone());
-
-        let mut whale_pred_score = 0.0;
-
-        if !is_whale && dir == "BUY" && flow_pct > 60.0 {
-            whale_pred_score += (flow_pct - 60.0) * 0.08;
-        }
-
-        if !is_whale && dir_5m == "BUY" && flow_pct_5m > 55.0 {
-            whale_pred_score += (flow_pct_5m - 55.0) * 0.06;
-        }
-
-        if !is_whale && volume < s1 * 0.8 {
-            whale_pred_score += 1.0;
-        }
-
-        let abs_ret_5s = ret_5s.abs();
-        let abs_ret_30s = ret_30s.abs();
-        if abs_ret_5s < 0.5 && abs_ret_30s < 1.0 && pct >= -0.5 {
-            whale_pred_score += 1.0;
-        }
-
-        if vol_ratio < 1.3 {
-            whale_pred_score += 0.5;
-        }
-
-        if let Some(ob) = self.orderbooks.get(pair) {
-            let age = ts_int.saturating_sub(ob.timestamp);
-            if age >= 0 && age <= 10 {
-                let bid_volume: f64 = ob.bids.iter().take(10).map(|(_, v)| v).sum();
-                let ask_volume: f64 = ob.asks.iter().take(10).map(|(_, v)| v).sum();
-                let total_volume = bid_volume + ask_volume;
-                if total_volume > 0.0 {
-                    let bid_ratio = bid_volume / total_volume;
-                    if bid_ratio > 0.65 {
-                        whale_pred_score += (bid_ratio - 0.65) * 2.0;
-                    }
-                }
-            }
-        }
-
-        if whale_pred_score < 0.0 {
-            whale_pred_score = 0.0;
-        }
-        if whale_pred_score > 10.0 {
-            whale_pred_score = 10.0;
-        }
-
-        let whale_pred_label = if whale_pred_score >= 7.0 {
-            "HIGH"
-        } else if whale_pred_score >= 4.0 {
-            "MEDIUM"
-        } else if whale_pred_score >= 2.0 {
-            "LOW"
-        } else {
-            "NONE"
-        }
-        .to_string();
-
         t.whale_pred_score = whale_pred_score;
         t.whale_pred_label = Some(whale_pred_label.clone());
-        t.last_whale_pred_high = whale_pred_label == "HIGH";
-
-        let mut new_early = "NONE".to_string();
-        let mut new_alpha = "NONE".to_string();
-
-        if dir == "BUY" {
-            if rating == "EARLY BUY" || rating == "BUY" {
-                new_early = "BUY".to_string();
-            } else if rating == "STRONG BUY" || rating == "ALPHA BUY" {
-                new_early = "BUY".to_string();
-                new_alpha = "BUY".to_string();
-            }
-        }
-
         t.last_early = Some(new_early.clone());
         t.last_alpha = Some(new_alpha.clone());
+        if is_whale || new_early != "NONE" || new_alpha != "NONE" {
+            t.signal_active_ts = ts_int;
+        }
 
-        // BETROUWBARE HISTORIE: Alleen bij HIGH + recente ANOM toevoegen, geen duplicate ts
-        if whale_pred_label == "HIGH" && has_recent_anom {
-            let history = self.stars_history.lock().unwrap();
-            let last_entry_ts = history.history.iter().filter(|r| r.pair == pair).map(|r| r.ts).max().unwrap_or(0);
-            let time_diff = ts_int.saturating_sub(last_entry_ts);
-            drop(history);
+        // BETROUWBARE HISTORIE: alleen als het pair aan cfg.stars voldoet, gededupliceerd per pair/ANOM
+        let last_anom_ts = self.tickers.get(pair).and_then(|tk| tk.last_anom_ts);
+        if cfg.stars.qualifies(&whale_pred_label, last_anom_ts, ts_int) {
+            let star_anom_ts = if cfg.stars.require_anom { last_anom_ts } else { None };
+            let accepted = self.stars_history.lock().unwrap().accepts(pair, ts_int, star_anom_ts, cfg.stars.history_dedup_sec);
 
-            if time_diff > 3600 && ts_int != last_entry_ts {  // Geen exact dezelfde ts, en minimaal 1 uur tussen entries per pair
-                println!("[STAR SNAPSHOT] Adding unique snapshot for {} at ts {} (time_diff {}s)", pair, ts_int, time_diff);
+            if accepted {
+                log_debug!("[STAR SNAPSHOT] Adding unique snapshot for {} at ts {} (anom {:?})", pair, ts_int, star_anom_ts);
+                // Zeldzaam pad: aliases en analysis-instellingen alleen hier ophalen
+                let app_cfg = self.config.lock().unwrap().clone();
                 let whale_side = t.last_whale_side.clone().unwrap_or_else(|| "-".to_string());
                 let whale_volume = t.last_whale_volume.unwrap_or(0.0);
                 let whale_notional = t.last_whale_notional.unwrap_or(0.0);
                 let row = TopRow {
                     ts: ts_int,
                     pair: pair.to_string(),
+                    // Alias wordt bij het serveren opgezocht; niet mee opslaan
+                    display_name: String::new(),
                     price,
                     pct,
+                    pct_1h: timeframe_return(&t.minute_closes, ts, price, 3600.0),
+                    pct_24h: self.tickers.get(pair).and_then(|tk| tk.day_change_pct),
                     flow_pct,
                     dir: dir.clone(),
                     early: new_early.clone(),
@@ -1214,20 +4729,24 @@ impl Engine {
                     whale_side: whale_side.clone(),
                     whale_volume,
                     whale_notional,
+                    whale_notional_fmt: format_notional(whale_notional),
                     total_score,
                     analysis: Self::build_analysis(&Row { 
                         pair: pair.to_string(), 
+                        display_name: pair_display_name(&app_cfg.pair_aliases, pair),
                         price, 
                         pct, 
                         whale: is_whale, 
                         whale_side: whale_side.clone(), 
                         whale_volume, 
                         whale_notional, 
+                        whale_notional_fmt: format_notional(whale_notional),
                         flow_pct, 
                         dir: dir.clone(), 
                         early: new_early.clone(), 
                         alpha: new_alpha.clone(), 
                         pump_score, 
+                        pump_conf: t.last_pump_conf,
                         pump_label: pump_label.clone(), 
                         trades: t.trade_count, 
                         buys: t.buy_volume, 
@@ -1240,22 +4759,44 @@ impl Engine {
                         rating: rating.clone(), 
                         whale_pred_score, 
                         whale_pred_label: whale_pred_label.clone(), 
-                        reliability_score: Self::compute_reliability(&t, ts_int).0, 
-                        reliability_label: Self::compute_reliability(&t, ts_int).1, 
-                        news_sentiment: t.news_sentiment 
-                    }),
+                        reliability_score: Self::compute_reliability(&t, ts_int, &cfg.reliability).0, 
+                        reliability_label: Self::compute_reliability(&t, ts_int, &cfg.reliability).1, 
+                        news_sentiment: t.news_sentiment,
+                        funding_rate: self.funding_rate(pair),
+                        open_interest: self.open_interest(pair),
+                        flow_pct_long: t.last_flow_pct_long,
+                        dir_long: t.last_dir_long.clone(),
+                        stale: false,
+                        precision: price_precision(price),
+                        max_notional: t.max_notional_seen,
+                        max_notional_side: t.max_notional_side.clone().unwrap_or_else(|| "-".to_string()),
+                        max_notional_fmt: format_notional(t.max_notional_seen),
+                        pct_5m: timeframe_return(&t.minute_closes, ts, price, 300.0),
+                        pct_1h: timeframe_return(&t.minute_closes, ts, price, 3600.0),
+                        pct_24h: self.tickers.get(pair).and_then(|tk| tk.day_change_pct),
+                        whale_buy_flow,
+                        whale_sell_flow,
+                        whale_net_flow: whale_buy_flow - whale_sell_flow,
+                        orderbook_available: book_ratio.is_some(),
+                        suspicious: t.suspicious,
+                        divergence: t.divergence.clone(),
+                    }, &app_cfg),
                     whale_pred_score,
                     whale_pred_label: whale_pred_label.clone(),
-                    reliability_score: Self::compute_reliability(&t, ts_int).0,
-                    reliability_label: Self::compute_reliability(&t, ts_int).1,
+                    reliability_score: Self::compute_reliability(&t, ts_int, &cfg.reliability).0,
+                    reliability_label: Self::compute_reliability(&t, ts_int, &cfg.reliability).1,
                     signal_type: "WH_PRED".to_string(),
+                    conviction: 0.0,
+                    anom_ts: star_anom_ts,
                 };
                 self.add_to_stars_history(row);
             } else {
-                println!("[STAR SKIP] {} skipped (time_diff {}s, ts {} == last {})", pair, time_diff, ts_int, last_entry_ts);
+                log_debug!("[STAR SKIP] {} skipped (dedup binnen {}s of zelfde ANOM {:?})", pair, cfg.stars.history_dedup_sec, star_anom_ts);
             }
         }
 
+        let signal_reliability = Self::compute_reliability(&t, ts_int, &cfg.reliability).0;
+
         if whale_pred_label == "HIGH" && prev_pred_label != "HIGH" {
             let ev = SignalEvent {
                 ts: ts_int,
@@ -1269,6 +4810,7 @@ impl Engine {
                 whale_side: side.to_string(),
                 volume,
                 notional,
+                notional_fmt: format_notional(notional),
                 price,
                 rating: rating.clone(),
                 total_score,
@@ -1281,6 +4823,12 @@ impl Engine {
                 evaluated: false,
                 ret_5m: None,
                 eval_horizon_sec: None,
+                seq: 0,
+                reliability_score: signal_reliability,
+                strength_norm: 0.0,
+                test: false,
+                received_at: Some(received_at),
+                horizon_sec: 0,
             };
             self.push_signal(ev);
         }
@@ -1298,6 +4846,7 @@ impl Engine {
                 whale_side: side.to_string(),
                 volume,
                 notional,
+                notional_fmt: format_notional(notional),
                 price,
                 rating: rating.clone(),
                 total_score,
@@ -1310,6 +4859,12 @@ impl Engine {
                 evaluated: false,
                 ret_5m: None,
                 eval_horizon_sec: None,
+                seq: 0,
+                reliability_score: signal_reliability,
+                strength_norm: 0.0,
+                test: false,
+                received_at: Some(received_at),
+                horizon_sec: 0,
             };
             self.push_signal(ev);
         }
@@ -1331,6 +4886,7 @@ impl Engine {
                 whale_side: side.to_string(),
                 volume,
                 notional,
+                notional_fmt: format_notional(notional),
                 price,
                 rating: rating.clone(),
                 total_score,
@@ -1343,80 +4899,129 @@ impl Engine {
                 evaluated: false,
                 ret_5m: None,
                 eval_horizon_sec: None,
+                seq: 0,
+                reliability_score: signal_reliability,
+                strength_norm: 0.0,
+                test: false,
+                received_at: Some(received_at),
+                horizon_sec: 0,
             };
             self.push_signal(ev);
         }
 
-        if new_early != "NONE" && new_early != prev_early {
-            let ev = SignalEvent {
-                ts: ts_int,
-                pair: pair.to_string(),
-                signal_type: "EARLY".to_string(),
-                direction: new_early.clone(),
-                strength: total_score,
-                flow_pct,
-                pct,
-                whale: is_whale,
-                whale_side: side.to_string(),
-                volume,
-                notional,
-                price,
-                rating: rating.clone(),
-                total_score,
-                flow_score,
-                price_score,
-                whale_score,
-                volume_score,
-                anomaly_score,
-                trend_score,
-                evaluated: false,
-                ret_5m: None,
-                eval_horizon_sec: None,
-            };
-            self.push_signal(ev);
+        // Template voor EARLY/ALPHA; signal_type/direction worden per emissie ingevuld
+        let rating_event = SignalEvent {
+            ts: ts_int,
+            pair: pair.to_string(),
+            signal_type: String::new(),
+            direction: String::new(),
+            strength: total_score,
+            flow_pct,
+            pct,
+            whale: is_whale,
+            whale_side: side.to_string(),
+            volume,
+            notional,
+            notional_fmt: format_notional(notional),
+            price,
+            rating: rating.clone(),
+            total_score,
+            flow_score,
+            price_score,
+            whale_score,
+            volume_score,
+            anomaly_score,
+            trend_score,
+            evaluated: false,
+            ret_5m: None,
+            eval_horizon_sec: None,
+            seq: 0,
+            reliability_score: signal_reliability,
+            strength_norm: 0.0,
+            test: false,
+            received_at: Some(received_at),
+            horizon_sec: 0,
+        };
+
+        if let Some((label, flow_delta)) = divergence {
+            let last_signal = (t.divergence_signal.as_str(), t.divergence_signal_ts);
+            if divergence_should_signal(label, &prev_divergence, last_signal, ts_int, cfg.divergence.cooldown_sec) {
+                t.divergence_signal = label.to_string();
+                t.divergence_signal_ts = ts_int;
+                // Eigen rating i.p.v. die van de trade: met "NONE" zou de self-evaluator
+                // het signal overslaan
+                self.push_signal(SignalEvent {
+                    signal_type: "DIVERGENCE".to_string(),
+                    direction: if label == "BULLISH" { "BUY" } else { "SELL" }.to_string(),
+                    strength: flow_delta.abs(),
+                    rating: label.to_string(),
+                    ..rating_event.clone()
+                });
+            }
         }
 
-        if new_alpha != "NONE" && new_alpha != prev_alpha {
-            let ev = SignalEvent {
-                ts: ts_int,
-                pair: pair.to_string(),
-                signal_type: "ALPHA".to_string(),
-                direction: new_alpha.clone(),
-                strength: total_score,
-                flow_pct,
-                pct,
-                whale: is_whale,
-                whale_side: side.to_string(),
-                volume,
-                notional,
-                price,
-                rating: rating.clone(),
-                total_score,
-                flow_score,
-                price_score,
-                whale_score,
-                volume_score,
-                anomaly_score,
-                trend_score,
-                evaluated: false,
-                ret_5m: None,
-                eval_horizon_sec: None,
+        self.auto_trade_check_exit(pair, price, ts_int);
+        self.manual_check_limit(pair, price);
+
+        if cfg.signal_on_candle_close {
+            // Meestal heeft run_signal_bar_closer de vorige bar al gesloten; zo niet,
+            // dan sluit deze trade hem alsnog voordat de nieuwe bar begint
+            let bar = ts_int.div_euclid(cfg.signal_candle_sec);
+            let closed = if bar != t.signal_bar {
+                close_signal_bar(&mut t, cfg.signal_candle_sec)
+            } else {
+                std::vec::Vec::new()
             };
-            self.push_signal(ev);
+            t.signal_bar = bar;
+            t.bar_eval = Some(BarCloseEval {
+                early: new_early.clone(),
+                alpha: new_alpha.clone(),
+                event: rating_event,
+            });
+            for ev in closed {
+                self.push_signal(ev);
+            }
+            return;
+        }
+
+        if new_early != "NONE" && new_early != prev_early {
+            self.push_signal(SignalEvent {
+                signal_type: "EARLY".to_string(),
+                direction: new_early.clone(),
+                ..rating_event.clone()
+            });
+        }
+
+        if new_alpha != "NONE" && new_alpha != prev_alpha {
+            self.push_signal(SignalEvent {
+                signal_type: "ALPHA".to_string(),
+                direction: new_alpha.clone(),
+                ..rating_event
+            });
         }
     }
 
-    fn handle_ticker(&self, pair: &str, last: f64, vol24h: f64, open: f64, ts_int: i64) {
+    /// `open` = Kraken's dag-open (REST/v1 "o"); None laat day_change_pct staan.
+    fn handle_ticker(&self, pair: &str, last: f64, vol24h: f64, open: Option<f64>, ts_int: i64) {
+        let (ewma_alpha, an, anomaly_strength_threshold, stars, reliability) = {
+            let cfg = self.config.lock().unwrap();
+            (
+                cfg.ewma_alpha,
+                cfg.anomaly.clone(),
+                cfg.anomaly_strength_threshold,
+                cfg.stars.clone(),
+                ReliabilityParams::from(&*cfg),
+            )
+        };
         let mut ts = self.tickers.entry(pair.to_string()).or_default();
 
         let prev_price = ts.last_price.unwrap_or(last);
         let prev_vol = ts.last_vol24h.unwrap_or(vol24h);
 
-        let day_ret = if open > 0.0 {
-            (last - open) / open * 100.0
-        } else {
-            0.0
-        };
+        if let Some(open) = open {
+            ts.day_change_pct = Some(if open > 0.0 { (last - open) / open * 100.0 } else { 0.0 });
+        }
+        let day_ret = ts.day_change_pct.unwrap_or(0.0);
 
         let jump = if prev_price > 0.0 {
             ((last - prev_price) / prev_price).abs() * 100.0
@@ -1431,15 +5036,16 @@ impl Engine {
         };
 
         let ew_vol0 = ts.ewma_vol24h.unwrap_or(vol24h);
-        let ew_vol1 = 0.9 * ew_vol0 + 0.1 * vol24h;
+        let ew_vol1 = ewma(ew_vol0, vol24h, ewma_alpha);
         ts.ewma_vol24h = Some(ew_vol1);
 
         let ew_ret0 = ts.ewma_abs_return.unwrap_or(jump);
-        let ew_ret1 = 0.9 * ew_ret0 + 0.1 * jump;
+        let ew_ret1 = ewma(ew_ret0, jump, ewma_alpha);
         ts.ewma_abs_return = Some(ew_ret1);
 
         ts.last_price = Some(last);
         ts.last_vol24h = Some(vol24h);
+        ts.last_update_ts = ts_int;
 
         let mut c = self.candles.entry(pair.to_string()).or_default();  // Verplaatst buiten {} blok
         c.last_update_ts = ts_int;
@@ -1447,8 +5053,10 @@ impl Engine {
         {
             let mut t = self.trades.entry(pair.to_string()).or_default();
             t.last_update_ts = ts_int;
+            push_minute_close(&mut t.minute_closes, ts_int as f64, last);
 
             if c.open.is_none() {
+                let open = open.unwrap_or(last);
                 c.open = Some(open);
                 c.high = Some(last);
                 c.low = Some(last);
@@ -1468,29 +5076,51 @@ impl Engine {
         }
 
         let mut score = 0.0;
-        score += jump * 2.0;
-        score += day_ret.abs() * 0.5;
+        score += jump * an.jump_weight;
+        score += day_ret.abs() * an.day_ret_weight;
         if vol_ratio > 1.0 {
-            score += (vol_ratio - 1.0) * 20.0;
+            score += (vol_ratio - 1.0) * an.vol_ratio_weight;
         }
-        score += ts.ewma_abs_return.unwrap_or(jump);
+        score += ts.ewma_abs_return.unwrap_or(jump) * an.ewma_return_weight;
 
-        if score > 40.0 && (jump > 0.3 || vol_ratio > 2.0) {
+        if score > anomaly_strength_threshold && (jump > an.min_jump_pct || vol_ratio > an.min_vol_ratio) {
             let direction = if last >= prev_price { "BUY" } else { "SELL" };
+            if an.direction != "BOTH" && an.direction != direction {
+                return;
+            }
+            if anomaly_in_cooldown(ts.last_anom_ts, ts.last_anom_dir.as_deref(), direction, ts_int, an.cooldown_sec) {
+                log_debug!("[ANOM] {} {} onderdrukt (cooldown {}s)", pair, direction, an.cooldown_sec);
+                return;
+            }
 
             ts.last_anom_ts = Some(ts_int);
             ts.last_anom_dir = Some(direction.to_string());
             ts.last_anom_strength = Some(score);
 
+            self.log_event("anomaly", serde_json::json!({
+                "pair": pair,
+                "direction": direction,
+                "strength": score,
+                "jump": jump,
+                "vol_ratio": vol_ratio,
+                "day_ret": day_ret,
+                "price": last,
+            }));
+
             let mut t = self.trades.entry(pair.to_string()).or_default();
             t.recent_anom = true;
 
             if pair == "POND/EUR" {
-                println!("[DEBUG POND] ANOM detected: strength={:.1}, setting recent_anom=true", score);
+                log_debug!("[DEBUG POND] ANOM detected: strength={:.1}, setting recent_anom=true", score);
             }
 
-            if t.last_whale_pred_high {
-                println!("[STAR SNAPSHOT] Adding snapshot for {} due to ANOM + recent HIGH", pair);
+            let star_anom_ts = if stars.require_anom { Some(ts_int) } else { None };
+            if stars.qualifies(t.whale_pred_label.as_deref().unwrap_or("NONE"), Some(ts_int), ts_int)
+                && self.stars_history.lock().unwrap().accepts(pair, ts_int, star_anom_ts, stars.history_dedup_sec)
+            {
+                log_debug!("[STAR SNAPSHOT] Adding snapshot for {} due to ANOM + WH_PRED", pair);
+                // Zeldzaam pad: hier mag de volledige config gekopieerd worden
+                let cfg = self.config.lock().unwrap().clone();
                 let price = last;
                 let pct = c.pct_change.unwrap_or(0.0);
                 let flow_pct = t.last_flow_pct;
@@ -1507,13 +5137,19 @@ impl Engine {
                 let rating = t.last_rating.clone().unwrap_or_else(|| "NONE".to_string());
                 let whale_pred_score = t.whale_pred_score;
                 let whale_pred_label = t.whale_pred_label.clone().unwrap_or_else(|| "NONE".to_string());
-                let reliability_score = Self::compute_reliability(&t, ts_int).0;
-                let reliability_label = Self::compute_reliability(&t, ts_int).1;
+                let reliability_score = Self::compute_reliability(&t, ts_int, &reliability).0;
+                let reliability_label = Self::compute_reliability(&t, ts_int, &reliability).1;
+                let (whale_buy_flow, whale_sell_flow) =
+                    whale_flow(&t.recent_whales, ts_int as f64, cfg.whale_flow_window_sec);
                 let row = TopRow {
                     ts: ts_int,
                     pair: pair.to_string(),
+                    // Alias wordt bij het serveren opgezocht; niet mee opslaan
+                    display_name: String::new(),
                     price,
                     pct,
+                    pct_1h: timeframe_return(&t.minute_closes, ts_int as f64, price, 3600.0),
+                    pct_24h: ts.day_change_pct,
                     flow_pct,
                     dir: dir.clone(),
                     early: new_early.clone(),
@@ -1524,20 +5160,24 @@ impl Engine {
                     whale_side: whale_side.clone(),
                     whale_volume,
                     whale_notional,
+                    whale_notional_fmt: format_notional(whale_notional),
                     total_score,
                     analysis: Self::build_analysis(&Row { 
                         pair: pair.to_string(), 
+                        display_name: pair_display_name(&cfg.pair_aliases, pair),
                         price, 
                         pct, 
                         whale: is_whale, 
                         whale_side: whale_side.clone(), 
                         whale_volume, 
                         whale_notional, 
+                        whale_notional_fmt: format_notional(whale_notional),
                         flow_pct, 
                         dir: dir.clone(), 
                         early: new_early.clone(), 
                         alpha: new_alpha.clone(), 
                         pump_score, 
+                        pump_conf: t.last_pump_conf,
                         pump_label: pump_label.clone(), 
                         trades: t.trade_count, 
                         buys: t.buy_volume, 
@@ -1552,13 +5192,33 @@ impl Engine {
                         whale_pred_label: whale_pred_label.clone(), 
                         reliability_score, 
                         reliability_label: reliability_label.clone(), 
-                        news_sentiment: t.news_sentiment 
-                    }),
+                        news_sentiment: t.news_sentiment,
+                        funding_rate: self.funding_rate(pair),
+                        open_interest: self.open_interest(pair),
+                        flow_pct_long: t.last_flow_pct_long,
+                        dir_long: t.last_dir_long.clone(),
+                        stale: false,
+                        precision: price_precision(price),
+                        max_notional: t.max_notional_seen,
+                        max_notional_side: t.max_notional_side.clone().unwrap_or_else(|| "-".to_string()),
+                        max_notional_fmt: format_notional(t.max_notional_seen),
+                        pct_5m: timeframe_return(&t.minute_closes, ts_int as f64, price, 300.0),
+                        pct_1h: timeframe_return(&t.minute_closes, ts_int as f64, price, 3600.0),
+                        pct_24h: ts.day_change_pct,
+                        whale_buy_flow,
+                        whale_sell_flow,
+                        whale_net_flow: whale_buy_flow - whale_sell_flow,
+                        orderbook_available: self.fresh_book_ratio(pair, ts_int, cfg.orderbook_fresh_sec).is_some(),
+                        suspicious: t.suspicious,
+                        divergence: t.divergence.clone(),
+                    }, &cfg),
                     whale_pred_score,
                     whale_pred_label: whale_pred_label.clone(),
                     reliability_score,
                     reliability_label: reliability_label.clone(),
                     signal_type: "ANOM".to_string(),
+                    conviction: 0.0,
+                    anom_ts: star_anom_ts,
                 };
                 self.add_to_stars_history(row);
             }
@@ -1575,6 +5235,7 @@ impl Engine {
                 whale_side: "-".to_string(),
                 volume: 0.0,
                 notional: 0.0,
+                notional_fmt: format_notional(0.0),
                 price: last,
                 rating: "NONE".to_string(),
                 total_score: 0.0,
@@ -1587,24 +5248,44 @@ impl Engine {
                 evaluated: true,
                 ret_5m: None,
                 eval_horizon_sec: None,
+                seq: 0,
+                reliability_score: Self::compute_reliability(&t, ts_int, &reliability).0,
+                strength_norm: 0.0,
+                test: false,
+                received_at: None,
+                horizon_sec: 0,
             };
             self.push_signal(ev);
         }
     }
 
-    fn compute_reliability(t: &TradeState, now_ts: i64) -> (f64, String) {
-        let now_f = now_ts as f64;
-
-        let cutoff_60 = now_f - 60.0;
-        let cutoff_300 = now_f - 300.0;
+    fn compute_reliability(t: &TradeState, now_ts: i64, params: &ReliabilityParams) -> (f64, String) {
+        let b = Self::reliability_breakdown(t, now_ts, params);
+        (b.score, b.label)
+    }
 
-        let mut recent_trades_60: usize = 0;
-        let _vol_60: f64 = 0.0;
-        for (_ts, _v) in t.recent_buys.iter().chain(t.recent_sells.iter()) {
-            if *_ts >= cutoff_60 {
-                recent_trades_60 += 1;
-            }
-        }
+    /// Sub-scores blijven zichtbaar, maar boven `max_age_sec` zonder update wordt
+    /// de totale reliability hard 0 met label "STALE". Bij een wash-trading
+    /// vermoeden gaat de score met `wash_penalty` (fractie) omlaag.
+    fn reliability_breakdown(t: &TradeState, now_ts: i64, params: &ReliabilityParams) -> ReliabilityBreakdown {
+        let now_f = now_ts as f64;
+        let (max_age_sec, wash_penalty) = (params.max_age_sec, params.wash_penalty);
+
+        // De buffers bevatten alleen de flow-vensters. Is een venster korter dan 60s/300s,
+        // dan meten we over het venster en schalen naar een 60s/300s tempo, zodat de
+        // drempels hieronder hetzelfde blijven betekenen.
+        let (span_60, scale_60) = reliability_span(params.short_window_sec, 60.0);
+        let (span_300, scale_300) = reliability_span(params.medium_window_sec, 300.0);
+        let cutoff_60 = now_f - span_60;
+        let cutoff_300 = now_f - span_300;
+
+        let raw_trades_60 = t
+            .recent_buys
+            .iter()
+            .chain(t.recent_sells.iter())
+            .filter(|(ts, _)| *ts >= cutoff_60)
+            .count();
+        let recent_trades_60 = (raw_trades_60 as f64 * scale_60).round() as usize;
 
         let mut vol_300: f64 = 0.0;
         for (_ts, v) in t.recent_buys_5m.iter().chain(t.recent_sells_5m.iter()) {
@@ -1612,6 +5293,7 @@ impl Engine {
                 vol_300 += *v;
             }
         }
+        vol_300 *= scale_300;
 
         let td = (recent_trades_60.min(30) as f64 / 30.0) * 40.0;
 
@@ -1638,22 +5320,23 @@ impl Engine {
                 sells_60 += *v;
             }
         }
-        let tot_60 = buys_60 + sells_60;
+        let tot_60 = (buys_60 + sells_60) * scale_60;
         let flow_pct_60 = if tot_60 > 0.0 {
-            buys_60 / tot_60 * 100.0
+            buys_60 * scale_60 / tot_60 * 100.0
         } else {
             50.0
         };
 
         let fc = if tot_60 < 1.0 {
             0.0
-        } else if flow_pct_60 > 70.0 || flow_pct_60 < 30.0 {
+        } else if !(30.0..=70.0).contains(&flow_pct_60) {
             20.0
         } else {
             15.0
         };
 
-        let dt = now_ts.saturating_sub(t.last_update_ts);
+        // Recency op de laatste WS trade: de REST ticker zet last_update_ts ook
+        let dt = now_ts.saturating_sub(t.last_trade_ts);
         let ras = if dt > 300 {
             0.0
         } else if dt > 120 {
@@ -1676,8 +5359,18 @@ impl Engine {
         if score > 100.0 {
             score = 100.0;
         }
+        if t.suspicious {
+            score *= 1.0 - wash_penalty;
+        }
+
+        let stale = dt > max_age_sec;
+        if stale {
+            score = 0.0;
+        }
 
-        let label = if score <= 25.0 {
+        let label = if stale {
+            "STALE"
+        } else if score <= 25.0 {
             "UNRELIABLE"
         } else if score <= 50.0 {
             "LOW"
@@ -1688,117 +5381,531 @@ impl Engine {
         }
         .to_string();
 
-        (score, label)
+        ReliabilityBreakdown {
+            trade_density: td,
+            volume_stability: vs,
+            flow_consistency: fc,
+            recency: ras,
+            time_density: tds,
+            trades_60s: recent_trades_60,
+            vol_ratio,
+            flow_pct_60s: flow_pct_60,
+            secs_since_update: dt,
+            suspicious: t.suspicious,
+            score,
+            label,
+        }
+    }
+
+    fn mark_ticker_ws(&self, pair: &str, ts: i64) {
+        if let Some(mut t) = self.tickers.get_mut(pair) {
+            t.ws_update_ts = ts;
+        }
+    }
+
+    fn ticker_ws_fresh(&self, pair: &str, now: i64) -> bool {
+        self.tickers
+            .get(pair)
+            .is_some_and(|t| t.ws_update_ts > 0 && now - t.ws_update_ts <= TICKER_WS_FRESH_SEC)
+    }
+
+    /// Bid-aandeel van het volume in de top 10 levels, alleen als het boek niet
+    /// ouder is dan `max_age` seconden. None = geen (vers) boek, dus geen orderbook boost.
+    fn fresh_book_ratio(&self, pair: &str, now: i64, max_age: i64) -> Option<f64> {
+        let ob = self.orderbooks.get(pair)?;
+        let age = now.saturating_sub(ob.timestamp);
+        if !(0..=max_age).contains(&age) {
+            return None;
+        }
+        let bid_volume: f64 = ob.bids.iter().take(10).map(|(_, v)| v).sum();
+        let ask_volume: f64 = ob.asks.iter().take(10).map(|(_, v)| v).sum();
+        let total_volume = bid_volume + ask_volume;
+        (total_volume > 0.0).then(|| bid_volume / total_volume)
+    }
+
+    /// Alle pairs die de engine kent (WS trades en/of REST ticker), gesorteerd op naam.
+    fn pairs_snapshot(&self) -> std::vec::Vec<PairInfo> {
+        let names: std::collections::BTreeSet<String> = self
+            .trades
+            .iter()
+            .map(|t| t.key().clone())
+            .chain(self.tickers.iter().map(|t| t.key().clone()))
+            .collect();
+        names
+            .into_iter()
+            .map(|pair| {
+                let (trade_count, trade_ts) = self.trades.get(&pair).map_or((0, 0), |t| (t.trade_count, t.last_update_ts));
+                let ticker_ts = self.tickers.get(&pair).map_or(0, |t| t.last_update_ts);
+                PairInfo {
+                    last_price: self.current_price(&pair),
+                    last_update_ts: trade_ts.max(ticker_ts),
+                    trade_count,
+                    pair,
+                }
+            })
+            .collect()
+    }
+
+    /// Tape van een pair, nieuwste trade eerst.
+    fn tape_snapshot(&self, pair: &str) -> Option<std::vec::Vec<TapeTrade>> {
+        let t = self.trades.get(pair)?;
+        Some(t.tape.iter().rev().cloned().collect())
+    }
+
+    /// Top 10 bids/asks met spread en imbalance; None als er geen recent boek is.
+    fn orderbook_snapshot(&self, pair: &str) -> Option<OrderbookView> {
+        let fresh_sec = self.config.lock().unwrap().orderbook_fresh_sec;
+        let ob = self.orderbooks.get(pair)?;
+        let age_sec = Utc::now().timestamp().saturating_sub(ob.timestamp);
+        if age_sec > ORDERBOOK_API_MAX_AGE_SEC {
+            return None;
+        }
+        let bids: std::vec::Vec<(f64, f64)> = ob.bids.iter().take(10).cloned().collect();
+        let asks: std::vec::Vec<(f64, f64)> = ob.asks.iter().take(10).cloned().collect();
+        let best_bid = bids.first().map(|(p, _)| *p).unwrap_or(0.0);
+        let best_ask = asks.first().map(|(p, _)| *p).unwrap_or(0.0);
+        let (spread, spread_pct) = if best_bid > 0.0 && best_ask > 0.0 {
+            let mid = (best_bid + best_ask) / 2.0;
+            (best_ask - best_bid, (best_ask - best_bid) / mid * 100.0)
+        } else {
+            (0.0, 0.0)
+        };
+        let bid_volume: f64 = bids.iter().map(|(_, v)| v).sum();
+        let ask_volume: f64 = asks.iter().map(|(_, v)| v).sum();
+        let total = bid_volume + ask_volume;
+        Some(OrderbookView {
+            pair: pair.to_string(),
+            bids,
+            asks,
+            timestamp: ob.timestamp,
+            age_sec,
+            best_bid,
+            best_ask,
+            spread,
+            spread_pct,
+            bid_volume,
+            ask_volume,
+            imbalance: if total > 0.0 { bid_volume / total } else { 0.5 },
+            score_active: age_sec <= fresh_sec,
+        })
+    }
+
+    fn pair_detail(&self, pair: &str) -> Option<PairDetail> {
+        let now_ts = chrono::Utc::now().timestamp();
+        let (reliability_params, flow) = {
+            let cfg = self.config.lock().unwrap();
+            (ReliabilityParams::from(&*cfg), cfg.flow.clone())
+        };
+        let (row, reliability, biggest_print, flow_comparison) = {
+            // Ook stille pairs (niet in de snapshot) en ticker-only pairs hebben een detail
+            let t = match self.trades.get(pair) {
+                Some(t) => t.clone(),
+                None if self.tickers.contains_key(pair) => TradeState::default(),
+                None => return None,
+            };
+            let biggest = t.max_notional_side.as_ref().map(|side| BiggestPrint {
+                notional: t.max_notional_seen,
+                side: side.clone(),
+                ts: t.max_notional_ts,
+            });
+            let now = now_ts as f64;
+            let comparison = [
+                ("short", flow.short_window_sec, &t.recent_buys, &t.recent_sells),
+                ("medium", flow.medium_window_sec, &t.recent_buys_5m, &t.recent_sells_5m),
+                ("long", flow.long_window_sec, &t.recent_buys_long, &t.recent_sells_long),
+            ]
+            .into_iter()
+            .map(|(window, window_sec, buys, sells)| {
+                let since = now - window_sec;
+                FlowBasisComparison {
+                    window,
+                    window_sec,
+                    volume_buy_pct: buy_share(buys.iter(), sells.iter(), since, false).map(|f| f * 100.0),
+                    count_buy_pct: buy_share(buys.iter(), sells.iter(), since, true).map(|f| f * 100.0),
+                    buy_trades: buys.iter().filter(|(ts, _)| *ts >= since).count(),
+                    sell_trades: sells.iter().filter(|(ts, _)| *ts >= since).count(),
+                }
+            })
+            .collect();
+            let rc = RowConfig::from(&*self.config.lock().unwrap());
+            let (mut row, _) = self.build_row(pair, &t, &rc, now_ts);
+            if row.price <= 0.0 {
+                // Nog geen candle (ticker-only): laatste tickerprijs
+                row.price = self.tickers.get(pair).and_then(|tk| tk.last_price).unwrap_or(0.0);
+                row.precision = price_precision(row.price);
+            }
+            (row, Self::reliability_breakdown(&t, now_ts, &reliability_params), biggest, comparison)
+        };
+        let derivatives = self.derivatives_for(pair);
+        Some(PairDetail { row, reliability, derivatives, biggest_print, flow_basis: flow.basis, flow_comparison })
+    }
+
+    fn derivatives_for(&self, pair: &str) -> Option<DerivativesState> {
+        let base = pair.split('/').next()?;
+        self.derivatives.get(base).map(|d| d.clone())
+    }
+
+    fn funding_rate(&self, pair: &str) -> Option<f64> {
+        self.derivatives_for(pair).map(|d| d.funding_rate)
+    }
+
+    fn open_interest(&self, pair: &str) -> Option<f64> {
+        self.derivatives_for(pair).map(|d| d.open_interest)
     }
 
     fn snapshot(&self) -> std::vec::Vec<Row> {
+        let ttl_ms = self.config.lock().unwrap().snapshot_cache_ttl_ms;
+        self.snapshot_cache.get_or_compute(ttl_ms, (), || self.compute_snapshot())
+    }
+
+    /// Pairs met een whale, pump of ANOM binnen active_window_sec, of een rating uit
+    /// een trade binnen dat venster. Op eigen timestamps: last_update_ts wordt ook door
+    /// de ticker gezet en rating/recent_anom blijven na het event staan.
+    fn active_snapshot(&self) -> std::vec::Vec<Row> {
+        let now_ts = Utc::now().timestamp();
+        let (window, rc) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.active_window_sec, RowConfig::from(&*cfg))
+        };
+        let recent = |ts: i64| ts > 0 && now_ts.saturating_sub(ts) <= window;
+        let mut rows: std::vec::Vec<Row> = self
+            .trades
+            .iter()
+            .filter_map(|t| {
+                let v = t.value();
+                let anom_ts = self.tickers.get(t.key()).and_then(|tk| tk.last_anom_ts).unwrap_or(0);
+                let (row, _) = self.build_row(t.key(), v, &rc, now_ts);
+                let active = recent(v.last_whale_ts)
+                    || recent(v.last_pump_ts)
+                    || recent(anom_ts)
+                    || (recent(v.last_trade_ts) && row.rating != "NONE");
+                active.then_some(row)
+            })
+            .collect();
+        rows.sort_by(|a, b| score_cmp(b.score, a.score));
+        rows
+    }
+
+    fn compute_snapshot(&self) -> std::vec::Vec<Row> {
         let mut rows = std::vec::Vec::new();
         let now_ts = chrono::Utc::now().timestamp();
+        let rc = RowConfig::from(&*self.config.lock().unwrap());
 
         for t in self.trades.iter() {
-            let pair = t.key().clone();
-            let v = t.value();
+            let (row, active) = self.build_row(t.key(), t.value(), &rc, now_ts);
+            if active {
+                rows.push(row);
+            }
+        }
 
-            let has_whale = v.last_whale;
-            let early = v
-                .last_early
-                .clone()
-                .unwrap_or_else(|| "NONE".to_string());
-            let alpha = v
-                .last_alpha
-                .clone()
-                .unwrap_or_else(|| "NONE".to_string());
-            let marked = self.signalled_pairs.get(&pair).is_some();
+        rows.sort_by(|a, b| score_cmp(b.score, a.score));
+        rows
+    }
 
-            if !has_whale && early == "NONE" && alpha == "NONE" && !marked {
-                continue;
-            }
+    /// Rows voor alle pairs met trade state, ook stille; gesorteerd op pair.
+    fn all_rows(&self) -> std::vec::Vec<Row> {
+        let now_ts = Utc::now().timestamp();
+        let rc = RowConfig::from(&*self.config.lock().unwrap());
+        let mut rows: std::vec::Vec<Row> =
+            self.trades.iter().map(|t| self.build_row(t.key(), t.value(), &rc, now_ts).0).collect();
+        rows.sort_by(|a, b| a.pair.cmp(&b.pair));
+        rows
+    }
 
-            let buys = v.buy_volume;
-            let sells = v.sell_volume;
-            let flow_pct = v.last_flow_pct;
-            let dir = if v.last_dir.is_empty() {
-                "NONE".to_string()
-            } else {
-                v.last_dir.clone()
-            };
+    /// Row voor één pair uit zijn TradeState. `active` = whale/early/alpha of een
+    /// signal binnen signal_expiry_sec; compute_snapshot toont alleen actieve pairs,
+    /// de pair detail ook stille.
+    fn build_row(&self, pair: &str, v: &TradeState, rc: &RowConfig, now_ts: i64) -> (Row, bool) {
+        let pair = pair.to_string();
+        // Verlopen setups (geen versterkende activiteit binnen signal_expiry_sec) tellen als NONE
+        let marked_ts = self.signalled_pairs.get(&pair).map(|ts| *ts);
+        let last_active = v.signal_active_ts.max(marked_ts.unwrap_or(0));
+        let expired = signal_expired(last_active, now_ts, rc.signal_expiry);
+        let has_whale = v.last_whale && !expired;
+        let early = v
+            .last_early
+            .clone()
+            .filter(|_| !expired)
+            .unwrap_or_else(|| "NONE".to_string());
+        let alpha = v
+            .last_alpha
+            .clone()
+            .filter(|_| !expired)
+            .unwrap_or_else(|| "NONE".to_string());
+        let marked = marked_ts.is_some() && !expired;
+        let active = has_whale || early != "NONE" || alpha != "NONE" || marked;
+
+        let buys = v.buy_volume;
+        let sells = v.sell_volume;
+        let flow_pct = v.last_flow_pct;
+        let dir = if v.last_dir.is_empty() {
+            "NONE".to_string()
+        } else {
+            v.last_dir.clone()
+        };
 
-            let c = self.candles.get(&pair);
-            let (o, h, l, cl, pct) = if let Some(c) = c {
-                (
-                    c.open.unwrap_or(0.0),
-                    c.high.unwrap_or(0.0),
-                    c.low.unwrap_or(0.0),
-                    c.close.unwrap_or(0.0),
-                    c.pct_change.unwrap_or(0.0),
-                )
-            } else {
-                (0.0, 0.0, 0.0, 0.0, 0.0)
-            };
+        let c = self.candles.get(&pair);
+        let (o, h, l, cl, pct) = if let Some(c) = c {
+            (
+                c.open.unwrap_or(0.0),
+                c.high.unwrap_or(0.0),
+                c.low.unwrap_or(0.0),
+                c.close.unwrap_or(0.0),
+                c.pct_change.unwrap_or(0.0),
+            )
+        } else {
+            (0.0, 0.0, 0.0, 0.0, 0.0)
+        };
 
-            let whale_side = v
-                .last_whale_side
+        let whale_side = v
+            .last_whale_side
+            .clone()
+            .unwrap_or_else(|| "-".to_string());
+        let whale_volume = v.last_whale_volume.unwrap_or(0.0);
+        let whale_notional = v.last_whale_notional.unwrap_or(0.0);
+
+        let rating = v
+            .last_rating
+            .clone()
+            .unwrap_or_else(|| "NONE".to_string());
+
+        let whale_pred_score = v.whale_pred_score;
+        let whale_pred_label = v
+            .whale_pred_label
+            .clone()
+            .unwrap_or_else(|| "NONE".to_string());
+
+        let (reliability_score, reliability_label) = Self::compute_reliability(v, now_ts, &rc.reliability);
+        let (whale_buy_flow, whale_sell_flow) = whale_flow(&v.recent_whales, now_ts as f64, rc.whale_window);
+
+        let row = Row {
+            display_name: pair_display_name(&rc.aliases, &pair),
+            pair: pair.clone(),
+            price: cl,
+            pct,
+            whale: has_whale,
+            whale_side,
+            whale_volume,
+            whale_notional,
+            whale_notional_fmt: format_notional(whale_notional),
+            flow_pct,
+            dir,
+            early,
+            alpha,
+            pump_score: v.last_pump_score,
+            pump_conf: v.last_pump_conf,
+            pump_label: v
+                .last_pump_signal
                 .clone()
-                .unwrap_or_else(|| "-".to_string());
-            let whale_volume = v.last_whale_volume.unwrap_or(0.0);
-            let whale_notional = v.last_whale_notional.unwrap_or(0.0);
+                .unwrap_or_else(|| "NONE".to_string()),
+            trades: v.trade_count,
+            buys,
+            sells,
+            o,
+            h,
+            l,
+            c: cl,
+            score: v.last_score,
+            rating,
+            whale_pred_score,
+            whale_pred_label,
+            reliability_score,
+            reliability_label,
+            news_sentiment: self.news_sentiment.get(&pair).map(|v| v.0).unwrap_or(0.5),
+            funding_rate: self.funding_rate(&pair),
+            open_interest: self.open_interest(&pair),
+            flow_pct_long: v.last_flow_pct_long,
+            dir_long: v.last_dir_long.clone(),
+            stale: now_ts.saturating_sub(v.last_trade_ts) > rc.stale_threshold,
+            precision: price_precision(cl),
+            max_notional: v.max_notional_seen,
+            max_notional_side: v.max_notional_side.clone().unwrap_or_else(|| "-".to_string()),
+            max_notional_fmt: format_notional(v.max_notional_seen),
+            pct_5m: timeframe_return(&v.minute_closes, now_ts as f64, cl, 300.0),
+            pct_1h: timeframe_return(&v.minute_closes, now_ts as f64, cl, 3600.0),
+            pct_24h: self.tickers.get(&pair).and_then(|tk| tk.day_change_pct),
+            whale_buy_flow,
+            whale_sell_flow,
+            whale_net_flow: whale_buy_flow - whale_sell_flow,
+            orderbook_available: self.fresh_book_ratio(&pair, now_ts, rc.book_fresh_sec).is_some(),
+            suspicious: v.suspicious,
+            divergence: v.divergence.clone(),
+        };
+        (row, active)
+    }
 
-            let rating = v
-                .last_rating
-                .clone()
-                .unwrap_or_else(|| "NONE".to_string());
+    /// (laatste seq, aantal signals met seq > since_seq) voor de unread-badge
+    fn signals_unread(&self, since_seq: u64) -> (u64, usize) {
+        let latest = self.signal_seq.load(Ordering::SeqCst);
+        let count = self.signals.lock().unwrap().iter().filter(|e| e.seq > since_seq).count();
+        (latest, count)
+    }
 
-            let whale_pred_score = v.whale_pred_score;
-            let whale_pred_label = v
-                .whale_pred_label
-                .clone()
-                .unwrap_or_else(|| "NONE".to_string());
+    /// Nog niet geëvalueerde signals, eerstvolgende evaluatie eerst.
+    fn pending_signals_snapshot(&self) -> std::vec::Vec<PendingSignal> {
+        let now_ts = Utc::now().timestamp();
+        let mut v: std::vec::Vec<PendingSignal> = self
+            .signals
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| !e.evaluated)
+            .map(|e| PendingSignal {
+                seconds_remaining: (e.ts + e.horizon() - now_ts).max(0),
+                signal: e.clone(),
+            })
+            .collect();
+        v.sort_by_key(|p| p.seconds_remaining);
+        v
+    }
 
-            let (reliability_score, reliability_label) = Self::compute_reliability(&v, now_ts);
+    fn signals_snapshot(&self, query: &SignalsQuery) -> std::vec::Vec<SignalEvent> {
+        let types: Option<std::vec::Vec<String>> = query
+            .types
+            .as_ref()
+            .map(|t| {
+                t.split(',')
+                    .map(|s| s.trim().to_uppercase())
+                    .filter(|s| !s.is_empty())
+                    .collect::<std::vec::Vec<_>>()
+            })
+            .filter(|t| !t.is_empty());
+        let buf = self.signals.lock().unwrap();
+        let mut v: std::vec::Vec<SignalEvent> = buf
+            .iter()
+            .filter(|e| query.pair.as_ref().is_none_or(|p| &e.pair == p))
+            .filter(|e| query.before.is_none_or(|b| e.seq < b))
+            .filter(|e| query.min_score.is_none_or(|m| e.total_score >= m))
+            .filter(|e| query.min_strength.is_none_or(|m| e.strength >= m))
+            .filter(|e| types.as_ref().is_none_or(|t| t.contains(&e.signal_type)))
+            .filter(|e| query.dir.as_ref().is_none_or(|d| e.direction.eq_ignore_ascii_case(d)))
+            .cloned()
+            .collect();
+        drop(buf);
+        v.sort_by_key(|e| std::cmp::Reverse(e.seq));
+        if let Some(limit) = query.limit {
+            v.truncate(limit);
+        }
+        v
+    }
 
-            rows.push(Row {
-                pair: pair.clone(),
-                price: cl,
-                pct,
-                whale: has_whale,
-                whale_side,
-                whale_volume,
-                whale_notional,
-                flow_pct,
-                dir,
-                early,
-                alpha,
-                pump_score: v.last_pump_score,
-                pump_label: v
-                    .last_pump_signal
-                    .clone()
-                    .unwrap_or_else(|| "NONE".to_string()),
-                trades: v.trade_count,
-                buys,
-                sells,
-                o,
-                h,
-                l,
-                c: cl,
-                score: v.last_score,
-                rating,
-                whale_pred_score,
-                whale_pred_label,
-                reliability_score,
-                reliability_label,
-                news_sentiment: self.news_sentiment.get(&pair).map(|v| v.0).unwrap_or(0.5),
-            });
+    fn market_breadth(&self) -> MarketBreadth {
+        let now_ts = Utc::now().timestamp();
+        let stale_threshold = self.config.lock().unwrap().stale_threshold_sec;
+        let mut breadth = MarketBreadth::default();
+        for t in self.trades.iter() {
+            let v = t.value();
+            if now_ts.saturating_sub(v.last_trade_ts) > stale_threshold {
+                breadth.stale_pairs += 1;
+                continue;
+            }
+            breadth.total.add(&v.last_dir);
+            if let Some(quote) = quote_currency(t.key()) {
+                let price = self.candles.get(t.key()).and_then(|c| c.close).unwrap_or(0.0);
+                let bucket = breadth.by_quote.entry(quote.to_string()).or_default();
+                bucket.counts.add(&v.last_dir);
+                bucket.buy_notional += v.buy_volume * price;
+                bucket.sell_notional += v.sell_volume * price;
+            }
         }
+        breadth
+    }
 
-        rows.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        rows
+    /// Entry/exit ladder voor niet-stale pairs met BUY flow en een rating, hoogste score eerst (max top_n).
+    fn trade_advice_snapshot(&self) -> TradeAdvice {
+        let (settings, top_n, notional) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.trade_advice.clone(), cfg.top_n, cfg.base_notional)
+        };
+        let exit_pcts = settings.exit_pcts();
+
+        let mut candidates: std::vec::Vec<Row> = self
+            .snapshot()
+            .into_iter()
+            .filter(|r| !r.stale && r.dir == "BUY" && r.rating != "NONE" && r.price > 0.0)
+            .collect();
+        candidates.sort_by(|a, b| score_cmp(b.score, a.score));
+        candidates.truncate(top_n);
+
+        let rows: std::vec::Vec<TradeAdviceRow> = candidates
+            .into_iter()
+            .map(|r| {
+                let entry_price = r.price * (1.0 - settings.entry_pct / 100.0);
+                let exit = |pct: f64| entry_price * (1.0 + pct / 100.0);
+                TradeAdviceRow {
+                    entry_price,
+                    exit_5: exit(exit_pcts[0]),
+                    exit_10: exit(exit_pcts[1]),
+                    exit_15: exit(exit_pcts[2]),
+                    exit_20: exit(exit_pcts[3]),
+                    precision: r.precision,
+                    price: r.price,
+                    rating: r.rating,
+                    score: r.score,
+                    pair: r.pair,
+                }
+            })
+            .collect();
+
+        let balance = self.manual_trader.lock().unwrap().balance;
+        let equity_at = |pct: f64| balance + rows.len() as f64 * notional * pct / 100.0;
+        let equity = TradeAdviceEquity {
+            balance,
+            notional_per_trade: notional,
+            equity_5: equity_at(exit_pcts[0]),
+            equity_10: equity_at(exit_pcts[1]),
+            equity_15: equity_at(exit_pcts[2]),
+            equity_20: equity_at(exit_pcts[3]),
+        };
+
+        TradeAdvice {
+            entry_pct: settings.entry_pct,
+            exit_pcts,
+            rows,
+            equity,
+        }
     }
 
-    fn signals_snapshot(&self) -> std::vec::Vec<SignalEvent> {
-        let buf = self.signals.lock().unwrap();
-        let mut v: std::vec::Vec<SignalEvent> = buf.iter().cloned().collect();
-        v.sort_by(|a, b| b.ts.cmp(&a.ts));
-        v
+    /// Grootste 24h stijgers/dalers over alle pairs met ticker data, los van signalen.
+    fn movers_snapshot(&self, query: &MoversQuery) -> std::vec::Vec<MoverRow> {
+        let down = query.dir.as_deref() == Some("down");
+        let limit = query.limit.unwrap_or(20).clamp(1, 200);
+
+        let mut rows: std::vec::Vec<MoverRow> = self
+            .tickers
+            .iter()
+            .filter_map(|t| {
+                let pair = t.key().clone();
+                let candle = self.candles.get(&pair);
+                let pct = t
+                    .day_change_pct
+                    .or_else(|| candle.as_ref().and_then(|c| c.pct_change))?;
+                let price = t
+                    .last_price
+                    .or_else(|| candle.as_ref().and_then(|c| c.close))
+                    .filter(|p| *p > 0.0)?;
+                let vol24h = t.last_vol24h.unwrap_or(0.0);
+                Some(MoverRow {
+                    signalled: self.signalled_pairs.contains_key(&pair),
+                    pair,
+                    price,
+                    precision: price_precision(price),
+                    pct,
+                    vol24h,
+                    quote_volume: vol24h * price,
+                    ts: t.last_update_ts,
+                })
+            })
+            .filter(|r| if down { r.pct < 0.0 } else { r.pct > 0.0 })
+            .collect();
+
+        if down {
+            rows.sort_by(|a, b| score_cmp(a.pct, b.pct));
+        } else {
+            rows.sort_by(|a, b| score_cmp(b.pct, a.pct));
+        }
+        rows.truncate(limit);
+        rows
     }
 
     fn heatmap_snapshot(&self) -> std::vec::Vec<HeatmapPoint> {
@@ -1807,25 +5914,37 @@ impl Engine {
             .map(|r| HeatmapPoint {
                 pair: r.pair.clone(),
                 flow_pct: r.flow_pct,
-                pump_score: r.pump_score.max(0.0).min(10.0),
+                pump_score: r.pump_score.clamp(0.0, 10.0),
                 ts: self
                     .trades
                     .get(&r.pair)
                     .map(|t| t.last_update_ts)
                     .unwrap_or(0),
                 reliability_score: r.reliability_score,
+                score: r.score,
+                whale_pred_score: r.whale_pred_score,
+                pct: r.pct,
             })
             .collect()
     }
 
-    fn backtest_snapshot(&self) -> std::vec::Vec<BacktestResult> {
+    fn backtest_snapshot(&self, query: &BacktestQuery, archived: &[SignalEvent]) -> std::vec::Vec<BacktestResult> {
+        let (start_balance, default_fraction) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.initial_balance, cfg.backtest_position_fraction)
+        };
+        let fraction = query.fraction.filter(|f| *f > 0.0 && *f <= 1.0).unwrap_or(default_fraction);
         let sigs = self.signals.lock().unwrap();
         let mut groups: HashMap<(String, String), std::vec::Vec<(i64, f64)>> = HashMap::new();
+        let min_trades = query.min_trades.unwrap_or(0);
 
-        for ev in sigs.iter() {
+        for ev in archived.iter().chain(sigs.iter()) {
             if !ev.evaluated {
                 continue;
             }
+            if query.since.is_some_and(|s| ev.ts < s) || query.until.is_some_and(|u| ev.ts > u) {
+                continue;
+            }
             if let Some(r) = ev.ret_5m {
                 let key = (ev.signal_type.clone(), ev.direction.clone());
                 groups.entry(key).or_default().push((ev.ts, r));
@@ -1899,6 +6018,25 @@ impl Engine {
                 0.0
             };
             let expectancy = pnl_sum / n as f64;
+            let return_stddev = if n > 1 {
+                let var = trades
+                    .iter()
+                    .map(|(_, r)| (r - expectancy).powi(2))
+                    .sum::<f64>()
+                    / (n - 1) as f64;
+                var.sqrt()
+            } else {
+                0.0
+            };
+            let sharpe = if return_stddev > 1e-12 {
+                expectancy / return_stddev
+            } else {
+                0.0
+            };
+            let returns: std::vec::Vec<f64> = trades.iter().map(|(_, r)| *r).collect();
+            let (equity_curve_compounded, compounded_max_drawdown_pct) =
+                compound_equity(&returns, start_balance, fraction);
+            let final_equity = equity_curve_compounded.last().copied().unwrap_or(start_balance);
 
             out.push(BacktestResult {
                 signal_type,
@@ -1921,28 +6059,90 @@ impl Engine {
                     worst_trade
                 },
                 max_losing_streak,
+                return_stddev,
+                sharpe,
                 equity_curve,
+                equity_curve_compounded,
+                start_balance,
+                position_fraction: fraction,
+                compounded_return_pct: (final_equity / start_balance - 1.0) * 100.0,
+                compounded_max_drawdown_pct,
+                low_sample: n < min_trades,
             });
         }
 
-        out.sort_by(|a, b| {
-            b.expectancy
-                .partial_cmp(&a.expectancy)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        out.sort_by(|a, b| score_cmp(b.expectancy, a.expectancy));
 
         out
     }
 
-    fn manual_trades_snapshot(&self) -> ManualTradesResponse {
+    fn portfolio_snapshot(&self) -> PortfolioSnapshot {
+        let (mut positions, balance, open_pnl) = {
+            let trader = self.manual_trader.lock().unwrap();
+            let mut open_pnl = 0.0;
+            let positions: std::vec::Vec<PortfolioPosition> = trader
+                .trades
+                .iter()
+                .map(|(pair, trade)| {
+                    let price = self.current_price(pair).unwrap_or(trade.entry_price);
+                    open_pnl += (price - trade.entry_price) * trade.size;
+                    PortfolioPosition { pair: pair.clone(), exposure: trade.size * price, weight: 0.0 }
+                })
+                .collect();
+            (positions, trader.balance, open_pnl)
+        };
+        positions.sort_by(|a, b| score_cmp(b.exposure, a.exposure));
+
+        let total_exposure: f64 = positions.iter().map(|p| p.exposure).sum();
+        for p in positions.iter_mut() {
+            p.weight = if total_exposure > 0.0 { p.exposure / total_exposure } else { 0.0 };
+        }
+
+        let returns: std::vec::Vec<_> = positions
+            .iter()
+            .map(|p| self.trades.get(&p.pair).map(|t| minute_returns(&t.minute_closes)).unwrap_or_default())
+            .collect();
+        let mut matrix = vec![vec![None; positions.len()]; positions.len()];
+        let mut correlations = std::vec::Vec::new();
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                if let Some((corr, samples)) = return_correlation(&returns[i], &returns[j]) {
+                    matrix[i][j] = Some(corr);
+                    matrix[j][i] = Some(corr);
+                    correlations.push(PortfolioCorrelation {
+                        a: positions[i].pair.clone(),
+                        b: positions[j].pair.clone(),
+                        corr,
+                        samples,
+                    });
+                }
+            }
+        }
+        correlations.sort_by(|a, b| score_cmp(b.corr, a.corr));
+
+        let weights: std::vec::Vec<f64> = positions.iter().map(|p| p.weight).collect();
+        let effective = effective_positions(&weights, |i, j| matrix[i][j]);
+        let equity = balance + open_pnl;
+
+        PortfolioSnapshot {
+            total_exposure,
+            exposure_pct: if equity > 0.0 { total_exposure / equity * 100.0 } else { 0.0 },
+            correlation_adjusted_exposure: if effective > 0.0 {
+                total_exposure / effective.sqrt()
+            } else {
+                total_exposure
+            },
+            positions,
+            correlations,
+            effective_positions: effective,
+        }
+    }
+
+    fn manual_trades_snapshot(&self, tag: Option<&str>) -> ManualTradesResponse {
         let trader = self.manual_trader.lock().unwrap();
         let mut list = std::vec::Vec::new();
         for (pair, trade) in trader.trades.iter() {
-            let current_price = self
-                .candles
-                .get(pair)
-                .and_then(|c| c.close)
-                .unwrap_or(trade.entry_price);
+            let current_price = self.current_price(pair).unwrap_or(trade.entry_price);
             let pnl = (current_price - trade.entry_price) * trade.size;
             let pnl_pct = if trade.entry_price > 0.0 {
                 (current_price - trade.entry_price) / trade.entry_price * 100.0
@@ -1961,83 +6161,146 @@ impl Engine {
                 pnl_pct,
                 fee_pct: trade.fee_pct,
                 manual_amount: trade.manual_amount,
+                note: trade.note.clone(),
+                tags: trade.tags.clone(),
+                limit_close: trade.limit_close,
             });
         }
+        let mut closed_trades: std::vec::Vec<TradeRecord> =
+            trader.closed_trades.iter().filter(|t| t.has_tag(tag)).cloned().collect();
+        closed_trades.reverse();
+        let cooldown_sec = self.config.lock().unwrap().loss_cooldown_sec();
+        let now_ts = Utc::now().timestamp();
+        let mut cooldowns: std::vec::Vec<(String, i64)> = trader
+            .loss_closes
+            .keys()
+            .filter_map(|pair| {
+                loss_cooldown_remaining(&trader.loss_closes, pair, now_ts, cooldown_sec).map(|r| (pair.clone(), r))
+            })
+            .collect();
+        cooldowns.sort_by_key(|(_, remaining)| *remaining);
         ManualTradesResponse {
             balance: trader.balance,
             initial_balance: trader.initial_balance,
             trades: list,
+            closed_trades,
+            cooldowns,
         }
     }
 
-    fn build_analysis(row: &Row) -> String {
+    fn build_analysis(row: &Row, cfg: &AppConfig) -> String {
+        let th = &cfg.analysis;
+        let en = th.language_for(&cfg.locale) == "en";
+        let say = |nl: String, en_text: String| if en { en_text } else { nl };
         let mut parts: std::vec::Vec<String> = std::vec::Vec::new();
-
-        if row.pct > 5.0 {
-            parts.push(format!("Prijs is gestegen met {:.1}%.", row.pct));
-        } else if row.pct > 1.0 {
-            parts.push(format!("Lichte prijsstijging van {:.1}%.", row.pct));
-        } else if row.pct < -1.0 {
-            parts.push(format!("Prijs is gedaald met {:.1}%.", row.pct.abs()));
+        let symbol = currency_symbol(quote_currency(&row.pair).unwrap_or(&cfg.display_currency));
+
+        if row.pct > th.strong_pct {
+            parts.push(say(format!("Prijs is gestegen met {:.1}%.", row.pct), format!("Price is up {:.1}%.", row.pct)));
+        } else if row.pct > th.mild_pct {
+            parts.push(say(format!("Lichte prijsstijging van {:.1}%.", row.pct), format!("Slight price rise of {:.1}%.", row.pct)));
+        } else if row.pct < -th.mild_pct {
+            parts.push(say(format!("Prijs is gedaald met {:.1}%.", row.pct.abs()), format!("Price is down {:.1}%.", row.pct.abs())));
         } else {
-            parts.push("Prijs beweegt zijwaarts.".to_string());
+            parts.push(say("Prijs beweegt zijwaarts.".to_string(), "Price is moving sideways.".to_string()));
         }
 
-        if row.flow_pct > 70.0 && row.dir == "BUY" {
-            parts.push(format!("Sterke koopdruk: {:.0}% buy-flow.", row.flow_pct));
-        } else if row.flow_pct > 60.0 && row.dir == "BUY" {
-            parts.push(format!("Matige koopdruk: {:.0}% buy-flow.", row.flow_pct));
-        } else if row.flow_pct > 60.0 && row.dir == "SELL" {
-            parts.push(format!("Verkoopdruk: {:.0}% sell-flow.", row.flow_pct));
+        if row.flow_pct > th.strong_flow_pct && row.dir == "BUY" {
+            parts.push(say(format!("Sterke koopdruk: {:.0}% buy-flow.", row.flow_pct), format!("Strong buying pressure: {:.0}% buy flow.", row.flow_pct)));
+        } else if row.flow_pct > th.moderate_flow_pct && row.dir == "BUY" {
+            parts.push(say(format!("Matige koopdruk: {:.0}% buy-flow.", row.flow_pct), format!("Moderate buying pressure: {:.0}% buy flow.", row.flow_pct)));
+        } else if row.flow_pct > th.moderate_flow_pct && row.dir == "SELL" {
+            parts.push(say(format!("Verkoopdruk: {:.0}% sell-flow.", row.flow_pct), format!("Selling pressure: {:.0}% sell flow.", row.flow_pct)));
         } else {
-            parts.push("Neutrale markt flow.".to_string());
+            parts.push(say("Neutrale markt flow.".to_string(), "Neutral market flow.".to_string()));
         }
 
         if row.whale {
             let whale_vol = row.whale_volume;
-            let whale_not = row.whale_notional / 1000.0;
-            parts.push(format!("Whale-trade gedetecteerd: {:.2} eenheden, €{:.0}k notional.", whale_vol, whale_not));
+            parts.push(say(format!("Whale-trade gedetecteerd: {:.*} eenheden, {}{} notional.", th.volume_decimals, whale_vol, symbol, row.whale_notional_fmt), format!("Whale trade detected: {:.*} units, {}{} notional.", th.volume_decimals, whale_vol, symbol, row.whale_notional_fmt)));
         }
 
-        if row.pump_score > 5.0 {
-            parts.push(format!("Pump-score van {:.1} duidt op mogelijke accumulatie.", row.pump_score));
-        } else if row.pump_score > 2.0 {
-            parts.push(format!("Matige pump-score van {:.1}.", row.pump_score));
+        if row.pump_score > th.strong_pump_score {
+            parts.push(say(format!("Pump-score van {:.1} duidt op mogelijke accumulatie.", row.pump_score), format!("Pump score of {:.1} suggests possible accumulation.", row.pump_score)));
+        } else if row.pump_score > th.moderate_pump_score {
+            parts.push(say(format!("Matige pump-score van {:.1}.", row.pump_score), format!("Moderate pump score of {:.1}.", row.pump_score)));
         }
 
         if row.whale_pred_label == "HIGH" {
-            parts.push(format!("Hoge kans op whale-activiteit (score {:.1}).", row.whale_pred_score));
+            parts.push(say(format!("Hoge kans op whale-activiteit (score {:.1}).", row.whale_pred_score), format!("High chance of whale activity (score {:.1}).", row.whale_pred_score)));
         } else if row.whale_pred_label == "MEDIUM" {
-            parts.push(format!("Matige kans op whales (score {:.1}).", row.whale_pred_score));
+            parts.push(say(format!("Matige kans op whales (score {:.1}).", row.whale_pred_score), format!("Moderate chance of whales (score {:.1}).", row.whale_pred_score)));
         }
 
         if row.reliability_label == "HIGH" {
-            parts.push(format!("Betrouwbaarheid hoog ({:.0}).", row.reliability_score));
+            parts.push(say(format!("Betrouwbaarheid hoog ({:.0}).", row.reliability_score), format!("Reliability high ({:.0}).", row.reliability_score)));
         } else if row.reliability_label == "LOW" {
-            parts.push(format!("Betrouwbaarheid laag ({:.0}) - let op.", row.reliability_score));
+            parts.push(say(format!("Betrouwbaarheid laag ({:.0}) - let op.", row.reliability_score), format!("Reliability low ({:.0}) - be careful.", row.reliability_score)));
         }
 
         if row.alpha == "BUY" {
-            parts.push("Alpha BUY signaal: sterke combinatie van factoren.".to_string());
+            parts.push(say("Alpha BUY signaal: sterke combinatie van factoren.".to_string(), "Alpha BUY signal: strong combination of factors.".to_string()));
         } else if row.early == "BUY" {
-            parts.push("Vroege koopindicatie.".to_string());
+            parts.push(say("Vroege koopindicatie.".to_string(), "Early buy indication.".to_string()));
         }
 
-        if row.news_sentiment > 0.7 {
-            parts.push(format!("Positieve nieuws sentiment ({:.1}).", row.news_sentiment));
-        } else if row.news_sentiment < 0.3 {
-            parts.push(format!("Negatieve nieuws sentiment ({:.1}).", row.news_sentiment));
+        if row.news_sentiment > th.positive_news {
+            parts.push(say(format!("Positieve nieuws sentiment ({:.1}).", row.news_sentiment), format!("Positive news sentiment ({:.1}).", row.news_sentiment)));
+        } else if row.news_sentiment < th.negative_news {
+            parts.push(say(format!("Negatieve nieuws sentiment ({:.1}).", row.news_sentiment), format!("Negative news sentiment ({:.1}).", row.news_sentiment)));
         }
 
         if parts.is_empty() {
-            parts.push("Neutrale marktcondities.".to_string());
+            parts.push(say("Neutrale marktcondities.".to_string(), "Neutral market conditions.".to_string()));
+        }
+
+        join_within(&parts, th.max_chars)
+    }
+
+    /// Stars historie, nieuwste eerst, met display_name volgens de huidige aliases.
+    fn stars_history_snapshot(&self) -> std::vec::Vec<TopRow> {
+        let aliases = self.config.lock().unwrap().pair_aliases.clone();
+        let mut rows = self.stars_history.lock().unwrap().history.clone();
+        rows.sort_by_key(|r| std::cmp::Reverse(r.ts));
+        for r in rows.iter_mut() {
+            r.display_name = pair_display_name(&aliases, &r.pair);
         }
+        rows
+    }
 
-        parts.join(" ").chars().take(200).collect::<String>()
+    /// Risers en fallers uit de Top 10 die aan cfg.stars voldoen.
+    fn stars_snapshot(&self) -> std::vec::Vec<TopRow> {
+        let stars = self.config.lock().unwrap().stars.clone();
+        let now = Utc::now().timestamp();
+        let top10 = self.top10_snapshot(&Top10Query::default());
+        top10
+            .risers
+            .into_iter()
+            .chain(top10.fallers)
+            .filter(|r| {
+                let last_anom_ts = self.tickers.get(&r.pair).and_then(|tk| tk.last_anom_ts);
+                stars.qualifies(&r.whale_pred_label, last_anom_ts, now)
+            })
+            .collect()
+    }
+
+    fn top10_snapshot(&self, query: &Top10Query) -> Top10Response {
+        let (ttl_ms, top_n) = {
+            let cfg = self.config.lock().unwrap();
+            (cfg.snapshot_cache_ttl_ms, query.limit.unwrap_or(cfg.top_n).clamp(1, TOP_N_MAX))
+        };
+        let mut top10 = self.top10_cache.get_or_compute(ttl_ms, top_n, || self.compute_top10(top_n));
+        if let Some(field) = query.sort {
+            sort_rows(&mut top10.risers, field, query.order);
+            sort_rows(&mut top10.fallers, field, query.order);
+        }
+        top10
     }
 
-    fn top10_snapshot(&self) -> Top10Response {
+    fn compute_top10(&self, top_n: usize) -> Top10Response {
         let rows = self.snapshot();
+        let cfg = self.config.lock().unwrap().clone();
+        let best_n = cfg.best_n.clamp(1, TOP_N_MAX);
 
         let get_last_signal_type = |pair: &str| -> String {
             let signals = self.signals.lock().unwrap();
@@ -2054,8 +6317,11 @@ impl Engine {
                     .map(|t| t.last_update_ts)
                     .unwrap_or(0),
                 pair: r.pair.clone(),
+                display_name: r.display_name.clone(),
                 price: r.price,
                 pct: r.pct,
+                pct_1h: r.pct_1h,
+                pct_24h: r.pct_24h,
                 flow_pct: r.flow_pct,
                 dir: r.dir.clone(),
                 early: r.early.clone(),
@@ -2066,34 +6332,31 @@ impl Engine {
                 whale_side: r.whale_side.clone(),
                 whale_volume: r.whale_volume,
                 whale_notional: r.whale_notional,
+                whale_notional_fmt: r.whale_notional_fmt.clone(),
                 total_score: r.score,
-                analysis: Self::build_analysis(r),
+                analysis: Self::build_analysis(r, &cfg),
                 whale_pred_score: r.whale_pred_score,
                 whale_pred_label: r.whale_pred_label.clone(),
                 reliability_score: r.reliability_score,
                 reliability_label: r.reliability_label.clone(),
                 signal_type: get_last_signal_type(&r.pair),
+                conviction: 0.0,
+                anom_ts: None,
+            })
+            .map(|mut row| {
+                row.conviction = cfg.conviction(row.rank_score(), row.reliability_score);
+                row
             })
             .collect();
 
-        let mut best3 = risers.clone();
-        best3.sort_by(|a, b| {
-            let sa = a.total_score + a.pump_score * 1.5 + a.whale_pred_score * 1.0;
-            let sb = b.total_score + b.pump_score * 1.5 + b.whale_pred_score * 1.0;
-            sb.partial_cmp(&sa).unwrap()
-        });
-        if best3.len() > 3 {
-            best3.truncate(3);
-        }
+        // Best N op conviction, zodat dunne/onbetrouwbare markten niet bovenaan komen
+        let mut best3: std::vec::Vec<TopRow> =
+            risers.iter().filter(|r| r.conviction > 0.0).cloned().collect();
+        best3.sort_by(|a, b| score_cmp(b.conviction, a.conviction));
+        best3.truncate(best_n);
 
-        risers.sort_by(|a, b| {
-            let sa = a.total_score + a.pump_score * 1.5 + a.whale_pred_score * 1.0;
-            let sb = b.total_score + b.pump_score * 1.5 + b.whale_pred_score * 1.0;
-            sb.partial_cmp(&sa).unwrap()
-        });
-        if risers.len() > 10 {
-            risers.truncate(10);
-        }
+        risers.sort_by(|a, b| score_cmp(b.rank_score(), a.rank_score()));
+        risers.truncate(top_n);
 
         let mut fallers: std::vec::Vec<TopRow> = rows
             .iter()
@@ -2114,8 +6377,11 @@ impl Engine {
                         .map(|t| t.last_update_ts)
                         .unwrap_or(0),
                     pair: r.pair.clone(),
+                    display_name: r.display_name.clone(),
                     price: r.price,
                     pct: r.pct,
+                    pct_1h: r.pct_1h,
+                    pct_24h: r.pct_24h,
                     flow_pct: r.flow_pct,
                     dir: r.dir.clone(),
                     early: r.early.clone(),
@@ -2126,21 +6392,22 @@ impl Engine {
                     whale_side: r.whale_side.clone(),
                     whale_volume: r.whale_volume,
                     whale_notional: r.whale_notional,
+                    whale_notional_fmt: r.whale_notional_fmt.clone(),
                     total_score,
-                    analysis: Self::build_analysis(r),
+                    analysis: Self::build_analysis(r, &cfg),
                     whale_pred_score: r.whale_pred_score,
                     whale_pred_label: r.whale_pred_label.clone(),
                     reliability_score: r.reliability_score,
                     reliability_label: r.reliability_label.clone(),
                     signal_type: get_last_signal_type(&r.pair),
+                    conviction: cfg.conviction(total_score, r.reliability_score),
+                    anom_ts: None,
                 }
             })
             .collect();
 
-        fallers.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap());
-        if fallers.len() > 10 {
-            fallers.truncate(10);
-        }
+        fallers.sort_by(|a, b| score_cmp(b.total_score, a.total_score));
+        fallers.truncate(top_n);
 
         Top10Response {
             best3,
@@ -2149,46 +6416,176 @@ impl Engine {
         }
     }
 
-    async fn manual_add_trade(&self, pair: &str, sl_pct: f64, tp_pct: f64, fee_pct: f64, manual_amount: f64) -> bool {
-        let current_price = self.candles.get(pair).and_then(|c| c.close).unwrap_or(0.0);
-        if current_price <= 0.0 {
-            return false;
+    /// Actuele prijs: candle close, met de REST ticker als fallback voor pairs
+    /// die nog niet via de WS stream getraded hebben.
+    fn current_price(&self, pair: &str) -> Option<f64> {
+        self.candles
+            .get(pair)
+            .and_then(|c| c.close)
+            .filter(|p| *p > 0.0)
+            .or_else(|| self.tickers.get(pair).and_then(|t| t.last_price).filter(|p| *p > 0.0))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn manual_add_trade(&self, pair: &str, sl_pct: f64, tp_pct: f64, fee_pct: f64, sizing_mode: &str, sizing_value: f64, annotation: TradeAnnotation) -> Result<(), String> {
+        if self.is_trading_paused() {
+            log_info!("[MANUAL TRADE] REJECT {}: trading is gepauzeerd", pair);
+            return Err("Trading is gepauzeerd (kill-switch actief)".to_string());
+        }
+        let current_price = self
+            .current_price(pair)
+            .ok_or_else(|| format!("Geen prijs beschikbaar voor {}", pair))?;
+        let cfg = self.config.lock().unwrap().clone();
+        let (result, state_clone) = {
+            let mut trader = self.manual_trader.lock().unwrap();
+            let max_amount = cfg.position_cap(trader.balance);
+            let fill = self.paper_fill(pair, current_price, true, &cfg, chrono::Utc::now().timestamp());
+            let result = match trader.compute_amount(sizing_mode, sizing_value, sl_pct) {
+                Some(amount) => trader.add_trade(pair, fill, sl_pct, tp_pct, fee_pct, amount, annotation, cfg.loss_cooldown_sec(), max_amount),
+                None => {
+                    log_info!(
+                        "[MANUAL TRADE] REJECT {}: sizing {} {:.2} exceeds balance {:.2} or is invalid",
+                        pair, sizing_mode, sizing_value, trader.balance
+                    );
+                    Err(format!(
+                        "Sizing {} {:.2} is ongeldig of groter dan de balance ({:.2})",
+                        sizing_mode, sizing_value, trader.balance
+                    ))
+                }
+            };
+            (result, trader.clone())
+        };
+        if result.is_ok() {
+            if let Some(trade) = state_clone.trades.get(pair) {
+                self.log_event("trade_open", serde_json::to_value(trade).unwrap_or_default());
+            }
+            if let Err(e) = state_clone.save().await {
+                log_error!("[ERROR] Failed to save manual trades: {}", e);
+            }
+            if let Err(e) = state_clone.save_equity().await {
+                log_error!("[ERROR] Failed to save equity: {}", e);
+            }
+        }
+        result
+    }
+
+    fn manual_exit_fill(&self, pair: &str, current_price: f64) -> Fill {
+        let cfg = self.config.lock().unwrap().clone();
+        self.paper_fill(pair, current_price, false, &cfg, chrono::Utc::now().timestamp())
+    }
+
+    async fn manual_close_trade(&self, pair: &str) -> Result<(), String> {
+        if !self.manual_trader.lock().unwrap().trades.contains_key(pair) {
+            return Err(format!("Geen open positie voor {}", pair));
         }
+        let current_price = self
+            .current_price(pair)
+            .ok_or_else(|| format!("Geen prijs beschikbaar voor {}", pair))?;
+        let fill = self.manual_exit_fill(pair, current_price);
         let (success, state_clone) = {
             let mut trader = self.manual_trader.lock().unwrap();
-            let success = trader.add_trade(pair, current_price, sl_pct, tp_pct, fee_pct, manual_amount);
+            let success = trader.close_trade(pair, fill, "MANUAL");
             (success, trader.clone())
         };
         if success {
+            if let Some(record) = state_clone.closed_trades.last() {
+                self.log_event("trade_close", serde_json::to_value(record).unwrap_or_default());
+            }
             if let Err(e) = state_clone.save().await {
-                eprintln!("[ERROR] Failed to save manual trades: {}", e);
+                log_error!("[ERROR] Failed to save manual trades: {}", e);
             }
             if let Err(e) = state_clone.save_equity().await {
-                eprintln!("[ERROR] Failed to save equity: {}", e);
+                log_error!("[ERROR] Failed to save equity: {}", e);
+            }
+            Ok(())
+        } else {
+            Err(format!("Geen open positie voor {}", pair))
+        }
+    }
+
+    /// Limit close voor een manual trade; `limit` None annuleert. Ok(true) = direct gevuld.
+    async fn manual_close_limit(&self, pair: &str, limit: Option<f64>) -> Result<bool, String> {
+        let current_price = self
+            .current_price(pair)
+            .ok_or_else(|| format!("Geen prijs beschikbaar voor {}", pair))?;
+        let fill = self.manual_exit_fill(pair, current_price);
+        let (filled, state_clone) = {
+            let mut trader = self.manual_trader.lock().unwrap();
+            let filled = trader.set_limit_close(pair, limit, fill)?;
+            trader.dirty = false;
+            (filled, trader.clone())
+        };
+        if filled {
+            if let Some(record) = state_clone.closed_trades.last() {
+                self.log_event("trade_close", serde_json::to_value(record).unwrap_or_default());
+            }
+        }
+        if let Err(e) = state_clone.save().await {
+            log_error!("[ERROR] Failed to save manual trades: {}", e);
+        }
+        if let Err(e) = state_clone.save_equity().await {
+            log_error!("[ERROR] Failed to save equity: {}", e);
+        }
+        Ok(filled)
+    }
+
+    /// Vanuit handle_trade: een trade op of boven de limit vult de close tegen de limit.
+    fn manual_check_limit(&self, pair: &str, price: f64) {
+        let record = {
+            let mut trader = self.manual_trader.lock().unwrap();
+            match trader.trades.get(pair).and_then(|t| t.limit_close) {
+                Some(limit) if price >= limit => trader.fill_limit_close(pair, Fill::exact(limit)),
+                _ => None,
             }
+        };
+        if let Some(record) = record {
+            self.log_event("trade_close", serde_json::to_value(&record).unwrap_or_default());
+        }
+    }
+
+    async fn manual_partial_close(&self, pair: &str, fraction: f64) -> Result<(), String> {
+        if !self.manual_trader.lock().unwrap().trades.contains_key(pair) {
+            return Err(format!("Geen open positie voor {}", pair));
+        }
+        let current_price = self
+            .current_price(pair)
+            .ok_or_else(|| format!("Geen prijs beschikbaar voor {}", pair))?;
+        let fill = self.manual_exit_fill(pair, current_price);
+        let state_clone = {
+            let mut trader = self.manual_trader.lock().unwrap();
+            trader.partial_close(pair, fill, fraction, "MANUAL_PARTIAL")?;
+            trader.clone()
+        };
+        if let Some(record) = state_clone.closed_trades.last() {
+            self.log_event("trade_close", serde_json::to_value(record).unwrap_or_default());
+        }
+        if let Err(e) = state_clone.save().await {
+            log_error!("[ERROR] Failed to save manual trades: {}", e);
         }
-        success
+        if let Err(e) = state_clone.save_equity().await {
+            log_error!("[ERROR] Failed to save equity: {}", e);
+        }
+        Ok(())
     }
 
-    async fn manual_close_trade(&self, pair: &str) -> bool {
-        let current_price = self.candles.get(pair).and_then(|c| c.close).unwrap_or(0.0);
-        if current_price <= 0.0 {
-            return false;
-        }
-        let (success, state_clone) = {
+    async fn manual_reset(&self) {
+        let state_clone = {
             let mut trader = self.manual_trader.lock().unwrap();
-            let success = trader.close_trade(pair, current_price);
-            (success, trader.clone())
+            let prices: HashMap<String, f64> = trader
+                .trades
+                .keys()
+                .filter_map(|p| self.current_price(p).map(|c| (p.clone(), c)))
+                .collect();
+            trader.reset(&prices);
+            trader.clone()
         };
-        if success {
-            if let Err(e) = state_clone.save().await {
-                eprintln!("[ERROR] Failed to save manual trades: {}", e);
-            }
-            if let Err(e) = state_clone.save_equity().await {
-                eprintln!("[ERROR] Failed to save equity: {}", e);
-            }
+        self.log_event("account_reset", serde_json::json!({ "balance": state_clone.balance }));
+        if let Err(e) = state_clone.save().await {
+            log_error!("[ERROR] Failed to save manual trades: {}", e);
+        }
+        if let Err(e) = state_clone.save_equity().await {
+            log_error!("[ERROR] Failed to save equity: {}", e);
         }
-        success
     }
 
     async fn load_manual_trader(&self) {
@@ -2196,6 +6593,48 @@ impl Engine {
         let mut trader = self.manual_trader.lock().unwrap();
         *trader = loaded_state;
     }
+
+    async fn load_auto_trader(&self) {
+        let initial_balance = self.config.lock().unwrap().initial_balance;
+        let loaded_state = AutoTraderState::load(initial_balance).await;
+        let mut trader = self.auto_trader.lock().unwrap();
+        *trader = loaded_state;
+    }
+
+    fn is_trading_paused(&self) -> bool {
+        self.trading_paused.load(Ordering::SeqCst)
+    }
+
+    async fn load_trading_pause(&self) {
+        let paused = match tokio::fs::read_to_string(TRADING_PAUSE_FILE).await {
+            Ok(content) => serde_json::from_str::<Value>(&content)
+                .ok()
+                .and_then(|v| v["paused"].as_bool())
+                .unwrap_or(false),
+            Err(_) => false,
+        };
+        self.trading_paused.store(paused, Ordering::SeqCst);
+        if paused {
+            log_warn!("[WARN] Trading is gepauzeerd (kill-switch uit {})", TRADING_PAUSE_FILE);
+        }
+    }
+
+    /// Kill-switch: blokkeert nieuwe manual en auto posities en onderdrukt signal-alerts.
+    /// Lopende posities blijven bewaakt, SL/TP exits lopen dus gewoon door.
+    async fn set_trading_paused(&self, paused: bool) -> Result<(), String> {
+        let now_ts = Utc::now().timestamp();
+        let state = serde_json::json!({ "paused": paused, "changed_at": now_ts });
+        let json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+        tokio::fs::write(TRADING_PAUSE_FILE, json)
+            .await
+            .map_err(|e| format!("Kon {} niet opslaan: {}", TRADING_PAUSE_FILE, e))?;
+        let previous = self.trading_paused.swap(paused, Ordering::SeqCst);
+        if previous != paused {
+            log_warn!("[WARN] Trading {}", if paused { "GEPAUZEERD" } else { "hervat" });
+            self.log_event("trading_pause", serde_json::json!({ "paused": paused }));
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -2223,6 +6662,37 @@ fn normalize_pair(wsname: &str) -> String {
     format!("{}/{}", base, quote)
 }
 
+/// Decimalen zodat een prijs ~5 significante cijfers toont:
+/// 65000.12 -> 2, 1.2345 -> 4, 0.00001234 -> 9 (max 10).
+fn price_precision(price: f64) -> u32 {
+    if !price.is_finite() || price <= 0.0 {
+        return 4;
+    }
+    if price >= 1000.0 {
+        return 2;
+    }
+    if price >= 1.0 {
+        return 4;
+    }
+    let leading_zeros = (-price.log10()).floor() as u32;
+    (leading_zeros + 5).min(10)
+}
+
+fn quote_currency(pair: &str) -> Option<&str> {
+    pair.split_once('/').map(|(_, q)| q).filter(|q| !q.is_empty())
+}
+
+fn currency_symbol(code: &str) -> String {
+    match code {
+        "EUR" => "€".to_string(),
+        "USD" | "USDT" | "USDC" => "$".to_string(),
+        "GBP" => "£".to_string(),
+        "JPY" => "¥".to_string(),
+        "BTC" => "₿".to_string(),
+        c => format!("{} ", c),
+    }
+}
+
 // ============================================================================
 // HOOFDSTUK 9 – FRONTEND (HTML DASHBOARD) (AANGEPAST VOOR STARS HISTORIE)
 // ============================================================================
@@ -2248,6 +6718,22 @@ header h1 { margin:0; }
   font-size:12px;
 }
 .tab-btn.active { background:#444; color:#fff; }
+#breadth { display:flex; align-items:center; gap:6px; font-size:12px; white-space:nowrap; }
+#breadth-bar { display:flex; width:160px; height:10px; background:#333; border-radius:3px; overflow:hidden; }
+#breadth-buy { background:#4caf50; }
+#breadth-neutral { background:#666; }
+#breadth-sell { background:#f44336; }
+th.sortable { cursor:pointer; user-select:none; }
+th.sortable:hover { color:#4fc3f7; }
+th.sort-asc::after { content:" \25B2"; font-size:9px; }
+th.sort-desc::after { content:" \25BC"; font-size:9px; }
+#pause-btn { padding:6px 12px; background:#333; border:1px solid #555; color:#fff; cursor:pointer; }
+#pause-btn.paused { background:#2e7d32; border-color:#4caf50; }
+#refresh-rate { padding:5px; background:#333; border:1px solid #555; color:#fff; }
+#refresh-rate.paused { border-color:#ffb300; color:#ffb300; }
+#paused-banner { display:none; background:#b71c1c; color:#fff; font-weight:bold; text-align:center; padding:6px; letter-spacing:1px; }
+.tab-badge { background:#f44336; color:#fff; border-radius:8px; padding:0 5px; margin-left:4px; font-size:10px; }
+.tag { background:#333; color:#ccc; border-radius:3px; padding:0 4px; font-size:11px; }
 table { width:100%; border-collapse:collapse; margin-top:10px; font-size:12px; }
 th { background:#222; padding:6px; border-bottom:1px solid #333; text-align:left; }
 td { padding:6px; border-bottom:1px solid #333; }
@@ -2266,6 +6752,7 @@ tr:nth-child(even){ background:#252525; }
 .signal_type_EARLY_PUMP { color:#00bcd4; }
 .signal_type_MEGA_PUMP { color:#ff4081; }
 .signal_type_WH_PRED { color:#00bcd4; }
+.signal_type_DIVERGENCE { color:#b388ff; }
 .signal_dir_BUY { color:#00e676; }
 .signal_dir_SELL { color:#ff1744; }
 .flow-bar {
@@ -2294,6 +6781,10 @@ tr:nth-child(even){ background:#252525; }
 .rel_med  { color:#cddc39; font-weight:bold; }
 .rel_low  { color:#ff9800; font-weight:bold; }
 .rel_bad  { color:#f44336; font-weight:bold; }
+.stale-badge { background:#555; color:#ffeb3b; font-size:10px; padding:1px 4px; border-radius:3px; }
+.nobook-badge { background:#333; color:#888; font-size:10px; padding:1px 4px; border-radius:3px; }
+.test-badge { background:#37474f; color:#80deea; font-size:10px; padding:1px 4px; border-radius:3px; }
+.wash-badge { background:#5d1f1f; color:#ff8a80; font-size:10px; padding:1px 4px; border-radius:3px; }
 </style>
 </head>
 <body>
@@ -2301,12 +6792,32 @@ tr:nth-child(even){ background:#252525; }
   <div class="header-top">
     <h1>WhaleRadar</h1>
     <input id="search" placeholder="Zoek coin (btc, eth, whale, alpha, anom)..." />
+    <div id="breadth" title="">
+      <span>Breadth</span>
+      <div id="breadth-bar"><div id="breadth-buy"></div><div id="breadth-neutral"></div><div id="breadth-sell"></div></div>
+      <span id="breadth-text">-</span>
+    </div>
+    <select id="refresh-rate" title="Auto-refresh interval">
+      <option value="config">Refresh: config</option>
+      <option value="1">Refresh: 1s</option>
+      <option value="2">Refresh: 2s</option>
+      <option value="5">Refresh: 5s</option>
+      <option value="10">Refresh: 10s</option>
+      <option value="30">Refresh: 30s</option>
+      <option value="0">Refresh: pauze</option>
+    </select>
+    <button id="pause-btn" onclick="toggleTradingPause()">Pause trading</button>
   </div>
+  <div id="paused-banner">TRADING GEPAUZEERD – geen nieuwe posities, geen signal alerts</div>
   <div id="tabs">
     <button class="tab-btn active" data-tab="markets">Markets</button>
-    <button class="tab-btn" data-tab="signals">Signals</button>
+    <button class="tab-btn" data-tab="signals">Signals<span id="signals-badge" class="tab-badge" style="display:none;"></span></button>
     <button class="tab-btn" data-tab="top10">Top 10</button>
+    <button class="tab-btn" data-tab="movers">Movers</button>
+    <button class="tab-btn" data-tab="trade_advice">Trade Advice</button>
     <button class="tab-btn" data-tab="manual_trades">Manual Trades</button>
+    <button class="tab-btn" data-tab="auto_trades">Auto Trader</button>
+    <button class="tab-btn" data-tab="alerts">Alerts</button>
     <button class="tab-btn" data-tab="backtest">Backtest</button>
     <button class="tab-btn" data-tab="heatmap">Heatmap</button>
     <button class="tab-btn" data-tab="stars">Stars</button>
@@ -2326,20 +6837,45 @@ tr:nth-child(even){ background:#252525; }
       </select>
       <label for="markets-stable-filter" style="margin-left:10px;">Include Stablecoins:</label>
       <input type="checkbox" id="markets-stable-filter" checked />
+      <label for="markets-active-filter" style="margin-left:10px;">Show only active:</label>
+      <input type="checkbox" id="markets-active-filter" />
+      <label for="markets-pct-mode" style="margin-left:10px;">% column:</label>
+      <select id="markets-pct-mode">
+        <option value="open">Since Kraken day-open</option>
+        <option value="rolling">Rolling 1h</option>
+        <option value="first_seen">Since first seen (process start)</option>
+      </select>
     </div>
     <table id="grid">
       <thead>
         <tr>
-          <th>Pair</th><th>Price</th><th>%</th><th>Whale</th>
-          <th>Flow</th><th>Dir</th><th>Early</th><th>Alpha</th><th>Pump</th>
-          <th>WhPred</th><th>Rel</th><th>News Sent.</th>
-          <th>Total score</th><th>Trades</th><th>Buys</th><th>Sells</th>
+          <th>Pair</th><th>Price</th><th id="markets-pct-header" class="sortable" data-sort="pct">%</th><th>5m</th><th>1h</th><th>24h</th><th>Whale</th>
+          <th>Whale net (<span class="whale-window">1m</span>)</th>
+          <th id="markets-flow-header" class="sortable" data-sort="flow">Flow</th><th>Dir</th><th>Early</th><th>Alpha</th><th class="sortable" data-sort="pump">Pump (conf)</th>
+          <th>WhPred</th><th class="sortable" data-sort="reliability">Rel</th><th>Div</th><th>News Sent.</th>
+          <th>Funding</th><th>OI</th><th id="markets-flow-long-header">Flow long</th><th>Biggest print</th>
+          <th class="sortable" data-sort="score">Total score</th><th>Trades</th><th>Buys</th><th>Sells</th>
           <th>O</th><th>H</th><th>L</th><th>C</th>
           <th>Visual</th>
         </tr>
       </thead>
       <tbody></tbody>
     </table>
+    <div id="pair-detail" style="display:none; margin-top:15px; padding:10px; background:#222; border-radius:5px;">
+      <div id="pair-detail-info"></div>
+      <h4>Orderboek (top 10)</h4>
+      <div id="pair-detail-book"></div>
+      <h4>Tape</h4>
+      <div id="pair-detail-tape" style="max-height:200px; overflow-y:auto; font-family:monospace; font-size:12px;"></div>
+      <h4>Signal historie</h4>
+      <div style="max-height:250px; overflow-y:auto;">
+        <table id="pair-detail-signals">
+          <thead><tr><th>Time</th><th>Type</th><th>Dir</th><th>Strength</th><th>Price</th><th>Score</th></tr></thead>
+          <tbody></tbody>
+        </table>
+      </div>
+      <button type="button" id="pair-detail-more" style="margin-top:5px;">Meer laden</button>
+    </div>
   </div>
 
   <div id="view-signals" style="display:none;">
@@ -2352,13 +6888,16 @@ tr:nth-child(even){ background:#252525; }
       </select>
       <label for="signals-stable-filter" style="margin-left:10px;">Include Stablecoins:</label>
       <input type="checkbox" id="signals-stable-filter" checked />
+      <label for="signals-raw-strength" style="margin-left:10px;">Ruwe strength:</label>
+      <input type="checkbox" id="signals-raw-strength" />
     </div>
     <table id="signals">
       <thead>
         <tr>
           <th>Time (ts)</th><th>Pair</th><th>Type</th><th>Dir</th>
-          <th>Strength</th><th>Flow</th><th>%</th><th>Total score</th>
+          <th>Strength (0-100)</th><th>Flow</th><th>%</th><th>Total score</th>
           <th>Whale</th><th>Vol</th><th>Notional</th><th>Price</th><th>Pump</th>
+          <th>Ret 5m</th><th>Outcome</th>
           <th>Visual</th>
         </tr>
       </thead>
@@ -2377,37 +6916,97 @@ tr:nth-child(even){ background:#252525; }
       <label for="top10-stable-filter" style="margin-left:10px;">Include Stablecoins:</label>
       <input type="checkbox" id="top10-stable-filter" checked />
     </div>
-    <h2>🔥 Best 3 Right Now</h2>
+    <h2>🔥 Best <span class="best-n">3</span> Right Now</h2>
     <table id="top3">
       <thead>
         <tr>
           <th>Time</th><th>Pair</th><th>Price</th><th>%</th><th>Flow</th><th>Dir</th>
-          <th>Early</th><th>Alpha</th><th>Whale</th><th>Total score</th><th>Pump</th>
+          <th>Early</th><th>Alpha</th><th>Whale</th><th>Total score</th><th>Conviction</th><th>Pump</th>
           <th>WhPred</th><th>Rel</th><th>Type</th><th>Visual</th><th>Analyse</th>
         </tr>
       </thead>
       <tbody></tbody>
     </table>
 
-    <h2>Top 10 Stijgers (strong buy)</h2>
+    <h2>Top <span class="top-n">10</span> Stijgers (strong buy)</h2>
     <table id="top10-up">
       <thead>
         <tr>
-          <th>Time</th><th>Pair</th><th>Price</th><th>%</th><th>Flow</th><th>Dir</th>
-          <th>Early</th><th>Alpha</th><th>Whale</th><th>Total score</th><th>Pump</th>
-          <th>WhPred</th><th>Rel</th><th>Type</th><th>Visual</th><th>Analyse</th>
+          <th>Time</th><th>Pair</th><th>Price</th><th class="sortable" data-sort="pct">%</th><th class="sortable" data-sort="flow">Flow</th><th>Dir</th>
+          <th>Early</th><th>Alpha</th><th>Whale</th><th class="sortable" data-sort="score">Total score</th><th>Conviction</th><th class="sortable" data-sort="pump">Pump</th>
+          <th>WhPred</th><th class="sortable" data-sort="reliability">Rel</th><th>Type</th><th>Visual</th><th>Analyse</th>
         </tr>
       </thead>
       <tbody></tbody>
     </table>
 
-    <h2>Top 10 Dalers (strong sell)</h2>
+    <h2>Top <span class="top-n">10</span> Dalers (strong sell)</h2>
     <table id="top10-down">
       <thead>
         <tr>
-          <th>Time</th><th>Pair</th><th>Price</th><th>%</th><th>Flow</th><th>Dir</th>
-          <th>Early</th><th>Alpha</th><th>Whale</th><th>Total score</th><th>Pump</th>
-          <th>Rel</th><th>Visual</th><th>Analyse</th>
+          <th>Time</th><th>Pair</th><th>Price</th><th class="sortable" data-sort="pct">%</th><th class="sortable" data-sort="flow">Flow</th><th>Dir</th>
+          <th>Early</th><th>Alpha</th><th>Whale</th><th class="sortable" data-sort="score">Total score</th><th>Conviction</th><th class="sortable" data-sort="pump">Pump</th>
+          <th>WhPred</th><th class="sortable" data-sort="reliability">Rel</th><th>Type</th><th>Visual</th><th>Analyse</th>
+        </tr>
+      </thead>
+      <tbody></tbody>
+    </table>
+  </div>
+
+  <div id="view-movers" style="display:none;">
+    <h2>Top Movers (24h, alle pairs)</h2>
+    <div style="margin-bottom:10px;">
+      <select id="movers-dir">
+        <option value="up">Stijgers</option>
+        <option value="down">Dalers</option>
+      </select>
+      <select id="movers-limit">
+        <option value="20">20</option>
+        <option value="50">50</option>
+        <option value="100">100</option>
+      </select>
+    </div>
+    <table id="movers-table">
+      <thead>
+        <tr>
+          <th>Pair</th><th>Price</th><th>24h %</th><th>Volume 24h</th><th>Quote Volume</th><th>Signal</th><th>Updated</th>
+        </tr>
+      </thead>
+      <tbody></tbody>
+    </table>
+  </div>
+
+  <div id="view-trade_advice" style="display:none;">
+    <h2>Trade Advice (BUY flow, hoogste score eerst)</h2>
+    <p id="trade-advice-info" style="color:#aaa;"></p>
+    <table id="trade-advice-table">
+      <thead>
+        <tr>
+          <th>Pair</th><th>Price</th><th>Entry</th>
+          <th class="trade-advice-exit">Exit 1</th><th class="trade-advice-exit">Exit 2</th>
+          <th class="trade-advice-exit">Exit 3</th><th class="trade-advice-exit">Exit 4</th>
+        </tr>
+      </thead>
+      <tbody></tbody>
+    </table>
+    <h3>Equity als alle adviezen het exit-niveau halen</h3>
+    <table>
+      <thead>
+        <tr>
+          <th class="trade-advice-exit">Exit 1</th><th class="trade-advice-exit">Exit 2</th>
+          <th class="trade-advice-exit">Exit 3</th><th class="trade-advice-exit">Exit 4</th>
+        </tr>
+      </thead>
+      <tbody id="trade-advice-equity"></tbody>
+    </table>
+  </div>
+
+  <div id="view-alerts" style="display:none;">
+    <h2>Alerts Inbox</h2>
+    <table id="alerts-table">
+      <thead>
+        <tr>
+          <th>Tijd</th><th>Pair</th><th>Type</th><th>Kanaal</th><th>Afgeleverd</th><th>Fout / bericht</th>
         </tr>
       </thead>
       <tbody></tbody>
@@ -2417,9 +7016,20 @@ tr:nth-child(even){ background:#252525; }
   <div id="view-manual_trades" style="display:none;">
     <h2>Manual Trades</h2>
     <div id="manual-summary" style="margin-bottom:15px; padding:10px; background:#222; border-radius:5px;">
-      <p><strong>Balance:</strong> <span id="manual-balance">€0.00</span></p>
-      <p><strong>Initial Balance:</strong> <span id="manual-initial">€0.00</span></p>
-      <p><strong>Total PnL:</strong> <span id="manual-pnl" class="pos">€0.00</span></p>
+      <p><strong>Balance:</strong> <span id="manual-balance">0.00</span></p>
+      <p><strong>Initial Balance:</strong> <span id="manual-initial">0.00</span></p>
+      <p><strong>Total PnL:</strong> <span id="manual-pnl" class="pos">0.00</span></p>
+      <p style="font-size:12px;">
+        <strong>Closed:</strong> <span id="manual-stat-trades">0</span> |
+        <strong>Winrate:</strong> <span id="manual-stat-winrate">0.0%</span> |
+        <strong>Avg win:</strong> <span id="manual-stat-avgwin">0.00</span> |
+        <strong>Avg loss:</strong> <span id="manual-stat-avgloss">0.00</span> |
+        <strong>Expectancy:</strong> <span id="manual-stat-expectancy">0.00</span> |
+        <strong>Profit factor:</strong> <span id="manual-stat-pf">0.00</span> |
+        <strong>Max drawdown:</strong> <span id="manual-stat-dd">0.00</span>
+      </p>
+      <p id="manual-portfolio" style="font-size:12px;"></p>
+      <button id="manual-reset-btn" style="padding:3px 8px; background:#5a1a1a; color:#fff; border:1px solid #f44336;">Reset account</button>
     </div>
     
     <h3>Open a Trade</h3>
@@ -2436,7 +7046,13 @@ tr:nth-child(even){ background:#252525; }
         <option value="0.26" selected>0.26%</option>
         <option value="0.5">0.5%</option>
       </select>
-      <label style="margin-left:20px; margin-right:10px;">Amount (€):</label>
+      <label style="margin-left:20px; margin-right:10px;">Sizing:</label>
+      <select id="manual-sizing-mode">
+        <option value="fixed" selected>Fixed amount</option>
+        <option value="pct_balance">% of balance</option>
+        <option value="fixed_risk">Fixed risk (SL)</option>
+      </select>
+      <label id="manual-amount-label" style="margin-left:10px; margin-right:10px;">Amount (<span class="account-currency">EUR</span>):</label>
       <input type="number" id="manual-amount" value="100" step="10" style="width:100px;" />
       <br/><br/>
       <label style="margin-right:10px;">Stop Loss %:</label>
@@ -2453,10 +7069,20 @@ tr:nth-child(even){ background:#252525; }
         <option value="5" selected>5%</option>
         <option value="10">10%</option>
       </select>
+      <br/><br/>
+      <label style="margin-right:10px;">Note:</label>
+      <input type="text" id="manual-note" placeholder="Waarom open ik deze trade?" style="width:350px;" />
+      <label style="margin-left:20px; margin-right:10px;">Tags:</label>
+      <input type="text" id="manual-tags" placeholder="breakout, news" style="width:180px;" />
       <button id="manual-open-btn" style="margin-left:20px; padding:5px 15px;">Open Trade</button>
     </div>
     
     <h3>Active Trades</h3>
+    <div style="margin-bottom:8px;">
+      <label style="margin-right:10px;">Partial close %:</label>
+      <input type="number" id="manual-partial-pct" value="50" min="1" max="100" step="1" style="width:70px;" />
+    </div>
+    <p id="manual-cooldowns" style="font-size:12px; display:none;"></p>
     <table id="manual-trades-table">
       <thead>
         <tr>
@@ -2469,25 +7095,94 @@ tr:nth-child(even){ background:#252525; }
           <th>Open TS</th>
           <th>Fee %</th>
           <th>Amount</th>
+          <th>Note</th>
+          <th>Tags</th>
           <th>Actions</th>
         </tr>
       </thead>
       <tbody></tbody>
     </table>
     
+    <h3>Closed Trades</h3>
+    <div style="margin-bottom:10px;">
+      <label>Filter op tag (ook stats):</label>
+      <input type="text" id="manual-tag-filter" placeholder="tag" style="width:150px;" />
+    </div>
+    <table id="manual-closed-table">
+      <thead>
+        <tr>
+          <th>Pair</th>
+          <th>Entry Price</th>
+          <th>Exit Price</th>
+          <th>Size</th>
+          <th>Net PnL</th>
+          <th>Opened</th>
+          <th>Closed</th>
+          <th>Reason</th>
+          <th>Note</th>
+          <th>Tags</th>
+        </tr>
+      </thead>
+      <tbody></tbody>
+    </table>
+
     <h3>Equity Curve</h3>
     <canvas id="manual-equity" width="900" height="260" style="border:1px solid #333; background:#111;"></canvas>
   </div>
 
+  <div id="view-auto_trades" style="display:none;">
+    <h2>Auto Paper Trader (ALPHA BUY)</h2>
+    <div style="margin-bottom:15px; padding:10px; background:#222; border-radius:5px;">
+      <p><strong>Status:</strong> <span id="auto-status">-</span></p>
+      <p><strong>Balance:</strong> <span id="auto-balance">0.00</span></p>
+      <p><strong>Total PnL:</strong> <span id="auto-pnl">0.00</span></p>
+      <p style="font-size:12px;">Opent automatisch een positie van Base Notional bij elk ALPHA BUY signaal en sluit op SL/TP (Config &rarr; Paper Trading). Los van de manual trades.</p>
+    </div>
+
+    <h3>Open Positions</h3>
+    <table id="auto-positions-table">
+      <thead>
+        <tr>
+          <th>Pair</th><th>Entry Price</th><th>Size</th><th>Current Price</th>
+          <th>PnL Abs</th><th>PnL %</th><th>SL</th><th>TP</th><th>Opened</th>
+        </tr>
+      </thead>
+      <tbody></tbody>
+    </table>
+
+    <h3>Closed Trades</h3>
+    <table id="auto-closed-table">
+      <thead>
+        <tr>
+          <th>Pair</th><th>Entry Price</th><th>Exit Price</th><th>Size</th>
+          <th>PnL</th><th>Opened</th><th>Closed</th><th>Reason</th>
+        </tr>
+      </thead>
+      <tbody></tbody>
+    </table>
+  </div>
+
   <div id="view-backtest" style="display:none;">
     <div style="margin-bottom:10px;">
       <label for="backtest-stable-filter">Include Stablecoins:</label>
       <input type="checkbox" id="backtest-stable-filter" checked />
+      <label for="backtest-since" style="margin-left:10px;">Vanaf:</label>
+      <input type="datetime-local" id="backtest-since" />
+      <label for="backtest-until" style="margin-left:10px;">Tot:</label>
+      <input type="datetime-local" id="backtest-until" />
+      <label for="backtest-min-trades" style="margin-left:10px;">Min trades:</label>
+      <input type="number" id="backtest-min-trades" min="0" step="1" value="0" style="width:60px;" />
+      <label for="backtest-archive" style="margin-left:10px;">Incl. archief:</label>
+      <input type="checkbox" id="backtest-archive" />
     </div>
     <h2>Backtest per signaaltype</h2>
     <p style="font-size:12px;">
       Gebaseerd op afgeronde signals (ongeveer 5 minuten na het signaal).
-      Alle waarden zijn % prijsverandering per trade.
+      Alle waarden zijn % prijsverandering per trade; "Samengesteld" rekent de returns door op de
+      startbalans met backtest_position_fraction van de equity per trade.
+    </p>
+    <p style="font-size:12px;">
+      <span id="backtest-pending-count">0</span> signals wachten nog op evaluatie<span id="backtest-pending-next"></span>.
     </p>
 
     <table id="backtest-table">
@@ -2505,34 +7200,57 @@ tr:nth-child(even){ background:#252525; }
           <th>Best trade</th>
           <th>Worst trade</th>
           <th>Max losing streak</th>
+          <th>Stddev</th>
+          <th>Sharpe</th>
+          <th title="Samengesteld rendement bij fixed fractional sizing (backtest_position_fraction)">Samengesteld</th>
+          <th>Account eind</th>
         </tr>
       </thead>
       <tbody></tbody>
     </table>
 
-    <h3>Equity curve (klik op een rij)</h3>
+    <h3>Equity curve (klik op een rij, shift-klik op een tweede rij om te vergelijken)</h3>
+    <p style="font-size:12px;">
+      <span style="color:#00e676;">&#9644; Som van % returns</span>
+      <span style="color:#ffb300; margin-left:12px;">&#9644; Samengesteld account (% t.o.v. startbalans)</span>
+    </p>
     <canvas id="backtest-equity" width="900" height="260"
             style="border:1px solid #333; background:#111;"></canvas>
     <div id="backtest-equity-label"
          style="margin-top:4px; font-size:12px; color:#aaa;">
       Klik op een rij om de equity curve van die strategie te zien.
     </div>
+
+    <h3>Zelflerende thresholds per signaaltype</h3>
+    <p style="font-size:12px;">
+      Adaptieve threshold en winrate na elke geëvalueerde trade (signals.json).
+    </p>
+    <table id="signal-stats-table">
+      <thead>
+        <tr><th>Signaaltype</th><th>W / L</th><th>Threshold</th><th>Threshold verloop</th><th>Winrate</th><th>Winrate verloop</th></tr>
+      </thead>
+      <tbody></tbody>
+    </table>
   </div>
 
   <div id="view-heatmap" style="display:none;">
     <div style="margin-bottom:10px;">
       <label for="heatmap-stable-filter">Include Stablecoins:</label>
       <input type="checkbox" id="heatmap-stable-filter" checked />
+      <label for="heatmap-x" style="margin-left:10px;">X-as:</label>
+      <select id="heatmap-x"></select>
+      <label for="heatmap-y" style="margin-left:10px;">Y-as:</label>
+      <select id="heatmap-y"></select>
     </div>
-    <h2>Heatmap: BUY-flow vs Pump-score</h2>
+    <h2 id="heatmap-title">Heatmap: BUY-flow vs Pump-score</h2>
     <canvas id="heatCanvas" width="800" height="400" style="border:0;"></canvas>
     <div style="margin-top:8px; font-size:12px;">
       <span style="background:#ff4081; padding:2px 6px; border-radius:4px; margin-right:6px;">MEGA pump</span>
       <span style="background:#00bcd4; padding:2px 6px; border-radius:4px; margin-right:6px;">EARLY pump</span>
       <span style="background:#4caf50; padding:2px 6px; border-radius:4px;">Sterke buy-flow</span>
       <div style="margin-top:4px;">
-        X-as: BUY-flow (%) &nbsp; | &nbsp; Y-as: Pump-score (0–10).<br/>
-        Rechtsboven = sterkste pump-kandidaten.
+        Standaard X-as: BUY-flow (%) &nbsp; | &nbsp; Y-as: Pump-score (0–10); rechtsboven = sterkste pump-kandidaten.<br/>
+        Kleur blijft op pump/flow gebaseerd, grootte en helderheid op reliability.
       </div>
     </div>
   </div>
@@ -2542,7 +7260,7 @@ tr:nth-child(even){ background:#252525; }
       <label for="stars-stable-filter">Include Stablecoins:</label>
       <input type="checkbox" id="stars-stable-filter" checked />
     </div>
-    <h2>⭐ Stars: ANOM & WH_PRED HIGH (last 5 hours)</h2>
+    <h2 id="stars-title">⭐ Stars</h2>
     <table id="stars-table">
       <thead>
         <tr>
@@ -2572,6 +7290,13 @@ tr:nth-child(even){ background:#252525; }
       <input type="checkbox" id="news-stable-filter" checked />
     </div>
     <h2>📰 News Sentiment</h2>
+    <div id="news-market-gauge" style="margin-bottom:10px; font-size:13px;">
+      Markt sentiment: <span id="news-market-value">–</span>
+      <span style="display:inline-block; width:200px; height:10px; background:#333; vertical-align:middle; margin:0 8px;">
+        <span id="news-market-bar" style="display:block; height:100%; width:50%; background:#888;"></span>
+      </span>
+      <span id="news-market-info" style="color:#888;"></span>
+    </div>
     <table id="news-table">
       <thead>
         <tr>
@@ -2586,20 +7311,190 @@ tr:nth-child(even){ background:#252525; }
     <h2>Configuration Settings</h2>
     <form id="config-form">
       <h3>1. Signal Drempels</h3>
-      <label>Pump Confidence Threshold (0.0-1.0):</label>
+      <label>Pump Confidence Threshold (0.0-1.0, EARLY_PUMP; MEGA_PUMP +0.2):</label>
       <input type="number" step="0.1" min="0.0" max="1.0" id="pump_conf_threshold" /><br/>
-      <label>Whale Prediction High Threshold (0.0-10.0):</label>
-      <input type="number" step="0.1" min="0.0" max="10.0" id="whale_pred_high_threshold" /><br/>
-      <label>Early Buy Threshold (0.0-5.0):</label>
+      <label>Pump score: return 5s / 30s / 120s drempel % en multiplier ((ret - drempel) * mult):</label>
+      <input type="number" step="0.1" min="0" id="pump.ret_5s_threshold" />
+      <input type="number" step="0.1" min="0" id="pump.ret_5s_mult" />
+      <input type="number" step="0.1" min="0" id="pump.ret_30s_threshold" />
+      <input type="number" step="0.1" min="0" id="pump.ret_30s_mult" />
+      <input type="number" step="0.1" min="0" id="pump.ret_120s_threshold" />
+      <input type="number" step="0.1" min="0" id="pump.ret_120s_mult" /><br/>
+      <label>Pump score: flow % / flow 5m % / vol ratio drempel en multiplier:</label>
+      <input type="number" step="1" min="0" max="100" id="pump.flow_threshold" />
+      <input type="number" step="0.01" min="0" id="pump.flow_mult" />
+      <input type="number" step="1" min="0" max="100" id="pump.flow_5m_threshold" />
+      <input type="number" step="0.01" min="0" id="pump.flow_5m_mult" />
+      <input type="number" step="0.1" min="0" id="pump.vol_ratio_threshold" />
+      <input type="number" step="0.1" min="0" id="pump.vol_ratio_mult" /><br/>
+      <label>Pump score: whale multiplier / max score:</label>
+      <input type="number" step="0.1" min="0" id="pump.whale_mult" />
+      <input type="number" step="0.5" min="0.5" max="100" id="pump.max_score" /><br/>
+      <label>Whale Prediction High / Medium / Low Threshold (0.0-10.0, HIGH voedt WH_PRED en Stars):</label>
+      <input type="number" step="0.1" min="0.0" max="10.0" id="whale_pred_high_threshold" />
+      <input type="number" step="0.1" min="0.0" max="10.0" id="whale_pred_medium_threshold" />
+      <input type="number" step="0.1" min="0.0" max="10.0" id="whale_pred_low_threshold" /><br/>
+      <label>Early Buy Threshold (0.0-5.0, total score voor Early flag):</label>
       <input type="number" step="0.1" min="0.0" max="5.0" id="early_buy_threshold" /><br/>
-      <label>Alpha Buy Threshold (0.0-10.0):</label>
+      <label>Alpha Buy Threshold (0.0-10.0, total score voor Alpha flag, &ge; Early):</label>
       <input type="number" step="0.1" min="0.0" max="10.0" id="alpha_buy_threshold" /><br/>
       <label>Strong Buy Threshold (0.0-10.0):</label>
       <input type="number" step="0.1" min="0.0" max="10.0" id="strong_buy_threshold" /><br/>
       <label>Whale Min Notional (0.0-10000.0):</label>
       <input type="number" step="100" min="0.0" max="10000.0" id="whale_min_notional" /><br/>
+      <label>Whale Net Flow Window sec:</label>
+      <input type="number" step="10" min="10" max="3600" id="whale_flow_window_sec" /><br/>
+      <label>Min Trade Notional (dust filter):</label>
+      <input type="number" step="0.5" min="0.0" id="min_trade_notional" /><br/>
       <label>Anomaly Strength Threshold (0.0-100.0):</label>
       <input type="number" step="1" min="0.0" max="100.0" id="anomaly_strength_threshold" /><br/>
+      <label>Anomaly Jump Weight (per % prijssprong):</label>
+      <input type="number" step="0.1" min="0.0" id="anomaly.jump_weight" /><br/>
+      <label>Anomaly Day Return Weight (per % 24h):</label>
+      <input type="number" step="0.1" min="0.0" id="anomaly.day_ret_weight" /><br/>
+      <label>Anomaly Volume Ratio Weight:</label>
+      <input type="number" step="1" min="0.0" id="anomaly.vol_ratio_weight" /><br/>
+      <label>Anomaly EWMA Return Weight:</label>
+      <input type="number" step="0.1" min="0.0" id="anomaly.ewma_return_weight" /><br/>
+      <label>Anomaly Trigger: min Jump % OF min Volume Ratio:</label>
+      <input type="number" step="0.05" min="0.0" id="anomaly.min_jump_pct" />
+      <input type="number" step="0.1" min="0.0" id="anomaly.min_vol_ratio" /><br/>
+      <label>Anomaly Cooldown sec per pair (0 = uit):</label>
+      <input type="number" step="30" min="0" id="anomaly.cooldown_sec" /><br/>
+      <label>Anomaly Direction:</label>
+      <select id="anomaly.direction">
+        <option value="BOTH">BOTH</option>
+        <option value="BUY">BUY only</option>
+        <option value="SELL">SELL only</option>
+      </select><br/>
+      <label>Stars: min WH_PRED label:</label>
+      <select id="stars.min_whale_pred_label">
+        <option value="LOW">LOW</option>
+        <option value="MEDIUM">MEDIUM</option>
+        <option value="HIGH">HIGH</option>
+      </select><br/>
+      <label>Stars: ANOM vereist / lookback sec:</label>
+      <input type="checkbox" id="stars.require_anom" />
+      <input type="number" step="600" min="60" id="stars.anom_lookback_sec" /><br/>
+      <label>Stars historie: min sec tussen entries per pair (zelfde ANOM telt nooit dubbel):</label>
+      <input type="number" step="600" min="0" id="stars.history_dedup_sec" /><br/>
+      <label>Wash-trading detectie (WASH badge):</label>
+      <input type="checkbox" id="wash.enabled" /><br/>
+      <label>Wash: window sec / volume tolerantie (0.001 = 0.1%):</label>
+      <input type="number" step="10" min="10" id="wash.window_sec" />
+      <input type="number" step="0.0005" min="0" max="0.5" id="wash.volume_tolerance" /><br/>
+      <label>Wash: min round trips / reliability penalty (0-1):</label>
+      <input type="number" step="1" min="1" id="wash.min_round_trips" />
+      <input type="number" step="0.05" min="0" max="1" id="wash.reliability_penalty" /><br/>
+      <label>Analyse: taal / max tekens / decimalen volume:</label>
+      <select id="analysis.language">
+        <option value="auto">auto (locale)</option>
+        <option value="nl">nl</option>
+        <option value="en">en</option>
+      </select>
+      <input type="number" step="10" min="20" id="analysis.max_chars" />
+      <input type="number" step="1" min="0" max="10" id="analysis.volume_decimals" /><br/>
+      <label>Analyse drempels: prijs % sterk / licht, flow % sterk / matig:</label>
+      <input type="number" step="0.5" id="analysis.strong_pct" />
+      <input type="number" step="0.5" id="analysis.mild_pct" />
+      <input type="number" step="1" id="analysis.strong_flow_pct" />
+      <input type="number" step="1" id="analysis.moderate_flow_pct" /><br/>
+      <label>Analyse drempels: pump sterk / matig, nieuws positief / negatief:</label>
+      <input type="number" step="0.5" id="analysis.strong_pump_score" />
+      <input type="number" step="0.5" id="analysis.moderate_pump_score" />
+      <input type="number" step="0.05" min="0" max="1" id="analysis.positive_news" />
+      <input type="number" step="0.05" min="0" max="1" id="analysis.negative_news" /><br/>
+      <label>Prijs/flow divergentie (DIVERGENCE signal) / window sec:</label>
+      <input type="checkbox" id="divergence.enabled" />
+      <input type="number" step="30" min="30" id="divergence.window_sec" /><br/>
+      <label>Divergentie: min flow delta (pp) / max prijs stilstand % / min prijsstijging %:</label>
+      <input type="number" step="1" min="1" max="100" id="divergence.min_flow_delta" />
+      <input type="number" step="0.05" min="0" id="divergence.max_stall_pct" />
+      <input type="number" step="0.05" min="0.05" id="divergence.min_price_rise_pct" /><br/>
+      <label>Divergentie: cooldown sec per pair voor hetzelfde label:</label>
+      <input type="number" step="60" min="0" id="divergence.cooldown_sec" /><br/>
+      <label>FX: base currency voor ranking/whale drempels / bron / refresh sec:</label>
+      <select id="fx.base_currency">
+        <option value="EUR">EUR</option>
+        <option value="USD">USD</option>
+        <option value="GBP">GBP</option>
+        <option value="CHF">CHF</option>
+        <option value="JPY">JPY</option>
+      </select>
+      <select id="fx.source">
+        <option value="kraken">kraken</option>
+        <option value="static">static</option>
+      </select>
+      <input type="number" step="60" min="60" id="fx.refresh_sec" /><br/>
+      <label>Trade Advice: entry % onder prijs:</label>
+      <input type="number" step="0.1" min="0" id="trade_advice.entry_pct" /><br/>
+      <label>Trade Advice: exit ladder % (1-4, oplopend):</label>
+      <input type="number" step="0.5" min="0.1" id="trade_advice.exit_pct_1" />
+      <input type="number" step="0.5" min="0.1" id="trade_advice.exit_pct_2" />
+      <input type="number" step="0.5" min="0.1" id="trade_advice.exit_pct_3" />
+      <input type="number" step="0.5" min="0.1" id="trade_advice.exit_pct_4" /><br/>
+      <label>Flow Short Window sec (Markets Flow/Dir):</label>
+      <input type="number" step="5" min="5" max="3600" id="flow.short_window_sec" /><br/>
+      <label>Flow Short BUY / SELL cutoff (0.5-1.0 / 0.0-0.5):</label>
+      <input type="number" step="0.01" min="0.5" max="1.0" id="flow.short_buy_cutoff" />
+      <input type="number" step="0.01" min="0.0" max="0.5" id="flow.short_sell_cutoff" /><br/>
+      <label>Flow Medium Window sec:</label>
+      <input type="number" step="30" min="30" max="3600" id="flow.medium_window_sec" /><br/>
+      <label>Flow Medium BUY / SELL cutoff:</label>
+      <input type="number" step="0.01" min="0.5" max="1.0" id="flow.medium_buy_cutoff" />
+      <input type="number" step="0.01" min="0.0" max="0.5" id="flow.medium_sell_cutoff" /><br/>
+      <label>Flow Long Window sec (context):</label>
+      <input type="number" step="60" min="60" max="7200" id="flow.long_window_sec" /><br/>
+      <label>Flow Long BUY / SELL cutoff:</label>
+      <input type="number" step="0.01" min="0.5" max="1.0" id="flow.long_buy_cutoff" />
+      <input type="number" step="0.01" min="0.0" max="0.5" id="flow.long_sell_cutoff" /><br/>
+      <label>Flow grondslag (alle vensters):</label>
+      <select id="flow.basis">
+        <option value="volume">volume (som van volumes)</option>
+        <option value="count">count (aantal trades)</option>
+      </select><br/>
+      <label>Actieve signaaltypes:</label>
+      <label><input type="checkbox" id="enabled_signal_types.WHALE" /> WHALE</label>
+      <label><input type="checkbox" id="enabled_signal_types.WH_PRED" /> WH_PRED</label>
+      <label><input type="checkbox" id="enabled_signal_types.EARLY" /> EARLY</label>
+      <label><input type="checkbox" id="enabled_signal_types.ALPHA" /> ALPHA</label>
+      <label><input type="checkbox" id="enabled_signal_types.EARLY_PUMP" /> EARLY_PUMP</label>
+      <label><input type="checkbox" id="enabled_signal_types.MEGA_PUMP" /> MEGA_PUMP</label>
+      <label><input type="checkbox" id="enabled_signal_types.ANOM" /> ANOM</label>
+      <label><input type="checkbox" id="enabled_signal_types.DIVERGENCE" /> DIVERGENCE</label><br/>
+      <label>Min Reliability voor signalen (0-100, 0 = uit):</label>
+      <input type="number" step="5" min="0" max="100" id="min_reliability_for_signal" /><br/>
+      <label><input type="checkbox" id="signal_on_candle_close" /> EARLY/ALPHA alleen bij candle close</label><br/>
+      <label>Candle interval voor signalen (sec):</label>
+      <input type="number" step="10" min="10" id="signal_candle_sec" /><br/>
+      <label>Reliability gate per type:</label>
+      <label><input type="checkbox" id="reliability_gate_types.WHALE" /> WHALE</label>
+      <label><input type="checkbox" id="reliability_gate_types.WH_PRED" /> WH_PRED</label>
+      <label><input type="checkbox" id="reliability_gate_types.EARLY" /> EARLY</label>
+      <label><input type="checkbox" id="reliability_gate_types.ALPHA" /> ALPHA</label>
+      <label><input type="checkbox" id="reliability_gate_types.EARLY_PUMP" /> EARLY_PUMP</label>
+      <label><input type="checkbox" id="reliability_gate_types.MEGA_PUMP" /> MEGA_PUMP</label>
+      <label><input type="checkbox" id="reliability_gate_types.ANOM" /> ANOM</label>
+      <label><input type="checkbox" id="reliability_gate_types.DIVERGENCE" /> DIVERGENCE</label><br/>
+      <label>Self-eval horizon per type (sec, leeg = eval_horizon_sec):</label>
+      WHALE <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.WHALE" />
+      WH_PRED <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.WH_PRED" />
+      EARLY <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.EARLY" />
+      ALPHA <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.ALPHA" /><br/>
+      EARLY_PUMP <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.EARLY_PUMP" />
+      MEGA_PUMP <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.MEGA_PUMP" />
+      ANOM <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.ANOM" />
+      DIVERGENCE <input type="number" step="60" min="60" data-inherit placeholder="inherit" id="eval_horizon_by_type.DIVERGENCE" /><br/>
+      <label>Whale Detect EWMA Multiplier (notional &gt; EWMA * x):</label>
+      <input type="number" step="0.1" min="1.0" max="20.0" id="whale_tiers.detect_ewma_mult" /><br/>
+      <label>Whale Large Tier Notional (score 3):</label>
+      <input type="number" step="1000" min="0.0" id="whale_tiers.large_notional" /><br/>
+      <label>Whale Large Tier EWMA Multiplier:</label>
+      <input type="number" step="0.5" min="1.0" max="50.0" id="whale_tiers.large_ewma_mult" /><br/>
+      <label>Whale Medium Tier Notional (score 2):</label>
+      <input type="number" step="1000" min="0.0" id="whale_tiers.medium_notional" /><br/>
+      <label>Whale Medium Tier EWMA Multiplier:</label>
+      <input type="number" step="0.5" min="1.0" max="50.0" id="whale_tiers.medium_ewma_mult" /><br/>
 
       <h3>2. Score Gewichten</h3>
       <label>Flow Weight (0.0-5.0):</label>
@@ -2618,6 +7513,8 @@ tr:nth-child(even){ background:#252525; }
       <h3>3. Paper Trading Instellingen</h3>
       <label>Initial Balance (1000.0-100000.0):</label>
       <input type="number" step="1000" min="1000.0" max="100000.0" id="initial_balance" /><br/>
+      <label>Backtest positie fractie (0.01-1.0, deel van equity per trade):</label>
+      <input type="number" step="0.05" min="0.01" max="1.0" id="backtest_position_fraction" /><br/>
       <label>Base Notional (10.0-1000.0):</label>
       <input type="number" step="10" min="10.0" max="1000.0" id="base_notional" /><br/>
       <label>Stop Loss Percentage (0.01-0.1):</label>
@@ -2626,8 +7523,20 @@ tr:nth-child(even){ background:#252525; }
       <input type="number" step="0.01" min="0.01" max="0.1" id="tp_pct" /><br/>
       <label>Max Positions (1-10):</label>
       <input type="number" step="1" min="1" max="10" id="max_positions" /><br/>
+      <label>Cooldown na verlies (min, 0 = uit):</label>
+      <input type="number" step="5" min="0" id="loss_cooldown_min" /><br/>
+      <label>Max Position % van balance (1-100):</label>
+      <input type="number" step="1" min="1" max="100" id="max_position_pct" /><br/>
+      <label>Max Pair Exposure (account currency, 0 = uit):</label>
+      <input type="number" step="50" min="0" id="max_pair_exposure" /><br/>
+      <label>Paper fills: slippage bps / tegen bid-ask bij vers boek:</label>
+      <input type="number" step="1" min="0" max="1000" id="slippage_bps" />
+      <input type="checkbox" id="fill_at_book" /><br/>
       <label>Enable Trading:</label>
       <input type="checkbox" id="enable_trading" /><br/>
+      <label>Auto paper trader op ALPHA BUY (opt-in) / fee % per kant:</label>
+      <input type="checkbox" id="auto_trading_enabled" />
+      <input type="number" step="0.01" min="0" max="5" id="auto_fee_pct" /><br/>
 
       <h3>4. Engine & Data Instellingen</h3>
       <label>WS Workers per Chunk (10-50):</label>
@@ -2636,10 +7545,74 @@ tr:nth-child(even){ background:#252525; }
       <input type="number" step="5" min="10" max="60" id="rest_scan_interval_sec" /><br/>
       <label>Cleanup Interval (300-1200):</label>
       <input type="number" step="100" min="300" max="1200" id="cleanup_interval_sec" /><br/>
-      <label>Eval Horizon (60-600):</label>
+      <label>Trade Retentie sec (pair zonder update):</label>
+      <input type="number" step="3600" min="3600" id="trade_retention_sec" /><br/>
+      <label>Candle Retentie sec (candle zonder trade):</label>
+      <input type="number" step="3600" min="3600" id="candle_retention_sec" /><br/>
+      <label>Orderbook Retentie sec:</label>
+      <input type="number" step="10" min="10" id="orderbook_retention_sec" /><br/>
+      <label>Orderbook Fresh sec (boek telt mee in whale scores):</label>
+      <input type="number" step="1" min="1" max="60" id="orderbook_fresh_sec" /><br/>
+      <label>Tape: ruwe trades per pair (1-1000):</label>
+      <input type="number" step="10" min="1" max="1000" id="tape_size" /><br/>
+      <label>Eval Horizon default (60-600, per type hieronder te overschrijven):</label>
       <input type="number" step="60" min="60" max="600" id="eval_horizon_sec" /><br/>
       <label>Max History (200-1000):</label>
-      <input type="number" step="100" min="200" max="1000" id="max_history" /><br/>
+      <input type="number" step="100" min="200" max="50000" id="max_history" /><br/>
+      <label>Signal Archive (signals_archive.jsonl, voor backtest):</label>
+      <input type="checkbox" id="signal_archive_enabled" /><br/>
+      <label>Signal Archive Max Bytes (rotatie):</label>
+      <input type="number" step="1000000" min="100000" id="signal_archive_max_bytes" /><br/>
+      <label>EWMA Alpha (0.01-1.0, hoger = sneller):</label>
+      <input type="number" step="0.01" min="0.01" max="1.0" id="ewma_alpha" /><br/>
+      <label>Volume Score op Notional (prijs*volume i.p.v. base volume):</label>
+      <input type="checkbox" id="volume_score_notional" /><br/>
+      <label>Event Log (events.jsonl):</label>
+      <input type="checkbox" id="event_log_enabled" /><br/>
+      <label>Event Log Max Bytes (rotatie):</label>
+      <input type="number" step="1000000" min="100000" id="event_log_max_bytes" /><br/>
+      <label>Alerts bewaren (alerts.jsonl):</label>
+      <input type="checkbox" id="alerts_persist" /><br/>
+      <label>News sentiment bewaren (news_sentiment.json) / interval (sec):</label>
+      <input type="checkbox" id="news_persist" />
+      <input type="number" step="5" min="5" max="3600" id="news_persist_interval_sec" /><br/>
+      <label>News catch-all pair voor koppen zonder coin (leeg = MARKET gauge):</label>
+      <input type="text" id="news_catch_all_pair" size="10" placeholder="MARKET" /><br/>
+      <label>Quiet hours (alerts stil, signals lopen door) / start / eind (HH:MM, timezone):</label>
+      <input type="checkbox" id="quiet_hours.enabled" />
+      <input type="text" id="quiet_hours.start" size="5" />
+      <input type="text" id="quiet_hours.end" size="5" /><br/>
+      <label>Quiet hours: samenvatting na afloop / stil kanaal:</label>
+      <input type="checkbox" id="quiet_hours.summary" />
+      <label><input type="checkbox" id="quiet_hours_channels.dashboard" /> dashboard</label><br/>
+      <label>Market Snapshots (snapshots/market_YYYY-MM-DD.jsonl):</label>
+      <input type="checkbox" id="market_snapshot_enabled" /><br/>
+      <label>Market Snapshot Interval (minuten):</label>
+      <input type="number" step="1" min="1" id="market_snapshot_interval_min" /><br/>
+      <label>Market Snapshot Retention (aantal dagbestanden):</label>
+      <input type="number" step="1" min="1" id="market_snapshot_retention" /><br/>
+      <label>Derivatives Scanner (funding / OI, Kraken Futures):</label>
+      <input type="checkbox" id="derivatives_enabled" /><br/>
+      <label>Derivatives Scan Interval (10-600):</label>
+      <input type="number" step="10" min="10" max="600" id="derivatives_scan_interval_sec" /><br/>
+      <label>Stale Threshold sec (geen update &rarr; STALE):</label>
+      <input type="number" step="30" min="30" max="3600" id="stale_threshold_sec" /><br/>
+      <label>Signal Expiry sec (Early/Alpha/whale zonder nieuwe activiteit &rarr; uit Top 10, 0 = uit):</label>
+      <input type="number" step="60" min="0" id="signal_expiry_sec" /><br/>
+      <label>Reliability Max Age sec (ouder = STALE, reliability 0):</label>
+      <input type="number" step="30" min="30" max="3600" id="reliability_max_age_sec" /><br/>
+      <label>Active Window sec (Markets "Show only active"):</label>
+      <input type="number" step="60" min="60" max="3600" id="active_window_sec" /><br/>
+      <label>Stale Reconnect Fraction (0.1-1.0, WS worker):</label>
+      <input type="number" step="0.05" min="0.1" max="1.0" id="stale_reconnect_fraction" /><br/>
+      <label>Kraken WebSocket v2 (na reconnect, v1 fallback):</label>
+      <input type="checkbox" id="kraken_ws_v2" /><br/>
+      <label>Ticker via WebSocket (REST als fallback, na herstart):</label>
+      <input type="checkbox" id="ticker_ws" /><br/>
+      <label>Ticker WS min interval sec per pair:</label>
+      <input type="number" step="1" min="0" max="60" id="ticker_ws_min_interval_sec" /><br/>
+      <label>Eén WS verbinding per chunk (trade + book + ticker, na herstart):</label>
+      <input type="checkbox" id="ws_multiplex" /><br/>
 
       <h3>5. UI & Filter Instellingen</h3>
       <label>Default DIR Filter:</label>
@@ -2650,12 +7623,47 @@ tr:nth-child(even){ background:#252525; }
       </select><br/>
       <label>Include Stablecoins Default:</label>
       <input type="checkbox" id="include_stablecoins_default" /><br/>
+      <label>Pair aliassen (één per regel, bv. BTC/EUR=Bitcoin):</label><br/>
+      <textarea id="pair-aliases" rows="4" cols="40"></textarea><br/>
       <label>Heatmap Min Radius (4.0-10.0):</label>
       <input type="number" step="0.5" min="4.0" max="10.0" id="heatmap_min_radius" /><br/>
       <label>Heatmap Max Radius (10.0-20.0):</label>
       <input type="number" step="0.5" min="10.0" max="10.0" id="heatmap_max_radius" /><br/>
-      <label>Chart Refresh Rate (0.5-5.0):</label>
-      <input type="number" step="0.5" min="0.5" max="5.0" id="chart_refresh_rate_sec" /><br/>
+      <label>Dashboard Refresh Rate sec (0.5-60):</label>
+      <input type="number" step="0.5" min="0.5" max="60" id="chart_refresh_rate_sec" /><br/>
+      <label>Conviction Mode (Best 3 ranking):</label>
+      <select id="conviction_mode">
+        <option value="multiply">multiply (score * reliability)</option>
+        <option value="gate">gate (min reliability)</option>
+        <option value="off">off</option>
+      </select><br/>
+      <label>Conviction Min Reliability (gate, 0-100):</label>
+      <input type="number" step="5" min="0" max="100" id="conviction_min_reliability" /><br/>
+      <label>Snapshot Cache TTL ms (0 = uit):</label>
+      <input type="number" step="100" min="0" max="10000" id="snapshot_cache_ttl_ms" /><br/>
+      <label>Best N (conviction):</label>
+      <input type="number" step="1" min="1" max="100" id="best_n" /><br/>
+      <label>Top N (stijgers / dalers):</label>
+      <input type="number" step="1" min="1" max="100" id="top_n" /><br/>
+      <label>Log Level (console):</label>
+      <select id="log_level">
+        <option value="error">error</option>
+        <option value="warn">warn</option>
+        <option value="info">info</option>
+        <option value="debug">debug</option>
+      </select><br/>
+      <label>Display Currency (account):</label>
+      <select id="display_currency">
+        <option value="EUR">EUR</option>
+        <option value="USD">USD</option>
+        <option value="GBP">GBP</option>
+        <option value="CHF">CHF</option>
+        <option value="JPY">JPY</option>
+      </select><br/>
+      <label>Locale (bv. nl-NL, en-US; leeg = browser):</label>
+      <input type="text" id="locale" /><br/>
+      <label>Tijdzone (bv. Europe/Amsterdam, UTC; leeg = browser):</label>
+      <input type="text" id="timezone" /><br/>
 
       <h3>6. AI & Self-Learning Instellingen</h3>
       <label>Success Threshold (0.5-1.0):</label>
@@ -2669,6 +7677,9 @@ tr:nth-child(even){ background:#252525; }
 
       <button type="button" id="save-config">Save Config</button>
       <button type="button" id="reset-config">Reset to Defaults</button>
+      <button type="button" id="export-config">Export</button>
+      <button type="button" id="import-config">Import</button>
+      <input type="file" id="import-config-file" accept="application/json,.json" style="display:none;" />
     </form>
     <div id="config-status"></div>
   </div>
@@ -2677,10 +7688,11 @@ tr:nth-child(even){ background:#252525; }
     <div id="guide">
       <h2>Kolommen uitleg</h2>
       <ul>
+        <li><b>%</b>: keuze via "% column": verandering t.o.v. Kraken's dag-open, rolling 1h, of sinds het pair voor het eerst gezien is (sinds process start &ndash; dus g&eacute;&eacute;n 24h).</li>
         <li><b>Flow</b>: percentage van volume dat BUY is in de laatste 60 seconden.</li>
         <li><b>Dir</b>: dominante richting van de recente flow (BUY / SELL / NEUTR).</li>
-        <li><b>Early</b>: vroege accumulatie (BUY) op basis van total score.</li>
-        <li><b>Alpha</b>: sterkste combinatie van trend, volume, whales en anomalies (alleen bij BUY).</li>
+        <li><b>Early</b>: vroege accumulatie (BUY): total score &ge; Early Buy Threshold bij BUY flow.</li>
+        <li><b>Alpha</b>: sterkste combinatie van trend, volume, whales en anomalies: total score &ge; Alpha Buy Threshold bij BUY flow (Alpha is altijd ook Early).</li>
         <li><b>Pump</b>: gecombineerde score van korte en middellange termijn prijsimpuls + flow.</li>
         <li><b>WhPred</b>: kans op aankomende whale (LOW / MEDIUM / HIGH).</li>
         <li><b>News Sent.</b>: sentiment van recente nieuwsartikelen (0-1).</li>
@@ -2705,6 +7717,85 @@ function isStablecoin(pair) {
   return stablecoins.includes(base);
 }
 
+// Valuta: pair-bedragen in de eigen quote, account-bedragen in display_currency
+let appConfig = { display_currency: "EUR" };
+
+function currencySymbol(code) {
+  switch (code) {
+    case "EUR": return "€";
+    case "USD": case "USDT": case "USDC": return "$";
+    case "GBP": return "£";
+    case "JPY": return "¥";
+    case "BTC": return "₿";
+    default: return code + " ";
+  }
+}
+
+function quoteSymbol(pair) {
+  const parts = pair.split('/');
+  return currencySymbol(parts.length === 2 && parts[1] ? parts[1] : appConfig.display_currency);
+}
+
+function accountSymbol() {
+  return currencySymbol(appConfig.display_currency);
+}
+
+// Locale/tijdzone uit de server-config, zodat iedereen hetzelfde ziet; leeg = browser default
+function fmtOptions(extra) {
+  const opts = Object.assign({}, extra);
+  if (appConfig.timezone) opts.timeZone = appConfig.timezone;
+  return opts;
+}
+
+function fmtDateTime(ts) {
+  try {
+    return new Date(ts * 1000).toLocaleString(appConfig.locale || undefined, fmtOptions());
+  } catch (e) {
+    return new Date(ts * 1000).toLocaleString();
+  }
+}
+
+function fmtTime(ts) {
+  try {
+    return new Date(ts * 1000).toLocaleTimeString(appConfig.locale || undefined, fmtOptions());
+  } catch (e) {
+    return new Date(ts * 1000).toLocaleTimeString();
+  }
+}
+
+function fmtAmount(value, decimals) {
+  try {
+    return value.toLocaleString(appConfig.locale || undefined, {
+      minimumFractionDigits: decimals,
+      maximumFractionDigits: decimals,
+    });
+  } catch (e) {
+    return value.toFixed(decimals);
+  }
+}
+
+function formatWindow(sec) {
+  return sec >= 60 && sec % 60 === 0 ? `${sec / 60}m` : `${sec}s`;
+}
+
+async function loadAppConfig() {
+  try {
+    appConfig = await fetch("/api/config").then(r => r.json());
+    document.querySelectorAll(".account-currency").forEach(el => {
+      el.textContent = appConfig.display_currency;
+    });
+    applyRefreshRate();
+    if (appConfig.flow) {
+      document.getElementById("markets-flow-header").textContent =
+        `Flow (${formatWindow(appConfig.flow.short_window_sec)})`;
+      document.getElementById("markets-flow-long-header").textContent =
+        `Flow (${formatWindow(appConfig.flow.long_window_sec)})`;
+    }
+  } catch (e) {
+    console.error("App config load error:", e);
+  }
+}
+
 function ensureHeatTooltip() {
   if (heatTooltip) return;
   heatTooltip = document.createElement("div");
@@ -2725,7 +7816,8 @@ function applyDirFilter(tableId, filterSelectId) {
   const tbody = document.querySelector(`#${tableId} tbody`);
   const rows = tbody.querySelectorAll('tr');
   rows.forEach(row => {
-    const dirCell = row.cells[5]; // Assuming DIR is the 6th column (index 5)
+    // DIR cel is gemarkeerd met .dir-cell; anders de 6e kolom (index 5)
+    const dirCell = row.querySelector('.dir-cell') || row.cells[5];
     if (dirCell) {
       const dirText = dirCell.textContent.trim();
       if (filterValue === 'ALL' || dirText === filterValue) {
@@ -2745,8 +7837,16 @@ function switchTab(tab) {
     tab === "signals" ? "block" : "none";
   document.getElementById("view-top10").style.display =
     tab === "top10" ? "block" : "none";
+  document.getElementById("view-movers").style.display =
+    tab === "movers" ? "block" : "none";
   document.getElementById("view-manual_trades").style.display =
     tab === "manual_trades" ? "block" : "none";
+  document.getElementById("view-auto_trades").style.display =
+    tab === "auto_trades" ? "block" : "none";
+  document.getElementById("view-trade_advice").style.display =
+    tab === "trade_advice" ? "block" : "none";
+  document.getElementById("view-alerts").style.display =
+    tab === "alerts" ? "block" : "none";
   document.getElementById("view-backtest").style.display =
     tab === "backtest" ? "block" : "none";
   document.getElementById("view-heatmap").style.display =
@@ -2766,15 +7866,29 @@ function switchTab(tab) {
     loadBacktest();
   } else if (tab === "manual_trades") {
     loadManualTrades();
+  } else if (tab === "auto_trades") {
+    loadAutoTrades();
+  } else if (tab === "movers") {
+    loadMovers();
+  } else if (tab === "trade_advice") {
+    loadTradeAdvice();
+  } else if (tab === "alerts") {
+    loadAlerts();
   } else if (tab === "stars") {
     loadStars();
   } else if (tab === "news") {
     loadNews();
   } else if (tab === "config") {
     loadConfig();
+  } else if (tab === "signals") {
+    markSignalsSeen();
   }
 }
 
+document.getElementById("pair-detail-more").addEventListener("click", loadPairSignals);
+document.getElementById("movers-dir").addEventListener("change", loadMovers);
+document.getElementById("movers-limit").addEventListener("change", loadMovers);
+
 document.querySelectorAll(".tab-btn").forEach(btn => {
   btn.addEventListener("click", () => switchTab(btn.dataset.tab));
 });
@@ -2786,25 +7900,234 @@ function buildVisualUrl(pair) {
          base.toLowerCase() + "-" + quote.toLowerCase();
 }
 
+let detailPair = null;
+let detailSignalsBefore = null;
+const DETAIL_SIGNALS_PAGE = 50;
+
+async function showPairDetail(pair) {
+  detailPair = pair;
+  detailSignalsBefore = null;
+  document.querySelector("#pair-detail-signals tbody").innerHTML = "";
+  await loadPairDetail();
+  await loadPairSignals();
+}
+
+// Cursor-paginatie op seq: elke pagina haalt signals ouder dan de laatst getoonde op
+async function loadPairSignals() {
+  if (!detailPair) return;
+  let url = `/api/signals?pair=${encodeURIComponent(detailPair)}&limit=${DETAIL_SIGNALS_PAGE}`;
+  if (detailSignalsBefore !== null) url += `&before=${detailSignalsBefore}`;
+  const data = await fetch(url).then(r => r.json());
+  const tbody = document.querySelector("#pair-detail-signals tbody");
+  data.forEach(s => {
+    tbody.innerHTML += `<tr>
+      <td>${fmtDateTime(s.ts)}</td>
+      <td>${s.signal_type}</td>
+      <td>${s.direction}</td>
+      <td>${s.strength.toFixed(2)}</td>
+      <td>${s.price.toPrecision(6)}</td>
+      <td>${s.total_score.toFixed(2)}</td>
+    </tr>`;
+  });
+  if (data.length > 0) detailSignalsBefore = data[data.length - 1].seq;
+  document.getElementById("pair-detail-more").style.display =
+    data.length < DETAIL_SIGNALS_PAGE ? "none" : "inline";
+}
+
+async function loadPairDetail() {
+  const box = document.getElementById("pair-detail");
+  if (!detailPair) {
+    box.style.display = "none";
+    return;
+  }
+  const res = await fetch("/api/pair/" + detailPair);
+  if (!res.ok) {
+    box.style.display = "none";
+    detailPair = null;
+    return;
+  }
+  const d = await res.json();
+  const rel = d.reliability;
+  box.style.display = "block";
+  document.getElementById("pair-detail-info").innerHTML = `
+    <h3>${d.row.pair} <a href="#" onclick="detailPair=null; loadPairDetail(); return false;" style="font-size:12px;">(sluiten)</a></h3>
+    <p><strong>Reliability:</strong> ${rel.label} (${rel.score.toFixed(0)}/100)${rel.suspicious ? ' <span class="wash-badge">WASH</span> gedempt wegens wash-trading patroon' : ''}</p>
+    <table>
+      <thead><tr><th>Component</th><th>Score</th><th>Max</th><th>Basis</th></tr></thead>
+      <tbody>
+        <tr><td>Trade density</td><td>${rel.trade_density.toFixed(1)}</td><td>40</td><td>${rel.trades_60s} trades / 60s</td></tr>
+        <tr><td>Volume stability</td><td>${rel.volume_stability.toFixed(1)}</td><td>20</td><td>vol ratio ${rel.vol_ratio.toFixed(2)}</td></tr>
+        <tr><td>Flow consistency</td><td>${rel.flow_consistency.toFixed(1)}</td><td>20</td><td>flow ${rel.flow_pct_60s.toFixed(1)}% (60s)</td></tr>
+        <tr><td>Recency</td><td>${rel.recency.toFixed(1)}</td><td>15</td><td>${rel.secs_since_update}s sinds laatste trade</td></tr>
+        <tr><td>Time density</td><td>${rel.time_density.toFixed(1)}</td><td>15</td><td>${rel.trades_60s} trades / 60s</td></tr>
+      </tbody>
+    </table>
+    <table>
+      <thead><tr><th>Flow venster</th><th>Buy % (volume)</th><th>Buy % (count)</th><th>Trades buy / sell</th></tr></thead>
+      <tbody>
+        ${(d.flow_comparison || []).map(f => `<tr>
+          <td>${f.window} (${formatWindow(f.window_sec)})</td>
+          <td${d.flow_basis === "volume" ? ' style="font-weight:bold;"' : ''}>${f.volume_buy_pct == null ? "-" : f.volume_buy_pct.toFixed(1) + "%"}</td>
+          <td${d.flow_basis === "count" ? ' style="font-weight:bold;"' : ''}>${f.count_buy_pct == null ? "-" : f.count_buy_pct.toFixed(1) + "%"}</td>
+          <td>${f.buy_trades} / ${f.sell_trades}</td>
+        </tr>`).join("")}
+      </tbody>
+    </table>
+    ${d.biggest_print ? `<p><strong>Biggest print:</strong>
+      ${d.biggest_print.side.toUpperCase()} ${quoteSymbol(d.row.pair)}${d.biggest_print.notional.toFixed(2)}
+      (${fmtDateTime(d.biggest_print.ts)})</p>` : ""}
+    ${d.derivatives ? `<p><strong>Perpetual ${d.derivatives.symbol}:</strong>
+      funding ${d.derivatives.funding_rate.toPrecision(3)},
+      open interest ${d.derivatives.open_interest.toFixed(0)},
+      mark ${d.derivatives.mark_price.toFixed(d.row.precision)}
+      (${fmtTime(d.derivatives.ts)})</p>` : ""}
+  `;
+  loadPairOrderbook(d.row.precision);
+  loadPairTape(d.row.precision);
+}
+
+// Ruwe trades, nieuwste bovenaan; groen = buy, rood = sell
+async function loadPairTape(precision) {
+  const el = document.getElementById("pair-detail-tape");
+  const res = await fetch("/api/tape/" + detailPair);
+  if (!res.ok) {
+    el.innerHTML = "";
+    return;
+  }
+  const tape = await res.json();
+  if (tape.length === 0) {
+    el.innerHTML = `<p>Nog geen trades.</p>`;
+    return;
+  }
+  el.innerHTML = tape.map(t => `<div class="${t.side === "b" ? "pos" : "neg"}">
+      ${fmtTime(t.ts)} ${t.side === "b" ? "BUY " : "SELL"} ${t.price.toFixed(precision)} x ${t.volume.toPrecision(5)}
+      (${quoteSymbol(detailPair)}${(t.price * t.volume).toFixed(2)})
+    </div>`).join("");
+}
+
+// Depth ladder: bids links, asks rechts, balk = volume t.o.v. grootste level
+async function loadPairOrderbook(precision) {
+  const el = document.getElementById("pair-detail-book");
+  const res = await fetch("/api/orderbook/" + detailPair);
+  if (!res.ok) {
+    el.innerHTML = `<p style="font-size:12px;">Geen recent orderboek.</p>`;
+    return;
+  }
+  const b = await res.json();
+  const maxVol = Math.max(...b.bids.map(l => l[1]), ...b.asks.map(l => l[1]), 1e-12);
+  const bar = (vol, color) =>
+    `<div style="background:${color}; height:8px; width:${(vol / maxVol * 100).toFixed(0)}%;"></div>`;
+  let rows = "";
+  for (let i = 0; i < Math.max(b.bids.length, b.asks.length); i++) {
+    const bid = b.bids[i];
+    const ask = b.asks[i];
+    rows += `<tr>
+      <td style="width:120px;">${bid ? bar(bid[1], "#4caf50") : ""}</td>
+      <td>${bid ? bid[1].toFixed(4) : ""}</td>
+      <td class="pos">${bid ? bid[0].toFixed(precision) : ""}</td>
+      <td class="neg">${ask ? ask[0].toFixed(precision) : ""}</td>
+      <td>${ask ? ask[1].toFixed(4) : ""}</td>
+      <td style="width:120px;">${ask ? bar(ask[1], "#f44336") : ""}</td>
+    </tr>`;
+  }
+  el.innerHTML = `
+    <p style="font-size:12px;">
+      Spread ${b.spread.toFixed(precision)} (${b.spread_pct.toFixed(3)}%) |
+      Imbalance ${(b.imbalance * 100).toFixed(1)}% bids |
+      ${b.age_sec}s oud ${b.score_active ? "(telt mee in scores)" : "(te oud voor scores)"}
+    </p>
+    <table>
+      <thead><tr><th></th><th>Bid vol</th><th>Bid</th><th>Ask</th><th>Ask vol</th><th></th></tr></thead>
+      <tbody>${rows}</tbody>
+    </table>
+  `;
+}
+
+// Welke % de Markets "%" kolom toont; de CandleState-% loopt vanaf first-seen en is dus geen 24h
+const PCT_MODES = {
+  open: { label: "% (day-open)", title: "Verandering t.o.v. Kraken's dag-open (ticker open)", value: r => r.pct_24h, sort: "pct_24h" },
+  rolling: { label: "% (1h)", title: "Rolling verandering over het laatste uur", value: r => r.pct_1h, sort: "pct_1h" },
+  first_seen: { label: "% (since start)", title: "Verandering sinds het pair voor het eerst gezien is (sinds process start)", value: r => r.pct, sort: "pct" },
+};
+
+// Server-side sortering per tabel ("markets", "top10"); geen entry = standaard ranking
+const tableSort = JSON.parse(localStorage.getItem("tableSort") || "{}");
+
+function sortParams(table) {
+  const s = tableSort[table];
+  return s ? `sort=${encodeURIComponent(s.field)}&order=${s.order}` : "";
+}
+
+function renderSortHeaders(table, selector) {
+  const s = tableSort[table];
+  document.querySelectorAll(`${selector} th.sortable`).forEach(th => {
+    th.classList.toggle("sort-asc", !!s && th.dataset.sort === s.field && s.order === "asc");
+    th.classList.toggle("sort-desc", !!s && th.dataset.sort === s.field && s.order === "desc");
+  });
+}
+
+// Klik: aflopend -> oplopend -> terug naar standaard ranking
+function bindSortHeaders(table, selector, reload) {
+  document.querySelectorAll(`${selector} th.sortable`).forEach(th => {
+    th.addEventListener("click", () => {
+      const s = tableSort[table];
+      if (!s || s.field !== th.dataset.sort) {
+        tableSort[table] = { field: th.dataset.sort, order: "desc" };
+      } else if (s.order === "desc") {
+        s.order = "asc";
+      } else {
+        delete tableSort[table];
+      }
+      localStorage.setItem("tableSort", JSON.stringify(tableSort));
+      renderSortHeaders(table, selector);
+      reload();
+    });
+  });
+  renderSortHeaders(table, selector);
+}
+
+function pctMode() {
+  return PCT_MODES[document.getElementById("markets-pct-mode").value] || PCT_MODES.open;
+}
+
+function pctCell(v) {
+  if (v === null || v === undefined) return `<td>-</td>`;
+  return `<td class="${v > 0 ? 'pos' : (v < 0 ? 'neg' : '')}">${v.toFixed(2)}%</td>`;
+}
+
 async function loadMarkets() {
   let q = document.getElementById("search").value.toLowerCase();
   let includeStable = document.getElementById("markets-stable-filter").checked;
-  let res = await fetch("/api/stats");
+  let activeOnly = document.getElementById("markets-active-filter").checked;
+  const mode = pctMode();
+  const pctHeader = document.getElementById("markets-pct-header");
+  // % kolom sorteert op het veld dat de gekozen %-modus toont
+  if (pctHeader.dataset.sort !== mode.sort) {
+    const s = tableSort.markets;
+    if (s && s.field === pctHeader.dataset.sort) s.field = mode.sort;
+    pctHeader.dataset.sort = mode.sort;
+    renderSortHeaders("markets", "#grid");
+  }
+  const params = [activeOnly ? "active_only=true" : "", sortParams("markets")].filter(p => p).join("&");
+  let res = await fetch(params ? `/api/stats?${params}` : "/api/stats");
   let data = await res.json();
   let tbody = document.querySelector("#grid tbody");
   tbody.innerHTML = "";
+  document.querySelectorAll(".whale-window").forEach(el =>
+    el.textContent = formatWindow(appConfig.whale_flow_window_sec ?? 60));
+  pctHeader.textContent = mode.label;
+  pctHeader.title = mode.title;
 
   let filtered = data.filter(r =>
-    r.pair.toLowerCase().includes(q) &&
+    (r.pair.toLowerCase().includes(q) || (r.display_name || "").toLowerCase().includes(q)) &&
     (includeStable || !isStablecoin(r.pair))
   );
 
   for (let r of filtered) {
-    let pctClass = r.pct > 0 ? "pos" : (r.pct < 0 ? "neg" : "");
     let whaleClass = r.whale ? "whale" : "";
     let whaleText = r.whale
       ? (r.whale_side.toUpperCase() + " " + r.whale_volume.toFixed(3) +
-         " (" + (r.whale_notional/1000).toFixed(1) + "k)")
+         " (" + quoteSymbol(r.pair) + r.whale_notional_fmt + ")")
       : "No";
 
     let earlyClass = (r.early === "BUY" || r.early === "SELL") ? "early" : "";
@@ -2829,33 +8152,44 @@ async function loadMarkets() {
     let visual = visualUrl ? `<a href="${visualUrl}" target="_blank">Visual</a>` : "-";
 
     let row = `<tr>
-      <td>${r.pair}</td>
-      <td>${r.price.toFixed(4)}</td>
-      <td class="${pctClass}">${r.pct.toFixed(2)}%</td>
+      <td><a href="#" onclick="showPairDetail('${r.pair}'); return false;">${pairName(r)}</a>${r.stale ? ' <span class="stale-badge">STALE</span>' : ''}${r.orderbook_available ? '' : ' <span class="nobook-badge" title="Geen vers orderboek: whale score/pred zonder orderbook boost">NO OB</span>'}${r.suspicious ? ' <span class="wash-badge" title="Wash-trading patroon: herhaalde gelijke buy/sell prints, volume/flow mogelijk opgeblazen">WASH</span>' : ''}</td>
+      <td>${r.price.toFixed(r.precision)}</td>
+      ${pctCell(mode.value(r))}
+      ${pctCell(r.pct_5m)}
+      ${pctCell(r.pct_1h)}
+      ${pctCell(r.pct_24h)}
       <td class="${whaleClass}">${whaleText}</td>
+      <td class="${r.whale_net_flow > 0 ? 'pos' : (r.whale_net_flow < 0 ? 'neg' : '')}"
+          title="buy ${r.whale_buy_flow.toFixed(0)} / sell ${r.whale_sell_flow.toFixed(0)}">${r.whale_net_flow !== 0 ? quoteSymbol(r.pair) + r.whale_net_flow.toFixed(0) : '-'}</td>
       <td>
         <div class="flow-bar">
           <div class="flow-fill" style="width:${r.flow_pct.toFixed(0)}%;background:${flowColor};"></div>
         </div>
         ${r.flow_pct.toFixed(1)}%
       </td>
-      <td>${r.dir}</td>
+      <td class="dir-cell">${r.dir}</td>
       <td class="${earlyClass}">${r.early}</td>
       <td class="${alphaClass}">${r.alpha}</td>
       <td style="color:${ r.pump_label === "MEGA_PUMP" ? "#ff4081" :
         r.pump_label === "EARLY_PUMP" ? "#00bcd4" :
-        "#ccc"}">${r.pump_score.toFixed(1)}</td>
+        "#ccc"}">${r.pump_score.toFixed(1)} (${r.pump_conf.toFixed(2)})</td>
       <td class="${predClass}">${r.whale_pred_label} (${r.whale_pred_score.toFixed(1)})</td>
       <td class="${relClass}">${r.reliability_label} (${r.reliability_score.toFixed(0)})</td>
+      <td class="${r.divergence === "BULLISH" ? "pos" : (r.divergence === "BEARISH" ? "neg" : "")}"
+          title="Prijs/flow divergentie: BULLISH = buy-flow stijgt bij stilstaande prijs, BEARISH = prijs stijgt bij dalende buy-flow">${r.divergence || "-"}</td>
       <td>${r.news_sentiment ? r.news_sentiment.toFixed(2) : "0.50"}</td>
+      <td>${r.funding_rate != null ? r.funding_rate.toPrecision(3) : "-"}</td>
+      <td>${r.open_interest != null ? r.open_interest.toFixed(0) : "-"}</td>
+      <td>${r.dir_long} ${r.flow_pct_long.toFixed(1)}%</td>
+      <td>${r.max_notional > 0 ? r.max_notional_side.toUpperCase() + " " + quoteSymbol(r.pair) + r.max_notional_fmt : "-"}</td>
       <td>${r.score.toFixed(2)}</td>
       <td>${r.trades}</td>
       <td>${r.buys.toFixed(4)}</td>
       <td>${r.sells.toFixed(4)}</td>
-      <td>${r.o.toFixed(4)}</td>
-      <td>${r.h.toFixed(4)}</td>
-      <td>${r.l.toFixed(4)}</td>
-      <td>${r.c.toFixed(4)}</td>
+      <td>${r.o.toFixed(r.precision)}</td>
+      <td>${r.h.toFixed(r.precision)}</td>
+      <td>${r.l.toFixed(r.precision)}</td>
+      <td>${r.c.toFixed(r.precision)}</td>
       <td>${visual}</td>
     </tr>`;
 
@@ -2866,6 +8200,7 @@ async function loadMarkets() {
 
 async function loadSignals() {
   let includeStable = document.getElementById("signals-stable-filter").checked;
+  let rawStrength = document.getElementById("signals-raw-strength").checked;
   let res = await fetch("/api/signals");
   let data = await res.json();
   let tbody = document.querySelector("#signals tbody");
@@ -2879,7 +8214,7 @@ async function loadSignals() {
 
     let whaleTxt = r.whale
       ? (r.whale_side.toUpperCase() + " " + r.volume.toFixed(3) +
-         " (" + (r.notional/1000).toFixed(1) + "k)")
+         " (" + quoteSymbol(r.pair) + r.notional_fmt + ")")
       : "No";
 
     let pumpText = (r.signal_type === "MEGA_PUMP" || r.signal_type === "EARLY_PUMP")
@@ -2888,23 +8223,38 @@ async function loadSignals() {
     let pumpColor = r.signal_type === "MEGA_PUMP" ? "#ff4081" :
       (r.signal_type === "EARLY_PUMP" ? "#00bcd4" : "#ccc");
 
+    // Uitkomst na de self-evaluator: groen als de prijs de richting van het signaal volgde
+    let retText = "-";
+    let outcome = "PENDING";
+    let outcomeClass = "";
+    if (r.evaluated && r.ret_5m !== null && r.ret_5m !== undefined) {
+      const hit = r.direction === "SELL" ? r.ret_5m < 0 : r.ret_5m > 0;
+      retText = `${r.ret_5m.toFixed(2)}%`;
+      outcome = hit ? "HIT" : "MISS";
+      outcomeClass = hit ? "pos" : "neg";
+    } else if (r.evaluated) {
+      outcome = "n/a";
+    }
+
     let visualUrl = buildVisualUrl(r.pair);
     let visual = visualUrl ? `<a href="${visualUrl}" target="_blank">Visual</a>` : "-";
 
     let row = `<tr>
       <td>${r.ts}</td>
       <td>${r.pair}</td>
-      <td class="${typeClass}">${r.signal_type}</td>
-      <td class="${dirClass}">${r.direction}</td>
-      <td>${r.strength.toFixed(3)}</td>
+      <td class="${typeClass}">${r.signal_type}${r.test ? ' <span class="test-badge" title="Handmatig geïnjecteerd test signal, telt niet mee in backtest">TEST</span>' : ''}</td>
+      <td class="dir-cell ${dirClass}">${r.direction}</td>
+      <td title="raw ${r.strength.toFixed(3)} | ${r.strength_norm.toFixed(0)}e percentiel binnen ${r.signal_type}">${rawStrength ? r.strength.toFixed(3) : r.strength_norm.toFixed(0)}</td>
       <td>${r.flow_pct.toFixed(1)}%</td>
       <td>${r.pct.toFixed(2)}%</td>
       <td>${r.total_score.toFixed(2)}</td>
       <td>${whaleTxt}</td>
       <td>${r.volume.toFixed(4)}</td>
-      <td>${(r.notional/1000).toFixed(1)}k</td>
+      <td title="${r.notional.toFixed(2)}">${quoteSymbol(r.pair)}${r.notional_fmt}</td>
       <td>${r.price.toFixed(4)}</td>
       <td style="color:${pumpColor}">${pumpText}</td>
+      <td class="${outcomeClass}">${retText}</td>
+      <td class="${outcomeClass}">${outcome}</td>
       <td>${visual}</td>
     </tr>`;
 
@@ -2915,8 +8265,11 @@ async function loadSignals() {
 
 async function loadTop10() {
   let includeStable = document.getElementById("top10-stable-filter").checked;
-  let res = await fetch("/api/top10");
+  const params = sortParams("top10");
+  let res = await fetch(params ? `/api/top10?${params}` : "/api/top10");
   let data = await res.json();
+  document.querySelectorAll(".best-n").forEach(el => el.textContent = appConfig.best_n ?? 3);
+  document.querySelectorAll(".top-n").forEach(el => el.textContent = appConfig.top_n ?? 10);
 
   let top3Body = document.querySelector("#top3 tbody");
   let upBody = document.querySelector("#top10-up tbody");
@@ -2925,17 +8278,12 @@ async function loadTop10() {
   upBody.innerHTML = "";
   downBody.innerHTML = "";
 
-  function fmtTime(ts) {
-    const d = new Date(ts * 1000);
-    return d.toLocaleTimeString();
-  }
-
   function renderRow(r) {
     let pctClass = r.pct > 0 ? "pos" : (r.pct < 0 ? "neg" : "");
     let flowColor = r.dir === "BUY" ? "#4caf50" : "#f44336";
     let whaleText = r.whale
       ? (r.whale_side.toUpperCase() + " " + r.whale_volume.toFixed(3) +
-         " (" + (r.whale_notional/1000).toFixed(1) + "k)")
+         " (" + quoteSymbol(r.pair) + r.whale_notional_fmt + ")")
       : "No";
     let visualUrl = buildVisualUrl(r.pair);
     let visual = visualUrl ? `<a href="${visualUrl}" target="_blank">Visual</a>` : "-";
@@ -2953,7 +8301,7 @@ async function loadTop10() {
 
     return `<tr>
       <td>${fmtTime(r.ts)}</td>
-      <td>${r.pair}</td>
+      <td>${pairName(r)}</td>
       <td>${r.price.toFixed(4)}</td>
       <td class="${pctClass}">${r.pct.toFixed(2)}%</td>
       <td>
@@ -2967,6 +8315,7 @@ async function loadTop10() {
       <td>${r.alpha}</td>
       <td>${whaleText}</td>
       <td>${r.total_score.toFixed(2)}</td>
+      <td>${r.conviction.toFixed(2)}</td>
       <td style="color:${ r.pump_label === "MEGA_PUMP" ? "#ff4081" :
         r.pump_label === "EARLY_PUMP" ? "#00bcd4" :
         "#ccc"}">${r.pump_score.toFixed(1)}</td>
@@ -2994,19 +8343,49 @@ async function loadTop10() {
   applyDirFilter('top10-down', 'top10-dir-filter');
 }
 
+// Alias uit pair_aliases tonen, echte pair als tooltip (links/API blijven op r.pair)
+function pairName(r) {
+  const name = r.display_name || r.pair;
+  return name === r.pair ? escapeHtml(r.pair) : `<span title="${escapeHtml(r.pair)}">${escapeHtml(name)}</span>`;
+}
+
+function parsePairAliases(text) {
+  const aliases = {};
+  text.split("\n").forEach(line => {
+    const eq = line.indexOf("=");
+    if (eq <= 0) return;
+    const pair = line.slice(0, eq).trim().toUpperCase();
+    const alias = line.slice(eq + 1).trim();
+    if (pair && alias) aliases[pair] = alias;
+  });
+  return aliases;
+}
+
+function escapeHtml(s) {
+  return String(s ?? "").replace(/[&<>"']/g, c => ({"&": "&amp;", "<": "&lt;", ">": "&gt;", '"': "&quot;", "'": "&#39;"}[c]));
+}
+
+function renderTags(tags) {
+  return (tags || []).map(t => `<span class="tag">${escapeHtml(t)}</span>`).join(" ");
+}
+
 async function loadManualTrades() {
-  // Get manual trades data
-  let tradesData = await fetch("/api/manual_trades").then(r => r.json());
+  // Get manual trades data (closed trades + stats optioneel gefilterd op tag)
+  const tagFilter = document.getElementById("manual-tag-filter").value.trim();
+  const tagQuery = tagFilter ? `?tag=${encodeURIComponent(tagFilter)}` : "";
+  let tradesData = await fetch(`/api/manual_trades${tagQuery}`).then(r => r.json());
   
   // Update summary
   let totalPnl = tradesData.balance - tradesData.initial_balance;
-  document.getElementById("manual-balance").textContent = `€${tradesData.balance.toFixed(2)}`;
-  document.getElementById("manual-initial").textContent = `€${tradesData.initial_balance.toFixed(2)}`;
-  document.getElementById("manual-pnl").textContent = `€${totalPnl.toFixed(2)}`;
+  let sym = accountSymbol();
+  document.getElementById("manual-balance").textContent = `${sym}${fmtAmount(tradesData.balance, 2)}`;
+  document.getElementById("manual-initial").textContent = `${sym}${fmtAmount(tradesData.initial_balance, 2)}`;
+  document.getElementById("manual-pnl").textContent = `${sym}${fmtAmount(totalPnl, 2)}`;
   document.getElementById("manual-pnl").className = totalPnl > 0 ? 'pos' : (totalPnl < 0 ? 'neg' : '');
 
   // Update global pairs list
-  manualTradePairs = await fetch("/api/stats").then(r => r.json()).then(d => d.map(r => r.pair));
+  // Alle gevolgde pairs, ook stille zonder signal (/api/stats toont alleen gesignaleerde)
+  manualTradePairs = await fetch("/api/pairs").then(r => r.json()).then(d => d.map(p => p.pair));
   
   // Initialize search filter once
   if (!manualTradeSearchInitialized) {
@@ -3026,6 +8405,12 @@ async function loadManualTrades() {
   // Display active trades
   let tbody = document.querySelector("#manual-trades-table tbody");
   tbody.innerHTML = "";
+  const partialPct = manualPartialPct();
+  const cooldownEl = document.getElementById("manual-cooldowns");
+  cooldownEl.style.display = tradesData.cooldowns.length > 0 ? "block" : "none";
+  cooldownEl.textContent = "Cooldown na verlies: " + tradesData.cooldowns
+    .map(([pair, sec]) => `${pair} nog ${Math.floor(sec / 60)}m${String(sec % 60).padStart(2, "0")}s`)
+    .join(", ");
   tradesData.trades.forEach(trade => {
     tbody.innerHTML += `
       <tr>
@@ -3033,12 +8418,47 @@ async function loadManualTrades() {
         <td>${trade.entry_price.toFixed(5)}</td>
         <td>${trade.size.toFixed(5)}</td>
         <td>${trade.current_price.toFixed(5)}</td>
-        <td class="${trade.pnl_abs > 0 ? 'pos' : 'neg'}">€${trade.pnl_abs.toFixed(2)}</td>
+        <td class="${trade.pnl_abs > 0 ? 'pos' : 'neg'}">${sym}${fmtAmount(trade.pnl_abs, 2)}</td>
         <td class="${trade.pnl_pct > 0 ? 'pos' : 'neg'}">${trade.pnl_pct.toFixed(2)}%</td>
-        <td>${new Date(trade.open_ts * 1000).toLocaleString()}</td>
+        <td>${fmtDateTime(trade.open_ts)}</td>
         <td>${trade.fee_pct.toFixed(2)}%</td>
-        <td>€${trade.manual_amount.toFixed(2)}</td>
-        <td><button onclick="closeManualTrade('${trade.pair}')" style="padding:3px 8px;">Close</button></td>
+        <td>${sym}${fmtAmount(trade.manual_amount, 2)}</td>
+        <td>${escapeHtml(trade.note)}</td>
+        <td>${renderTags(trade.tags)}</td>
+        <td>
+          <button onclick="closeManualTrade('${trade.pair}')" style="padding:3px 8px;">Close</button>
+          <button onclick="partialCloseManualTrade('${trade.pair}')" style="padding:3px 8px;">Close ${partialPct}%</button>
+          <button onclick="limitCloseManualTrade('${trade.pair}', ${trade.limit_close || 0})" style="padding:3px 8px;">${trade.limit_close ? `Limit @ ${trade.limit_close.toFixed(5)}` : "Limit"}</button>
+        </td>
+      </tr>
+    `;
+  });
+
+  let stats = await fetch(`/api/manual_stats${tagQuery}`).then(r => r.json());
+  document.getElementById("manual-stat-trades").textContent = stats.total_trades;
+  document.getElementById("manual-stat-winrate").textContent = `${stats.winrate.toFixed(1)}%`;
+  document.getElementById("manual-stat-avgwin").textContent = `${sym}${fmtAmount(stats.avg_win, 2)}`;
+  document.getElementById("manual-stat-avgloss").textContent = `${sym}${fmtAmount(stats.avg_loss, 2)}`;
+  document.getElementById("manual-stat-expectancy").textContent = `${sym}${fmtAmount(stats.expectancy, 2)}`;
+  document.getElementById("manual-stat-pf").textContent = stats.profit_factor.toFixed(2);
+  document.getElementById("manual-stat-dd").textContent = `${sym}${fmtAmount(stats.max_drawdown, 2)}`;
+  loadPortfolio(sym);
+
+  let closedBody = document.querySelector("#manual-closed-table tbody");
+  closedBody.innerHTML = "";
+  tradesData.closed_trades.forEach(t => {
+    closedBody.innerHTML += `
+      <tr>
+        <td>${t.pair}</td>
+        <td>${t.entry_price.toFixed(5)}</td>
+        <td>${t.exit_price.toFixed(5)}</td>
+        <td>${t.size.toFixed(5)}</td>
+        <td class="${t.pnl > 0 ? 'pos' : 'neg'}">${sym}${fmtAmount(t.pnl, 2)}</td>
+        <td>${fmtDateTime(t.open_ts)}</td>
+        <td>${fmtDateTime(t.close_ts)}</td>
+        <td title="spread + slippage ${(t.slippage_bps || 0).toFixed(1)} bps">${t.reason}${t.partial_fraction ? ` (${(t.partial_fraction * 100).toFixed(0)}%)` : ''}${t.limit_price ? ` @ ${t.limit_price.toFixed(5)}` : ''}</td>
+        <td>${escapeHtml(t.note)}</td>
+        <td>${renderTags(t.tags)}</td>
       </tr>
     `;
   });
@@ -3048,6 +8468,51 @@ async function loadManualTrades() {
   drawManualEquity(equity);
 }
 
+async function loadAutoTrades() {
+  let data = await fetch("/api/auto_trades").then(r => r.json());
+  let sym = accountSymbol();
+  let totalPnl = data.balance - data.initial_balance;
+  document.getElementById("auto-status").textContent = data.enabled ? "ENABLED" : "DISABLED (enable_trading + auto_trading_enabled)";
+  document.getElementById("auto-balance").textContent = `${sym}${fmtAmount(data.balance, 2)}`;
+  document.getElementById("auto-pnl").textContent = `${sym}${fmtAmount(totalPnl, 2)}`;
+  document.getElementById("auto-pnl").className = totalPnl > 0 ? 'pos' : (totalPnl < 0 ? 'neg' : '');
+
+  let posBody = document.querySelector("#auto-positions-table tbody");
+  posBody.innerHTML = "";
+  data.positions.forEach(p => {
+    posBody.innerHTML += `
+      <tr>
+        <td>${p.pair}</td>
+        <td>${p.entry_price.toFixed(5)}</td>
+        <td>${p.size.toFixed(5)}</td>
+        <td>${p.current_price.toFixed(5)}</td>
+        <td class="${p.pnl_abs > 0 ? 'pos' : 'neg'}">${sym}${fmtAmount(p.pnl_abs, 2)}</td>
+        <td class="${p.pnl_pct > 0 ? 'pos' : 'neg'}">${p.pnl_pct.toFixed(2)}%</td>
+        <td>${p.stop_loss.toFixed(5)}</td>
+        <td>${p.take_profit.toFixed(5)}</td>
+        <td>${fmtDateTime(p.open_ts)}</td>
+      </tr>
+    `;
+  });
+
+  let closedBody = document.querySelector("#auto-closed-table tbody");
+  closedBody.innerHTML = "";
+  data.closed_trades.forEach(t => {
+    closedBody.innerHTML += `
+      <tr>
+        <td>${t.pair}</td>
+        <td>${t.entry_price.toFixed(5)}</td>
+        <td>${t.exit_price.toFixed(5)}</td>
+        <td>${t.size.toFixed(5)}</td>
+        <td class="${t.pnl > 0 ? 'pos' : 'neg'}">${sym}${fmtAmount(t.pnl, 2)}</td>
+        <td>${fmtDateTime(t.open_ts)}</td>
+        <td>${fmtDateTime(t.close_ts)}</td>
+        <td title="spread + slippage ${(t.slippage_bps || 0).toFixed(1)} bps">${t.reason}</td>
+      </tr>
+    `;
+  });
+}
+
 function filterManualTradePairs() {
   let searchInput = document.getElementById("manual-pair-search");
   let select = document.getElementById("manual-pair");
@@ -3073,7 +8538,10 @@ window.addEventListener("load", () => {
     let sl_pct = parseFloat(document.getElementById("manual-sl").value);
     let tp_pct = parseFloat(document.getElementById("manual-tp").value);
     let fee_pct = parseFloat(document.getElementById("manual-fee").value);
-    let manual_amount = parseFloat(document.getElementById("manual-amount").value);
+    let sizing_mode = document.getElementById("manual-sizing-mode").value;
+    let sizing_value = parseFloat(document.getElementById("manual-amount").value);
+    let note = document.getElementById("manual-note").value;
+    let tags = document.getElementById("manual-tags").value.split(",").map(t => t.trim()).filter(t => t);
     
     if (!pair) {
       alert("Please select a pair!");
@@ -3083,14 +8551,44 @@ window.addEventListener("load", () => {
     let res = await fetch("/api/manual_trade", {
       method: "POST",
       headers: {"Content-Type": "application/json"},
-      body: JSON.stringify({pair, sl_pct, tp_pct, fee_pct, manual_amount})
+      body: JSON.stringify({pair, sl_pct, tp_pct, fee_pct, sizing_mode, sizing_value, note, tags})
     });
     let result = await res.json();
     if (result.success) {
       alert(`Trade opened for ${pair}!`);
+      document.getElementById("manual-note").value = "";
+      document.getElementById("manual-tags").value = "";
       loadManualTrades();
     } else {
-      alert(`Failed to open trade for ${pair}. Trade may already exist or price not available.`);
+      alert(`Failed to open trade for ${pair}: ${result.error || "unknown error"}`);
+    }
+  });
+
+  document.getElementById("manual-tag-filter").addEventListener("input", loadManualTrades);
+
+  document.getElementById("manual-reset-btn").addEventListener("click", async () => {
+    if (!confirm("Reset the paper account? All open trades are closed at the current price and the balance is restored to the initial balance.")) {
+      return;
+    }
+    await fetch("/api/manual_trades/reset", { method: "POST" });
+    loadManualTrades();
+  });
+
+  document.getElementById("manual-sizing-mode").addEventListener("change", (ev) => {
+    let label = document.getElementById("manual-amount-label");
+    let input = document.getElementById("manual-amount");
+    if (ev.target.value === "pct_balance") {
+      label.textContent = "% of balance:";
+      input.value = 5;
+      input.step = 1;
+    } else if (ev.target.value === "fixed_risk") {
+      label.textContent = "Risk % of balance:";
+      input.value = 1;
+      input.step = 0.25;
+    } else {
+      label.innerHTML = `Amount (<span class="account-currency">${appConfig.display_currency}</span>):`;
+      input.value = 100;
+      input.step = 10;
     }
   });
 });
@@ -3110,8 +8608,74 @@ async function closeManualTrade(pair) {
     alert(`Trade closed for ${pair}!`);
     loadManualTrades();
   } else {
-    alert(`Failed to close trade for ${pair}.`);
+    alert(`Failed to close trade for ${pair}: ${result.error || "unknown error"}`);
+  }
+}
+
+// Exposure van de open posities en hoeveel onafhankelijke bets dat ongeveer zijn
+async function loadPortfolio(sym) {
+  const el = document.getElementById("manual-portfolio");
+  try {
+    const p = await fetch("/api/portfolio").then(r => r.json());
+    if (p.positions.length === 0) {
+      el.textContent = "";
+      return;
+    }
+    const topCorr = p.correlations.slice(0, 3)
+      .map(c => `${c.a}~${c.b} ${c.corr.toFixed(2)}`)
+      .join(", ");
+    el.innerHTML =
+      `<strong>Exposure:</strong> ${sym}${fmtAmount(p.total_exposure, 2)} (${p.exposure_pct.toFixed(1)}% van equity) | ` +
+      `<strong>Posities:</strong> ${p.positions.length} ≈ ${p.effective_positions.toFixed(2)} effectief | ` +
+      `<strong>Corr-adjusted:</strong> ${sym}${fmtAmount(p.correlation_adjusted_exposure, 2)}` +
+      (topCorr ? ` | <strong>Hoogste 1m-correlaties:</strong> ${escapeHtml(topCorr)}` : "");
+  } catch (e) {
+    console.error("Portfolio error:", e);
+  }
+}
+
+function manualPartialPct() {
+  const pct = parseFloat(document.getElementById("manual-partial-pct").value);
+  return pct > 0 && pct <= 100 ? pct : 50;
+}
+
+async function partialCloseManualTrade(pair) {
+  const pct = manualPartialPct();
+  if (!confirm(`Close ${pct}% of trade for ${pair}?`)) {
+    return;
+  }
+  let res = await fetch("/api/manual_trade/partial", {
+    method: "POST",
+    headers: {"Content-Type": "application/json"},
+    body: JSON.stringify({pair, fraction: pct / 100})
+  });
+  let result = await res.json();
+  if (result.success) {
+    loadManualTrades();
+  } else {
+    alert(`Failed to partially close trade for ${pair}: ${result.error || "unknown error"}`);
+  }
+}
+
+// Limit close: vult zodra een trade-prijs de limit bereikt; leeg laten annuleert
+async function limitCloseManualTrade(pair, current) {
+  const input = prompt(`Limit close prijs voor ${pair} (leeg = annuleren):`, current > 0 ? current : "");
+  if (input === null) {
+    return;
+  }
+  const limit = parseFloat(input);
+  let res = await fetch("/api/manual_trade/limit", {
+    method: "POST",
+    headers: {"Content-Type": "application/json"},
+    body: JSON.stringify({pair, limit_price: limit > 0 ? limit : null})
+  });
+  let result = await res.json();
+  if (!result.success) {
+    alert(`Failed to set limit close for ${pair}: ${result.error || "unknown error"}`);
+  } else if (result.filled) {
+    alert(`Prijs staat al op of boven de limit: ${pair} direct gesloten.`);
   }
+  loadManualTrades();
 }
 
 function drawManualEquity(equity) {
@@ -3140,10 +8704,104 @@ function drawManualEquity(equity) {
   ctx.stroke();
 }
 
+async function loadMovers() {
+  const dir = document.getElementById("movers-dir").value;
+  const limit = document.getElementById("movers-limit").value;
+  let data = await fetch(`/api/movers?dir=${dir}&limit=${limit}`).then(r => r.json());
+  let tbody = document.querySelector("#movers-table tbody");
+  tbody.innerHTML = "";
+  data.forEach(m => {
+    const sym = quoteSymbol(m.pair);
+    tbody.innerHTML += `
+      <tr>
+        <td>${m.pair}</td>
+        <td>${sym}${m.price.toFixed(m.precision)}</td>
+        <td class="${m.pct > 0 ? 'pos' : 'neg'}">${m.pct.toFixed(2)}%</td>
+        <td>${m.vol24h.toFixed(2)}</td>
+        <td>${sym}${fmtAmount(m.quote_volume, 0)}</td>
+        <td>${m.signalled ? 'ja' : '-'}</td>
+        <td>${m.ts ? fmtTime(m.ts) : '-'}</td>
+      </tr>
+    `;
+  });
+}
+
+async function loadAlerts() {
+  let data = await fetch("/api/alerts").then(r => r.json());
+  let tbody = document.querySelector("#alerts-table tbody");
+  tbody.innerHTML = "";
+  data.forEach(a => {
+    tbody.innerHTML += `
+      <tr>
+        <td>${fmtDateTime(a.ts)}</td>
+        <td>${a.pair}</td>
+        <td class="signal_type signal_type_${a.type}">${a.type}</td>
+        <td>${a.channel}</td>
+        <td class="${a.delivered ? 'pos' : 'neg'}">${a.delivered ? 'ja' : 'nee'}</td>
+        <td>${escapeHtml(a.error || a.message)}</td>
+      </tr>
+    `;
+  });
+}
+
+// Kleine inline SVG lijn; vaste schaal (lo..hi) zodat types onderling vergelijkbaar zijn
+function sparkline(values, lo, hi, color) {
+  if (values.length < 2) return "-";
+  const w = 120, h = 24;
+  const span = (hi - lo) || 1;
+  const pts = values.map((v, i) => {
+    const x = (i / (values.length - 1)) * w;
+    const y = h - ((Math.min(Math.max(v, lo), hi) - lo) / span) * h;
+    return `${x.toFixed(1)},${y.toFixed(1)}`;
+  }).join(" ");
+  return `<svg width="${w}" height="${h}"><polyline fill="none" stroke="${color}" stroke-width="1.5" points="${pts}"/></svg>`;
+}
+
+async function loadSignalStatsHistory() {
+  let stats = await fetch("/api/signal_stats/history").then(r => r.json());
+  let tbody = document.querySelector("#signal-stats-table tbody");
+  let types = Object.keys(stats);
+  if (types.length === 0) {
+    tbody.innerHTML = `<tr><td colspan="6">Nog geen geëvalueerde signals.</td></tr>`;
+    return;
+  }
+  tbody.innerHTML = types.map(t => {
+    const s = stats[t];
+    const winrate = s.wins + s.losses > 0 ? s.wins / (s.wins + s.losses) * 100 : 0;
+    const last = s.history.length ? s.history[s.history.length - 1] : null;
+    return `<tr>
+      <td>${escapeHtml(t)}</td>
+      <td>${s.wins} / ${s.losses}</td>
+      <td>${s.threshold.toFixed(3)}</td>
+      <td>${sparkline(s.history.map(p => p.threshold), 0.1, 0.99, "#03a9f4")}</td>
+      <td>${winrate.toFixed(1)}%</td>
+      <td title="${last ? fmtDateTime(last.ts) : ''}">${sparkline(s.history.map(p => p.winrate), 0, 100, "#4caf50")}</td>
+    </tr>`;
+  }).join("");
+}
+
+async function loadPendingSignals() {
+  let pending = await fetch("/api/pending_signals").then(r => r.json());
+  document.getElementById("backtest-pending-count").textContent = pending.length;
+  document.getElementById("backtest-pending-next").textContent = pending.length > 0
+    ? ` (eerstvolgende over ${pending[0].seconds_remaining}s)`
+    : "";
+}
+
 async function loadBacktest() {
   let includeStable = document.getElementById("backtest-stable-filter").checked;
   try {
-    let res = await fetch("/api/backtest");
+    let params = new URLSearchParams();
+    let since = document.getElementById("backtest-since").value;
+    let until = document.getElementById("backtest-until").value;
+    let minTrades = parseInt(document.getElementById("backtest-min-trades").value, 10);
+    if (since) params.set("since", Math.floor(new Date(since).getTime() / 1000));
+    if (until) params.set("until", Math.floor(new Date(until).getTime() / 1000));
+    if (minTrades > 0) params.set("min_trades", minTrades);
+    if (document.getElementById("backtest-archive").checked) params.set("archive", "true");
+    loadPendingSignals();
+    loadSignalStatsHistory();
+    let res = await fetch("/api/backtest?" + params.toString());
     let data = await res.json();
     let tbody = document.querySelector("#backtest-table tbody");
     if (!tbody) return;
@@ -3151,10 +8809,14 @@ async function loadBacktest() {
 
     data.forEach((r, idx) => {
       let tr = document.createElement("tr");
+      if (r.low_sample) {
+        tr.style.opacity = "0.5";
+        tr.title = "Te weinig trades: statistisch onbetrouwbaar";
+      }
       tr.innerHTML = `
         <td>${r.signal_type}</td>
         <td>${r.direction}</td>
-        <td>${r.total_trades}</td>
+        <td>${r.total_trades}${r.low_sample ? " (!)" : ""}</td>
         <td>${r.winrate.toFixed(1)}%</td>
         <td>${r.avg_win.toFixed(2)}</td>
         <td>${r.avg_loss.toFixed(2)}</td>
@@ -3164,15 +8826,29 @@ async function loadBacktest() {
         <td>${r.best_trade.toFixed(2)}</td>
         <td>${r.worst_trade.toFixed(2)}</td>
         <td>${r.max_losing_streak}</td>
+        <td>${r.return_stddev.toFixed(2)}%</td>
+        <td>${r.sharpe.toFixed(2)}</td>
+        <td class="${r.compounded_return_pct >= 0 ? 'pos' : 'neg'}">${r.compounded_return_pct.toFixed(2)}%</td>
+        <td>${accountSymbol()}${(r.equity_curve_compounded.at(-1) ?? r.start_balance).toFixed(2)}</td>
       `;
-      tr.addEventListener("click", () => {
-        drawEquityCurve(r);
+      tr.dataset.key = backtestKey(r);
+      tr.style.cursor = "pointer";
+      tr.addEventListener("click", (ev) => {
+        selectBacktestRow(r, ev.shiftKey);
       });
       tbody.appendChild(tr);
     });
 
-    if (data.length > 0) {
-      drawEquityCurve(data[0]);
+    // Vergelijking overleeft een refresh zolang beide rijen nog bestaan
+    let keys = backtestSelection.map(backtestKey);
+    backtestSelection = keys
+      .map(k => data.find(r => backtestKey(r) === k))
+      .filter(r => r);
+    if (backtestSelection.length === 0 && data.length > 0) {
+      backtestSelection = [data[0]];
+    }
+    if (backtestSelection.length > 0) {
+      renderBacktestSelection();
     } else {
       let canvas = document.getElementById("backtest-equity");
       let ctx = canvas.getContext("2d");
@@ -3185,22 +8861,50 @@ async function loadBacktest() {
   }
 }
 
-function drawEquityCurve(result) {
-  let canvas = document.getElementById("backtest-equity");
-  if (!canvas) return;
-  let ctx = canvas.getContext("2d");
-  let eq = result.equity_curve || [];
+// Geselecteerde backtest-rijen: één = detail, twee = overlay-vergelijking
+let backtestSelection = [];
+const BACKTEST_COMPARE_COLORS = ["#00e676", "#40c4ff"];
 
-  ctx.clearRect(0, 0, canvas.width, canvas.height);
+function backtestKey(r) {
+  return `${r.signal_type}|${r.direction}`;
+}
 
-  if (!eq.length) {
-    document.getElementById("backtest-equity-label").textContent =
-      `Geen equity curve beschikbaar voor ${result.signal_type} / ${result.direction}.`;
-    return;
+function selectBacktestRow(r, compare) {
+  let key = backtestKey(r);
+  if (!compare) {
+    backtestSelection = [r];
+  } else if (backtestSelection.some(s => backtestKey(s) === key)) {
+    // Nogmaals shift-klikken haalt de rij uit de vergelijking
+    let rest = backtestSelection.filter(s => backtestKey(s) !== key);
+    backtestSelection = rest.length ? rest : [r];
+  } else {
+    backtestSelection = [...backtestSelection, r].slice(-2);
+  }
+  renderBacktestSelection();
+}
+
+function renderBacktestSelection() {
+  let keys = backtestSelection.map(backtestKey);
+  document.querySelectorAll("#backtest-table tbody tr").forEach(tr => {
+    let i = keys.indexOf(tr.dataset.key);
+    tr.style.outline = i >= 0 ? `1px solid ${BACKTEST_COMPARE_COLORS[i]}` : "";
+  });
+  if (backtestSelection.length === 2) {
+    drawEquityComparison(backtestSelection);
+  } else if (backtestSelection.length === 1) {
+    drawEquityCurve(backtestSelection[0]);
   }
+}
+
+// Tekent series [{values, color}] op één y-as; x = trade index, zodat curves met
+// minder trades eerder eindigen in plaats van uitgerekt te worden.
+function plotEquitySeries(canvas, series) {
+  let ctx = canvas.getContext("2d");
+  let all = series.flatMap(s => s.values);
+  let maxLen = Math.max(...series.map(s => s.values.length));
 
-  let minY = Math.min(...eq);
-  let maxY = Math.max(...eq);
+  let minY = Math.min(...all);
+  let maxY = Math.max(...all);
   if (minY === maxY) {
     minY -= 1;
     maxY += 1;
@@ -3219,24 +8923,87 @@ function drawEquityCurve(result) {
   ctx.lineTo(40, h - 30);
   ctx.stroke();
 
-  ctx.strokeStyle = "#00e676";
-  ctx.lineWidth = 2;
-  ctx.beginPath();
+  series.forEach(({ values, color }) => {
+    ctx.strokeStyle = color;
+    ctx.lineWidth = 2;
+    ctx.beginPath();
 
-  eq.forEach((yVal, i) => {
-    let x = padding + (w * i) / Math.max(eq.length - 1, 1);
-    let normY = (yVal - minY) / (maxY - minY);
-    let y = padding + h - normY * h;
+    values.forEach((yVal, i) => {
+      let x = padding + (w * i) / Math.max(maxLen - 1, 1);
+      let normY = (yVal - minY) / (maxY - minY);
+      let y = padding + h - normY * h;
 
-    if (i === 0) ctx.moveTo(x, y);
-    else ctx.lineTo(x, y);
+      if (i === 0) ctx.moveTo(x, y);
+      else ctx.lineTo(x, y);
+    });
+
+    ctx.stroke();
   });
+  return ctx;
+}
 
-  ctx.stroke();
+function drawEquityComparison(results) {
+  let canvas = document.getElementById("backtest-equity");
+  if (!canvas) return;
+  let ctx = canvas.getContext("2d");
+  ctx.clearRect(0, 0, canvas.width, canvas.height);
+
+  let series = results.map((r, i) => ({ values: r.equity_curve || [], color: BACKTEST_COMPARE_COLORS[i] }));
+  if (series.every(s => !s.values.length)) {
+    document.getElementById("backtest-equity-label").textContent =
+      "Geen equity curves beschikbaar voor de geselecteerde strategieën.";
+    return;
+  }
+  plotEquitySeries(canvas, series);
+
+  // Legenda linksboven in de canvas
+  ctx.font = "11px sans-serif";
+  results.forEach((r, i) => {
+    let y = 14 + i * 14;
+    ctx.fillStyle = BACKTEST_COMPARE_COLORS[i];
+    ctx.fillRect(48, y - 8, 12, 3);
+    ctx.fillStyle = "#ccc";
+    ctx.fillText(`${r.signal_type} / ${r.direction} (${r.total_trades} trades, som ${r.pnl_sum.toFixed(2)}%)`, 66, y - 4);
+  });
+
+  document.getElementById("backtest-equity-label").textContent =
+    "Vergelijking som van % returns per trade index | " +
+    results.map(r =>
+      `${r.signal_type} / ${r.direction}: winrate ${r.winrate.toFixed(1)}%, ` +
+      `expectancy ${r.expectancy.toFixed(2)}%, max DD ${r.max_drawdown.toFixed(2)}%`
+    ).join(" vs ");
+}
+
+function drawEquityCurve(result) {
+  let canvas = document.getElementById("backtest-equity");
+  if (!canvas) return;
+  let ctx = canvas.getContext("2d");
+  let eq = result.equity_curve || [];
+
+  ctx.clearRect(0, 0, canvas.width, canvas.height);
+
+  if (!eq.length) {
+    document.getElementById("backtest-equity-label").textContent =
+      `Geen equity curve beschikbaar voor ${result.signal_type} / ${result.direction}.`;
+    return;
+  }
+
+  // Samengestelde accountwaarde als % t.o.v. startbalans, zodat beide curves één as delen
+  let start = result.start_balance || 1;
+  let comp = (result.equity_curve_compounded || []).map(v => (v / start - 1) * 100);
+
+  plotEquitySeries(canvas, [
+    { values: eq, color: "#00e676" },
+    { values: comp, color: "#ffb300" },
+  ]);
 
+  let finalEquity = (result.equity_curve_compounded || []).at(-1) ?? start;
   document.getElementById("backtest-equity-label").textContent =
     `${result.signal_type} / ${result.direction} | trades: ${result.total_trades} | ` +
-    `expectancy: ${result.expectancy.toFixed(2)}% | max DD: ${result.max_drawdown.toFixed(2)}%`;
+    `expectancy: ${result.expectancy.toFixed(2)}% | som: ${result.pnl_sum.toFixed(2)}% (max DD ${result.max_drawdown.toFixed(2)}%) | ` +
+    `samengesteld (${(result.position_fraction * 100).toFixed(0)}% per trade): ` +
+    `${accountSymbol()}${start.toFixed(2)} → ${accountSymbol()}${finalEquity.toFixed(2)} ` +
+    `(${result.compounded_return_pct.toFixed(2)}%, max DD ${result.compounded_max_drawdown_pct.toFixed(2)}%)`;
 }
 
 // ---------- TRADE ADVICE JS ----------
@@ -3252,16 +9019,29 @@ async function loadTradeAdvice() {
     tbody.innerHTML = "";
     eqBody.innerHTML = "";
 
+    const sym = accountSymbol();
+    // Kolomkoppen volgen de geconfigureerde ladder (tabel + equity)
+    document.querySelectorAll("#view-trade_advice .trade-advice-exit").forEach((th, i) => {
+      th.textContent = `Exit +${data.exit_pcts[i % 4]}%`;
+    });
+    document.getElementById("trade-advice-info").textContent =
+      `Entry ${data.entry_pct}% onder de prijs | ${sym}${fmtAmount(data.equity.notional_per_trade, 2)} per advies | ` +
+      `balance ${sym}${fmtAmount(data.equity.balance, 2)}`;
+
+    if (data.rows.length === 0) {
+      tbody.innerHTML = `<tr><td colspan="7" style="color:#888;">Geen pairs met BUY flow en een rating</td></tr>`;
+    }
+
     for (let r of data.rows) {
       let tr = document.createElement("tr");
       tr.innerHTML = `
-        <td>${r.pair}</td>
-        <td>${r.price.toFixed(5)}</td>
-        <td>${r.entry_price.toFixed(5)}</td>
-        <td>${r.exit_5.toFixed(5)}</td>
-        <td>${r.exit_10.toFixed(5)}</td>
-        <td>${r.exit_15.toFixed(5)}</td>
-        <td>${r.exit_20.toFixed(5)}</td>
+        <td><a href="#" onclick="showPairDetail('${r.pair}'); return false;">${r.pair}</a> <span style="color:#888;">${r.rating}</span></td>
+        <td>${r.price.toFixed(r.precision)}</td>
+        <td>${r.entry_price.toFixed(r.precision)}</td>
+        <td>${r.exit_5.toFixed(r.precision)}</td>
+        <td>${r.exit_10.toFixed(r.precision)}</td>
+        <td>${r.exit_15.toFixed(r.precision)}</td>
+        <td>${r.exit_20.toFixed(r.precision)}</td>
       `;
       tbody.appendChild(tr);
     }
@@ -3270,10 +9050,10 @@ async function loadTradeAdvice() {
     if (e) {
       let tr = document.createElement("tr");
       tr.innerHTML = `
-        <td>${e.equity_5.toFixed(5)}</td>
-        <td>${e.equity_10.toFixed(5)}</td>
-        <td>${e.equity_15.toFixed(5)}</td>
-        <td>${e.equity_20.toFixed(5)}</td>
+        <td>${sym}${fmtAmount(e.equity_5, 2)}</td>
+        <td>${sym}${fmtAmount(e.equity_10, 2)}</td>
+        <td>${sym}${fmtAmount(e.equity_15, 2)}</td>
+        <td>${sym}${fmtAmount(e.equity_20, 2)}</td>
       `;
       eqBody.appendChild(tr);
     }
@@ -3282,11 +9062,44 @@ async function loadTradeAdvice() {
   }
 }
 
+// Assen voor de heatmap; min/max null = schaal op de data
+const HEATMAP_METRICS = {
+  flow_pct: { label: "Flow %", min: 0, max: 100 },
+  pump_score: { label: "Pump-score", min: 0, max: 10 },
+  score: { label: "Total score", min: null, max: null },
+  whale_pred_score: { label: "WhPred score", min: 0, max: 10 },
+  reliability_score: { label: "Reliability", min: 0, max: 100 },
+  pct: { label: "% change", min: null, max: null },
+};
+
+function heatmapAxis(select, fallback) {
+  const el = document.getElementById(select);
+  if (!el.options.length) {
+    el.innerHTML = Object.entries(HEATMAP_METRICS)
+      .map(([k, m]) => `<option value="${k}">${m.label}</option>`).join("");
+    el.value = fallback;
+  }
+  return HEATMAP_METRICS[el.value] ? el.value : fallback;
+}
+
+function heatmapRange(key, values) {
+  const m = HEATMAP_METRICS[key];
+  let lo = m.min ?? Math.min(0, ...values);
+  let hi = m.max ?? Math.max(0, ...values);
+  if (hi - lo < 1e-9) hi = lo + 1;
+  return [lo, hi];
+}
+
 function loadHeatmap() {
   let includeStable = document.getElementById("heatmap-stable-filter").checked;
+  const xKey = heatmapAxis("heatmap-x", "flow_pct");
+  const yKey = heatmapAxis("heatmap-y", "pump_score");
+  document.getElementById("heatmap-title").textContent =
+    `Heatmap: ${HEATMAP_METRICS[xKey].label} vs ${HEATMAP_METRICS[yKey].label}`;
   fetch("/api/heatmap")
     .then(r => r.json())
     .then(data => {
+      data = data.filter(pt => includeStable || !isStablecoin(pt.pair));
       const canvas = document.getElementById("heatCanvas");
       if (!canvas) return;
       const ctx = canvas.getContext("2d");
@@ -3307,15 +9120,18 @@ function loadHeatmap() {
 
       ctx.fillStyle = "#ccc";
       ctx.font = "11px sans-serif";
-      ctx.fillText("Flow %", w/2 - 20, h - 10);
+      ctx.fillText(HEATMAP_METRICS[xKey].label, w/2 - 20, h - 10);
       ctx.save();
       ctx.translate(10, h/2 + 20);
       ctx.rotate(-Math.PI/2);
-      ctx.fillText("Pump-score", 0, 0);
+      ctx.fillText(HEATMAP_METRICS[yKey].label, 0, 0);
       ctx.restore();
 
-      const x_min = 0.0, x_max = 100.0;
-      const y_min = 0.0, y_max = 10.0;
+      const [x_min, x_max] = heatmapRange(xKey, data.map(p => p[xKey]));
+      const [y_min, y_max] = heatmapRange(yKey, data.map(p => p[yKey]));
+      ctx.fillText(x_min.toFixed(1), 40, h - 18);
+      ctx.fillText(x_max.toFixed(1), w - 40, h - 18);
+      ctx.fillText(y_max.toFixed(1), 14, 18);
 
       function x_to_px(x) {
         let frac = (x - x_min) / (x_max - x_min);
@@ -3332,9 +9148,9 @@ function loadHeatmap() {
 
       heatmapPoints = [];
 
-      for (let p of data.filter(pt => includeStable || !isStablecoin(pt.pair))) {
-        const x = x_to_px(p.flow_pct);
-        const y = y_to_px(p.pump_score);
+      for (let p of data) {
+        const x = x_to_px(p[xKey]);
+        const y = y_to_px(p[yKey]);
 
         let color = "#4caf50";
         if (p.pump_score >= 8.0 && p.flow_pct >= 80.0) {
@@ -3364,6 +9180,10 @@ function loadHeatmap() {
           pair: p.pair,
           flow: p.flow_pct,
           pump: p.pump_score,
+          xLabel: HEATMAP_METRICS[xKey].label,
+          xValue: p[xKey],
+          yLabel: HEATMAP_METRICS[yKey].label,
+          yValue: p[yKey],
           ts: p.ts,
           color,
           rel: p.reliability_score,
@@ -3375,101 +9195,81 @@ function loadHeatmap() {
 
 async function loadStars() {
   let includeStable = document.getElementById("stars-stable-filter").checked;
-  let currentTime = Math.floor(Date.now() / 1000);
-  let fiveHoursAgo = currentTime - (5 * 3600);
-  fetch("/api/top10")
+  const stars = appConfig.stars || {};
+  document.getElementById("stars-title").textContent =
+    `⭐ Stars: WH_PRED ≥ ${stars.min_whale_pred_label || "HIGH"}` +
+    (stars.require_anom === false ? "" : ` & ANOM (last ${formatWindow(stars.anom_lookback_sec ?? 18000)})`);
+  fetch("/api/stars")
     .then(r => r.json())
-    .then(top10Data => {
-      let filtered = [];
-      // Get pairs with high WH_PRED from risers and fallers
-      for (let r of top10Data.risers.concat(top10Data.fallers)) {
-        if (r.whale_pred_label === "HIGH" && (includeStable || !isStablecoin(r.pair))) {
-          filtered.push(r);
-        }
+    .then(starRows => {
+      let finalFiltered = starRows.filter(r => includeStable || !isStablecoin(r.pair));
+      let tbody = document.querySelector("#stars-table tbody");
+      tbody.innerHTML = "";
+      function renderRow(r) {
+        let pctClass = r.pct > 0 ? "pos" : (r.pct < 0 ? "neg" : "");
+        let flowColor = r.dir === "BUY" ? "#4caf50" : "#f44336";
+        let whaleText = r.whale
+          ? (r.whale_side.toUpperCase() + " " + r.whale_volume.toFixed(3) +
+             " (" + quoteSymbol(r.pair) + r.whale_notional_fmt + ")")
+          : "No";
+        let visualUrl = buildVisualUrl(r.pair);
+        let visual = visualUrl ? `<a href="${visualUrl}" target="_blank">Visual</a>` : "-";
+
+        let predClass = r.whale_pred_label === "HIGH" ? "pred_high" :
+          (r.whale_pred_label === "MEDIUM" ? "pred_med" : "pred_low");
+        let relClass = r.reliability_label === "HIGH" ? "rel_high" :
+          (r.reliability_label === "MEDIUM" ? "rel_med" :
+          (r.reliability_label === "LOW" ? "rel_low" : "rel_bad"));
+        return `<tr>
+          <td>${fmtTime(r.ts)}</td>
+          <td>${pairName(r)}</td>
+          <td>${r.price.toFixed(4)}</td>
+          <td class="${pctClass}">${r.pct.toFixed(2)}%</td>
+          <td>
+            <div class="flow-bar">
+              <div class="flow-fill" style="width:${r.flow_pct.toFixed(0)}%;background:${flowColor};"></div>
+            </div>
+            ${r.flow_pct.toFixed(1)}%
+          </td>
+          <td>${r.dir}</td>
+          <td>${r.early}</td>
+          <td>${r.alpha}</td>
+          <td>${whaleText}</td>
+          <td>${r.total_score.toFixed(2)}</td>
+          <td style="color:${ r.pump_label === "MEGA_PUMP" ? "#ff4081" :
+            r.pump_label === "EARLY_PUMP" ? "#00bcd4" :
+            "#ccc"}">${r.pump_score.toFixed(1)}</td>
+          <td class="${predClass}">${r.whale_pred_label} (${r.whale_pred_score.toFixed(1)})</td>
+          <td class="${relClass}">${r.reliability_label} (${r.reliability_score.toFixed(0)})</td>
+          <td class="signal_type signal_type_${r.signal_type}">${r.signal_type}${r.anom_ts ? ` <span title="ANOM die deze entry kwalificeerde" style="color:#888;">(ANOM ${fmtTime(r.anom_ts)})</span>` : ""}</td>
+          <td>${visual}</td>
+          <td>${r.analysis}</td>
+        </tr>`;
       }
-      // Now filter those that have recent ANOM signal within 5 hours
-      fetch("/api/signals")
+      for (let r of finalFiltered) {
+        tbody.innerHTML += renderRow(r);
+      }
+
+      // Load historie tabel: GEEN FILTERS, alleen sorteren op ts desc, dan pair asc
+      fetch("/api/stars_history")
         .then(r => r.json())
-        .then(signals => {
-          let anomPairs = new Set();
-          for (let s of signals) {
-            if (s.signal_type === "ANOM" && s.ts >= fiveHoursAgo) {
-              anomPairs.add(s.pair);
+        .then(history => {
+          let historyFiltered = history; // GEEN FILTERS
+          // Sorteer: eerst op ts desc, dan pair asc
+          historyFiltered.sort((a, b) => {
+            if (b.ts !== a.ts) {
+              return b.ts - a.ts; // Jongste eerst
             }
+            return a.pair.localeCompare(b.pair); // Pair asc
+          });
+          let histTbody = document.querySelector("#stars-history-table tbody");
+          histTbody.innerHTML = "";
+          for (let r of historyFiltered.slice(0, 100)) {  // Beperk tot 100 voor performance
+            histTbody.innerHTML += renderRow(r);
           }
-          let finalFiltered = filtered.filter(r => anomPairs.has(r.pair));
-          let tbody = document.querySelector("#stars-table tbody");
-          tbody.innerHTML = "";
-          function fmtTime(ts) {
-            const d = new Date(ts * 1000);
-            return d.toLocaleTimeString();
-          }
-          function renderRow(r) {
-            let pctClass = r.pct > 0 ? "pos" : (r.pct < 0 ? "neg" : "");
-            let flowColor = r.dir === "BUY" ? "#4caf50" : "#f44336";
-            let whaleText = r.whale
-              ? (r.whale_side.toUpperCase() + " " + r.whale_volume.toFixed(3) +
-                 " (" + (r.whale_notional/1000).toFixed(1) + "k)")
-              : "No";
-            let visualUrl = buildVisualUrl(r.pair);
-            let visual = visualUrl ? `<a href="${visualUrl}" target="_blank">Visual</a>` : "-";
-
-            let predClass = r.whale_pred_label === "HIGH" ? "pred_high" :
-              (r.whale_pred_label === "MEDIUM" ? "pred_med" : "pred_low");
-            let relClass = r.reliability_label === "HIGH" ? "rel_high" :
-              (r.reliability_label === "MEDIUM" ? "rel_med" :
-              (r.reliability_label === "LOW" ? "rel_low" : "rel_bad"));
-            return `<tr>
-              <td>${fmtTime(r.ts)}</td>
-              <td>${r.pair}</td>
-              <td>${r.price.toFixed(4)}</td>
-              <td class="${pctClass}">${r.pct.toFixed(2)}%</td>
-              <td>
-                <div class="flow-bar">
-                  <div class="flow-fill" style="width:${r.flow_pct.toFixed(0)}%;background:${flowColor};"></div>
-                </div>
-                ${r.flow_pct.toFixed(1)}%
-              </td>
-              <td>${r.dir}</td>
-              <td>${r.early}</td>
-              <td>${r.alpha}</td>
-              <td>${whaleText}</td>
-              <td>${r.total_score.toFixed(2)}</td>
-              <td style="color:${ r.pump_label === "MEGA_PUMP" ? "#ff4081" :
-                r.pump_label === "EARLY_PUMP" ? "#00bcd4" :
-                "#ccc"}">${r.pump_score.toFixed(1)}</td>
-              <td class="${predClass}">${r.whale_pred_label} (${r.whale_pred_score.toFixed(1)})</td>
-              <td class="${relClass}">${r.reliability_label} (${r.reliability_score.toFixed(0)})</td>
-              <td class="signal_type signal_type_${r.signal_type}">${r.signal_type}</td>
-              <td>${visual}</td>
-              <td>${r.analysis}</td>
-            </tr>`;
-          }
-          for (let r of finalFiltered) {
-            tbody.innerHTML += renderRow(r);
-          }
-
-          // Load historie tabel: GEEN FILTERS, alleen sorteren op ts desc, dan pair asc
-          fetch("/api/stars_history")
-            .then(r => r.json())
-            .then(history => {
-              let historyFiltered = history; // GEEN FILTERS
-              // Sorteer: eerst op ts desc, dan pair asc
-              historyFiltered.sort((a, b) => {
-                if (b.ts !== a.ts) {
-                  return b.ts - a.ts; // Jongste eerst
-                }
-                return a.pair.localeCompare(b.pair); // Pair asc
-              });
-              let histTbody = document.querySelector("#stars-history-table tbody");
-              histTbody.innerHTML = "";
-              for (let r of historyFiltered.slice(0, 100)) {  // Beperk tot 100 voor performance
-                histTbody.innerHTML += renderRow(r);
-              }
-              console.log(`Loaded ${historyFiltered.length} history entries (no filters, sorted by ts desc, pair asc)`);
-            })
-            .catch(err => console.error("stars history error", err));
-        });
+          console.log(`Loaded ${historyFiltered.length} history entries (no filters, sorted by ts desc, pair asc)`);
+        })
+        .catch(err => console.error("stars history error", err));
     })
     .catch(err => console.error("stars error", err));
 }
@@ -3479,15 +9279,36 @@ async function loadNews() {
   fetch("/api/news")
     .then(r => r.json())
     .then(data => {
+      // Algemeen nieuws (zonder coin) als marktbrede gauge boven de tabel
+      let market = data.find(row => row.pair === "MARKET");
+      let mValue = document.getElementById("news-market-value");
+      let mBar = document.getElementById("news-market-bar");
+      if (market) {
+        let m = market.sentiment;
+        mValue.textContent = m.toFixed(2);
+        mValue.className = m > 0.7 ? "pos" : (m < 0.3 ? "neg" : "");
+        mBar.style.width = `${Math.round(m * 100)}%`;
+        mBar.style.background = m > 0.7 ? "#00e676" : (m < 0.3 ? "#ff5252" : "#888");
+        document.getElementById("news-market-info").textContent =
+          `${fmtDateTime(market.last_update)} | ${market.articles}`;
+      } else {
+        mValue.textContent = "–";
+        mValue.className = "";
+        mBar.style.width = "50%";
+        mBar.style.background = "#888";
+        document.getElementById("news-market-info").textContent =
+          appConfig.news_catch_all_pair ? `algemeen nieuws telt mee voor ${appConfig.news_catch_all_pair}` : "nog geen algemeen nieuws";
+      }
+
       let tbody = document.querySelector("#news-table tbody");
       tbody.innerHTML = "";
-      for (let r of data.filter(row => includeStable || !isStablecoin(row.pair))) {
+      for (let r of data.filter(row => row.pair !== "MARKET" && (includeStable || !isStablecoin(row.pair)))) {
         let sentiment = r.sentiment || 0.5;
         let classSent = sentiment > 0.7 ? "pos" : (sentiment < 0.3 ? "neg" : "");
         tbody.innerHTML += `<tr>
           <td>${r.pair}</td>
           <td class="${classSent}">${sentiment.toFixed(2)}</td>
-          <td>${new Date(r.last_update * 1000).toLocaleString()}</td>
+          <td>${fmtDateTime(r.last_update)}</td>
           <td>${r.articles}</td>
         </tr>`;
       }
@@ -3495,20 +9316,52 @@ async function loadNews() {
     .catch(err => console.error("news error", err));
 }
 
+// "flow_weight 2.2 → 2.5; ..." uit de changes van een save/import response
+async function showConfigChanges(res, label) {
+  const status = document.getElementById('config-status');
+  let changes = [];
+  try {
+    changes = (await res.json()).changes || [];
+  } catch (e) {}
+  const fmt = v => typeof v === 'object' && v !== null ? JSON.stringify(v) : String(v);
+  status.textContent = changes.length
+    ? `${label}: ` + changes.map(c => `${c.field} ${fmt(c.old)} → ${fmt(c.new)}`).join('; ')
+    : `${label}: geen wijzigingen`;
+  setTimeout(() => status.textContent = '', changes.length ? 10000 : 3000);
+}
+
+async function showConfigErrors(res) {
+  const status = document.getElementById('config-status');
+  try {
+    const data = await res.json();
+    status.textContent = 'Ongeldige config: ' + (data.errors || []).join('; ');
+  } catch (e) {
+    status.textContent = 'Ongeldige config (HTTP ' + res.status + ')';
+  }
+}
+
 async function loadConfig() {
   try {
     let res = await fetch("/api/config");
     let cfg = await res.json();
-    Object.keys(cfg).forEach(key => {
-      const el = document.getElementById(key);
-      if (el) {
-        if (el.type === 'checkbox') {
-          el.checked = cfg[key];
-        } else {
-          el.value = cfg[key];
-        }
+    appConfig = cfg;
+    // Ids met een punt (bv. "whale_tiers.large_notional") verwijzen naar geneste secties
+    document.querySelectorAll('#config-form input, #config-form select').forEach(el => {
+      const value = el.id.split('.').reduce((o, k) => (o == null ? undefined : o[k]), cfg);
+      // Optionele velden (data-inherit) zonder waarde tonen leeg: ze erven de default
+      if (value === undefined) {
+        if (el.hasAttribute('data-inherit')) el.value = '';
+        return;
+      }
+      if (el.type === 'checkbox') {
+        el.checked = value;
+      } else {
+        el.value = value;
       }
     });
+    document.getElementById('pair-aliases').value = Object.entries(cfg.pair_aliases || {})
+      .map(([pair, alias]) => `${pair}=${alias}`)
+      .join('\n');
   } catch (e) {
     console.error("Config load error:", e);
   }
@@ -3540,18 +9393,8 @@ window.addEventListener("load", () => {
     const R2 = 12*12; // Larger radius for bigger points
     if (closest && closestDist <= R2) {
       heatTooltip.style.display = "block";
-      if (!window.fmtTime) {
-        window.fmtTime = function(ts) {
-          const d = new Date(ts * 1000);
-          const dd = String(d.getDate()).padStart(2,'0');
-          const mm = String(d.getMonth()+1).padStart(2,'0');
-          const hh = String(d.getHours()).padStart(2,'0');
-          const mi = String(d.getMinutes()).padStart(2,'0');
-          return `${dd}-${mm} ${hh}:${mi}`;
-        }
-      }
       heatTooltip.textContent =
-        `${closest.pair} | ${fmtTime(closest.ts)} | Flow ${closest.flow.toFixed(1)}% | Pump ${closest.pump.toFixed(1)} | REL ${closest.rel.toFixed(0)}`;
+        `${closest.pair} | ${fmtTime(closest.ts)} | ${closest.xLabel} ${closest.xValue.toFixed(1)} | ${closest.yLabel} ${closest.yValue.toFixed(1)} | REL ${closest.rel.toFixed(0)}`;
       heatTooltip.style.left = (ev.clientX + 12) + "px";
       heatTooltip.style.top  = (ev.clientY + 12) + "px";
     } else {
@@ -3591,32 +9434,82 @@ window.addEventListener("load", () => {
 
   // Config event listeners
   document.getElementById('save-config').addEventListener('click', () => {
-    const cfg = {};
+    const cfg = Object.assign({}, appConfig);
     const inputs = document.querySelectorAll('#config-form input, #config-form select');
     inputs.forEach(el => {
-      if (el.type === 'checkbox') {
-        cfg[el.id] = el.checked;
+      let target = cfg;
+      let key = el.id;
+      const dot = key.indexOf('.');
+      if (dot > 0) {
+        const section = key.slice(0, dot);
+        target = cfg[section] = Object.assign({}, cfg[section]);
+        key = key.slice(dot + 1);
+      }
+      if (el.hasAttribute('data-inherit') && el.value === '') {
+        delete target[key];
+      } else if (el.type === 'checkbox') {
+        target[key] = el.checked;
       } else if (el.type === 'number') {
-        cfg[el.id] = parseFloat(el.value);
+        target[key] = parseFloat(el.value);
       } else {
-        cfg[el.id] = el.value;
+        target[key] = el.value;
       }
     });
+    cfg.pair_aliases = parsePairAliases(document.getElementById('pair-aliases').value);
     fetch('/api/config', {
       method: 'POST',
       headers: {'Content-Type': 'application/json'},
       body: JSON.stringify(cfg)
-    }).then(() => {
-      document.getElementById('config-status').textContent = 'Saved successfully!';
-      setTimeout(() => document.getElementById('config-status').textContent = '', 3000);
+    }).then(async res => {
+      if (!res.ok) {
+        showConfigErrors(res);
+        return;
+      }
+      loadAppConfig();
+      showConfigChanges(res, 'Saved');
     }).catch(() => {
       document.getElementById('config-status').textContent = 'Save failed!';
     });
   });
 
+  document.getElementById('export-config').addEventListener('click', () => {
+    window.location.href = '/api/config/export';
+  });
+
+  document.getElementById('import-config').addEventListener('click', () => {
+    document.getElementById('import-config-file').click();
+  });
+
+  document.getElementById('import-config-file').addEventListener('change', async (ev) => {
+    const file = ev.target.files[0];
+    ev.target.value = '';
+    if (!file) return;
+    const status = document.getElementById('config-status');
+    let body;
+    try {
+      body = JSON.stringify(JSON.parse(await file.text()));
+    } catch (e) {
+      status.textContent = 'Import failed: geen geldige JSON';
+      return;
+    }
+    const res = await fetch('/api/config/import', {
+      method: 'POST',
+      headers: {'Content-Type': 'application/json'},
+      body
+    });
+    if (!res.ok) {
+      showConfigErrors(res);
+      return;
+    }
+    loadConfig();
+    loadAppConfig();
+    showConfigChanges(res, 'Imported');
+  });
+
   document.getElementById('reset-config').addEventListener('click', () => {
     fetch('/api/config/reset', {method: 'POST'}).then(() => {
       loadConfig();
+      loadAppConfig();
       document.getElementById('config-status').textContent = 'Reset to defaults!';
       setTimeout(() => document.getElementById('config-status').textContent = '', 3000);
     });
@@ -3625,6 +9518,16 @@ window.addEventListener("load", () => {
 
 // Event listeners voor filters
 document.getElementById('markets-dir-filter').addEventListener('change', () => applyDirFilter('grid', 'markets-dir-filter'));
+['heatmap-x', 'heatmap-y'].forEach(id =>
+  document.getElementById(id).addEventListener('change', () => loadHeatmap()));
+const pctModeSelect = document.getElementById('markets-pct-mode');
+pctModeSelect.value = localStorage.getItem("marketsPctMode") || "open";
+pctModeSelect.addEventListener('change', () => {
+  localStorage.setItem("marketsPctMode", pctModeSelect.value);
+  loadMarkets();
+});
+bindSortHeaders("markets", "#grid", () => loadMarkets());
+bindSortHeaders("top10", "#top10-up, #top10-down", () => loadTop10());
 document.getElementById('signals-dir-filter').addEventListener('change', () => applyDirFilter('signals', 'signals-dir-filter'));
 document.getElementById('top10-dir-filter').addEventListener('change', () => {
   applyDirFilter('top3', 'top10-dir-filter');
@@ -3632,15 +9535,101 @@ document.getElementById('top10-dir-filter').addEventListener('change', () => {
   applyDirFilter('top10-down', 'top10-dir-filter');
 });
 
+let signalsLastSeen = parseInt(localStorage.getItem("signalsLastSeenSeq") || "0", 10);
+let signalsLatestSeq = 0;
+
+function markSignalsSeen() {
+  signalsLastSeen = signalsLatestSeq;
+  localStorage.setItem("signalsLastSeenSeq", String(signalsLastSeen));
+  document.getElementById("signals-badge").style.display = "none";
+}
+
+function renderPauseState(paused) {
+  document.getElementById("paused-banner").style.display = paused ? "block" : "none";
+  const btn = document.getElementById("pause-btn");
+  btn.textContent = paused ? "Resume trading" : "Pause trading";
+  btn.classList.toggle("paused", paused);
+}
+
+async function toggleTradingPause() {
+  const paused = document.getElementById("pause-btn").classList.contains("paused");
+  try {
+    const data = await fetch("/api/pause", {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ paused: !paused }),
+    }).then(r => r.json());
+    if (data.success) {
+      renderPauseState(data.paused);
+    } else {
+      alert("Pauze wijzigen mislukt: " + data.error);
+    }
+  } catch (e) {
+    console.error("Pause toggle error:", e);
+  }
+}
+
+async function updateSignalsBadge() {
+  try {
+    let data = await fetch(`/api/signals/unread?since_seq=${signalsLastSeen}`).then(r => r.json());
+    renderPauseState(data.paused);
+    signalsLatestSeq = data.latest_seq;
+    // Server herstart: volgnummers beginnen opnieuw
+    if (signalsLatestSeq < signalsLastSeen) {
+      markSignalsSeen();
+      return;
+    }
+    if (activeTab === "signals") {
+      markSignalsSeen();
+      return;
+    }
+    const badge = document.getElementById("signals-badge");
+    badge.textContent = data.unread > 99 ? "99+" : String(data.unread);
+    badge.style.display = data.unread > 0 ? "inline" : "none";
+  } catch (e) {
+    console.error("Signals badge error:", e);
+  }
+}
+
+async function updateBreadth() {
+  try {
+    const b = await fetch("/api/market_breadth").then(r => r.json());
+    const t = b.total;
+    const neutralPct = t.pairs > 0 ? 100 - t.buy_pct - t.sell_pct : 100;
+    document.getElementById("breadth-buy").style.width = `${t.buy_pct}%`;
+    document.getElementById("breadth-neutral").style.width = `${neutralPct}%`;
+    document.getElementById("breadth-sell").style.width = `${t.sell_pct}%`;
+    document.getElementById("breadth-text").textContent =
+      `${t.buy} BUY / ${t.sell} SELL / ${t.neutral} NEUTR`;
+    // Tooltip: buy/sell notional per quote valuta
+    document.getElementById("breadth").title = Object.entries(b.by_quote)
+      .map(([q, v]) => `${q}: ${v.buy} buy / ${v.sell} sell pairs, buy ${v.buy_notional.toFixed(0)} / sell ${v.sell_notional.toFixed(0)} ${q}`)
+      .join("\n");
+  } catch (e) {
+    console.error("Breadth error:", e);
+  }
+}
+
 function tick() {
+  updateSignalsBadge();
+  updateBreadth();
   if (activeTab === "markets") {
     loadMarkets();
+    loadPairDetail();
   } else if (activeTab === "signals") {
     loadSignals();
   } else if (activeTab === "top10") {
     loadTop10();
+  } else if (activeTab === "movers") {
+    loadMovers();
+  } else if (activeTab === "trade_advice") {
+    loadTradeAdvice();
+  } else if (activeTab === "alerts") {
+    loadAlerts();
   } else if (activeTab === "manual_trades") {
     loadManualTrades();
+  } else if (activeTab === "auto_trades") {
+    loadAutoTrades();
   } else if (activeTab === "backtest") {
     loadBacktest();
   } else if (activeTab === "news") {
@@ -3650,11 +9639,30 @@ function tick() {
   }
 }
 
-setInterval(tick, 1000);
+// Auto-refresh: "config" volgt chart_refresh_rate_sec, "0" pauzeert (keuze per browser bewaard)
+let refreshTimer = null;
+const refreshRateSelect = document.getElementById("refresh-rate");
+refreshRateSelect.value = localStorage.getItem("refreshRate") || "config";
+
+function applyRefreshRate() {
+  if (refreshTimer) clearInterval(refreshTimer);
+  refreshTimer = null;
+  const choice = refreshRateSelect.value;
+  const sec = choice === "config" ? Number(appConfig.chart_refresh_rate_sec) || 1 : parseFloat(choice);
+  refreshRateSelect.classList.toggle("paused", sec <= 0);
+  if (sec > 0) refreshTimer = setInterval(tick, Math.max(sec, 0.5) * 1000);
+}
+
+refreshRateSelect.addEventListener("change", () => {
+  localStorage.setItem("refreshRate", refreshRateSelect.value);
+  applyRefreshRate();
+  if (refreshRateSelect.value !== "0") tick();
+});
+applyRefreshRate();
 document.getElementById("search").addEventListener("input", () => {
   if (activeTab === "markets") loadMarkets();
 });
-tick();
+loadAppConfig().then(tick);
 </script>
 </body>
 </html>
@@ -3665,110 +9673,311 @@ tick();
 // ============================================================================
 
 
-async fn run_kraken_worker(
-    engine: Engine,
-    ws_pairs: std::vec::Vec<String>,
-    worker_id: usize,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let url = "wss://ws.kraken.com";
+const WS_STALE_CHECK_INTERVAL_SEC: u64 = 30;
+// Kraken stuurt ~1x/s een heartbeat; zonder berichten binnen de timeout is de verbinding dood
+const WS_PING_INTERVAL_SEC: u64 = 10;
+const WS_READ_TIMEOUT_SEC: i64 = 30;
+const KRAKEN_WS_V1_URL: &str = "wss://ws.kraken.com";
+const KRAKEN_WS_V2_URL: &str = "wss://ws.kraken.com/v2";
+// Na zoveel mislukte v2 connects valt de worker terug op v1
+const WS_V2_MAX_FAILURES: u32 = 3;
+
+// Kraken v2 stuurt JSON objecten: {"channel":"trade","type":"update","data":[...]}.
+// Heartbeats/status hebben geen data; subscribe-acks hebben geen channel.
+#[derive(Debug, Deserialize)]
+struct KrakenV2Message<T> {
+    channel: String,
+    #[serde(rename = "type", default)]
+    msg_type: String,
+    #[serde(default = "std::vec::Vec::new")]
+    data: std::vec::Vec<T>,
+}
 
-    loop {
-        println!(
-            "WS{}: connecting to Kraken ({} pairs)...",
-            worker_id,
-            ws_pairs.len()
-        );
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenV2Trade {
+    symbol: String,
+    side: String,
+    price: f64,
+    qty: f64,
+    timestamp: String,
+}
 
-        let connect_res = connect_async(url).await;
-        let (ws, _) = match connect_res {
-            Ok(v) => v,
-            Err(e) => {
-                eprintln!("WS{}: connect error {:?}, retry in 5s", worker_id, e);
-                sleep(Duration::from_secs(5)).await;
-                continue;
-            }
-        };
+// v2 ticker heeft geen dag-open; open = last - change (rolling 24h)
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenV2Ticker {
+    symbol: String,
+    last: f64,
+    volume: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenV2BookLevel {
+    price: f64,
+    qty: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenV2Book {
+    symbol: String,
+    #[serde(default)]
+    bids: std::vec::Vec<KrakenV2BookLevel>,
+    #[serde(default)]
+    asks: std::vec::Vec<KrakenV2BookLevel>,
+}
+
+fn parse_v2_channel<T: serde::de::DeserializeOwned>(txt: &str, channel: &str) -> std::vec::Vec<T> {
+    match serde_json::from_str::<KrakenV2Message<T>>(txt) {
+        Ok(m) if m.channel == channel && (m.msg_type == "snapshot" || m.msg_type == "update") => m.data,
+        _ => std::vec::Vec::new(),
+    }
+}
+
+/// Trades uit een v2 "trade" bericht als (pair, price, volume, side "b"/"s", ts).
+fn parse_v2_trades(txt: &str) -> std::vec::Vec<(String, f64, f64, &'static str, f64)> {
+    parse_v2_channel::<KrakenV2Trade>(txt, "trade")
+        .into_iter()
+        .filter(|t| t.price > 0.0 && t.qty > 0.0)
+        .map(|t| {
+            let ts = chrono::DateTime::parse_from_rfc3339(&t.timestamp)
+                .map(|d| d.timestamp_micros() as f64 / 1_000_000.0)
+                .unwrap_or_else(|_| Utc::now().timestamp() as f64);
+            let side = if t.side == "sell" { "s" } else { "b" };
+            (normalize_pair(&t.symbol), t.price, t.qty, side, ts)
+        })
+        .collect()
+}
+
+/// Tickers uit een v2 "ticker" bericht als (pair, last, vol24h, open). v2 kent geen
+/// dag-open ("change" is rolling 24h), dus open = None: de dag-open basis blijft van REST.
+fn parse_v2_tickers(txt: &str) -> std::vec::Vec<(String, f64, f64, Option<f64>)> {
+    parse_v2_channel::<KrakenV2Ticker>(txt, "ticker")
+        .into_iter()
+        .filter(|t| t.last > 0.0)
+        .map(|t| (normalize_pair(&t.symbol), t.last, t.volume, None))
+        .collect()
+}
+
+/// v1 ticker: [channelID, {"c":[last,..],"v":[today,24h],"o":[today,24h],..}, "ticker", pair].
+/// "o"[0] is de dag-open, net als "o" in de REST Ticker.
+fn parse_v1_ticker(val: &Value) -> Option<(String, f64, f64, Option<f64>)> {
+    let arr = val.as_array()?;
+    if arr.len() < 4 || arr[2].as_str() != Some("ticker") {
+        return None;
+    }
+    let data = arr[1].as_object()?;
+    let num = |v: &Value| v.as_str().and_then(|s| s.parse::<f64>().ok());
+    let last = num(&data.get("c")?[0])?;
+    let vol24h = num(&data.get("v")?[1])?;
+    let open = num(&data.get("o")?[0])?;
+    Some((normalize_pair(arr[3].as_str()?), last, vol24h, Some(open)))
+}
+
+/// v1 trades: [channelID, [[price, volume, time, side, ..], ..], "trade", pair].
+fn parse_v1_trades(val: &Value) -> std::vec::Vec<(String, f64, f64, &'static str, f64)> {
+    let Some(arr) = val.as_array() else {
+        return std::vec::Vec::new();
+    };
+    if arr.len() < 4 {
+        return std::vec::Vec::new();
+    }
+    let pair = normalize_pair(arr[3].as_str().unwrap_or("UNKNOWN"));
+    let num = |v: &Value| v.as_str().and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+    arr[1]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_array())
+        .filter(|ta| ta.len() >= 4)
+        .map(|ta| {
+            let side = if ta[3].as_str() == Some("s") { "s" } else { "b" };
+            (pair.clone(), num(&ta[0]), num(&ta[1]), side, num(&ta[2]))
+        })
+        .filter(|(_, price, vol, _, _)| *price > 0.0 && *vol > 0.0)
+        .collect()
+}
+
+/// Aantal levels per kant waarop we abonneren en dat we na elke update bewaren.
+const BOOK_DEPTH: usize = 10;
+
+/// Eén boekbericht. Een snapshot vervangt het boek; een update bevat alleen de
+/// gewijzigde levels, waarbij qty 0 betekent dat het level verdwijnt.
+#[derive(Debug, Clone, PartialEq)]
+struct BookUpdate {
+    pair: String,
+    bids: std::vec::Vec<(f64, f64)>,
+    asks: std::vec::Vec<(f64, f64)>,
+    snapshot: bool,
+}
+
+/// v1 book: [channelID, {"as"/"bs"}, "book-10", pair] als snapshot, of
+/// [channelID, {"a"}, {"b"}, "book-10", pair] als update (één of beide objecten).
+fn parse_v1_book(val: &Value) -> Option<BookUpdate> {
+    let arr = val.as_array()?;
+    if arr.len() < 4 {
+        return None;
+    }
+    let pair = normalize_pair(arr[arr.len() - 1].as_str().unwrap_or("UNKNOWN"));
+    let objects: std::vec::Vec<&serde_json::Map<String, Value>> =
+        arr[1..arr.len() - 2].iter().filter_map(|v| v.as_object()).collect();
+    if objects.is_empty() {
+        return None;
+    }
+    let snapshot = objects.iter().any(|o| o.contains_key("bs") || o.contains_key("as"));
+    let levels = |keys: [&str; 2]| -> std::vec::Vec<(f64, f64)> {
+        let num = |v: &Value| v.as_str().unwrap_or("0").parse::<f64>().unwrap_or(0.0);
+        objects
+            .iter()
+            .filter_map(|o| o.get(keys[0]).or_else(|| o.get(keys[1])))
+            .filter_map(|v| v.as_array())
+            .flatten()
+            .filter_map(|item| item.as_array())
+            .filter(|lv| lv.len() >= 2)
+            .map(|lv| (num(&lv[0]), num(&lv[1])))
+            .filter(|(price, volume)| *price > 0.0 && *volume >= 0.0)
+            .collect()
+    };
+    Some(BookUpdate { pair, bids: levels(["b", "bs"]), asks: levels(["a", "as"]), snapshot })
+}
 
-        println!("WS{}: connected", worker_id);
+/// Kanaalnaam van een v1 data bericht ("trade", "book-10", "ticker"), voorlaatste element.
+fn v1_channel_name(val: &Value) -> Option<&str> {
+    let arr = val.as_array()?;
+    arr.len().checked_sub(2).and_then(|i| arr[i].as_str())
+}
 
-        let (mut write, mut read) = ws.split();
+#[derive(Debug, Deserialize)]
+struct KrakenV2Peek {
+    #[serde(default)]
+    channel: String,
+}
 
-        let sub = serde_json::json!({
-            "event": "subscribe",
-            "pair": ws_pairs,
-            "subscription": { "name": "trade" }
-        });
+/// Boeken uit een v2 "book" bericht. Levels met qty 0 blijven staan: in een update
+/// betekenen ze dat het level verwijderd moet worden.
+fn parse_v2_books(txt: &str) -> std::vec::Vec<BookUpdate> {
+    let Ok(m) = serde_json::from_str::<KrakenV2Message<KrakenV2Book>>(txt) else {
+        return std::vec::Vec::new();
+    };
+    if m.channel != "book" || (m.msg_type != "snapshot" && m.msg_type != "update") {
+        return std::vec::Vec::new();
+    }
+    let snapshot = m.msg_type == "snapshot";
+    let levels = |l: std::vec::Vec<KrakenV2BookLevel>| -> std::vec::Vec<(f64, f64)> {
+        l.into_iter()
+            .filter(|lv| lv.price > 0.0 && lv.qty >= 0.0)
+            .map(|lv| (lv.price, lv.qty))
+            .collect()
+    };
+    m.data
+        .into_iter()
+        .map(|b| BookUpdate {
+            pair: normalize_pair(&b.symbol),
+            bids: levels(b.bids),
+            asks: levels(b.asks),
+            snapshot,
+        })
+        .collect()
+}
 
-        if let Err(e) = write.send(Message::Text(sub.to_string())).await {
-            eprintln!(
-                "WS{}: subscribe send error {:?}, reconnecting...",
-                worker_id, e
-            );
-            sleep(Duration::from_secs(5)).await;
-            continue;
+/// Past gewijzigde levels toe op één kant van het boek: qty 0 verwijdert het level,
+/// anders wordt het vervangen of toegevoegd. Daarna gesorteerd (bids aflopend,
+/// asks oplopend) en afgekapt op BOOK_DEPTH.
+fn apply_book_levels(side: &mut std::vec::Vec<(f64, f64)>, changes: &[(f64, f64)], descending: bool) {
+    for &(price, qty) in changes {
+        side.retain(|(p, _)| *p != price);
+        if qty > 0.0 {
+            side.push((price, qty));
         }
+    }
+    if descending {
+        side.sort_by(|a, b| b.0.total_cmp(&a.0));
+    } else {
+        side.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    side.truncate(BOOK_DEPTH);
+}
 
-        println!(
-            "WS{}: subscribed to {} pairs via WebSocket",
-            worker_id,
-            ws_pairs.len()
-        );
-
-        while let Some(msg_res) = read.next().await {
-            let msg = match msg_res {
-                Ok(m) => m,
-                Err(e) => {
-                    eprintln!("WS{}: read error {:?}, reconnecting...", worker_id, e);
-                    break;
-                }
-            };
-
-            if let Ok(txt) = msg.to_text() {
-                if txt.contains("\"event\"") {
-                    continue;
-                }
-                if let Ok(val) = serde_json::from_str::<Value>(txt) {
-                    if val.is_array() && val.as_array().unwrap().len() >= 4 {
-                        let arr = val.as_array().unwrap();
-                        let trades = arr[1].as_array().unwrap();
-                        let pair_raw = arr[3].as_str().unwrap_or("UNKNOWN");
-                        let pair = normalize_pair(pair_raw);
-
-                        for t in trades {
-                            let ta = t.as_array().unwrap();
-                            let price: f64 =
-                                ta[0].as_str().unwrap().parse().unwrap_or(0.0);
-                            let vol: f64 =
-                                ta[1].as_str().unwrap().parse().unwrap_or(0.0);
-                            let ts: f64 =
-                                ta[2].as_str().unwrap().parse().unwrap_or(0.0);
-                            let side = ta[3].as_str().unwrap_or("b");
-
-                            if price > 0.0 && vol > 0.0 {
-                                engine.handle_trade(&pair, price, vol, side, ts);
-                            }
-                        }
-                    }
-                }
+fn store_orderbook(engine: &Engine, update: BookUpdate) {
+    let BookUpdate { pair, bids, asks, snapshot } = update;
+    if bids.is_empty() && asks.is_empty() && !snapshot {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    match engine.orderbooks.get_mut(&pair) {
+        Some(mut ob) => {
+            if snapshot {
+                ob.bids.clear();
+                ob.asks.clear();
+            }
+            apply_book_levels(&mut ob.bids, &bids, true);
+            apply_book_levels(&mut ob.asks, &asks, false);
+            ob.timestamp = now;
+        }
+        None => {
+            let mut ob_state = OrderbookState { bids: std::vec::Vec::new(), asks: std::vec::Vec::new(), timestamp: now };
+            apply_book_levels(&mut ob_state.bids, &bids, true);
+            apply_book_levels(&mut ob_state.asks, &asks, false);
+            if ob_state.bids.is_empty() && ob_state.asks.is_empty() {
+                return;
             }
+            log_info!("[OB] {} heeft orderbook data; whale boosts tellen vanaf nu mee", pair);
+            engine.orderbooks.insert(pair, ob_state);
         }
+    }
+}
 
-        eprintln!("WS{}: stream ended, reconnecting in 5s...", worker_id);
-        sleep(Duration::from_secs(5)).await;
+/// Subscribe bericht voor v1 (wsnames) of v2 (genormaliseerde symbols, BTC i.p.v. XBT).
+fn kraken_ws_subscription(ws_pairs: &[String], channel: &str, v2: bool) -> Value {
+    if v2 {
+        let symbols: std::vec::Vec<String> = ws_pairs.iter().map(|p| normalize_pair(p)).collect();
+        let mut params = serde_json::json!({ "channel": channel, "symbol": symbols });
+        if channel == "book" {
+            params["depth"] = serde_json::json!(BOOK_DEPTH);
+        } else if channel == "trade" {
+            params["snapshot"] = serde_json::json!(false);
+        }
+        serde_json::json!({ "method": "subscribe", "params": params })
+    } else {
+        let mut subscription = serde_json::json!({ "name": channel });
+        if channel == "book" {
+            subscription["depth"] = serde_json::json!(BOOK_DEPTH);
+        }
+        serde_json::json!({
+            "event": "subscribe",
+            "pair": ws_pairs,
+            "subscription": subscription
+        })
     }
 }
 
-async fn run_orderbook_worker(
+/// `channels` = "trade" plus eventueel "book"/"ticker" bij ws_multiplex: alles over
+/// één verbinding, berichten worden op kanaalnaam gerouteerd. Zonder multiplex draaien
+/// aparte workers met alleen ["book"] of ["ticker"].
+async fn run_kraken_worker(
     engine: Engine,
     ws_pairs: std::vec::Vec<String>,
     worker_id: usize,
+    channels: std::vec::Vec<&'static str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let url = "wss://ws.kraken.com";
+    let norm_pairs: std::vec::Vec<String> = ws_pairs.iter().map(|p| normalize_pair(p)).collect();
+    let mut v2_failures: u32 = 0;
+    // Per pair het laatste moment dat handle_ticker gevoed is (throttle)
+    let mut last_fed: HashMap<String, i64> = HashMap::new();
+    let tag = match channels.as_slice() {
+        ["book"] => format!("OB_WS{}", worker_id),
+        ["ticker"] => format!("TK_WS{}", worker_id),
+        _ => format!("WS{}", worker_id),
+    };
+    // Stale-check kijkt naar trades; zonder trade kanaal zegt dat niets over deze verbinding
+    let has_trades = channels.contains(&"trade");
 
     loop {
-        println!(
-            "OB_WS{}: connecting to Kraken orderbook ({} pairs)...",
-            worker_id,
+        let use_v2 =
+            engine.config.lock().unwrap().kraken_ws_v2 && v2_failures < WS_V2_MAX_FAILURES;
+        let url = if use_v2 { KRAKEN_WS_V2_URL } else { KRAKEN_WS_V1_URL };
+        log_info!(
+            "{}: connecting to Kraken {} ({} pairs)...",
+            tag,
+            if use_v2 { "v2" } else { "v1" },
             ws_pairs.len()
         );
 
@@ -3776,191 +9985,544 @@ async fn run_orderbook_worker(
         let (ws, _) = match connect_res {
             Ok(v) => v,
             Err(e) => {
-                eprintln!("OB_WS{}: connect error {:?}, retry in 5s", worker_id, e);
+                if use_v2 {
+                    v2_failures += 1;
+                    if v2_failures >= WS_V2_MAX_FAILURES {
+                        log_warn!("[WARN] {}: v2 faalt herhaaldelijk, fallback naar v1", tag);
+                    }
+                }
+                log_warn!("{}: connect error {:?}, retry in 5s", tag, e);
                 sleep(Duration::from_secs(5)).await;
                 continue;
             }
         };
+        if use_v2 {
+            v2_failures = 0;
+        }
 
-        println!("OB_WS{}: connected", worker_id);
+        log_info!("{}: connected", tag);
 
         let (mut write, mut read) = ws.split();
 
-        // Subscribe to orderbook updates (depth 10)
-        let sub = serde_json::json!({
-            "event": "subscribe",
-            "pair": ws_pairs,
-            "subscription": { "name": "book", "depth": 10 }
-        });
-
-        if let Err(e) = write.send(Message::Text(sub.to_string())).await {
-            eprintln!(
-                "OB_WS{}: subscribe send error {:?}, reconnecting...",
-                worker_id, e
-            );
+        let mut subscribed = true;
+        for channel in &channels {
+            let sub = kraken_ws_subscription(&ws_pairs, channel, use_v2);
+            if let Err(e) = write.send(Message::Text(sub.to_string())).await {
+                log_warn!(
+                    "{}: subscribe send error {:?}, reconnecting...",
+                    tag, e
+                );
+                subscribed = false;
+                break;
+            }
+        }
+        if !subscribed {
             sleep(Duration::from_secs(5)).await;
             continue;
         }
 
-        println!(
-            "OB_WS{}: subscribed to orderbook for {} pairs",
-            worker_id,
-            ws_pairs.len()
+        log_info!(
+            "{}: subscribed to {} pairs via WebSocket ({})",
+            tag,
+            ws_pairs.len(),
+            channels.join("+")
         );
 
-        while let Some(msg_res) = read.next().await {
+        let connected_at = Utc::now().timestamp();
+        let mut last_stale_check = connected_at;
+        let mut last_msg_at = connected_at;
+
+        loop {
+            // Timeout zodat ping en stale-check ook draaien als er helemaal niets binnenkomt
+            let next = tokio::time::timeout(
+                Duration::from_secs(WS_PING_INTERVAL_SEC),
+                read.next(),
+            )
+            .await;
+            let received_at = std::time::Instant::now();
+
+            let now = Utc::now().timestamp();
+            if has_trades && now - last_stale_check >= WS_STALE_CHECK_INTERVAL_SEC as i64 {
+                last_stale_check = now;
+                let (threshold, max_fraction) = {
+                    let cfg = engine.config.lock().unwrap();
+                    (cfg.stale_threshold_sec, cfg.stale_reconnect_fraction)
+                };
+                if let Some(fraction) =
+                    engine.stale_trade_fraction(&norm_pairs, connected_at, now, threshold)
+                {
+                    if fraction >= max_fraction {
+                        log_warn!(
+                            "[WARN] {}: {:.0}% van actieve pairs stale (>{}s), reconnecting...",
+                            tag,
+                            fraction * 100.0,
+                            threshold
+                        );
+                        break;
+                    }
+                }
+            }
+
+            let msg_res = match next {
+                Ok(Some(m)) => {
+                    last_msg_at = now;
+                    m
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    if now - last_msg_at >= WS_READ_TIMEOUT_SEC {
+                        log_warn!(
+                            "{}: geen berichten in {}s, reconnecting...",
+                            tag,
+                            now - last_msg_at
+                        );
+                        break;
+                    }
+                    if let Err(e) = write.send(Message::Ping(std::vec::Vec::new())).await {
+                        log_warn!("{}: ping error {:?}, reconnecting...", tag, e);
+                        break;
+                    }
+                    continue;
+                }
+            };
             let msg = match msg_res {
                 Ok(m) => m,
                 Err(e) => {
-                    eprintln!("OB_WS{}: read error {:?}, reconnecting...", worker_id, e);
+                    log_warn!("{}: read error {:?}, reconnecting...", tag, e);
                     break;
                 }
             };
 
             if let Ok(txt) = msg.to_text() {
+                if use_v2 {
+                    let channel = serde_json::from_str::<KrakenV2Peek>(txt)
+                        .map(|p| p.channel)
+                        .unwrap_or_default();
+                    match channel.as_str() {
+                        "trade" => {
+                            for (pair, price, vol, side, ts) in parse_v2_trades(txt) {
+                                engine.handle_trade(&pair, price, vol, side, ts, received_at);
+                            }
+                        }
+                        "book" => {
+                            for update in parse_v2_books(txt) {
+                                store_orderbook(&engine, update);
+                            }
+                        }
+                        "ticker" => feed_ws_tickers(&engine, &mut last_fed, parse_v2_tickers(txt), now),
+                        _ => {}
+                    }
+                    continue;
+                }
                 if txt.contains("\"event\"") {
                     continue;
                 }
                 if let Ok(val) = serde_json::from_str::<Value>(txt) {
-                    if val.is_array() {
-                        let arr = val.as_array().unwrap();
-                        if arr.len() >= 4 {
-                            let pair_raw = arr[arr.len() - 1].as_str().unwrap_or("UNKNOWN");
-                            let pair = normalize_pair(pair_raw);
-
-                            // Parse orderbook data
-                            if let Some(data) = arr.get(1).and_then(|v| v.as_object()) {
-                                let ts_int = chrono::Utc::now().timestamp();
-                                let mut bids: std::vec::Vec<(f64, f64)> = std::vec::Vec::new();
-                                let mut asks: std::vec::Vec<(f64, f64)> = std::vec::Vec::new();
-
-                                // Parse bids (either 'b' or 'bs')
-                                if let Some(bid_arr) = data.get("b").or_else(|| data.get("bs")) {
-                                    if let Some(bid_list) = bid_arr.as_array() {
-                                        for item in bid_list {
-                                            if let Some(bid) = item.as_array() {
-                                                if bid.len() >= 2 {
-                                                    let price: f64 = bid[0]
-                                                        .as_str()
-                                                        .unwrap_or("0")
-                                                        .parse()
-                                                        .unwrap_or(0.0);
-                                                    let volume: f64 = bid[1]
-                                                        .as_str()
-                                                        .unwrap_or("0")
-                                                        .parse()
-                                                        .unwrap_or(0.0);
-                                                    if price > 0.0 && volume > 0.0 {
-                                                        bids.push((price, volume));
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // Parse asks (either 'a' or 'as')
-                                if let Some(ask_arr) = data.get("a").or_else(|| data.get("as")) {
-                                    if let Some(ask_list) = ask_arr.as_array() {
-                                        for item in ask_list {
-                                            if let Some(ask) = item.as_array() {
-                                                if ask.len() >= 2 {
-                                                    let price: f64 = ask[0]
-                                                        .as_str()
-                                                        .unwrap_or("0")
-                                                        .parse()
-                                                        .unwrap_or(0.0);
-                                                    let volume: f64 = ask[1]
-                                                        .as_str()
-                                                        .unwrap_or("0")
-                                                        .parse()
-                                                        .unwrap_or(0.0);
-                                                    if price > 0.0 && volume > 0.0 {
-                                                        asks.push((price, volume));
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // Update orderbook in engine if we have data
-                                if !bids.is_empty() || !asks.is_empty() {
-                                    // Sort bids descending (highest first)
-                                    bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-                                    // Sort asks ascending (lowest first)
-                                    asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-
-                                    let ob_state = OrderbookState {
-                                        bids,
-                                        asks,
-                                        timestamp: ts_int,
-                                    };
-                                    engine.orderbooks.insert(pair.clone(), ob_state);
-                                }
+                    match v1_channel_name(&val) {
+                        Some("trade") => {
+                            for (pair, price, vol, side, ts) in parse_v1_trades(&val) {
+                                engine.handle_trade(&pair, price, vol, side, ts, received_at);
+                            }
+                        }
+                        Some(name) if name.starts_with("book") => {
+                            if let Some(update) = parse_v1_book(&val) {
+                                store_orderbook(&engine, update);
                             }
                         }
+                        Some("ticker") => {
+                            let tickers = parse_v1_ticker(&val).into_iter().collect();
+                            feed_ws_tickers(&engine, &mut last_fed, tickers, now);
+                        }
+                        _ => {}
                     }
                 }
             }
         }
 
-        eprintln!("OB_WS{}: stream ended, reconnecting in 5s...", worker_id);
+        log_warn!("{}: stream ended, reconnecting in 5s...", tag);
         sleep(Duration::from_secs(5)).await;
     }
 }
 
+/// WS tickers naar handle_ticker, per pair hooguit eens per ticker_ws_min_interval_sec.
+/// Alleen tickers met dag-open (v1) markeren het pair als WS-vers; zonder (v2) blijft
+/// de REST scanner het pair pollen, anders veroudert day_change_pct.
+fn feed_ws_tickers(
+    engine: &Engine,
+    last_fed: &mut HashMap<String, i64>,
+    tickers: std::vec::Vec<(String, f64, f64, Option<f64>)>,
+    now: i64,
+) {
+    let min_interval = engine.config.lock().unwrap().ticker_ws_min_interval_sec;
+    for (pair, last, vol24h, open) in tickers {
+        if last <= 0.0 || open.is_some_and(|o| o <= 0.0) {
+            continue;
+        }
+        if last_fed.get(&pair).is_none_or(|at| now - at >= min_interval) {
+            engine.handle_ticker(&pair, last, vol24h, open, now);
+            last_fed.insert(pair.clone(), now);
+        }
+        if open.is_some() {
+            engine.mark_ticker_ws(&pair, now);
+        }
+    }
+}
+
 // ============================================================================
 // HOOFDSTUK 11 – REST ANOMALY SCANNER
 // ============================================================================
 
+// Pairs met een WS ticker jonger dan dit slaat de REST scanner over
+const TICKER_WS_FRESH_SEC: i64 = 60;
+
+const KRAKEN_BACKOFF_START_SEC: u64 = 10;
+const KRAKEN_BACKOFF_MAX_SEC: u64 = 300;
+
+#[derive(Debug)]
+enum KrakenRestError {
+    RateLimited(std::vec::Vec<String>),
+    Api(std::vec::Vec<String>),
+    Http(reqwest::Error),
+}
+
+impl std::fmt::Display for KrakenRestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KrakenRestError::RateLimited(errs) => write!(f, "rate limited: {}", errs.join(", ")),
+            KrakenRestError::Api(errs) => write!(f, "API error: {}", errs.join(", ")),
+            KrakenRestError::Http(e) => write!(f, "HTTP error: {}", e),
+        }
+    }
+}
+
+/// GET op een Kraken public endpoint. Kraken geeft fouten in de `error` array
+/// (met HTTP 200), dus die wordt hier expliciet gecontroleerd.
+async fn kraken_public_get(url: &str) -> Result<Value, KrakenRestError> {
+    let json: Value = reqwest::get(url)
+        .await
+        .map_err(KrakenRestError::Http)?
+        .json()
+        .await
+        .map_err(KrakenRestError::Http)?;
+
+    let errors: std::vec::Vec<String> = json["error"]
+        .as_array()
+        .map(|a| a.iter().filter_map(|e| e.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+
+    if errors.is_empty() {
+        return Ok(json);
+    }
+    if errors
+        .iter()
+        .any(|e| e.contains("Too many requests") || e.contains("Rate limit"))
+    {
+        return Err(KrakenRestError::RateLimited(errors));
+    }
+    if json["result"].is_object() {
+        // Gedeeltelijke fout (bv. onbekend pair in een batch): resultaat toch gebruiken
+        log_warn!("[KRAKEN] Waarschuwing van {}: {}", url, errors.join(", "));
+        return Ok(json);
+    }
+    Err(KrakenRestError::Api(errors))
+}
+
+/// Haalt AssetPairs op en blijft het met exponentiële backoff proberen,
+/// zodat een tijdelijke storing bij het opstarten niet het proces stopt.
+async fn fetch_asset_pairs() -> serde_json::Map<String, Value> {
+    let mut backoff = KRAKEN_BACKOFF_START_SEC;
+    let mut attempt: u32 = 1;
+    loop {
+        match kraken_public_get("https://api.kraken.com/0/public/AssetPairs").await {
+            Ok(json) => match json["result"].as_object() {
+                // Een lege lijst is net zo onbruikbaar als een fout: ook opnieuw proberen
+                Some(result) if !result.is_empty() => {
+                    if attempt > 1 {
+                        log_info!("[KRAKEN] AssetPairs opgehaald na {} pogingen", attempt);
+                    }
+                    return result.clone();
+                }
+                _ => log_warn!(
+                    "[KRAKEN] AssetPairs poging {}: geen markten in result, opnieuw over {}s",
+                    attempt, backoff
+                ),
+            },
+            Err(e) => log_warn!(
+                "[KRAKEN] AssetPairs poging {} mislukt ({}), opnieuw over {}s",
+                attempt, e, backoff
+            ),
+        }
+        attempt += 1;
+        sleep(Duration::from_secs(backoff)).await;
+        backoff = (backoff * 2).min(KRAKEN_BACKOFF_MAX_SEC);
+    }
+}
 
 async fn run_anomaly_scanner(
     engine: Engine,
     kraken_keys: std::vec::Vec<String>,
     key_to_norm: HashMap<String, String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!(
+    log_info!(
         "Starting anomaly scanner over {} Kraken pairs (REST)...",
         kraken_keys.len()
     );
 
+    let mut backoff = KRAKEN_BACKOFF_START_SEC;
+
     loop {
         for chunk in kraken_keys.chunks(20) {
-            let keys: std::vec::Vec<String> = chunk.iter().cloned().collect();
+            // Fallback: alleen pairs zonder verse WS ticker via REST
+            let now = Utc::now().timestamp();
+            let keys: std::vec::Vec<String> = chunk
+                .iter()
+                .filter(|k| !engine.ticker_ws_fresh(key_to_norm.get(*k).unwrap_or(k), now))
+                .cloned()
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
             let joined = keys.join(",");
             let url =
                 format!("https://api.kraken.com/0/public/Ticker?pair={}", joined);
 
-            if let Ok(resp) = reqwest::get(&url).await {
-                if let Ok(json) = resp.json::<Value>().await {
+            // Bij een rate limit dezelfde chunk opnieuw na de backoff, anders mist die een hele ronde
+            let json = loop {
+                match kraken_public_get(&url).await {
+                    Err(KrakenRestError::RateLimited(errs)) => {
+                        log_warn!(
+                            "[KRAKEN] Ticker rate limited ({}), chunk opnieuw over {}s",
+                            errs.join(", "),
+                            backoff
+                        );
+                        sleep(Duration::from_secs(backoff)).await;
+                        backoff = (backoff * 2).min(KRAKEN_BACKOFF_MAX_SEC);
+                    }
+                    Err(e) => {
+                        log_warn!("[KRAKEN] Ticker fout: {}", e);
+                        break None;
+                    }
+                    Ok(json) => {
+                        backoff = KRAKEN_BACKOFF_START_SEC;
+                        break Some(json);
+                    }
+                }
+            };
+
+            if let Some(obj) = json.as_ref().and_then(|j| j["result"].as_object()) {
+                for (k, v) in obj.iter() {
+                    let last_str = v["c"][0].as_str().unwrap_or("0");
+                    let vol_str = v["v"][1].as_str().unwrap_or("0");
+                    let open_str = v["o"].as_str().unwrap_or("0");
+
+                    let last: f64 = last_str.parse().unwrap_or(0.0);
+                    let vol24h: f64 = vol_str.parse().unwrap_or(0.0);
+                    let open: f64 = open_str.parse().unwrap_or(0.0);
+
+                    if last > 0.0 && open > 0.0 {
+                        let ts_int = Utc::now().timestamp();
+                        let norm = key_to_norm
+                            .get(k)
+                            .cloned()
+                            .unwrap_or_else(|| k.clone());
+                        engine.handle_ticker(&norm, last, vol24h, Some(open), ts_int);
+                    }
+                }
+            }
+
+            sleep(Duration::from_millis(500)).await;
+        }
+
+        sleep(Duration::from_secs(20)).await;
+    }
+}
+
+// ============================================================================
+// HOOFDSTUK 11b – DERIVATIVES SCANNER (FUNDING / OPEN INTEREST)
+// ============================================================================
+
+const KRAKEN_FUTURES_TICKERS_URL: &str = "https://futures.kraken.com/derivatives/api/v3/tickers";
+
+async fn fetch_derivatives(engine: &Engine) -> Result<usize, Box<dyn std::error::Error>> {
+    let json: Value = reqwest::get(KRAKEN_FUTURES_TICKERS_URL).await?.json().await?;
+    if json["result"].as_str() != Some("success") {
+        return Err(format!("futures tickers error: {}", json["error"]).into());
+    }
+
+    let fresh = parse_perpetuals(&json, Utc::now().timestamp());
+    // Bases die niet meer als perpetual bestaan verdwijnen; de rest wordt ververst
+    engine.derivatives.retain(|base, _| fresh.contains_key(base));
+    let count = fresh.len();
+    for (base, state) in fresh {
+        engine.derivatives.insert(base, state);
+    }
+    Ok(count)
+}
+
+/// Perpetuals per genormaliseerde base uit één tickers-response. Linear (PF_)
+/// heeft voorrang op inverse (PI_), maar alleen binnen deze scan: een base met
+/// alleen een PI_ contract wordt dus elke scan ververst.
+fn parse_perpetuals(json: &Value, ts: i64) -> HashMap<String, DerivativesState> {
+    let mut out: HashMap<String, DerivativesState> = HashMap::new();
+    for t in json["tickers"].as_array().into_iter().flatten() {
+        if t["tag"].as_str() != Some("perpetual") {
+            continue;
+        }
+        let symbol = t["symbol"].as_str().unwrap_or_default();
+        // "XBT:USD" -> "BTC"
+        let base = match t["pair"].as_str().and_then(|p| p.split(':').next()) {
+            Some(b) if !b.is_empty() => normalize_asset(b),
+            _ => continue,
+        };
+        if !symbol.starts_with("PF_") && out.get(&base).is_some_and(|d| d.symbol.starts_with("PF_")) {
+            continue;
+        }
+        out.insert(
+            base,
+            DerivativesState {
+                symbol: symbol.to_string(),
+                funding_rate: t["fundingRate"].as_f64().unwrap_or(0.0),
+                open_interest: t["openInterest"].as_f64().unwrap_or(0.0),
+                mark_price: t["markPrice"].as_f64().unwrap_or(0.0),
+                ts,
+            },
+        );
+    }
+    out
+}
+
+async fn run_derivatives_scanner(engine: Engine) {
+    loop {
+        let (enabled, interval) = {
+            let cfg = engine.config.lock().unwrap();
+            (cfg.derivatives_enabled, cfg.derivatives_scan_interval_sec.max(10))
+        };
+
+        if enabled {
+            match fetch_derivatives(&engine).await {
+                Ok(n) => log_debug!("[DERIVATIVES] {} perpetuals bijgewerkt", n),
+                Err(e) => log_warn!("[DERIVATIVES] Fout bij ophalen: {}", e),
+            }
+        }
+
+        sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+// ============================================================================
+// HOOFDSTUK 11c – FX KOERSEN (CROSS-QUOTE NOTIONALS)
+// ============================================================================
+
+/// /api/fx: actuele koersen naar base_currency (1 quote = rate base).
+#[derive(Debug, Clone, Default, Serialize)]
+struct FxRates {
+    base_currency: String,
+    source: String,
+    updated_ts: i64,
+    rates: HashMap<String, f64>,
+    // Quotes zonder koers; hun notionals tellen ongeconverteerd (1:1)
+    missing: std::vec::Vec<String>,
+}
+
+/// Koers 1 quote = x base uit prijzen per genormaliseerd pair: "Q/B" direct of
+/// "B/Q" omgekeerd.
+fn fx_rate_from_prices(quote: &str, base: &str, prices: &HashMap<String, f64>) -> Option<f64> {
+    let direct = prices.get(&format!("{}/{}", quote, base)).copied().filter(|p| *p > 0.0);
+    direct.or_else(|| {
+        prices
+            .get(&format!("{}/{}", base, quote))
+            .copied()
+            .filter(|p| *p > 0.0)
+            .map(|p| 1.0 / p)
+    })
+}
+
+/// Haalt de koersen voor alle gevolgde quotes op. Per quote: Kraken (bij source
+/// "kraken"), dan de vorige koers bij dezelfde base, dan static_rates.
+async fn fetch_fx_rates(
+    fx: &FxSettings,
+    quotes: &[String],
+    pair_keys: &HashMap<String, String>,
+    previous: &FxRates,
+) -> FxRates {
+    let base = fx.base_currency.as_str();
+    let wanted: std::vec::Vec<&String> = quotes.iter().filter(|q| q.as_str() != base).collect();
+
+    let mut prices: HashMap<String, f64> = HashMap::new();
+    if fx.source == "kraken" {
+        let mut key_to_norm: HashMap<String, String> = HashMap::new();
+        for q in wanted.iter() {
+            for norm in [format!("{}/{}", q, base), format!("{}/{}", base, q)] {
+                if let Some(key) = pair_keys.get(&norm) {
+                    key_to_norm.insert(key.clone(), norm);
+                }
+            }
+        }
+        if !key_to_norm.is_empty() {
+            let keys: std::vec::Vec<&str> = key_to_norm.keys().map(|k| k.as_str()).collect();
+            let url = format!("https://api.kraken.com/0/public/Ticker?pair={}", keys.join(","));
+            match kraken_public_get(&url).await {
+                Ok(json) => {
                     if let Some(obj) = json["result"].as_object() {
                         for (k, v) in obj.iter() {
-                            let last_str = v["c"][0].as_str().unwrap_or("0");
-                            let vol_str = v["v"][1].as_str().unwrap_or("0");
-                            let open_str = v["o"].as_str().unwrap_or("0");
-
-                            let last: f64 = last_str.parse().unwrap_or(0.0);
-                            let vol24h: f64 = vol_str.parse().unwrap_or(0.0);
-                            let open: f64 = open_str.parse().unwrap_or(0.0);
-
-                            if last > 0.0 && open > 0.0 {
-                                let ts_int = Utc::now().timestamp();
-                                let norm = key_to_norm
-                                    .get(k)
-                                    .cloned()
-                                    .unwrap_or_else(|| k.clone());
-                                engine.handle_ticker(&norm, last, vol24h, open, ts_int);
+                            let last: f64 = v["c"][0].as_str().and_then(|s| s.parse().ok()).unwrap_or(0.0);
+                            if let (Some(norm), true) = (key_to_norm.get(k), last > 0.0) {
+                                prices.insert(norm.clone(), last);
                             }
                         }
                     }
                 }
+                Err(e) => log_warn!("[FX] Kraken koersen ophalen mislukt: {}", e),
             }
+        }
+    }
 
-            sleep(Duration::from_millis(500)).await;
+    let mut rates = HashMap::new();
+    let mut missing = std::vec::Vec::new();
+    for q in wanted {
+        let rate = fx_rate_from_prices(q, base, &prices)
+            .or_else(|| {
+                (previous.base_currency == base)
+                    .then(|| previous.rates.get(q.as_str()).copied())
+                    .flatten()
+            })
+            .or_else(|| fx.static_rates.get(q.as_str()).copied().filter(|r| *r > 0.0));
+        match rate {
+            Some(r) => {
+                rates.insert(q.clone(), r);
+            }
+            None => missing.push(q.clone()),
         }
+    }
 
-        sleep(Duration::from_secs(20)).await;
+    FxRates {
+        base_currency: base.to_string(),
+        source: fx.source.clone(),
+        updated_ts: Utc::now().timestamp(),
+        rates,
+        missing,
+    }
+}
+
+/// `pair_keys`: genormaliseerd pair -> Kraken pair key, voor alle Kraken markten.
+async fn run_fx_refresher(engine: Engine, pair_keys: HashMap<String, String>) {
+    loop {
+        let (fx, quotes) = {
+            let cfg = engine.config.lock().unwrap();
+            (cfg.fx.clone(), cfg.quote_currencies.clone())
+        };
+        let previous = engine.fx.lock().unwrap().clone();
+        let rates = fetch_fx_rates(&fx, &quotes, &pair_keys, &previous).await;
+        if !rates.missing.is_empty() {
+            log_warn!(
+                "[FX] Geen koers naar {} voor {}; notionals tellen 1:1",
+                rates.base_currency,
+                rates.missing.join(", ")
+            );
+        }
+        log_debug!("[FX] {} koersen naar {} bijgewerkt", rates.rates.len(), rates.base_currency);
+        *engine.fx.lock().unwrap() = rates;
+        sleep(Duration::from_secs(fx.refresh_sec)).await;
     }
 }
 
@@ -3969,8 +10531,9 @@ async fn run_anomaly_scanner(
 // ============================================================================
 
 // NIEUW: run_news_scanner functie (stap 2)
-async fn run_news_scanner(engine: Engine) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Starting news sentiment scanner...");
+async fn run_news_scanner(engine: Engine, pairs: std::vec::Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    log_info!("Starting news sentiment scanner...");
+    let keywords = NewsKeywords::load(&pairs).await;
 
     loop {
         // Voorbeeld: RSS feed van een crypto nieuws site (bijv. CoinDesk)
@@ -4000,58 +10563,350 @@ async fn run_news_scanner(engine: Engine) -> Result<(), Box<dyn std::error::Erro
                                 0.5
                             };
 
-                            // Extract pair van title (bijv. "BTC" of "Bitcoin")
-                            if let Some(pair) = extract_pair_from_title(&title) {
-                                engine.update_sentiment(&pair, sentiment, &title);
-                                println!("[NEWS] {} sentiment {:.2} for {}", title, sentiment, pair);
-                            } else {
-                                engine.update_sentiment("BTC/EUR", sentiment, &title);
-                                println!("[NEWS] {} sentiment {:.2} for BTC/EUR (general)", title, sentiment);
-                            }
-                        }
-                    }
-                }
-            }
+                            // Extract pair van title (bijv. "BTC" of "Bitcoin")
+                            let matched = keywords.match_pair(&title);
+                            let general = matched.is_none();
+                            let catch_all = engine.config.lock().unwrap().news_catch_all_pair.clone();
+                            let target = news_sentiment_target(matched, &catch_all);
+                            engine.update_sentiment(&target, sentiment, &title);
+                            log_debug!(
+                                "[NEWS] {} sentiment {:.2} for {}{}",
+                                title,
+                                sentiment,
+                                target,
+                                if general { " (general)" } else { "" }
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // Wacht 1 minuut voor volgende scan
+        sleep(Duration::from_secs(60)).await;
+    }
+}
+
+// Keyword -> pair mapping, uitbreidbaar door de gebruiker (bv. {"shiba inu": "SHIB/EUR"})
+const NEWS_KEYWORDS_FILE: &str = "news_keywords.json";
+// Sentiment key voor algemeen crypto nieuws; geen echt pair, dus nooit in rows/scores
+const MARKET_SENTIMENT_KEY: &str = "MARKET";
+
+/// Waar het sentiment van een kop landt: de gematchte pair, anders
+/// `news_catch_all_pair`, of MARKET als die leeg is.
+fn news_sentiment_target(matched: Option<String>, catch_all: &str) -> String {
+    matched.unwrap_or_else(|| {
+        if catch_all.trim().is_empty() {
+            MARKET_SENTIMENT_KEY.to_string()
+        } else {
+            catch_all.trim().to_string()
+        }
+    })
+}
+const NEWS_SENTIMENT_FILE: &str = "news_sentiment.json";
+
+/// Laatste sentiment per pair zoals in news_sentiment.json (news_persist).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NewsSentimentEntry {
+    pair: String,
+    sentiment: f64,
+    last_update: i64,
+    #[serde(default)]
+    title: String,
+}
+
+/// Koppelt nieuwstitels aan pairs: eerst keywords (KEYWORD_MAP + news_keywords.json,
+/// langste eerst, hele woorden), daarna de ticker van elk gevolgd pair ("SOL" -> "SOL/EUR").
+struct NewsKeywords {
+    keywords: std::vec::Vec<(String, String)>,
+    symbols: HashMap<String, String>,
+}
+
+impl NewsKeywords {
+    async fn load(pairs: &[String]) -> Self {
+        let mut map: HashMap<String, String> = KEYWORD_MAP.clone();
+        match tokio::fs::read_to_string(NEWS_KEYWORDS_FILE).await {
+            Ok(content) => match serde_json::from_str::<HashMap<String, String>>(&content) {
+                Ok(user) => {
+                    for (k, v) in user {
+                        map.insert(k.trim().to_lowercase(), v);
+                    }
+                }
+                Err(e) => log_warn!("[WARN] Failed to parse {}: {}. Alleen standaard keywords.", NEWS_KEYWORDS_FILE, e),
+            },
+            Err(_) => {
+                // Eerste start: schrijf de standaard mapping weg als voorbeeld om uit te breiden
+                if let Ok(json) = serde_json::to_string_pretty(&*KEYWORD_MAP) {
+                    if let Err(e) = tokio::fs::write(NEWS_KEYWORDS_FILE, json).await {
+                        log_warn!("[WARN] Kon {} niet aanmaken: {}", NEWS_KEYWORDS_FILE, e);
+                    }
+                }
+            }
+        }
+        let mut keywords: std::vec::Vec<(String, String)> = map.into_iter().filter(|(k, _)| !k.is_empty()).collect();
+        keywords.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        let mut symbols = HashMap::new();
+        for pair in pairs {
+            if let Some((base, _)) = pair.split_once('/') {
+                symbols.entry(base.to_uppercase()).or_insert_with(|| pair.clone());
+            }
+        }
+        log_info!("[NEWS] {} keywords, {} pair symbols", keywords.len(), symbols.len());
+        Self { keywords, symbols }
+    }
+
+    fn match_pair(&self, title: &str) -> Option<String> {
+        let title_lower = title.to_lowercase();
+        if let Some((_, pair)) = self.keywords.iter().find(|(k, _)| contains_word(&title_lower, k)) {
+            return Some(pair.clone());
+        }
+        // Fallback: ticker in hoofdletters als los woord ("SOL", "$PEPE"); hoofdlettergevoelig
+        // zodat gewone woorden ("one", "gas") niet matchen
+        title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| w.len() >= 2 && w.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()))
+            .find_map(|w| self.symbols.get(w).cloned())
+    }
+}
+
+/// `needle` komt als los woord (niet als deel van een woord) voor in `haystack`.
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(i, _)| {
+        let before = haystack[..i].chars().next_back();
+        let after = haystack[i + needle.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric()) && !after.is_some_and(|c| c.is_alphanumeric())
+    })
+}
+
+// ============================================================================
+// HOOFDSTUK 17 – EVENT LOG (JSONL)
+// ============================================================================
+
+const EVENT_LOG_FILE: &str = "events.jsonl";
+const EVENT_LOG_ROTATED_FILE: &str = "events.jsonl.1";
+// Signals die uit de in-memory buffer vallen (max_history) voor langere backtests
+const SIGNAL_ARCHIVE_FILE: &str = "signals_archive.jsonl";
+const SIGNAL_ARCHIVE_ROTATED_FILE: &str = "signals_archive.jsonl.1";
+const ALERTS_FILE: &str = "alerts.jsonl";
+const ALERTS_ROTATED_FILE: &str = "alerts.jsonl.1";
+
+// Append-only: één JSON object per regel. Boven max_bytes wordt het bestand
+// naar <file>.1 geroteerd (vorige rotatie wordt overschreven).
+fn append_jsonl_line(path: &str, rotated_path: &str, line: &str, max_bytes: u64) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Ok(meta) = std::fs::metadata(path) {
+        if max_bytes > 0 && meta.len() + line.len() as u64 > max_bytes {
+            std::fs::rename(path, rotated_path)?;
+        }
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Eén regel voor een JSONL bestand, geschreven door de jsonl-writer thread.
+struct JsonlLine {
+    path: &'static str,
+    rotated_path: &'static str,
+    line: String,
+    max_bytes: u64,
+}
+
+/// Schrijft JSONL regels op een eigen thread zodat handle_trade en alerts (tokio
+/// workers) nooit op disk I/O wachten. Eén consumer: volgorde per bestand blijft
+/// behouden. De thread stopt zodra alle senders (Engine clones) weg zijn.
+fn spawn_jsonl_writer() -> std::sync::mpsc::Sender<JsonlLine> {
+    let (tx, rx) = std::sync::mpsc::channel::<JsonlLine>();
+    std::thread::Builder::new()
+        .name("jsonl-writer".to_string())
+        .spawn(move || {
+            for l in rx {
+                if let Err(e) = append_jsonl_line(l.path, l.rotated_path, &l.line, l.max_bytes) {
+                    log_error!("[ERR] Kon regel niet schrijven naar {}: {}", l.path, e);
+                }
+            }
+        })
+        .expect("jsonl-writer thread starten");
+    tx
+}
+
+/// (lengte, mtime) van de geroteerde en de huidige archieffile; verandert bij elke
+/// append en rotatie.
+type SignalArchiveStamp = [(u64, Option<std::time::SystemTime>); 2];
+type SignalArchiveCache = (SignalArchiveStamp, Arc<std::vec::Vec<SignalEvent>>);
+
+async fn signal_archive_stamp() -> SignalArchiveStamp {
+    let mut stamp: SignalArchiveStamp = [(0, None); 2];
+    for (i, path) in [SIGNAL_ARCHIVE_ROTATED_FILE, SIGNAL_ARCHIVE_FILE].iter().enumerate() {
+        if let Ok(meta) = tokio::fs::metadata(path).await {
+            stamp[i] = (meta.len(), meta.modified().ok());
+        }
+    }
+    stamp
+}
+
+/// Het archief wordt alleen opnieuw geparst als een van de bestanden gewijzigd is;
+/// anders krijgt elke backtest request dezelfde geparste Vec.
+async fn load_signal_archive_cached(engine: &Engine) -> Arc<std::vec::Vec<SignalEvent>> {
+    let stamp = signal_archive_stamp().await;
+    if let Some((s, v)) = engine.signal_archive.lock().unwrap().as_ref() {
+        if *s == stamp {
+            return v.clone();
+        }
+    }
+    let v = Arc::new(load_signal_archive().await);
+    *engine.signal_archive.lock().unwrap() = Some((stamp, v.clone()));
+    v
+}
+
+/// Leest het signal-archief (eerst de geroteerde, dan de huidige file).
+/// Kapotte regels worden overgeslagen.
+async fn load_signal_archive() -> std::vec::Vec<SignalEvent> {
+    let mut out = std::vec::Vec::new();
+    for path in [SIGNAL_ARCHIVE_ROTATED_FILE, SIGNAL_ARCHIVE_FILE] {
+        if let Ok(content) = tokio::fs::read_to_string(path).await {
+            out.extend(content.lines().filter_map(|l| serde_json::from_str::<SignalEvent>(l).ok()));
+        }
+    }
+    out
+}
+
+const MARKET_SNAPSHOT_DIR: &str = "snapshots";
+
+#[derive(Serialize)]
+struct MarketSnapshotLine<'a> {
+    ts: i64,
+    #[serde(flatten)]
+    row: &'a Row,
+}
+
+// Eén regel per Row met het snapshot-tijdstip, zodat de bestanden direct in
+// pandas/duckdb (read_json lines) te laden zijn. Eén bestand per UTC dag.
+async fn write_market_snapshot(rows: &[Row], ts: i64) -> std::io::Result<usize> {
+    use tokio::io::AsyncWriteExt;
+
+    let day = chrono::DateTime::from_timestamp(ts, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d");
+    let path = format!("{}/market_{}.jsonl", MARKET_SNAPSHOT_DIR, day);
+
+    let mut buf = String::new();
+    for row in rows {
+        let line = serde_json::to_string(&MarketSnapshotLine { ts, row })
+            .map_err(std::io::Error::other)?;
+        buf.push_str(&line);
+        buf.push('\n');
+    }
+
+    tokio::fs::create_dir_all(MARKET_SNAPSHOT_DIR).await?;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await?;
+    file.write_all(buf.as_bytes()).await?;
+    Ok(rows.len())
+}
+
+// Houdt alleen de nieuwste `keep` dagbestanden; de datum in de naam sorteert chronologisch.
+async fn prune_market_snapshots(keep: usize) -> std::io::Result<usize> {
+    let mut files = std::vec::Vec::new();
+    let mut dir = tokio::fs::read_dir(MARKET_SNAPSHOT_DIR).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("market_") && name.ends_with(".jsonl") {
+            files.push(name);
         }
+    }
+    files.sort();
 
-        // Wacht 1 minuut voor volgende scan
-        sleep(Duration::from_secs(60)).await;
+    let excess = files.len().saturating_sub(keep);
+    for name in &files[..excess] {
+        tokio::fs::remove_file(format!("{}/{}", MARKET_SNAPSHOT_DIR, name)).await?;
     }
+    Ok(excess)
 }
 
-// NIEUW: Helper functie om pair uit title te extraheren
-fn extract_pair_from_title(title: &str) -> Option<String> {
-    let title_lower = title.to_lowercase();
+/// Sluit bars op de klok (signal_on_candle_close): zonder deze taak sluit een bar pas
+/// bij de eerste trade in de volgende bar, wat op stille pairs minuten kan duren.
+async fn run_signal_bar_closer(engine: Engine) {
+    loop {
+        sleep(Duration::from_secs(1)).await;
+        let (enabled, candle_sec) = {
+            let cfg = engine.config.lock().unwrap();
+            (cfg.signal_on_candle_close, cfg.signal_candle_sec)
+        };
+        if !enabled {
+            continue;
+        }
+        let now_bar = Utc::now().timestamp().div_euclid(candle_sec);
+        let mut closed = std::vec::Vec::new();
+        for mut t in engine.trades.iter_mut() {
+            if t.bar_eval.is_some() && now_bar > t.signal_bar {
+                closed.extend(close_signal_bar(&mut t, candle_sec));
+            }
+        }
+        // Pas na de iteratie pushen: push_signal mag de trades map niet geblokkeerd vinden
+        for ev in closed {
+            engine.push_signal(ev);
+        }
+    }
+}
 
-    // Use pre-sorted keywords to check more specific keywords first
-    for (keyword, pair) in SORTED_KEYWORDS.iter() {
-        if title_lower.contains(keyword) {
-            return Some(pair.clone());
+async fn run_market_snapshotter(engine: Engine) {
+    loop {
+        let (enabled, interval_min, retention) = {
+            let cfg = engine.config.lock().unwrap();
+            (cfg.market_snapshot_enabled, cfg.market_snapshot_interval_min.max(1), cfg.market_snapshot_retention.max(1))
+        };
+
+        if enabled {
+            // Alle pairs, niet alleen de actieve uit snapshot(): ook stille markten horen in de dataset
+            let rows = engine.all_rows();
+            match write_market_snapshot(&rows, chrono::Utc::now().timestamp()).await {
+                Ok(n) => log_debug!("[SNAPSHOT] {} rows weggeschreven", n),
+                Err(e) => log_warn!("[SNAPSHOT] Schrijven mislukt: {}", e),
+            }
+            match prune_market_snapshots(retention).await {
+                Ok(0) => {}
+                Ok(n) => log_info!("[SNAPSHOT] {} oude dagbestanden verwijderd", n),
+                Err(e) => log_warn!("[SNAPSHOT] Opruimen mislukt: {}", e),
+            }
         }
+
+        sleep(Duration::from_secs(interval_min * 60)).await;
     }
-    None
 }
 
 // ============================================================================
 // HOOFDSTUK 12 – SELF-EVALUATOR (ZELFLEREND)
 // ============================================================================
 
+// Signals worden geëvalueerd zodra ze zo oud zijn, bij de eerstvolgende run
+const SELF_EVAL_HORIZON_SEC: i64 = 300;
+const SELF_EVAL_INTERVAL_SEC: u64 = 60;
 
 async fn run_self_evaluator(engine: Engine) {
     loop {
-        sleep(Duration::from_secs(60)).await;
+        sleep(Duration::from_secs(SELF_EVAL_INTERVAL_SEC)).await;
         let now_ts = Utc::now().timestamp();
 
         let mut updated = false;
-        {
+        let start_threshold = engine.config.lock().unwrap().ai_success_threshold;
+        let stats_to_save = {
             let mut weights = engine.weights.lock().unwrap();
             let mut sigs = engine.signals.lock().unwrap();
+            let mut stats = engine.signal_stats.lock().unwrap();
 
             for ev in sigs.iter_mut() {
                 if ev.evaluated {
                     continue;
                 }
-                if now_ts - ev.ts < 300 {
+                if now_ts - ev.ts < ev.horizon() {
                     continue;
                 }
                 if ev.rating == "NONE" {
@@ -4068,7 +10923,7 @@ async fn run_self_evaluator(engine: Engine) {
                 let ret = (current_price - ev.price) / ev.price * 100.0;
 
                 let success_strong = ret >= 2.0;
-                let success_weak = ret >= 0.5 && ret < 2.0;
+                let success_weak = (0.5..2.0).contains(&ret);
                 let fail = ret <= -0.5;
 
                 let strong_step_up = 1.02;
@@ -4086,12 +10941,7 @@ async fn run_self_evaluator(engine: Engine) {
                     } else if fail {
                         *w *= step_down;
                     }
-                    if *w < 0.2 {
-                        *w = 0.2;
-                    }
-                    if *w > 5.0 {
-                        *w = 5.0;
-                    }
+                    *w = w.clamp(0.2, 5.0);
                 };
 
                 adjust(&mut weights.flow_w, ev.flow_score);
@@ -4101,6 +10951,13 @@ async fn run_self_evaluator(engine: Engine) {
                 adjust(&mut weights.anomaly_w, ev.anomaly_score);
                 adjust(&mut weights.trend_w, ev.trend_score);
 
+                // Per signaaltype leren: winst in de richting van het signaal
+                let profit = if ev.direction == "SELL" { -ret } else { ret };
+                stats
+                    .entry(ev.signal_type.clone())
+                    .or_insert_with(|| SignalStats::new(start_threshold))
+                    .update(profit);
+
                 // backtest-data invullen
                 ev.ret_5m = Some(ret);
                 ev.eval_horizon_sec = Some(now_ts - ev.ts);
@@ -4110,7 +10967,7 @@ async fn run_self_evaluator(engine: Engine) {
             }
 
             if updated {
-                println!(
+                log_debug!(
                     "Gewichten geüpdatet -> flow:{:.2} price:{:.2} whale:{:.2} vol:{:.2} anom:{:.2} trend:{:.2}",
                     weights.flow_w,
                     weights.price_w,
@@ -4119,7 +10976,19 @@ async fn run_self_evaluator(engine: Engine) {
                     weights.anomaly_w,
                     weights.trend_w
                 );
+                engine.log_event("weights", serde_json::json!({
+                    "flow_w": weights.flow_w,
+                    "price_w": weights.price_w,
+                    "whale_w": weights.whale_w,
+                    "volume_w": weights.volume_w,
+                    "anomaly_w": weights.anomaly_w,
+                    "trend_w": weights.trend_w,
+                }));
             }
+            updated.then(|| stats.clone())
+        };
+        if let Some(stats) = stats_to_save {
+            save_signal_stats(&stats).await;
         }
     }
 }
@@ -4129,40 +10998,100 @@ async fn run_self_evaluator(engine: Engine) {
 // ============================================================================
 
 
-async fn run_cleanup(engine: Engine) {
-    loop {
-        sleep(Duration::from_secs(600)).await;
+const QUIET_SUMMARY_INTERVAL_SEC: u64 = 60;
 
-        let now = Utc::now().timestamp();
-        let cutoff_trades = now - 12 * 3600;
-        let cutoff_candles = now - 24 * 3600;
-        let cutoff_orderbooks = now - 60; // Remove orderbooks older than 1 minute
+async fn run_quiet_hours_summary(engine: Engine) {
+    loop {
+        sleep(Duration::from_secs(QUIET_SUMMARY_INTERVAL_SEC)).await;
+        engine.flush_quiet_summary(Utc::now().timestamp());
+    }
+}
 
-        engine.trades.retain(|_, v| v.last_update_ts >= cutoff_trades);
+/// Retentiegrenzen voor één run_cleanup ronde. Los van de Engine zodat de
+/// beslissing wat weg mag zonder DashMaps te testen is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CleanupCutoffs {
+    trades: i64,
+    candles: i64,
+    orderbooks: i64,
+    // Recente ANOM flags vervallen na 5 uur
+    anom: i64,
+}
 
-        let mut to_reset = std::vec::Vec::new();
-        for c in engine.candles.iter() {
-            let last_ts = c.last_ts.unwrap_or(0);
-            if last_ts < cutoff_candles {
-                to_reset.push(c.key().clone());
-            }
+impl CleanupCutoffs {
+    fn new(now: i64, cfg: &AppConfig) -> Self {
+        Self {
+            trades: now - cfg.trade_retention_sec,
+            candles: now - cfg.candle_retention_sec,
+            orderbooks: now - cfg.orderbook_retention_sec,
+            anom: now - 5 * 3600,
         }
+    }
+
+    fn keep_trade(&self, t: &TradeState) -> bool {
+        t.last_update_ts >= self.trades
+    }
+
+    // Verlopen candles weghalen i.p.v. leeg resetten: een lege CandleState
+    // leest in snapshot als allemaal nullen. De volgende trade maakt hem opnieuw aan.
+    fn remove_candle(&self, c: &CandleState) -> bool {
+        c.last_ts.unwrap_or(0) < self.candles
+    }
+
+    fn keep_orderbook(&self, ob: &OrderbookState) -> bool {
+        ob.timestamp >= self.orderbooks
+    }
+
+    fn reset_anom(&self, t: &TradeState) -> bool {
+        t.last_update_ts < self.anom
+    }
+}
+
+async fn run_cleanup(engine: Engine) {
+    loop {
+        let interval = engine.config.lock().unwrap().cleanup_interval_sec.max(1);
+        sleep(Duration::from_secs(interval)).await;
+
+        let (cutoffs, trade_retention, candle_retention, orderbook_retention) = {
+            let cfg = engine.config.lock().unwrap();
+            (
+                CleanupCutoffs::new(Utc::now().timestamp(), &cfg),
+                cfg.trade_retention_sec,
+                cfg.candle_retention_sec,
+                cfg.orderbook_retention_sec,
+            )
+        };
+
+        engine.trades.retain(|_, v| cutoffs.keep_trade(v));
+
+        let to_reset: std::vec::Vec<String> = engine
+            .candles
+            .iter()
+            .filter(|c| cutoffs.remove_candle(c.value()))
+            .map(|c| c.key().clone())
+            .collect();
         for k in to_reset {
-            engine.candles.insert(k, CandleState::default());
+            // Biggest print hoort bij dezelfde sessie als de candle
+            if let Some(mut t) = engine.trades.get_mut(&k) {
+                t.max_notional_seen = 0.0;
+                t.max_notional_ts = 0;
+                t.max_notional_side = None;
+            }
+            engine.candles.remove(&k);
         }
 
-        // Cleanup old orderbooks
-        engine.orderbooks.retain(|_, v| v.timestamp >= cutoff_orderbooks);
+        engine.orderbooks.retain(|_, v| cutoffs.keep_orderbook(v));
 
-        // NIEUW: Reset recente ANOM flags na 5 uur
-        let cutoff_anom = now - (5 * 3600); // 5 uur
         for mut t in engine.trades.iter_mut() {
-            if t.last_update_ts < cutoff_anom {
+            if cutoffs.reset_anom(&t) {
                 t.recent_anom = false;
             }
         }
 
-        println!("Cleanup: oude trades (>12u), candles (>24u) en orderbooks (>1m) opgeschoond, oude ANOM flags gereset.");
+        log_debug!(
+            "Cleanup: oude trades (>{}s), candles (>{}s) en orderbooks (>{}s) opgeschoond, oude ANOM flags gereset.",
+            trade_retention, candle_retention, orderbook_retention
+        );
     }
 }
 
@@ -4170,34 +11099,273 @@ async fn run_cleanup(engine: Engine) {
 // HOOFDSTUK 14 – HTTP SERVER & API
 // ============================================================================
 
+// API schema versie voor externe tooling: ophogen bij elke wijziging in de vorm
+// van een response (velden hernoemd/verwijderd, nieuwe velden in Row e.d.).
+const API_SCHEMA_VERSION: u32 = 14;
+const API_VERSION_PREFIX: &str = "/api/v1/";
+
+/// /api/v1/<x> is een alias van /api/<x>: herschrijf de URI vóór de routing.
+fn strip_api_version(mut req: warp::http::Request<warp::hyper::Body>) -> warp::http::Request<warp::hyper::Body> {
+    let path = req.uri().path();
+    if let Some(rest) = path.strip_prefix(API_VERSION_PREFIX) {
+        let new_uri = match req.uri().query() {
+            Some(q) => format!("/api/{}?{}", rest, q),
+            None => format!("/api/{}", rest),
+        };
+        if let Ok(uri) = new_uri.parse() {
+            *req.uri_mut() = uri;
+        }
+    }
+    req
+}
+
+
+/// Valideert, activeert en bewaart een nieuwe config (form save en import).
+async fn apply_config(
+    config: Arc<Mutex<AppConfig>>,
+    new_cfg: AppConfig,
+    status: &str,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    if let Err(errors) = new_cfg.validate() {
+        return warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"status": "invalid", "errors": errors})),
+            warp::http::StatusCode::BAD_REQUEST,
+        );
+    }
+    init_log_level(&new_cfg.log_level);
+    let changes = {
+        let mut current = config.lock().unwrap();
+        let changes = config_diff(&current, &new_cfg);
+        *current = new_cfg.clone();
+        changes
+    };
+    if changes.is_empty() {
+        log_info!("[CONFIG] {}: geen wijzigingen", status);
+    }
+    for c in &changes {
+        log_info!("[CONFIG] {}: {} {} -> {}", status, c.field, c.old, c.new);
+    }
+    if let Err(e) = save_config(&new_cfg).await {
+        log_error!("[ERR] Kon config niet opslaan: {}", e);
+    }
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"status": status, "changes": changes})),
+        warp::http::StatusCode::OK,
+    )
+}
 
 async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
+    let timing_engine = engine.clone();
     let engine_filter = warp::any().map(move || engine.clone());
     let config_filter = warp::any().map(move || config.clone());
 
     let api_stats = warp::path!("api" / "stats")
         .and(engine_filter.clone())
-        .map(|engine: Engine| warp::reply::json(&engine.snapshot()));
+        .and(warp::query::<StatsQuery>())
+        .map(|engine: Engine, query: StatsQuery| {
+            let mut rows = if query.active_only.unwrap_or(false) {
+                engine.active_snapshot()
+            } else {
+                engine.snapshot()
+            };
+            if let Some(field) = query.sort {
+                sort_rows(&mut rows, field, query.order);
+            }
+            warp::reply::json(&rows)
+        });
 
     let api_signals = warp::path!("api" / "signals")
         .and(engine_filter.clone())
-        .map(|engine: Engine| warp::reply::json(&engine.signals_snapshot()));
+        .and(warp::query::<SignalsQuery>())
+        .map(|engine: Engine, query: SignalsQuery| warp::reply::json(&engine.signals_snapshot(&query)));
+
+    let api_signals_unread = warp::path!("api" / "signals" / "unread")
+        .and(warp::query::<HashMap<String, String>>())
+        .and(engine_filter.clone())
+        .map(|q: HashMap<String, String>, engine: Engine| {
+            let since_seq = q.get("since_seq").and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+            let (latest_seq, unread) = engine.signals_unread(since_seq);
+            // Tijdens een pauze worden signals nog wel vastgelegd, maar niet gemeld
+            let paused = engine.is_trading_paused();
+            warp::reply::json(&serde_json::json!({
+                "latest_seq": latest_seq,
+                "unread": if paused { 0 } else { unread },
+                "paused": paused,
+            }))
+        });
 
     let api_top10 = warp::path!("api" / "top10")
         .and(engine_filter.clone())
-        .map(|engine: Engine| warp::reply::json(&engine.top10_snapshot()));
+        .and(warp::query::<Top10Query>())
+        .map(|engine: Engine, query: Top10Query| warp::reply::json(&engine.top10_snapshot(&query)));
 
     let api_heatmap = warp::path!("api" / "heatmap")
         .and(engine_filter.clone())
         .map(|engine: Engine| warp::reply::json(&engine.heatmap_snapshot()));
 
+    let api_movers = warp::path!("api" / "movers")
+        .and(engine_filter.clone())
+        .and(warp::query::<MoversQuery>())
+        .map(|engine: Engine, query: MoversQuery| warp::reply::json(&engine.movers_snapshot(&query)));
+
+    let api_fx = warp::path!("api" / "fx")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.fx.lock().unwrap().clone()));
+
+    let api_portfolio = warp::path!("api" / "portfolio")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.portfolio_snapshot()));
+
+    let api_trade_advice = warp::path!("api" / "trade_advice")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.trade_advice_snapshot()));
+
+    let api_signal_stats_history = warp::path!("api" / "signal_stats" / "history")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| {
+            let stats: std::collections::BTreeMap<String, SignalStats> = engine
+                .signal_stats
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            warp::reply::json(&stats)
+        });
+
+    let api_pending_signals = warp::path!("api" / "pending_signals")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.pending_signals_snapshot()));
+
+    let api_market_breadth = warp::path!("api" / "market_breadth")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.market_breadth()));
+
+    let api_alerts = warp::path!("api" / "alerts")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.alerts_snapshot()));
+
+    // Optioneel: ?since=<unix>&until=<unix>&min_trades=<n>&archive=true
     let api_backtest = warp::path!("api" / "backtest")
+        .and(warp::query::<BacktestQuery>())
         .and(engine_filter.clone())
-        .map(|engine: Engine| warp::reply::json(&engine.backtest_snapshot()));
+        .and_then(|query: BacktestQuery, engine: Engine| async move {
+            let archived = if query.archive.unwrap_or(false) {
+                load_signal_archive_cached(&engine).await
+            } else {
+                Arc::new(std::vec::Vec::new())
+            };
+            Ok::<_, warp::Rejection>(warp::reply::json(&engine.backtest_snapshot(&query, &archived)))
+        });
 
     let api_manual_trades = warp::path!("api" / "manual_trades")
         .and(engine_filter.clone())
-        .map(|engine: Engine| warp::reply::json(&engine.manual_trades_snapshot()));
+        .and(warp::query::<ManualTradesQuery>())
+        .map(|engine: Engine, query: ManualTradesQuery| {
+            warp::reply::json(&engine.manual_trades_snapshot(query.tag.as_deref()))
+        });
+
+    let api_manual_stats = warp::path!("api" / "manual_stats")
+        .and(engine_filter.clone())
+        .and(warp::query::<ManualTradesQuery>())
+        .map(|engine: Engine, query: ManualTradesQuery| {
+            let trader = engine.manual_trader.lock().unwrap();
+            warp::reply::json(&trader.stats(query.tag.as_deref()))
+        });
+
+    let api_auto_trades = warp::path!("api" / "auto_trades")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.auto_trades_snapshot()));
+
+    // Pairs bevatten een "/", dus base en quote als twee segmenten: /api/pair/BTC/EUR
+    let api_pair = warp::path!("api" / "pair" / String / String)
+        .and(engine_filter.clone())
+        .map(|base: String, quote: String, engine: Engine| {
+            let pair = format!("{}/{}", base.to_uppercase(), quote.to_uppercase());
+            match engine.pair_detail(&pair) {
+                Some(detail) => warp::reply::with_status(warp::reply::json(&detail), warp::http::StatusCode::OK),
+                None => warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({"error": "unknown pair", "pair": pair})),
+                    warp::http::StatusCode::NOT_FOUND,
+                ),
+            }
+        });
+
+    let api_pairs = warp::path!("api" / "pairs")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.pairs_snapshot()));
+
+    let api_tape = warp::path!("api" / "tape" / String / String)
+        .and(engine_filter.clone())
+        .map(|base: String, quote: String, engine: Engine| {
+            let pair = format!("{}/{}", base.to_uppercase(), quote.to_uppercase());
+            match engine.tape_snapshot(&pair) {
+                Some(tape) => warp::reply::with_status(warp::reply::json(&tape), warp::http::StatusCode::OK),
+                None => warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({"error": "unknown pair", "pair": pair})),
+                    warp::http::StatusCode::NOT_FOUND,
+                ),
+            }
+        });
+
+    let api_orderbook = warp::path!("api" / "orderbook" / String / String)
+        .and(engine_filter.clone())
+        .map(|base: String, quote: String, engine: Engine| {
+            let pair = format!("{}/{}", base.to_uppercase(), quote.to_uppercase());
+            match engine.orderbook_snapshot(&pair) {
+                Some(book) => warp::reply::with_status(warp::reply::json(&book), warp::http::StatusCode::OK),
+                None => warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({"error": "no recent orderbook", "pair": pair})),
+                    warp::http::StatusCode::NOT_FOUND,
+                ),
+            }
+        });
+
+    let api_version = warp::path!("api" / "version")
+        .map(|| {
+            warp::reply::json(&serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "schema_version": API_SCHEMA_VERSION,
+                "prefix": API_VERSION_PREFIX,
+            }))
+        });
+
+    let api_http_stats = warp::path!("api" / "http_stats")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.http_stats.snapshot()));
+
+    let metrics = warp::path!("metrics")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| {
+            warp::reply::with_header(
+                render_metrics(&engine.signal_latency.snapshot()),
+                "content-type",
+                "text/plain; version=0.0.4",
+            )
+        });
+
+    let api_health = warp::path!("api" / "health")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.health_snapshot()));
+
+    let api_pause_get = warp::path!("api" / "pause")
+        .and(warp::get())
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&serde_json::json!({ "paused": engine.is_trading_paused() })));
+
+    let api_pause_post = warp::path!("api" / "pause")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(engine_filter.clone())
+        .and_then(|body: serde_json::Value, engine: Engine| async move {
+            // zonder "paused" in de body wordt de huidige stand omgedraaid
+            let paused = body["paused"].as_bool().unwrap_or(!engine.is_trading_paused());
+            let reply = match engine.set_trading_paused(paused).await {
+                Ok(()) => serde_json::json!({"success": true, "paused": paused}),
+                Err(error) => serde_json::json!({"success": false, "error": error}),
+            };
+            Ok::<_, warp::Rejection>(warp::reply::json(&reply))
+        });
 
     let api_manual_equity = warp::path!("api" / "manual_equity")
         .and(engine_filter.clone())
@@ -4207,6 +11375,7 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
         });
 
     let api_config_get = warp::path!("api" / "config")
+        .and(warp::get())
         .and(config_filter.clone())
         .map(|config: Arc<Mutex<AppConfig>>| {
             let cfg = config.lock().unwrap();
@@ -4214,21 +11383,44 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
         });
 
     let api_config_post = warp::path!("api" / "config")
+        .and(warp::post())
         .and(config_filter.clone())
         .and(warp::body::json())
-        .map(|config: Arc<Mutex<AppConfig>>, new_cfg: AppConfig| {
-            *config.lock().unwrap() = new_cfg.clone();
-            let _ = save_config(&new_cfg);
-            warp::reply::json(&serde_json::json!({"status": "saved"}))
+        .and_then(|config: Arc<Mutex<AppConfig>>, new_cfg: AppConfig| async move {
+            Ok::<_, warp::Rejection>(apply_config(config, new_cfg, "saved").await)
         });
 
-    let api_config_reset = warp::path!("api" / "config" / "reset")
+    let api_config_export = warp::path!("api" / "config" / "export")
+        .and(warp::get())
         .and(config_filter.clone())
         .map(|config: Arc<Mutex<AppConfig>>| {
+            let cfg = config.lock().unwrap().clone();
+            let body = serde_json::to_string_pretty(&cfg).unwrap_or_default();
+            warp::http::Response::builder()
+                .header("Content-Type", "application/json")
+                .header("Content-Disposition", "attachment; filename=\"whale_radar_config.json\"")
+                .body(body)
+        });
+
+    let api_config_import = warp::path!("api" / "config" / "import")
+        .and(warp::post())
+        .and(config_filter.clone())
+        .and(warp::body::json())
+        .and_then(|config: Arc<Mutex<AppConfig>>, new_cfg: AppConfig| async move {
+            Ok::<_, warp::Rejection>(apply_config(config, new_cfg, "imported").await)
+        });
+
+    let api_config_reset = warp::path!("api" / "config" / "reset")
+        .and(warp::post())
+        .and(config_filter.clone())
+        .and_then(|config: Arc<Mutex<AppConfig>>| async move {
             let default = AppConfig::default();
+            init_log_level(&default.log_level);
             *config.lock().unwrap() = default.clone();
-            let _ = save_config(&default);
-            warp::reply::json(&serde_json::json!({"status": "reset"}))
+            if let Err(e) = save_config(&default).await {
+                log_error!("[ERR] Kon config niet opslaan: {}", e);
+            }
+            Ok::<_, warp::Rejection>(warp::reply::json(&serde_json::json!({"status": "reset"})))
         });
 
     // NIEUW: API voor nieuws-sentiment (stap 4)
@@ -4253,14 +11445,13 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
         });
 
     // NIEUW: API voor stars historie
+    let api_stars = warp::path!("api" / "stars")
+        .and(engine_filter.clone())
+        .map(|engine: Engine| warp::reply::json(&engine.stars_snapshot()));
+
     let api_stars_history = warp::path!("api" / "stars_history")
         .and(engine_filter.clone())
-        .map(|engine: Engine| {
-            let history = engine.stars_history.lock().unwrap();
-            let mut sorted_history = history.history.clone();
-            sorted_history.sort_by(|a, b| b.ts.cmp(&a.ts));
-            warp::reply::json(&sorted_history)
-        });
+        .map(|engine: Engine| warp::reply::json(&engine.stars_history_snapshot()));
 
     let api_manual_trade_post = warp::path!("api" / "manual_trade")
         .and(warp::post())
@@ -4271,9 +11462,80 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
             let sl_pct = body["sl_pct"].as_f64().unwrap_or(2.0);
             let tp_pct = body["tp_pct"].as_f64().unwrap_or(5.0);
             let fee_pct = body["fee_pct"].as_f64().unwrap_or(0.26);
-            let manual_amount = body["manual_amount"].as_f64().unwrap_or(100.0);
-            let success = engine.manual_add_trade(pair, sl_pct, tp_pct, fee_pct, manual_amount).await;
-            Ok::<_, warp::Rejection>(warp::reply::json(&serde_json::json!({"success": success})))
+            let manual_amount = body["manual_amount"].as_f64().unwrap_or(MANUAL_BASE_NOTIONAL);
+            let sizing_mode = body["sizing_mode"].as_str().unwrap_or("fixed");
+            let sizing_value = body["sizing_value"].as_f64().unwrap_or(manual_amount);
+            // tags als array of als komma-gescheiden string
+            let tags: std::vec::Vec<String> = match &body["tags"] {
+                Value::Array(items) => items.iter().filter_map(|t| t.as_str().map(String::from)).collect(),
+                Value::String(s) => s.split(',').map(String::from).collect(),
+                _ => std::vec::Vec::new(),
+            };
+            let annotation = TradeAnnotation::new(body["note"].as_str(), &tags);
+            let reply = match engine.manual_add_trade(pair, sl_pct, tp_pct, fee_pct, sizing_mode, sizing_value, annotation).await {
+                Ok(()) => serde_json::json!({"success": true}),
+                Err(error) => serde_json::json!({"success": false, "error": error}),
+            };
+            Ok::<_, warp::Rejection>(warp::reply::json(&reply))
+        });
+
+    let api_manual_trade_partial = warp::path!("api" / "manual_trade" / "partial")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(engine_filter.clone())
+        .and_then(|body: serde_json::Value, engine: Engine| async move {
+            let pair = body["pair"].as_str().unwrap_or("");
+            let fraction = body["fraction"].as_f64().unwrap_or(0.5);
+            let reply = match engine.manual_partial_close(pair, fraction).await {
+                Ok(()) => serde_json::json!({"success": true}),
+                Err(error) => serde_json::json!({"success": false, "error": error}),
+            };
+            Ok::<_, warp::Rejection>(warp::reply::json(&reply))
+        });
+
+    // limit_price null of <= 0 annuleert de openstaande limit close
+    let api_manual_trade_limit = warp::path!("api" / "manual_trade" / "limit")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(engine_filter.clone())
+        .and_then(|body: serde_json::Value, engine: Engine| async move {
+            let pair = body["pair"].as_str().unwrap_or("");
+            let limit = body["limit_price"].as_f64().filter(|l| *l > 0.0);
+            let reply = match engine.manual_close_limit(pair, limit).await {
+                Ok(filled) => serde_json::json!({"success": true, "filled": filled}),
+                Err(error) => serde_json::json!({"success": false, "error": error}),
+            };
+            Ok::<_, warp::Rejection>(warp::reply::json(&reply))
+        });
+
+    // Alleen met Authorization: Bearer <WHALE_RADAR_API_TOKEN>; zonder env var staat het uit
+    let api_test_signal = warp::path!("api" / "test_signal")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::body::json())
+        .and(engine_filter.clone())
+        .map(|auth: Option<String>, body: serde_json::Value, engine: Engine| {
+            let token = std::env::var(API_TOKEN_ENV_VAR).ok().filter(|t| !t.is_empty());
+            let Some(token) = token else {
+                return warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({"success": false, "error": format!("{} niet gezet", API_TOKEN_ENV_VAR)})),
+                    warp::http::StatusCode::FORBIDDEN,
+                );
+            };
+            if auth.as_deref().and_then(|a| a.strip_prefix("Bearer ")) != Some(token.as_str()) {
+                return warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({"success": false, "error": "ongeldig token"})),
+                    warp::http::StatusCode::UNAUTHORIZED,
+                );
+            }
+            let reply = match test_signal_from_json(&body, Utc::now().timestamp()) {
+                Ok(ev) => {
+                    engine.push_signal(ev);
+                    serde_json::json!({"success": true, "seq": engine.signal_seq.load(Ordering::SeqCst)})
+                }
+                Err(error) => serde_json::json!({"success": false, "error": error}),
+            };
+            warp::reply::with_status(warp::reply::json(&reply), warp::http::StatusCode::OK)
         });
 
     let api_manual_trade_delete = warp::path!("api" / "manual_trade")
@@ -4282,27 +11544,83 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
         .and(engine_filter.clone())
         .and_then(|body: serde_json::Value, engine: Engine| async move {
             let pair = body["pair"].as_str().unwrap_or("");
-            let success = engine.manual_close_trade(pair).await;
-            Ok::<_, warp::Rejection>(warp::reply::json(&serde_json::json!({"success": success})))
+            let reply = match engine.manual_close_trade(pair).await {
+                Ok(()) => serde_json::json!({"success": true}),
+                Err(error) => serde_json::json!({"success": false, "error": error}),
+            };
+            Ok::<_, warp::Rejection>(warp::reply::json(&reply))
+        });
+
+    let api_manual_reset = warp::path!("api" / "manual_trades" / "reset")
+        .and(warp::post())
+        .and(engine_filter.clone())
+        .and_then(|engine: Engine| async move {
+            engine.manual_reset().await;
+            Ok::<_, warp::Rejection>(warp::reply::json(&serde_json::json!({"success": true})))
         });
 
     let index = warp::path::end().map(|| warp::reply::html(DASHBOARD_HTML));
 
-    let routes = api_stats
+    // In groepen geboxt: één lange .or() keten overschrijdt in release builds de
+    // query depth limit van rustc bij het berekenen van het future type
+    let market_routes = api_stats
         .or(api_signals)
         .or(api_top10)
         .or(api_heatmap)
+        .or(api_movers)
+        .or(api_trade_advice)
+        .or(api_portfolio)
+        .or(api_fx)
+        .or(api_alerts)
+        .or(api_market_breadth)
+        .or(api_pending_signals)
+        .or(api_signal_stats_history)
         .or(api_backtest)
-        .or(api_manual_trades)
+        .map(warp::Reply::into_response)
+        .boxed();
+
+    let trade_routes = api_manual_trades
         .or(api_manual_equity)
+        .or(api_manual_stats)
+        .or(api_auto_trades)
+        .or(api_pair)
+        .or(api_orderbook)
+        .or(api_tape)
+        .or(api_pairs)
+        .or(api_health)
+        .or(api_version)
+        .or(api_http_stats)
+        .or(metrics)
+        .or(api_pause_get)
+        .or(api_pause_post)
+        .or(api_signals_unread)
         .or(api_manual_trade_post)
         .or(api_manual_trade_delete)
-        .or(api_config_get)
+        .or(api_manual_trade_partial)
+        .or(api_manual_trade_limit)
+        .or(api_test_signal)
+        .or(api_manual_reset)
+        .map(warp::Reply::into_response)
+        .boxed();
+
+    let other_routes = api_config_get
         .or(api_config_post)
         .or(api_config_reset)
+        .or(api_config_export)
+        .or(api_config_import)
         .or(api_news)
+        .or(api_stars)
         .or(api_stars_history)
-        .or(index);
+        .or(index)
+        .map(warp::Reply::into_response)
+        .boxed();
+
+    let routes = market_routes
+        .or(trade_routes)
+        .or(other_routes)
+        .with(warp::log::custom(move |info| {
+            timing_engine.record_http(info.path(), info.status().as_u16(), info.elapsed());
+        }));
 
     let mut port: u16 = 8080;
     loop {
@@ -4311,18 +11629,29 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
         match TcpListener::bind(&addr_str) {
             Ok(listener) => {
                 drop(listener);
-                println!("Dashboard: http://0.0.0.0:{} (or http://localhost:{})", port, port);
-                println!("Open in browser: http://localhost:{}", port);
-                warp::serve(routes.clone())
-                    .run(([0, 0, 0, 0], port))  // Bind op alle interfaces
-                    .await;
+                log_info!("Dashboard: http://0.0.0.0:{} (or http://localhost:{})", port, port);
+                log_info!("Open in browser: http://localhost:{}", port);
+                let svc = warp::service(routes.clone());
+                let make_svc = warp::hyper::service::make_service_fn(move |_| {
+                    let svc = svc.clone();
+                    async move {
+                        Ok::<_, std::convert::Infallible>(warp::hyper::service::service_fn(move |req| {
+                            let mut svc = svc.clone();
+                            warp::hyper::service::Service::call(&mut svc, strip_api_version(req))
+                        }))
+                    }
+                });
+                let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));  // Bind op alle interfaces
+                if let Err(e) = warp::hyper::Server::bind(&addr).serve(make_svc).await {
+                    log_error!("[ERR] HTTP-server gestopt: {}", e);
+                }
                 break;
             }
             Err(_) => {
-                eprintln!("Port {} bezet, probeer volgende...", port);
+                log_warn!("Port {} bezet, probeer volgende...", port);
                 port += 1;
                 if port > 8090 {
-                    eprintln!(
+                    log_error!(
                         "Geen vrije poort gevonden tussen 8080 en 8090, HTTP-server stopt."
                     );
                     break;
@@ -4339,26 +11668,24 @@ async fn run_http(engine: Engine, config: Arc<Mutex<AppConfig>>) {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Fetching Kraken markets...");
-    let data: Value =
-        reqwest::get("https://api.kraken.com/0/public/AssetPairs")
-            .await?
-            .json()
-            .await?;
-
-    let result = data["result"]
-        .as_object()
-        .expect("Invalid JSON from Kraken AssetPairs");
-    println!("Kraken markets: {}", result.len());
+    let config = Arc::new(Mutex::new(load_config().await));
+    init_log_level(&config.lock().unwrap().log_level);
+
+    log_info!("Fetching Kraken markets...");
+    let result = fetch_asset_pairs().await;
+    log_info!("Kraken markets: {}", result.len());
 
     let mut kraken_keys: std::vec::Vec<String> = std::vec::Vec::new();
     let mut key_to_norm: HashMap<String, String> = HashMap::new();
     let mut ws_pairs: std::vec::Vec<String> = std::vec::Vec::new();
+    let mut fx_pair_keys: HashMap<String, String> = HashMap::new();
+    let quotes = config.lock().unwrap().quote_currencies.clone();
 
     for (k, v) in result.iter() {
         if let Some(wsname) = v["wsname"].as_str() {
             let norm = normalize_pair(wsname);
-            if norm.ends_with("/EUR") {
+            fx_pair_keys.insert(norm.clone(), k.clone());
+            if quote_currency(&norm).is_some_and(|q| quotes.iter().any(|c| c == q)) {
                 kraken_keys.push(k.clone());
                 key_to_norm.insert(k.clone(), norm);
                 ws_pairs.push(wsname.to_string());
@@ -4377,28 +11704,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let chunk_size = 20;
     let chunks: std::vec::Vec<std::vec::Vec<String>> = ws_pairs.chunks(chunk_size).map(|c| c.to_vec()).collect();
 
-    println!(
-        "Using {} pairs for anomaly scanner (REST), {} EUR pairs via WebSocket trades ({} WS workers)",
+    log_info!(
+        "Using {} pairs for anomaly scanner (REST), {} {} pairs via WebSocket trades ({} WS workers)",
         kraken_keys.len(),
         total_ws_pairs,
+        quotes.join("/"),
         chunks.len()
     );
 
-    let config = Arc::new(Mutex::new(load_config().await));
-    let engine = Engine::new();
+    let engine = Engine::new(config.clone());
     
     // Load manual trader state from JSON
     engine.load_manual_trader().await;
-    println!("Loaded manual trader state");
+    log_info!("Loaded manual trader state");
+
+    engine.load_auto_trader().await;
+    log_info!("Loaded auto trader state");
+    engine.load_trading_pause().await;
+    engine.load_alerts().await;
+    engine.load_news_sentiment().await;
+    *engine.signal_stats.lock().unwrap() = load_signal_stats().await;
 
     // Load stars history
-    engine.load_stars_history().await;
-    println!("Loaded stars history");
+    if let Err(e) = engine.load_stars_history().await {
+        log_error!("[ERR] Kon stars history niet laden: {}", e);
+    }
+    log_info!("Loaded stars history");
 
     let engine_for_ws = engine.clone();
 
     // Clone chunks for orderbook workers
     let ob_chunks: std::vec::Vec<std::vec::Vec<String>> = ws_pairs.chunks(chunk_size).map(|c| c.to_vec()).collect();
+    let ticker_chunks: std::vec::Vec<std::vec::Vec<String>> = ws_pairs.chunks(chunk_size).map(|c| c.to_vec()).collect();
 
     // Spawn HTTP server als eerste, zodat direct beschikbaar
     let engine_http = engine.clone();
@@ -4406,71 +11743,141 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tokio::spawn(async move {
         run_http(engine_http, config_http).await;  // Geen if let Err, want geen Result
     });
-    println!("HTTP server spawned, should be available soon at http://localhost:8080/");
+    log_info!("HTTP server spawned, should be available soon at http://localhost:8080/");
+
+    // ws_multiplex: trade + book (+ ticker) over één verbinding per chunk i.p.v. aparte workers
+    let (multiplex, ticker_ws) = {
+        let cfg = config.lock().unwrap();
+        (cfg.ws_multiplex, cfg.ticker_ws)
+    };
+    let mut ws_channels = vec!["trade"];
+    if multiplex {
+        ws_channels.push("book");
+        if ticker_ws {
+            ws_channels.push("ticker");
+        }
+    }
 
     // Spawn andere tasks
     for (i, chunk) in chunks.into_iter().enumerate() {
         let e = engine_for_ws.clone();
+        let channels = ws_channels.clone();
         tokio::spawn(async move {
-            if let Err(err) = run_kraken_worker(e, chunk, i).await {
-                eprintln!("WS worker {} error: {:?}", i, err);
+            if let Err(err) = run_kraken_worker(e, chunk, i, channels).await {
+                log_error!("WS worker {} error: {:?}", i, err);
             }
         });
         sleep(Duration::from_secs(2)).await;
     }
 
-    let engine_for_ob = engine.clone();
-    for (i, chunk) in ob_chunks.into_iter().enumerate() {
-        let e = engine_for_ob.clone();
-        tokio::spawn(async move {
-            if let Err(err) = run_orderbook_worker(e, chunk, i).await {
-                eprintln!("OB worker {} error: {:?}", i, err);
-            }
-        });
-        sleep(Duration::from_secs(2)).await;
+    if !multiplex {
+        let engine_for_ob = engine.clone();
+        for (i, chunk) in ob_chunks.into_iter().enumerate() {
+            let e = engine_for_ob.clone();
+            tokio::spawn(async move {
+                if let Err(err) = run_kraken_worker(e, chunk, i, vec!["book"]).await {
+                    log_error!("OB worker {} error: {:?}", i, err);
+                }
+            });
+            sleep(Duration::from_secs(2)).await;
+        }
     }
 
+    if ticker_ws && !multiplex {
+        for (i, chunk) in ticker_chunks.into_iter().enumerate() {
+            let e = engine.clone();
+            tokio::spawn(async move {
+                if let Err(err) = run_kraken_worker(e, chunk, i, vec!["ticker"]).await {
+                    log_error!("Ticker WS worker {} error: {:?}", i, err);
+                }
+            });
+            sleep(Duration::from_secs(2)).await;
+        }
+    }
+
+    // Tickers van alle gevolgde pairs voor de news keyword fallback
+    let news_pairs: std::vec::Vec<String> = key_to_norm.values().cloned().collect();
     let engine_anom = engine.clone();
     tokio::spawn(async move {
         if let Err(err) = run_anomaly_scanner(engine_anom, kraken_keys, key_to_norm).await {
-            eprintln!("Anomaly scanner error: {}", err);
+            log_error!("Anomaly scanner error: {}", err);
+        }
+    });
+
+    let engine_deriv = engine.clone();
+    tokio::spawn(async move {
+        run_derivatives_scanner(engine_deriv).await;
+    });
+
+    let engine_eval = engine.clone();
+    tokio::spawn(async move {
+        run_self_evaluator(engine_eval).await;  // Dit heeft geen error return, dus geen if
+    });
+
+    let engine_cleanup = engine.clone();
+    tokio::spawn(async move {
+        run_cleanup(engine_cleanup).await;  // Geen error
+    });
+
+    let engine_news = engine.clone();
+    tokio::spawn(async move {
+        if let Err(err) = run_news_scanner(engine_news, news_pairs).await {
+            log_error!("News scanner error: {}", err);
         }
     });
 
-    let engine_eval = engine.clone();
+    let engine_snapshot = engine.clone();
+    tokio::spawn(async move {
+        run_market_snapshotter(engine_snapshot).await;
+    });
+
+    let engine_quiet = engine.clone();
+    tokio::spawn(async move {
+        run_quiet_hours_summary(engine_quiet).await;
+    });
+
+    let engine_bar_closer = engine.clone();
+    tokio::spawn(async move {
+        run_signal_bar_closer(engine_bar_closer).await;
+    });
+
+    let engine_fx = engine.clone();
     tokio::spawn(async move {
-        run_self_evaluator(engine_eval).await;  // Dit heeft geen error return, dus geen if
+        run_fx_refresher(engine_fx, fx_pair_keys).await;
     });
 
-    let engine_cleanup = engine.clone();
+    let engine_auto_saver = engine.clone();
     tokio::spawn(async move {
-        run_cleanup(engine_cleanup).await;  // Geen error
+        run_auto_trader_saver(engine_auto_saver).await;
     });
 
-    let engine_news = engine.clone();
+    let engine_manual_saver = engine.clone();
     tokio::spawn(async move {
-        if let Err(err) = run_news_scanner(engine_news).await {
-            eprintln!("News scanner error: {}", err);
-        }
+        run_manual_trader_saver(engine_manual_saver).await;
     });
 
     let engine_stars_saver = engine.clone();
     tokio::spawn(async move {
         if let Err(err) = run_stars_history_saver(engine_stars_saver).await {
-            eprintln!("Stars saver error: {}", err);
+            log_error!("Stars saver error: {}", err);
         }
     });
 
+    let engine_news_saver = engine.clone();
+    tokio::spawn(async move {
+        run_news_sentiment_saver(engine_news_saver).await;
+    });
+
     // Wacht op shutdown (bv. Ctrl+C) in plaats van join, zodat app niet stopt bij worker failure
-    println!("All tasks spawned. App running. Press Ctrl+C to stop.");
+    log_info!("All tasks spawned. App running. Press Ctrl+C to stop.");
     tokio::signal::ctrl_c().await?;
-    println!("Shutting down...");
+    log_info!("Shutting down...");
     Ok(())
 }
 
 // NIEUW: Automatische saver voor stars historie
 async fn run_stars_history_saver(engine: Engine) -> Result<(), Box<dyn std::error::Error>> {
-    println!("[STARS SAVER] Started, will save every 10 seconds if dirty");
+    log_info!("[STARS SAVER] Started, will save every 10 seconds if dirty");
     loop {
         sleep(Duration::from_secs(10)).await;
 
@@ -4489,10 +11896,77 @@ async fn run_stars_history_saver(engine: Engine) -> Result<(), Box<dyn std::erro
                 Ok(_) => {
                     let mut history_guard = engine.stars_history.lock().unwrap();
                     history_guard.dirty = false;
-                    println!("[STARS SAVER] Saved successfully, set dirty=false");
+                    log_debug!("[STARS SAVER] Saved successfully, set dirty=false");
                 }
-                Err(e) => eprintln!("[STARS SAVER] Save error: {}", e),
+                Err(e) => log_error!("[STARS SAVER] Save error: {}", e),
+            }
+        }
+    }
+}
+
+async fn run_news_sentiment_saver(engine: Engine) {
+    loop {
+        let (persist, interval) = {
+            let cfg = engine.config.lock().unwrap();
+            (cfg.news_persist, cfg.news_persist_interval_sec.max(5))
+        };
+        sleep(Duration::from_secs(interval)).await;
+
+        if !persist || !engine.news_dirty.swap(false, Ordering::SeqCst) {
+            continue;
+        }
+        let entries = engine.news_sentiment_snapshot();
+        let result = match serde_json::to_string_pretty(&entries) {
+            Ok(json) => tokio::fs::write(NEWS_SENTIMENT_FILE, json).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            log_error!("[NEWS SAVER] Save error: {}", e);
+            engine.news_dirty.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+async fn run_auto_trader_saver(engine: Engine) {
+    loop {
+        sleep(Duration::from_secs(10)).await;
+
+        let snapshot = {
+            let mut trader = engine.auto_trader.lock().unwrap();
+            if !trader.dirty {
+                continue;
+            }
+            trader.dirty = false;
+            trader.clone()
+        };
+
+        if let Err(e) = snapshot.save().await {
+            log_error!("[AUTO SAVER] Save error: {}", e);
+            engine.auto_trader.lock().unwrap().dirty = true;
+        }
+    }
+}
+
+async fn run_manual_trader_saver(engine: Engine) {
+    loop {
+        sleep(Duration::from_secs(10)).await;
+
+        let snapshot = {
+            let mut trader = engine.manual_trader.lock().unwrap();
+            if !trader.dirty {
+                continue;
             }
+            trader.dirty = false;
+            trader.clone()
+        };
+
+        if let Err(e) = snapshot.save().await {
+            log_error!("[MANUAL SAVER] Save error: {}", e);
+            engine.manual_trader.lock().unwrap().dirty = true;
+            continue;
+        }
+        if let Err(e) = snapshot.save_equity().await {
+            log_error!("[MANUAL SAVER] Equity save error: {}", e);
         }
     }
 }
@@ -4502,3 +11976,1205 @@ async fn save_stars_history_to_file(data: &[TopRow]) -> Result<(), Box<dyn std::
     tokio::fs::write(STARS_HISTORY_FILE, json).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet_input() -> TradeScoreInput<'static> {
+        TradeScoreInput {
+            is_buy: true,
+            is_whale: false,
+            volume: 1.0,
+            notional: 100.0,
+            ewma_trade_size: 1.0,
+            ewma_notional: 100.0,
+            ewma_volume: 1.0,
+            pct: 0.0,
+            flow_pct: 50.0,
+            dir: "NEUTRAL",
+            flow_pct_5m: 50.0,
+            dir_5m: "NEUTRAL",
+            ret_5s: 0.0,
+            ret_30s: 0.0,
+            ret_120s: 0.0,
+            book_ratio: None,
+            anom_strength: None,
+        }
+    }
+
+    #[test]
+    fn rating_boundaries() {
+        assert_eq!(rating_for_score(7.5), "ALPHA BUY");
+        assert_eq!(rating_for_score(7.49), "STRONG BUY");
+        assert_eq!(rating_for_score(5.0), "STRONG BUY");
+        assert_eq!(rating_for_score(4.99), "BUY");
+        assert_eq!(rating_for_score(3.5), "BUY");
+        assert_eq!(rating_for_score(3.49), "EARLY BUY");
+        assert_eq!(rating_for_score(2.2), "EARLY BUY");
+        assert_eq!(rating_for_score(2.19), "NONE");
+        assert_eq!(rating_for_score(0.0), "NONE");
+    }
+
+    #[test]
+    fn pump_labels() {
+        let th = 0.6;
+        assert_eq!(pump_label_for(7.0, th + PUMP_CONF_MEGA_MARGIN, "BUY", th), "MEGA_PUMP");
+        // Genoeg score voor MEGA maar conf zonder marge: EARLY
+        assert_eq!(pump_label_for(9.0, th, "BUY", th), "EARLY_PUMP");
+        assert_eq!(pump_label_for(4.0, th, "BUY", th), "EARLY_PUMP");
+        assert_eq!(pump_label_for(3.99, 1.0, "BUY", th), "NONE");
+        assert_eq!(pump_label_for(4.0, th - 0.01, "BUY", th), "NONE");
+        assert_eq!(pump_label_for(10.0, 2.0, "SELL", th), "NONE");
+    }
+
+    #[test]
+    fn whale_pred_labels() {
+        let cfg = AppConfig::default();
+        assert_eq!(whale_pred_label_for(7.0, &ScoreConfig::from(&cfg)), "HIGH");
+        assert_eq!(whale_pred_label_for(4.0, &ScoreConfig::from(&cfg)), "MEDIUM");
+        assert_eq!(whale_pred_label_for(2.0, &ScoreConfig::from(&cfg)), "LOW");
+        assert_eq!(whale_pred_label_for(1.99, &ScoreConfig::from(&cfg)), "NONE");
+    }
+
+    #[test]
+    fn whale_pred_labels_follow_config_thresholds() {
+        let cfg = AppConfig {
+            whale_pred_high_threshold: 8.5,
+            whale_pred_medium_threshold: 5.0,
+            whale_pred_low_threshold: 3.0,
+            ..AppConfig::default()
+        };
+        assert!(cfg.validate().is_ok());
+        assert_eq!(whale_pred_label_for(8.5, &ScoreConfig::from(&cfg)), "HIGH");
+        assert_eq!(whale_pred_label_for(8.49, &ScoreConfig::from(&cfg)), "MEDIUM");
+        assert_eq!(whale_pred_label_for(5.0, &ScoreConfig::from(&cfg)), "MEDIUM");
+        assert_eq!(whale_pred_label_for(4.99, &ScoreConfig::from(&cfg)), "LOW");
+        assert_eq!(whale_pred_label_for(3.0, &ScoreConfig::from(&cfg)), "LOW");
+        assert_eq!(whale_pred_label_for(2.99, &ScoreConfig::from(&cfg)), "NONE");
+        let inverted = AppConfig { whale_pred_medium_threshold: 9.0, ..cfg };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn notional_formatting() {
+        assert_eq!(format_notional(950.0), "950");
+        assert_eq!(format_notional(999.6), "1.0k");
+        assert_eq!(format_notional(12_345.0), "12.3k");
+        assert_eq!(format_notional(999_960.0), "1.00M");
+        assert_eq!(format_notional(4_560_000.0), "4.56M");
+        assert_eq!(format_notional(1.2e9), "1.20B");
+        assert_eq!(format_notional(-25_000.0), "-25.0k");
+    }
+
+    fn manual_with_position(pair: &str, price: f64) -> ManualTraderState {
+        let mut trader = ManualTraderState::new();
+        trader
+            .add_trade(pair, Fill::exact(price), 5.0, 10.0, 0.0, 100.0, TradeAnnotation::new(None, &[]), 0, f64::MAX)
+            .unwrap();
+        trader
+    }
+
+    #[test]
+    fn limit_close_waits_for_price() {
+        let mut trader = manual_with_position("BTC/EUR", 100.0);
+        assert_eq!(trader.set_limit_close("BTC/EUR", Some(110.0), Fill::exact(100.0)), Ok(false));
+        assert!(trader.fill_limit_close("BTC/EUR", Fill::exact(109.9)).is_none());
+
+        let record = trader.fill_limit_close("BTC/EUR", Fill::exact(110.0)).unwrap();
+        assert_eq!(record.reason, "LIMIT");
+        assert_eq!(record.limit_price, Some(110.0));
+        assert!((record.pnl - 10.0).abs() < 1e-9);
+        assert!(trader.trades.is_empty());
+        assert!(trader.dirty);
+    }
+
+    #[test]
+    fn marketable_limit_fills_at_current_price() {
+        let mut trader = manual_with_position("BTC/EUR", 100.0);
+        assert_eq!(trader.set_limit_close("BTC/EUR", Some(95.0), Fill::exact(102.0)), Ok(true));
+        let record = trader.closed_trades.last().unwrap();
+        assert_eq!(record.exit_price, 102.0);
+        assert_eq!(record.limit_price, Some(95.0));
+    }
+
+    #[test]
+    fn limit_close_can_be_cancelled() {
+        let mut trader = manual_with_position("BTC/EUR", 100.0);
+        trader.set_limit_close("BTC/EUR", Some(110.0), Fill::exact(100.0)).unwrap();
+        trader.set_limit_close("BTC/EUR", None, Fill::exact(100.0)).unwrap();
+        assert!(trader.fill_limit_close("BTC/EUR", Fill::exact(200.0)).is_none());
+        assert!(trader.set_limit_close("BTC/EUR", Some(-1.0), Fill::exact(100.0)).is_err());
+        assert!(trader.set_limit_close("ETH/EUR", Some(1.0), Fill::exact(100.0)).is_err());
+    }
+
+    #[test]
+    fn paper_fills_cross_the_spread_and_slip() {
+        let buy = simulate_fill(100.0, true, Some((99.9, 100.1)), 10.0);
+        assert!((buy.price - 100.1 * 1.001).abs() < 1e-9);
+        assert!(buy.slippage_bps > 10.0);
+
+        let sell = simulate_fill(100.0, false, Some((99.9, 100.1)), 10.0);
+        assert!((sell.price - 99.9 * 0.999).abs() < 1e-9);
+        assert!(sell.slippage_bps > 10.0);
+
+        // Zonder boek alleen de slippage t.o.v. de referentie
+        let plain = simulate_fill(100.0, true, None, 5.0);
+        assert!((plain.price - 100.05).abs() < 1e-9);
+        assert!((plain.slippage_bps - 5.0).abs() < 1e-9);
+        assert_eq!(simulate_fill(100.0, false, None, 0.0), Fill::exact(100.0));
+    }
+
+    #[test]
+    fn trade_record_carries_entry_and_exit_slippage() {
+        let mut trader = ManualTraderState::new();
+        let entry = simulate_fill(100.0, true, None, 10.0);
+        trader
+            .add_trade("BTC/EUR", entry, 5.0, 10.0, 0.0, 100.0, TradeAnnotation::new(None, &[]), 0, f64::MAX)
+            .unwrap();
+        trader.close_trade("BTC/EUR", simulate_fill(100.0, false, None, 10.0), "MANUAL");
+        let record = trader.closed_trades.last().unwrap();
+        assert!((record.slippage_bps - 20.0).abs() < 1e-6);
+        assert!(record.pnl < 0.0);
+    }
+
+    #[test]
+    fn effective_positions_follow_correlation() {
+        let w = [1.0 / 3.0; 3];
+        assert!((effective_positions(&w, |_, _| Some(0.0)) - 3.0).abs() < 1e-9);
+        assert!((effective_positions(&w, |_, _| Some(1.0)) - 1.0).abs() < 1e-9);
+        assert!((effective_positions(&w, |_, _| None) - 3.0).abs() < 1e-9);
+        assert!((effective_positions(&[1.0], |_, _| None) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn correlation_of_minute_returns() {
+        let closes_a: std::vec::Vec<(f64, f64)> =
+            (0..30).map(|i| (i as f64 * 60.0, 100.0 + if i % 2 == 0 { 1.0 } else { 0.0 } + i as f64 * 0.1)).collect();
+        let closes_b: std::vec::Vec<(f64, f64)> = closes_a.iter().map(|(t, p)| (*t, p * 2.0)).collect();
+        let closes_c: std::vec::Vec<(f64, f64)> = closes_a.iter().map(|(t, p)| (*t, 1000.0 / p)).collect();
+        let (a, b, c) = (minute_returns(&closes_a), minute_returns(&closes_b), minute_returns(&closes_c));
+        let (corr_ab, n) = return_correlation(&a, &b).unwrap();
+        assert_eq!(n, 29);
+        assert!(corr_ab > 0.999);
+        assert!(return_correlation(&a, &c).unwrap().0 < -0.99);
+        // Te weinig overlap
+        let short = minute_returns(&closes_a[..5]);
+        assert!(return_correlation(&short, &b).is_none());
+    }
+
+    #[test]
+    fn quiet_trade_scores_nothing() {
+        let s = score_trade(&quiet_input(), &ScoreWeights::default(), &ScoreConfig::from(&AppConfig::default()));
+        assert_eq!(s.total_score, 0.0);
+        assert_eq!(s.rating, "NONE");
+        assert_eq!(s.pump_score, 0.0);
+        assert_eq!(s.pump_label, "NONE");
+        assert_eq!(s.early, "NONE");
+        assert_eq!(s.alpha, "NONE");
+    }
+
+    #[test]
+    fn score_trade_is_deterministic() {
+        let mut input = quiet_input();
+        input.is_whale = true;
+        input.notional = 50_000.0;
+        input.dir = "BUY";
+        input.flow_pct = 80.0;
+        input.ret_5s = 0.8;
+        input.book_ratio = Some(0.8);
+        let (w, cfg) = (ScoreWeights::default(), AppConfig::default());
+        assert_eq!(score_trade(&input, &w, &ScoreConfig::from(&cfg)), score_trade(&input, &w, &ScoreConfig::from(&cfg)));
+    }
+
+    #[test]
+    fn whale_score_book_boost_only_with_fresh_book() {
+        let mut input = quiet_input();
+        input.is_whale = true;
+        let (w, cfg) = (ScoreWeights::default(), AppConfig::default());
+        let base = score_trade(&input, &w, &ScoreConfig::from(&cfg)).whale_score;
+
+        input.book_ratio = Some(0.8);
+        assert!((score_trade(&input, &w, &ScoreConfig::from(&cfg)).whale_score - (base + 0.8)).abs() < 1e-9);
+
+        // Sell tegen een bid-zwaar boek krijgt geen boost
+        input.is_buy = false;
+        assert_eq!(score_trade(&input, &w, &ScoreConfig::from(&cfg)).whale_score, base);
+    }
+
+    #[test]
+    fn negative_returns_do_not_pump() {
+        let mut input = quiet_input();
+        input.ret_5s = -5.0;
+        input.ret_30s = -5.0;
+        input.ret_120s = -5.0;
+        let s = score_trade(&input, &ScoreWeights::default(), &ScoreConfig::from(&AppConfig::default()));
+        assert_eq!(s.pump_score, 0.0);
+        assert_eq!(s.pump_conf, 0.0);
+    }
+
+    #[test]
+    fn strong_buy_flow_sets_early_and_alpha_flags() {
+        let mut input = quiet_input();
+        input.is_whale = true;
+        input.notional = 1_000_000.0;
+        input.pct = 3.0;
+        input.dir = "BUY";
+        input.flow_pct = 90.0;
+        input.dir_5m = "BUY";
+        input.flow_pct_5m = 90.0;
+        input.anom_strength = Some(100.0);
+        let cfg = AppConfig::default();
+        let s = score_trade(&input, &ScoreWeights::default(), &ScoreConfig::from(&cfg));
+        assert_eq!(s.rating, "ALPHA BUY");
+        assert_eq!(s.early, "BUY");
+        assert_eq!(s.alpha, if s.total_score >= cfg.alpha_buy_threshold { "BUY" } else { "NONE" });
+
+        // Zelfde score zonder BUY richting: geen flags
+        input.dir = "SELL";
+        let s = score_trade(&input, &ScoreWeights::default(), &ScoreConfig::from(&cfg));
+        assert_eq!(s.early, "NONE");
+        assert_eq!(s.alpha, "NONE");
+    }
+
+    #[test]
+    fn test_signal_fills_defaults_and_is_excluded_from_evaluation() {
+        let body = serde_json::json!({"pair": "XBT/EUR", "signal_type": "WHALE", "notional": 250000.0, "evaluated": false, "ret_5m": 3.0});
+        let ev = test_signal_from_json(&body, 1_700_000_000).unwrap();
+        assert!(ev.test);
+        assert_eq!(ev.pair, "XBT/EUR");
+        assert_eq!(ev.signal_type, "WHALE");
+        assert_eq!(ev.direction, "BUY");
+        assert_eq!(ev.ts, 1_700_000_000);
+        assert_eq!(ev.notional_fmt, "250.0k");
+        assert!(ev.evaluated);
+        assert_eq!(ev.ret_5m, None);
+
+        assert!(test_signal_from_json(&serde_json::json!({"signal_type": "NOPE"}), 0).is_err());
+        assert!(test_signal_from_json(&serde_json::json!([1, 2]), 0).is_err());
+        assert!(test_signal_from_json(&Value::Null, 0).is_ok());
+    }
+
+    #[test]
+    fn fx_rate_uses_direct_or_inverted_pair() {
+        let mut prices = HashMap::new();
+        prices.insert("EUR/USD".to_string(), 1.25);
+        prices.insert("USDT/EUR".to_string(), 0.8);
+        assert_eq!(fx_rate_from_prices("USDT", "EUR", &prices), Some(0.8));
+        assert_eq!(fx_rate_from_prices("USD", "EUR", &prices), Some(0.8));
+        assert_eq!(fx_rate_from_prices("EUR", "USD", &prices), Some(1.25));
+        assert_eq!(fx_rate_from_prices("GBP", "EUR", &prices), None);
+    }
+
+    #[test]
+    fn flow_window_evicts_old_samples_and_caps_length() {
+        let (mut buys, mut sells) = (VecDeque::new(), VecDeque::new());
+        for i in 0..10 {
+            update_flow_window(&mut buys, &mut sells, i as f64, 1.0, i % 2 == 0, (4.0, 0.6, 0.4), false);
+        }
+        assert!(buys.iter().chain(sells.iter()).all(|(ts, _)| *ts >= 5.0));
+        assert_eq!(buys.len() + sells.len(), 5);
+
+        let mut prices = VecDeque::new();
+        for i in 0..(SAMPLE_BUFFER_CAP + 100) {
+            push_bounded(&mut prices, (1_000.0, i as f64), 0.0);
+        }
+        assert_eq!(prices.len(), SAMPLE_BUFFER_CAP);
+        assert_eq!(prices.front().map(|p| p.1), Some(100.0));
+
+        // De cap geldt voor beide kanten samen; de oudste samples gaan eerst
+        let (mut buys, mut sells) = (VecDeque::new(), VecDeque::new());
+        for i in 0..(SAMPLE_BUFFER_CAP + 100) {
+            let side = if i % 2 == 0 { &mut buys } else { &mut sells };
+            side.push_back((i as f64 * 0.001, 1.0));
+            evict_flow_before(&mut buys, &mut sells, 0.0);
+        }
+        assert_eq!(buys.len() + sells.len(), SAMPLE_BUFFER_CAP);
+        assert_eq!(buys.front().map(|b| b.0), Some(100.0 * 0.001));
+        assert_eq!(sells.front().map(|s| s.0), Some(101.0 * 0.001));
+    }
+
+    // Handmatige benchmark: cargo test --release -- --ignored --nocapture flow_window_high_rate
+    #[test]
+    #[ignore]
+    fn bench_flow_window_high_rate() {
+        let (mut buys, mut sells) = (VecDeque::new(), VecDeque::new());
+        let trades = 100_000;
+        // ~50 trades/s binnen een venster van 300s: ~15k samples in de buffers
+        let ts_of = |i: usize| i as f64 * 0.02;
+        let start = std::time::Instant::now();
+        for i in 0..trades {
+            update_flow_window(&mut buys, &mut sells, ts_of(i), 1.0, i % 3 != 0, (300.0, 0.6, 0.4), false);
+        }
+        let per_trade = start.elapsed().as_nanos() as f64 / trades as f64;
+        println!("update_flow_window: {:.0} ns/trade, {} samples", per_trade, buys.len() + sells.len());
+
+        // Baseline: Vec + retain over de hele buffer bij elke trade
+        let mut vb: Vec<(f64, f64)> = Vec::new();
+        let mut vs = vb.clone();
+        let start = std::time::Instant::now();
+        for i in 0..trades {
+            let ts = ts_of(i);
+            if i % 3 != 0 {
+                vb.push((ts, 1.0));
+            } else {
+                vs.push((ts, 1.0));
+            }
+            vb.retain(|(x, _)| *x >= ts - 300.0);
+            vs.retain(|(x, _)| *x >= ts - 300.0);
+            std::hint::black_box(buy_share(vb.iter(), vs.iter(), ts - 300.0, false));
+        }
+        let baseline = start.elapsed().as_nanos() as f64 / trades as f64;
+        println!(
+            "Vec+retain baseline: {:.0} ns/trade, {} samples ({:.1}x)",
+            baseline,
+            vb.len() + vs.len(),
+            baseline / per_trade.max(1e-9)
+        );
+    }
+
+    #[test]
+    fn quiet_window_wraps_midnight() {
+        let (start, end) = (parse_hhmm("23:00").unwrap(), parse_hhmm("07:00").unwrap());
+        assert!(in_quiet_window(parse_hhmm("04:00").unwrap(), start, end));
+        assert!(in_quiet_window(start, start, end));
+        assert!(!in_quiet_window(end, start, end));
+        assert!(!in_quiet_window(parse_hhmm("12:30").unwrap(), start, end));
+        assert!(!in_quiet_window(600, 600, 600));
+        assert_eq!(parse_hhmm("24:00"), None);
+        assert_eq!(parse_hhmm("7"), None);
+    }
+
+    fn utc_ts(y: i32, m: u32, d: u32, h: u32) -> i64 {
+        chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, 0, 0).unwrap().and_utc().timestamp()
+    }
+
+    #[test]
+    fn tz_offset_follows_dst() {
+        let ams = "Europe/Amsterdam";
+        assert_eq!(tz_offset_sec(ams, utc_ts(2024, 1, 15, 12)), 3600);
+        assert_eq!(tz_offset_sec(ams, utc_ts(2024, 7, 1, 12)), 7200);
+        // 31 maart 02:00 CET = 01:00 UTC, 27 oktober 03:00 CEST = 01:00 UTC
+        assert_eq!(tz_offset_sec(ams, utc_ts(2024, 3, 31, 1) - 1), 3600);
+        assert_eq!(tz_offset_sec(ams, utc_ts(2024, 3, 31, 1)), 7200);
+        assert_eq!(tz_offset_sec(ams, utc_ts(2024, 10, 27, 1) - 1), 7200);
+        assert_eq!(tz_offset_sec(ams, utc_ts(2024, 10, 27, 1)), 3600);
+        // Ver voorbij 2037
+        assert_eq!(tz_offset_sec(ams, utc_ts(2050, 7, 1, 12)), 7200);
+
+        assert_eq!(tz_offset_sec("Australia/Sydney", utc_ts(2024, 1, 15, 0)), 39600);
+        assert_eq!(tz_offset_sec("Australia/Sydney", utc_ts(2024, 7, 15, 0)), 36000);
+        assert_eq!(tz_offset_sec("America/New_York", utc_ts(2024, 7, 1, 12)), -14400);
+        assert_eq!(tz_offset_sec("UTC", 0), 0);
+        // Onbekende zone rekent in UTC
+        assert_eq!(tz_offset_sec("Mars/Olympus", utc_ts(2024, 7, 1, 12)), 0);
+    }
+
+    #[test]
+    fn signal_latency_percentiles_over_window() {
+        let latency = SignalLatency::default();
+        for ms in 1..=100u64 {
+            latency.record(Duration::from_millis(ms));
+        }
+        let snap = latency.snapshot();
+        assert_eq!(snap.count, 100);
+        assert_eq!(snap.p50_ms, 50.0);
+        assert_eq!(snap.p99_ms, 99.0);
+        assert_eq!(snap.max_ms, 100.0);
+        let text = render_metrics(&snap);
+        assert!(text.contains("whale_radar_signal_latency_seconds{quantile=\"0.99\"} 0.099"));
+        assert!(text.contains("whale_radar_signal_latency_seconds_count 100"));
+    }
+
+    #[test]
+    fn eval_horizon_per_type_falls_back_to_default() {
+        assert!(AppConfig::default().eval_horizon_by_type.is_empty());
+        let cfg = AppConfig {
+            eval_horizon_by_type: HashMap::from([("WHALE".to_string(), 900)]),
+            eval_horizon_sec: 240,
+            ..AppConfig::default()
+        };
+        assert_eq!(cfg.eval_horizon_for("WHALE"), 900);
+        assert_eq!(cfg.eval_horizon_for("EARLY"), 240);
+        assert_eq!(cfg.eval_horizon_for("ANOM"), 240);
+
+        // Oude, vooraf ingevulde map telt als niet ingesteld
+        let mut raw = serde_json::json!({
+            "eval_horizon_by_type": SIGNAL_TYPES.iter().map(|t| (t.to_string(), SELF_EVAL_HORIZON_SEC)).collect::<HashMap<_, _>>(),
+            "eval_horizon_sec": 240,
+        });
+        assert_eq!(migrate_config_defaults(&mut raw).len(), 1);
+        let cfg: AppConfig = serde_json::from_value(raw).unwrap();
+        assert_eq!(cfg.eval_horizon_for("EARLY"), 240);
+    }
+
+    #[test]
+    fn divergence_detects_accumulation_and_distribution() {
+        let settings = DivergenceSettings::default();
+        let window = |samples: &[(f64, f64, f64)]| samples.iter().copied().collect::<VecDeque<_>>();
+
+        // Buy-aandeel 45% -> 70% bij vlakke prijs: accumulatie
+        let acc = window(&[(0.0, 45.0, 100.0), (200.0, 60.0, 100.1), (280.0, 70.0, 100.2)]);
+        assert_eq!(detect_divergence(&acc, 280.0, &settings).map(|d| d.0), Some("BULLISH"));
+
+        // Prijs +1% terwijl buy-aandeel 65% -> 40%: distributie
+        let dist = window(&[(0.0, 65.0, 100.0), (280.0, 40.0, 101.0)]);
+        assert_eq!(detect_divergence(&dist, 280.0, &settings).map(|d| d.0), Some("BEARISH"));
+
+        // Flow en prijs samen omhoog: momentum, geen divergentie
+        let momentum = window(&[(0.0, 45.0, 100.0), (280.0, 70.0, 101.0)]);
+        assert_eq!(detect_divergence(&momentum, 280.0, &settings), None);
+
+        // Venster nog niet half gevuld
+        assert_eq!(detect_divergence(&acc, 100.0, &settings), None);
+    }
+
+    #[test]
+    fn join_within_drops_whole_sentences() {
+        let parts = vec!["Prijs beweegt zijwaarts.".to_string(), "Neutrale markt flow.".to_string(), "Vroege koopindicatie.".to_string()];
+        assert_eq!(join_within(&parts, 200), "Prijs beweegt zijwaarts. Neutrale markt flow. Vroege koopindicatie.");
+        assert_eq!(join_within(&parts, 50), "Prijs beweegt zijwaarts. Neutrale markt flow.");
+        assert_eq!(join_within(&parts, 10), "Prijs bewe");
+    }
+
+    #[test]
+    fn analysis_language_follows_locale_when_auto() {
+        let mut settings = AnalysisSettings::default();
+        assert_eq!(settings.language_for("nl-NL"), "nl");
+        assert_eq!(settings.language_for("en-GB"), "en");
+        settings.language = "nl".to_string();
+        assert_eq!(settings.language_for("en-GB"), "nl");
+    }
+
+    #[test]
+    fn sort_rows_orders_and_keeps_missing_last() {
+        struct P(Option<f64>);
+        impl SortableRow for P {
+            fn sort_value(&self, _field: SortField) -> Option<f64> {
+                self.0
+            }
+        }
+        let mut rows = vec![P(Some(1.0)), P(None), P(Some(3.0)), P(Some(2.0))];
+        sort_rows(&mut rows, SortField::Pct1h, SortOrder::Desc);
+        assert_eq!(rows.iter().map(|p| p.0).collect::<Vec<_>>(), vec![Some(3.0), Some(2.0), Some(1.0), None]);
+        sort_rows(&mut rows, SortField::Pct1h, SortOrder::Asc);
+        assert_eq!(rows.iter().map(|p| p.0).collect::<Vec<_>>(), vec![Some(1.0), Some(2.0), Some(3.0), None]);
+    }
+
+    #[test]
+    fn config_diff_reports_changed_fields_with_dotted_paths() {
+        let old = AppConfig::default();
+        assert!(config_diff(&old, &old).is_empty());
+        let mut new = old.clone();
+        new.flow_weight = old.flow_weight + 0.3;
+        new.flow.short_window_sec = old.flow.short_window_sec + 10.0;
+        let changes = config_diff(&old, &new);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["flow.short_window_sec", "flow_weight"]);
+        assert_eq!(changes[1].old, serde_json::json!(old.flow_weight));
+        assert_eq!(changes[1].new, serde_json::json!(new.flow_weight));
+    }
+
+    #[test]
+    fn nan_inputs_yield_finite_scores_and_sort_without_panic() {
+        let input = TradeScoreInput {
+            pct: f64::NAN,
+            flow_pct: f64::NAN,
+            notional: f64::INFINITY,
+            ewma_trade_size: 0.0,
+            ewma_notional: 0.0,
+            ewma_volume: 0.0,
+            ret_5s: f64::NAN,
+            book_ratio: Some(f64::NAN),
+            ..quiet_input()
+        };
+        let s = score_trade(&input, &ScoreWeights::default(), &ScoreConfig::from(&AppConfig::default()));
+        for v in [s.flow_score, s.price_score, s.whale_score, s.volume_score, s.anomaly_score, s.trend_score] {
+            assert!(v.is_finite());
+        }
+        assert!(s.total_score.is_finite() && s.pump_score.is_finite() && s.whale_pred_score.is_finite());
+
+        let mut scores = [1.0, f64::NAN, 3.0, f64::NEG_INFINITY, -2.0];
+        scores.sort_by(|a, b| score_cmp(*b, *a));
+        assert_eq!(scores[0], 3.0);
+        assert_eq!(scores[1], 1.0);
+        assert_eq!(scores[4], -2.0);
+    }
+
+    #[test]
+    fn pair_aliases_only_change_display_name() {
+        let mut cfg = AppConfig::default();
+        cfg.pair_aliases.insert("BTC/EUR".to_string(), " Bitcoin ".to_string());
+        assert!(cfg.validate().is_ok());
+        assert_eq!(pair_display_name(&cfg.pair_aliases, "BTC/EUR"), "Bitcoin");
+        assert_eq!(pair_display_name(&cfg.pair_aliases, "ETH/EUR"), "ETH/EUR");
+        cfg.pair_aliases.insert("BTCEUR".to_string(), "Bitcoin".to_string());
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn compound_equity_differs_from_summed_returns() {
+        // +10% dan -10%: som is 0%, samengesteld verlies van 1%
+        let (curve, dd) = compound_equity(&[10.0, -10.0], 1000.0, 1.0);
+        assert!((curve[0] - 1100.0).abs() < 1e-9);
+        assert!((curve[1] - 990.0).abs() < 1e-9);
+        assert!((dd - 10.0).abs() < 1e-9);
+        // Halve positie: 1000 * 1.05 * 0.95
+        let (half, _) = compound_equity(&[10.0, -10.0], 1000.0, 0.5);
+        assert!((half[1] - 997.5).abs() < 1e-9);
+        // Verlies > 100% kan de account niet negatief maken
+        let (wiped, _) = compound_equity(&[-150.0, 20.0], 1000.0, 1.0);
+        assert_eq!(wiped, vec![0.0, 0.0]);
+    }
+
+    fn star_entry(pair: &str, ts: i64, anom_ts: Option<i64>) -> TopRow {
+        TopRow {
+            ts,
+            pair: pair.to_string(),
+            display_name: String::new(),
+            price: 1.0,
+            pct: 0.0,
+            pct_1h: None,
+            pct_24h: None,
+            flow_pct: 50.0,
+            dir: "BUY".to_string(),
+            early: "NONE".to_string(),
+            alpha: "NONE".to_string(),
+            pump_score: 0.0,
+            pump_label: "NONE".to_string(),
+            whale: false,
+            whale_side: "-".to_string(),
+            whale_volume: 0.0,
+            whale_notional: 0.0,
+            whale_notional_fmt: String::new(),
+            total_score: 0.0,
+            analysis: String::new(),
+            whale_pred_score: 8.0,
+            whale_pred_label: "HIGH".to_string(),
+            reliability_score: 80.0,
+            reliability_label: "HIGH".to_string(),
+            signal_type: "ANOM".to_string(),
+            conviction: 0.0,
+            anom_ts,
+        }
+    }
+
+    #[test]
+    fn stars_history_dedups_per_pair_window_and_anom() {
+        let dedup = AppConfig::default().stars.history_dedup_sec;
+        let history = StarsHistory { history: vec![star_entry("BTC/EUR", 10_000, Some(10_000))], dirty: false };
+        // Binnen het window: geweigerd
+        assert!(!history.accepts("BTC/EUR", 10_000 + dedup - 1, None, dedup));
+        // Buiten het window maar dezelfde ANOM: geweigerd
+        assert!(!history.accepts("BTC/EUR", 10_000 + 2 * dedup, Some(10_000), dedup));
+        // Buiten het window met een nieuwe ANOM: toegestaan
+        assert!(history.accepts("BTC/EUR", 10_000 + 2 * dedup, Some(10_000 + 2 * dedup), dedup));
+        // Ander pair: altijd toegestaan
+        assert!(history.accepts("ETH/EUR", 10_000, Some(10_000), dedup));
+    }
+
+    #[test]
+    fn signal_expiry_boundaries() {
+        assert!(!signal_expired(1_000, 1_900, 900));
+        assert!(signal_expired(1_000, 1_901, 900));
+        // 0 = uitgeschakeld
+        assert!(!signal_expired(0, 1_000_000, 0));
+    }
+
+    #[test]
+    fn count_based_flow_ignores_one_outsized_print() {
+        let (mut buys, mut sells) = (VecDeque::new(), VecDeque::new());
+        for i in 0..4 {
+            buys.push_back((i as f64, 1.0));
+        }
+        // Eén grote (mogelijk nep) sell domineert het volume
+        sells.push_back((5.0, 100.0));
+        let by_volume = buy_share(buys.iter(), sells.iter(), 0.0, false).unwrap();
+        let by_count = buy_share(buys.iter(), sells.iter(), 0.0, true).unwrap();
+        assert!(by_volume < 0.05);
+        assert!((by_count - 0.8).abs() < 1e-9);
+        // Samples voor `since` tellen niet mee
+        assert_eq!(buy_share(buys.iter(), sells.iter(), 4.0, true), Some(0.0));
+        assert_eq!(buy_share(buys.iter(), sells.iter(), 10.0, true), None);
+
+        let (pct, dir) = update_flow_window(&mut buys, &mut sells, 6.0, 1.0, true, (60.0, 0.75, 0.25), true);
+        assert_eq!(dir, "BUY");
+        assert!((pct - 5.0 / 6.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pairs_snapshot_includes_quiet_ticker_only_pairs() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        engine.trades.insert(
+            "BTC/EUR".to_string(),
+            TradeState { trade_count: 3, last_update_ts: 100, ..Default::default() },
+        );
+        engine.tickers.insert(
+            "ADA/EUR".to_string(),
+            TickerState { last_price: Some(0.4), last_update_ts: 200, ..Default::default() },
+        );
+        let pairs = engine.pairs_snapshot();
+        assert_eq!(
+            pairs,
+            vec![
+                PairInfo { pair: "ADA/EUR".to_string(), last_price: Some(0.4), last_update_ts: 200, trade_count: 0 },
+                PairInfo { pair: "BTC/EUR".to_string(), last_price: None, last_update_ts: 100, trade_count: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn pump_settings_drive_pump_score() {
+        let input = TradeScoreInput { ret_5s: 1.3, ..quiet_input() };
+        let w = ScoreWeights::default();
+        // Default: (1.3 - 0.3) * 2.0, gelijk aan de oude hardcoded formule
+        let base = score_trade(&input, &w, &ScoreConfig::from(&AppConfig::default())).pump_score;
+        assert!((base - 2.0).abs() < 1e-9);
+
+        let mut cfg = AppConfig::default();
+        cfg.pump.ret_5s_mult = 4.0;
+        assert!((score_trade(&input, &w, &ScoreConfig::from(&cfg)).pump_score - 4.0).abs() < 1e-9);
+        cfg.pump.max_score = 3.0;
+        assert!((score_trade(&input, &w, &ScoreConfig::from(&cfg)).pump_score - 3.0).abs() < 1e-9);
+        cfg.pump.flow_mult = -1.0;
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn news_sentiment_entries_roundtrip() {
+        let json = r#"[{"pair":"SOL/EUR","sentiment":0.8,"last_update":1700000000,"title":"SOL rallies"},
+                       {"pair":"BTC/EUR","sentiment":0.2,"last_update":1700000100}]"#;
+        let entries: std::vec::Vec<NewsSentimentEntry> = serde_json::from_str(json).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "SOL rallies");
+        // Titel ontbreekt mag: oudere/handmatige bestanden blijven laadbaar
+        assert!(entries[1].title.is_empty());
+        let back: std::vec::Vec<NewsSentimentEntry> =
+            serde_json::from_str(&serde_json::to_string(&entries).unwrap()).unwrap();
+        assert_eq!(back[1].last_update, 1700000100);
+
+        let cfg = AppConfig { news_persist_interval_sec: 1, ..AppConfig::default() };
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn news_catch_all_goes_to_market_unless_configured() {
+        assert_eq!(news_sentiment_target(Some("SOL/EUR".into()), ""), "SOL/EUR");
+        assert_eq!(news_sentiment_target(None, ""), MARKET_SENTIMENT_KEY);
+        assert_eq!(news_sentiment_target(None, "BTC/EUR"), "BTC/EUR");
+        assert_eq!(news_sentiment_target(Some("ETH/EUR".into()), "BTC/EUR"), "ETH/EUR");
+
+        let cfg = AppConfig { news_catch_all_pair: "BTC".into(), ..AppConfig::default() };
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn ewma_converges_to_constant_input() {
+        // Constante input: fout krimpt elke stap met factor (1 - alpha)
+        let mut v = 0.0;
+        for _ in 0..100 {
+            v = ewma(v, 10.0, 0.1);
+        }
+        assert!((v - 10.0).abs() < 10.0 * 0.9_f64.powi(100) + 1e-9);
+        // Hogere alpha convergeert sneller
+        let (mut slow, mut fast) = (0.0, 0.0);
+        for _ in 0..10 {
+            slow = ewma(slow, 10.0, 0.05);
+            fast = ewma(fast, 10.0, 0.5);
+        }
+        assert!((10.0 - fast) < (10.0 - slow));
+        // Alpha buiten [0, 1] wordt geklemd
+        assert_eq!(ewma(3.0, 10.0, 2.0), 10.0);
+        assert_eq!(ewma(3.0, 10.0, -1.0), 3.0);
+    }
+
+    #[test]
+    fn auto_trader_charges_fees_on_both_sides() {
+        let cfg = AppConfig { auto_fee_pct: 0.5, base_notional: 100.0, ..AppConfig::default() };
+        let mut trader = AutoTraderState::new(10_000.0);
+        assert!(trader.open_position("SOL/EUR", Fill::exact(10.0), 0, 5.0, &cfg));
+        // +10% op 100 notional = 10 bruto; fees 0.5% van 100 (entry) + 110 (exit) = 1.05
+        let record = trader.close_position("SOL/EUR", Fill::exact(11.0), 60, "TP").unwrap();
+        assert!((record.pnl - (10.0 - 1.05)).abs() < 1e-9);
+        assert!((trader.balance - (10_000.0 + 8.95)).abs() < 1e-9);
+        // Zonder opt-in doet de auto trader niets, ook niet met enable_trading aan
+        let defaults = AppConfig::default();
+        assert!(defaults.enable_trading && !defaults.auto_trading_enabled);
+    }
+
+    #[test]
+    fn return_near_age_handles_sparse_samples() {
+        // Dun verhandeld: samples op 4s, 35s en 150s geleden, geen enkele in de oude smalle vensters
+        let now = 1_000.0;
+        let samples: VecDeque<(f64, f64)> = [(now - 150.0, 80.0), (now - 35.0, 90.0), (now - 4.0, 99.0)].into_iter().collect();
+        assert!((return_near_age(&samples, now, 100.0, 5.0) - (100.0 / 99.0 - 1.0) * 100.0).abs() < 1e-9);
+        assert!((return_near_age(&samples, now, 100.0, 30.0) - (100.0 / 90.0 - 1.0) * 100.0).abs() < 1e-9);
+        assert!((return_near_age(&samples, now, 100.0, 120.0) - 25.0).abs() < 1e-9);
+
+        // Eén recente sample telt alleen voor het venster waar hij in valt
+        let single: VecDeque<(f64, f64)> = [(now - 3.0, 90.0)].into_iter().collect();
+        assert!(return_near_age(&single, now, 100.0, 5.0) > 0.0);
+        assert_eq!(return_near_age(&single, now, 100.0, 30.0), 0.0);
+        assert_eq!(return_near_age(&single, now, 100.0, 120.0), 0.0);
+    }
+
+    #[test]
+    fn pair_detail_covers_quiet_and_ticker_only_pairs() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        engine.trades.insert(
+            "BTC/EUR".to_string(),
+            TradeState { trade_count: 3, last_update_ts: 100, ..Default::default() },
+        );
+        engine.tickers.insert(
+            "ADA/EUR".to_string(),
+            TickerState { last_price: Some(0.4), last_update_ts: 200, ..Default::default() },
+        );
+        // Geen whale/early/alpha: niet in de snapshot, wel een detail
+        assert!(engine.snapshot().is_empty());
+        let btc = engine.pair_detail("BTC/EUR").unwrap();
+        assert_eq!(btc.row.trades, 3);
+        let ada = engine.pair_detail("ADA/EUR").unwrap();
+        assert_eq!(ada.row.price, 0.4);
+        assert!(engine.pair_detail("XYZ/EUR").is_none());
+    }
+
+    #[test]
+    fn parse_perpetuals_prefers_linear_within_one_scan() {
+        let json = serde_json::json!({
+            "result": "success",
+            "tickers": [
+                {"tag": "perpetual", "symbol": "PF_XBTUSD", "pair": "XBT:USD", "fundingRate": 0.01},
+                {"tag": "perpetual", "symbol": "PI_XBTUSD", "pair": "XBT:USD", "fundingRate": 0.02},
+                {"tag": "perpetual", "symbol": "PI_ETHUSD", "pair": "ETH:USD", "fundingRate": 0.03},
+                {"tag": "perpetual", "symbol": "PF_ETHUSD", "pair": "ETH:USD", "fundingRate": 0.04},
+                {"tag": "perpetual", "symbol": "PI_LTCUSD", "pair": "LTC:USD", "fundingRate": 0.05},
+                {"tag": "month", "symbol": "FI_XBTUSD_250101", "pair": "XBT:USD", "fundingRate": 0.9}
+            ]
+        });
+        let map = parse_perpetuals(&json, 100);
+        assert_eq!(map["BTC"].symbol, "PF_XBTUSD");
+        assert_eq!(map["ETH"].symbol, "PF_ETHUSD");
+        // Alleen inverse beschikbaar: wordt toch elke scan gevuld
+        assert_eq!(map["LTC"].symbol, "PI_LTCUSD");
+        assert_eq!(map["LTC"].ts, 100);
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn reliability_scales_short_flow_window_to_per_minute_rate() {
+        let now = 10_000i64;
+        // 1 trade per 2s, 60s lang
+        let mut full = TradeState::default();
+        for i in 0..30 {
+            full.recent_buys.push_back((now as f64 - i as f64 * 2.0, 10.0));
+        }
+        // zelfde tempo, maar buffer afgekapt op een 30s venster
+        let mut short = TradeState::default();
+        for i in 0..15 {
+            short.recent_buys.push_back((now as f64 - i as f64 * 2.0, 10.0));
+        }
+        let base = ReliabilityParams::from(&AppConfig::default());
+        let p60 = ReliabilityParams { short_window_sec: 60.0, ..base.clone() };
+        let p30 = ReliabilityParams { short_window_sec: 30.0, ..base };
+        let a = Engine::reliability_breakdown(&full, now, &p60);
+        let b = Engine::reliability_breakdown(&short, now, &p30);
+        assert_eq!(a.trades_60s, 30);
+        assert_eq!(b.trades_60s, a.trades_60s);
+        assert!((b.trade_density - a.trade_density).abs() < 1e-9);
+        assert!((b.flow_consistency - a.flow_consistency).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ticker_refresh_does_not_hide_stale_trades() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        let now = Utc::now().timestamp();
+        // REST ticker heeft last_update_ts net ververst, laatste WS trade is oud
+        engine.trades.insert(
+            "BTC/EUR".to_string(),
+            TradeState { last_update_ts: now, last_trade_ts: now - 3600, ..Default::default() },
+        );
+        assert_eq!(engine.stale_pair_count(now), 1);
+        assert_eq!(engine.market_breadth().stale_pairs, 1);
+        let rc = RowConfig::from(&AppConfig::default());
+        let t = engine.trades.get("BTC/EUR").unwrap().clone();
+        let (row, _) = engine.build_row("BTC/EUR", &t, &rc, now);
+        assert!(row.stale);
+    }
+
+    #[test]
+    fn signals_snapshot_paginates_on_seq_within_one_second() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        {
+            let mut buf = engine.signals.lock().unwrap();
+            for seq in 1..=3 {
+                let mut ev = test_signal_from_json(&serde_json::json!({"pair": "BTC/EUR"}), 1_000).unwrap();
+                ev.seq = seq;
+                buf.push(ev);
+            }
+        }
+        let page1 = engine.signals_snapshot(&SignalsQuery { limit: Some(2), ..Default::default() });
+        assert_eq!(page1.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![3, 2]);
+        let before = page1.last().map(|e| e.seq);
+        let page2 = engine.signals_snapshot(&SignalsQuery { limit: Some(2), before, ..Default::default() });
+        assert_eq!(page2.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn parse_v2_trades_reads_sample_payload() {
+        let txt = r#"{"channel":"trade","type":"update","data":[
+            {"symbol":"BTC/EUR","side":"sell","price":58000.5,"qty":0.25,"ord_type":"market","trade_id":1,"timestamp":"2024-05-01T12:00:00.500000Z"},
+            {"symbol":"ETH/EUR","side":"buy","price":3000.0,"qty":0.0,"ord_type":"limit","trade_id":2,"timestamp":"2024-05-01T12:00:01.000000Z"}]}"#;
+        let trades = parse_v2_trades(txt);
+        // qty 0 valt weg
+        assert_eq!(trades.len(), 1);
+        let (pair, price, qty, side, ts) = &trades[0];
+        assert_eq!(pair, "BTC/EUR");
+        assert_eq!((*price, *qty, *side), (58000.5, 0.25, "s"));
+        assert!((ts - 1_714_564_800.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn v2_book_updates_apply_deltas_to_existing_levels() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        let snapshot = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/EUR",
+            "bids":[{"price":100.0,"qty":1.0},{"price":99.0,"qty":2.0},{"price":98.0,"qty":3.0}],
+            "asks":[{"price":101.0,"qty":1.0},{"price":102.0,"qty":2.0}],"checksum":1}]}"#;
+        for u in parse_v2_books(snapshot) {
+            assert!(u.snapshot);
+            store_orderbook(&engine, u);
+        }
+        // Alleen gewijzigde levels: 99 krijgt een nieuwe qty, 101 verdwijnt, 100.5 komt erbij
+        let update = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/EUR",
+            "bids":[{"price":99.0,"qty":5.0},{"price":100.5,"qty":0.5}],
+            "asks":[{"price":101.0,"qty":0.0}],"checksum":2,"timestamp":"2024-05-01T12:00:00.000000Z"}]}"#;
+        let updates = parse_v2_books(update);
+        assert_eq!(updates.len(), 1);
+        assert!(!updates[0].snapshot);
+        assert_eq!(updates[0].asks, vec![(101.0, 0.0)]);
+        for u in updates {
+            store_orderbook(&engine, u);
+        }
+        let ob = engine.orderbooks.get("BTC/EUR").unwrap().clone();
+        assert_eq!(ob.bids, vec![(100.5, 0.5), (100.0, 1.0), (99.0, 5.0), (98.0, 3.0)]);
+        assert_eq!(ob.asks, vec![(102.0, 2.0)]);
+
+        // Een nieuwe snapshot vervangt het boek volledig
+        let snapshot2 = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/EUR",
+            "bids":[{"price":90.0,"qty":1.0}],"asks":[{"price":91.0,"qty":1.0}]}]}"#;
+        for u in parse_v2_books(snapshot2) {
+            store_orderbook(&engine, u);
+        }
+        let ob = engine.orderbooks.get("BTC/EUR").unwrap().clone();
+        assert_eq!(ob.bids, vec![(90.0, 1.0)]);
+        assert_eq!(ob.asks, vec![(91.0, 1.0)]);
+    }
+
+    #[test]
+    fn v1_book_update_removes_zero_volume_levels() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        let snapshot = serde_json::json!([1, {"bs": [["100.0", "1.0", "1"], ["99.0", "2.0", "1"]],
+            "as": [["101.0", "1.0", "1"]]}, "book-10", "XBT/EUR"]);
+        store_orderbook(&engine, parse_v1_book(&snapshot).unwrap());
+        let update = serde_json::json!([1, {"a": [["101.0", "0.00000000", "2"]]},
+            {"b": [["99.0", "4.0", "2"]], "c": "123"}, "book-10", "XBT/EUR"]);
+        let u = parse_v1_book(&update).unwrap();
+        assert!(!u.snapshot);
+        store_orderbook(&engine, u);
+        let ob = engine.orderbooks.get("BTC/EUR").unwrap().clone();
+        assert_eq!(ob.bids, vec![(100.0, 1.0), (99.0, 4.0)]);
+        assert!(ob.asks.is_empty());
+    }
+
+    #[test]
+    fn dust_trades_stay_out_of_flow_sums() {
+        let cfg = AppConfig { min_trade_notional: 10.0, ..AppConfig::default() };
+        let engine = Engine::new(Arc::new(Mutex::new(cfg)));
+        let now = Utc::now().timestamp() as f64;
+        let at = std::time::Instant::now();
+        // 100 * 0.05 = 5 EUR: dust
+        engine.handle_trade("BTC/EUR", 100.0, 0.05, "b", now, at);
+        engine.handle_trade("BTC/EUR", 100.0, 0.05, "s", now, at);
+        assert!(engine.trades.get("BTC/EUR").is_none());
+        // 100 * 0.5 = 50 EUR telt wel mee
+        engine.handle_trade("BTC/EUR", 100.0, 0.5, "b", now, at);
+        engine.handle_trade("BTC/EUR", 100.0, 0.01, "s", now, at);
+        let t = engine.trades.get("BTC/EUR").unwrap();
+        assert_eq!(t.trade_count, 1);
+        assert_eq!(t.recent_buys.iter().map(|(_, v)| v).sum::<f64>(), 0.5);
+        assert!(t.recent_sells.is_empty());
+        assert_eq!(t.sell_volume, 0.0);
+    }
+
+    // cargo test --release bench_snapshot_cache_concurrent_polling -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_snapshot_cache_concurrent_polling() {
+        let now = Utc::now().timestamp();
+        let run = |ttl_ms: u64| {
+            let cfg = AppConfig { snapshot_cache_ttl_ms: ttl_ms, ..AppConfig::default() };
+            let engine = Engine::new(Arc::new(Mutex::new(cfg)));
+            for i in 0..2_000 {
+                engine.trades.insert(
+                    format!("P{}/EUR", i),
+                    TradeState {
+                        last_whale: true,
+                        signal_active_ts: now,
+                        last_trade_ts: now,
+                        last_update_ts: now,
+                        ..Default::default()
+                    },
+                );
+            }
+            // 8 "tabs" die elk 200x de snapshot opvragen
+            let start = std::time::Instant::now();
+            let handles: std::vec::Vec<_> = (0..8)
+                .map(|_| {
+                    let engine = engine.clone();
+                    std::thread::spawn(move || {
+                        for _ in 0..200 {
+                            std::hint::black_box(engine.snapshot());
+                        }
+                    })
+                })
+                .collect();
+            for h in handles {
+                h.join().unwrap();
+            }
+            start.elapsed()
+        };
+        let uncached = run(0);
+        let cached = run(500);
+        println!(
+            "snapshot 8x200 polls, 2000 pairs: zonder cache {:?}, met cache (500ms) {:?} ({:.1}x)",
+            uncached,
+            cached,
+            uncached.as_secs_f64() / cached.as_secs_f64().max(1e-9)
+        );
+    }
+
+    #[test]
+    fn cleanup_cutoffs_follow_retention_settings() {
+        let cfg = AppConfig {
+            trade_retention_sec: 600,
+            candle_retention_sec: 300,
+            orderbook_retention_sec: 60,
+            ..AppConfig::default()
+        };
+        let now = 10_000;
+        let c = CleanupCutoffs::new(now, &cfg);
+        assert!(c.keep_trade(&TradeState { last_update_ts: now - 600, ..Default::default() }));
+        assert!(!c.keep_trade(&TradeState { last_update_ts: now - 601, ..Default::default() }));
+
+        assert!(!c.remove_candle(&CandleState { last_ts: Some(now - 300), ..Default::default() }));
+        assert!(c.remove_candle(&CandleState { last_ts: Some(now - 301), ..Default::default() }));
+        // Candle zonder trades is altijd verlopen
+        assert!(c.remove_candle(&CandleState::default()));
+
+        assert!(c.keep_orderbook(&OrderbookState { timestamp: now - 60, ..Default::default() }));
+        assert!(!c.keep_orderbook(&OrderbookState { timestamp: now - 61, ..Default::default() }));
+
+        assert!(c.reset_anom(&TradeState { last_update_ts: now - 5 * 3600 - 1, ..Default::default() }));
+        assert!(!c.reset_anom(&TradeState { last_update_ts: now - 60, ..Default::default() }));
+    }
+
+    #[test]
+    fn old_early_alpha_defaults_are_migrated() {
+        let mut raw = serde_json::json!({"early_buy_threshold": 3.0, "alpha_buy_threshold": 7.5});
+        let migrated = migrate_config_defaults(&mut raw);
+        assert_eq!(migrated.len(), 2);
+        let cfg: AppConfig = serde_json::from_value(raw).unwrap();
+        assert_eq!((cfg.early_buy_threshold, cfg.alpha_buy_threshold), (2.2, 5.0));
+
+        // Zelf gekozen waarden blijven staan, ook als één veld toevallig de oude default heeft
+        let mut raw = serde_json::json!({"early_buy_threshold": 3.0, "alpha_buy_threshold": 6.0});
+        assert!(migrate_config_defaults(&mut raw).is_empty());
+        assert_eq!(raw["early_buy_threshold"], 3.0);
+    }
+
+    #[test]
+    fn old_whale_pred_high_default_is_migrated() {
+        let mut raw = serde_json::json!({"whale_pred_high_threshold": 8.0});
+        assert_eq!(migrate_config_defaults(&mut raw), vec!["whale_pred_high_threshold: 8 -> 7".to_string()]);
+        let cfg: AppConfig = serde_json::from_value(raw).unwrap();
+        assert_eq!(cfg.whale_pred_high_threshold, 7.0);
+        assert_eq!(whale_pred_label_for(7.5, &ScoreConfig::from(&cfg)), "HIGH");
+    }
+
+    #[test]
+    fn active_snapshot_uses_event_timestamps() {
+        let cfg = AppConfig { active_window_sec: 600, ..AppConfig::default() };
+        let engine = Engine::new(Arc::new(Mutex::new(cfg)));
+        let now = Utc::now().timestamp();
+        let old = now - 3600;
+        // Sticky rating en recent_anom, maar alles ouder dan het venster; de ticker
+        // heeft last_update_ts wel net ververst
+        engine.trades.insert(
+            "OLD/EUR".to_string(),
+            TradeState {
+                last_rating: Some("BUY".to_string()),
+                recent_anom: true,
+                last_whale_ts: old,
+                last_trade_ts: old,
+                last_update_ts: now,
+                ..Default::default()
+            },
+        );
+        engine.trades.insert(
+            "WHALE/EUR".to_string(),
+            TradeState { last_whale_ts: now - 60, last_trade_ts: now - 60, ..Default::default() },
+        );
+        engine.trades.insert("ANOM/EUR".to_string(), TradeState { last_trade_ts: old, ..Default::default() });
+        engine.tickers.insert(
+            "ANOM/EUR".to_string(),
+            TickerState { last_anom_ts: Some(now - 30), ..Default::default() },
+        );
+        engine.trades.insert(
+            "RATED/EUR".to_string(),
+            TradeState { last_rating: Some("BUY".to_string()), last_trade_ts: now - 10, ..Default::default() },
+        );
+        let mut pairs: Vec<String> = engine.active_snapshot().into_iter().map(|r| r.pair).collect();
+        pairs.sort();
+        assert_eq!(pairs, vec!["ANOM/EUR", "RATED/EUR", "WHALE/EUR"]);
+    }
+
+    #[test]
+    fn http_route_key_groups_unknown_api_paths() {
+        assert_eq!(HttpStats::route_key("/"), "/");
+        assert_eq!(HttpStats::route_key("/api/stats"), "/api/stats");
+        assert_eq!(HttpStats::route_key("/api/v1/stats"), "/api/stats");
+        assert_eq!(HttpStats::route_key("/api/config/export"), "/api/config/export");
+        assert_eq!(HttpStats::route_key("/api/pair/BTC/EUR"), "/api/pair");
+        assert_eq!(HttpStats::route_key("/api/orderbook/btc/eur"), "/api/orderbook");
+        assert_eq!(HttpStats::route_key("/api/does_not_exist"), "other");
+        assert_eq!(HttpStats::route_key("/api/stats/abc123"), "other");
+        assert_eq!(HttpStats::route_key("/wp-login.php"), "other");
+    }
+
+    #[test]
+    fn reliability_recency_ignores_ticker_refresh() {
+        let now = 10_000i64;
+        let t = TradeState { last_update_ts: now, last_trade_ts: now - 200, ..Default::default() };
+        let params = ReliabilityParams::from(&AppConfig::default());
+        let b = Engine::reliability_breakdown(&t, now, &params);
+        assert_eq!(b.secs_since_update, 200);
+        assert_eq!(b.recency, 5.0);
+    }
+
+    #[test]
+    fn all_rows_includes_quiet_pairs() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        engine.trades.insert("ETH/EUR".to_string(), TradeState { trade_count: 2, ..Default::default() });
+        engine.trades.insert("ADA/EUR".to_string(), TradeState { trade_count: 1, ..Default::default() });
+        assert!(engine.snapshot().is_empty());
+        let pairs: Vec<String> = engine.all_rows().into_iter().map(|r| r.pair).collect();
+        assert_eq!(pairs, vec!["ADA/EUR", "ETH/EUR"]);
+    }
+
+    #[test]
+    fn paper_fill_uses_book_after_deltas() {
+        let cfg = AppConfig { fill_at_book: true, slippage_bps: 0.0, ..AppConfig::default() };
+        let engine = Engine::new(Arc::new(Mutex::new(cfg.clone())));
+        let snapshot = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/EUR",
+            "bids":[{"price":100.0,"qty":1.0},{"price":99.0,"qty":2.0}],
+            "asks":[{"price":101.0,"qty":1.0},{"price":102.0,"qty":2.0}]}]}"#;
+        // Beste ask wordt opgegeten, alleen de bids krijgen een nieuw level
+        let updates = [
+            r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/EUR","bids":[],"asks":[{"price":101.0,"qty":0.0}]}]}"#,
+            r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/EUR","bids":[{"price":100.5,"qty":0.3}],"asks":[]}]}"#,
+        ];
+        for txt in std::iter::once(snapshot).chain(updates) {
+            for u in parse_v2_books(txt) {
+                store_orderbook(&engine, u);
+            }
+        }
+        let now = Utc::now().timestamp();
+        assert_eq!(engine.paper_fill("BTC/EUR", 100.0, true, &cfg, now).price, 102.0);
+        assert_eq!(engine.paper_fill("BTC/EUR", 100.0, false, &cfg, now).price, 100.5);
+    }
+
+    #[test]
+    fn signal_bar_close_stamps_events_with_bar_end() {
+        let mut event = test_signal_from_json(&serde_json::json!({"pair": "BTC/EUR", "price": 101.0}), 1_030).unwrap();
+        event.test = false;
+        let mut t = TradeState {
+            signal_bar: 1_030 / 60,
+            bar_eval: Some(BarCloseEval { early: "BUY".to_string(), alpha: "NONE".to_string(), event }),
+            bar_early: "NONE".to_string(),
+            bar_alpha: "NONE".to_string(),
+            ..Default::default()
+        };
+        let out = close_signal_bar(&mut t, 60);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].signal_type, "EARLY");
+        // Bar 17 loopt van 1020 tot 1080
+        assert_eq!(out[0].ts, 1_080);
+        assert_eq!(out[0].price, 101.0);
+        assert!(t.bar_eval.is_none());
+        assert_eq!(t.bar_early, "BUY");
+        // Zonder nieuwe evaluatie valt er niets meer te sluiten
+        assert!(close_signal_bar(&mut t, 60).is_empty());
+    }
+
+    #[test]
+    fn divergence_signal_respects_cooldown() {
+        let cooldown = 900;
+        // Eerste BULLISH
+        assert!(divergence_should_signal("BULLISH", "", ("", 0), 1_000, cooldown));
+        // Nog steeds BULLISH: geen nieuw signal
+        assert!(!divergence_should_signal("BULLISH", "BULLISH", ("BULLISH", 1_000), 1_010, cooldown));
+        // Flip "" -> BULLISH binnen de cooldown: onderdrukt
+        assert!(!divergence_should_signal("BULLISH", "", ("BULLISH", 1_000), 1_300, cooldown));
+        // Na de cooldown weer wel, en een ander label altijd
+        assert!(divergence_should_signal("BULLISH", "", ("BULLISH", 1_000), 1_900, cooldown));
+        assert!(divergence_should_signal("BEARISH", "", ("BULLISH", 1_000), 1_300, cooldown));
+    }
+
+    #[test]
+    fn stars_history_resolves_alias_when_served() {
+        let cfg = AppConfig {
+            pair_aliases: HashMap::from([("BTC/EUR".to_string(), "Bitcoin".to_string())]),
+            ..AppConfig::default()
+        };
+        let engine = Engine::new(Arc::new(Mutex::new(cfg)));
+        engine.add_to_stars_history(star_entry("BTC/EUR", 100, None));
+        let stored = engine.stars_history.lock().unwrap().history.clone();
+        assert!(serde_json::to_value(&stored[0]).unwrap().get("display_name").is_none());
+        assert_eq!(engine.stars_history_snapshot()[0].display_name, "Bitcoin");
+
+        // Een gewijzigde alias geldt ook voor oude entries
+        engine.config.lock().unwrap().pair_aliases.clear();
+        assert_eq!(engine.stars_history_snapshot()[0].display_name, "BTC/EUR");
+    }
+
+    #[test]
+    fn v2_ticker_keeps_day_open_basis() {
+        let engine = Engine::new(Arc::new(Mutex::new(AppConfig::default())));
+        let mut last_fed = HashMap::new();
+        engine.handle_ticker("BTC/EUR", 110.0, 1000.0, Some(100.0), 1_000);
+        let txt = r#"{"channel":"ticker","type":"update","data":[
+            {"symbol":"BTC/EUR","last":121.0,"volume":1000.0,"change":-9.0,"change_pct":-6.9}]}"#;
+        feed_ws_tickers(&engine, &mut last_fed, parse_v2_tickers(txt), 2_000);
+        let tk = engine.tickers.get("BTC/EUR").unwrap().clone();
+        assert_eq!(tk.last_price, Some(121.0));
+        // Rolling 24h change overschrijft de dag-open basis niet
+        assert!((tk.day_change_pct.unwrap() - 10.0).abs() < 1e-9);
+        // Zonder dag-open blijft REST het pair pollen
+        assert!(!engine.ticker_ws_fresh("BTC/EUR", 2_000));
+    }
+
+    #[test]
+    fn top_rows_sort_on_their_own_timeframe() {
+        let mut rows = vec![
+            TopRow { pct: 5.0, pct_1h: Some(1.0), ..star_entry("A/EUR", 1, None) },
+            TopRow { pct: 1.0, pct_1h: None, ..star_entry("B/EUR", 1, None) },
+            TopRow { pct: 2.0, pct_1h: Some(4.0), ..star_entry("C/EUR", 1, None) },
+        ];
+        sort_rows(&mut rows, SortField::Pct1h, SortOrder::Desc);
+        assert_eq!(rows.iter().map(|r| r.pair.as_str()).collect::<Vec<_>>(), vec!["C/EUR", "A/EUR", "B/EUR"]);
+        // Oudere stars_history.json entries zonder pct_1h/pct_24h laden nog
+        let mut raw = serde_json::to_value(star_entry("A/EUR", 1, None)).unwrap();
+        raw.as_object_mut().unwrap().retain(|k, _| k != "pct_1h" && k != "pct_24h");
+        let row: TopRow = serde_json::from_value(raw).unwrap();
+        assert_eq!((row.pct_1h, row.pct_24h), (None, None));
+    }
+}